@@ -2,11 +2,56 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
+use std::sync::Arc;
+
 use rustpower_klu_sys::*;
+
+/// Owner of one `klu_l_symbolic`, freed when the last handle drops.
+struct SymbolicHandle(*mut klu_l_symbolic);
+
+impl Drop for SymbolicHandle {
+    fn drop(&mut self) {
+        unsafe {
+            let mut common = klu_l_common::default();
+            klu_l_defaults(&mut common);
+            klu_l_free_symbolic(&mut self.0 as *mut *mut klu_l_symbolic, &mut common);
+        }
+    }
+}
+
+// KLU only reads the symbolic object in factor/refactor/solve.
+unsafe impl Send for SymbolicHandle {}
+unsafe impl Sync for SymbolicHandle {}
+
+/// Reference-counted KLU symbolic analysis (BTF + fill-reducing ordering).
+///
+/// Clones share the same analysis, so any number of [`KLUSolver`]s working
+/// on the same sparsity pattern, on any thread, can skip `klu_l_analyze`.
+#[derive(Clone)]
+pub struct SharedSymbolic(Arc<SymbolicHandle>);
+
+impl SharedSymbolic {
+    /// Runs `klu_l_analyze`; `None` if KLU fails (status in `common`).
+    pub unsafe fn analyze(
+        Ap: *mut i64,
+        Ai: *mut i64,
+        n: i64,
+        common: *mut klu_l_common,
+    ) -> Option<Self> {
+        let ptr = klu_l_analyze(n, Ap, Ai, common);
+        (!ptr.is_null()).then(|| SharedSymbolic(Arc::new(SymbolicHandle(ptr))))
+    }
+
+    pub fn as_ptr(&self) -> *mut klu_l_symbolic {
+        self.0 .0
+    }
+}
+
 pub struct KLUSolver {
     pub common: *mut klu_l_common,
     pub symbolic: *mut klu_l_symbolic,
     pub numeric: *mut klu_l_numeric,
+    shared: Option<SharedSymbolic>, // owns `symbolic`
 }
 
 impl Default for KLUSolver {
@@ -19,6 +64,7 @@ impl Default for KLUSolver {
                 common: common_ptr,
                 symbolic: std::ptr::null_mut(),
                 numeric: std::ptr::null_mut(),
+                shared: None,
             }
         }
     }
@@ -26,9 +72,9 @@ impl Default for KLUSolver {
 impl Drop for KLUSolver {
     fn drop(&mut self) {
         unsafe {
-            klu_l_free_symbolic(&mut self.symbolic as *mut *mut klu_l_symbolic, self.common);
             klu_l_free_numeric(&mut self.numeric as *mut *mut klu_l_numeric, self.common);
-            
+            self.shared = None;
+
             if !self.common.is_null() {
                 let _ = Box::from_raw(self.common);
             }
@@ -37,12 +83,26 @@ impl Drop for KLUSolver {
 }
 impl KLUSolver {
     pub unsafe fn solve_sym(&mut self, Ap: *mut i64, Ai: *mut i64, n: i64) -> i64 {
-        if !self.symbolic.is_null() {
-            klu_l_free_symbolic(&mut self.symbolic as *mut *mut klu_l_symbolic, self.common);
-        }
-        self.symbolic = klu_l_analyze(n, Ap, Ai, self.common);
+        self.shared = SharedSymbolic::analyze(Ap, Ai, n, self.common);
+        self.symbolic = self.shared.as_ref().map_or(std::ptr::null_mut(), |s| s.as_ptr());
         (*self.common).status.into()
     }
+
+    /// The current symbolic analysis, shareable with other solvers.
+    pub fn shared_symbolic(&self) -> Option<SharedSymbolic> {
+        self.shared.clone()
+    }
+
+    /// Adopts a symbolic analysis computed elsewhere for the same pattern.
+    /// Any numeric factorization is dropped; the next call must `factor`.
+    pub fn set_symbolic(&mut self, symbolic: SharedSymbolic) {
+        unsafe {
+            klu_l_free_numeric(&mut self.numeric as *mut *mut klu_l_numeric, self.common);
+        }
+        self.numeric = std::ptr::null_mut();
+        self.symbolic = symbolic.as_ptr();
+        self.shared = Some(symbolic);
+    }
     pub unsafe fn factor(&mut self, Ap: *mut i64, Ai: *mut i64, Ax: *mut f64) -> i64 {
         if !self.numeric.is_null() {
             klu_l_free_numeric(&mut self.numeric as *mut *mut klu_l_numeric, self.common);
@@ -60,9 +120,15 @@ impl KLUSolver {
         klu_l_solve(self.symbolic, self.numeric, n, bn, b, self.common);
         (*self.common).status.into()
     }
+
+    /// Solves `A' x = b` for `bn` right-hand sides (leading dimension `n`).
+    pub unsafe fn tsolve(&mut self, b: *mut f64, n: i64, bn: i64) -> i64 {
+        klu_l_tsolve(self.symbolic, self.numeric, n, bn, b, self.common);
+        (*self.common).status.into()
+    }
     pub fn reset(&mut self) {
         unsafe {
-            klu_l_free_symbolic(&mut self.symbolic as *mut *mut klu_l_symbolic, self.common);
+            self.shared = None;
 
             klu_l_free_numeric(&mut self.numeric as *mut *mut klu_l_numeric, self.common);
 
@@ -84,5 +150,23 @@ fn reset_test() {
     let mut klu = KLUSolver::default();
     klu.reset();
 }
+#[test]
+fn shared_symbolic_test() {
+    // [2 1; 1 3] x = [3 4]  ->  x = [1 1]
+    let mut ap = [0i64, 2, 4];
+    let mut ai = [0i64, 1, 0, 1];
+    let mut ax = [2.0, 1.0, 1.0, 3.0];
+    let mut first = KLUSolver::default();
+    let mut second = KLUSolver::default();
+    unsafe {
+        first.solve_sym(ap.as_mut_ptr(), ai.as_mut_ptr(), 2);
+        second.set_symbolic(first.shared_symbolic().unwrap());
+        drop(first);
+        assert_eq!(second.factor(ap.as_mut_ptr(), ai.as_mut_ptr(), ax.as_mut_ptr()), 0);
+        let mut b = [3.0, 4.0];
+        assert_eq!(second.solve(b.as_mut_ptr(), 2, 1), 0);
+        assert!((b[0] - 1.0).abs() < 1e-12 && (b[1] - 1.0).abs() < 1e-12);
+    }
+}
 unsafe impl Send for KLUSolver {}
 unsafe impl Sync for KLUSolver {}
@@ -1,4 +1,5 @@
 use rustpower::prelude::*;
+use rustpower::prelude::ecs::network::SharedSymbolicCache;
 use rustpower::io::pandapower::load_csv_zip;
 use std::env;
 use std::sync::Arc;
@@ -13,6 +14,16 @@ fn main() {
     let net = load_csv_zip(&zip_9241).expect("Failed to load 9241 data");
     let net_arc = Arc::new(net);
 
+    // One warm-up solve publishes the symbolic analysis; every worker below
+    // is seeded from it and only factorizes numerically.
+    let cache = SharedSymbolicCache::default();
+    let mut warm = PowerGrid::default();
+    warm.world_mut().insert_resource(PPNetwork((*net_arc).clone()));
+    warm.world_mut().insert_resource(cache.clone());
+    warm.init_pf_net();
+    warm.run_pf();
+    drop(warm);
+
     let num_instances = 100;
     println!("Starting {} parallel power flow instances...", num_instances);
     
@@ -21,9 +32,11 @@ fn main() {
 
     for i in 0..num_instances {
         let net_clone = Arc::clone(&net_arc);
+        let cache = cache.clone();
         let handle = thread::spawn(move || {
             let mut pf_net = PowerGrid::default();
             pf_net.world_mut().insert_resource(PPNetwork((*net_clone).clone()));
+            pf_net.world_mut().insert_resource(cache);
             
             pf_net.init_pf_net();
             pf_net.run_pf();
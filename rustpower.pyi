@@ -7,7 +7,7 @@ element properties take the incremental path with warm starts.
 """
 import numpy as np
 import pandas as pd
-from typing import Any, List, Optional, Tuple
+from typing import Any, Dict, List, Optional, Tuple
 
 # ---------------------------------------------------------------------------
 # Element proxies (live views over ECS entities; hold no data themselves)
@@ -95,6 +95,12 @@ class SolveReport:
     def rebuild(self) -> str:
         """Rebuild level this solve triggered: 'full' | 'incremental'."""
         ...
+    @property
+    def trace(self) -> Optional[List[Dict[str, Any]]]:
+        """Per-iteration convergence trace (None unless enable_trace(True)).
+        Keys: iteration, mismatch_norm, max_p_bus, max_p_mismatch, max_q_bus,
+        max_q_mismatch, step_norm, fill_ms, factor_ms, solve_ms."""
+        ...
     def __bool__(self) -> bool: ...
 
 # ---------------------------------------------------------------------------
@@ -199,9 +205,28 @@ class PowerGrid:
         (empty grid, no slack); divergence is reported via a falsy report.
         v_init: Optional initial voltage guess (p.u. complex) for all buses."""
         ...
+    def solve_batch(self, s_bus: np.ndarray, threads: Optional[int] = None) -> Tuple[np.ndarray, np.ndarray]:
+        """Solve many injection scenarios on the current topology in parallel.
+        s_bus: complex (scenarios x buses) p.u. injections, bus-id order.
+        Returns (v, converged): complex voltages of the same shape and a
+        per-scenario boolean convergence array. Grid state is not modified.
+        Raises ValueError for grids with buses merged by closed switches.
+        The GIL is released while solving."""
+        ...
+    def sensitivities(self, buses: List[int]) -> Dict[str, Any]:
+        """Voltage and loss sensitivities of the last converged solve.
+        dvm_dp, dvm_dq (p.u./MW, p.u./MVAr) and dva_dp, dva_dq (degree/MW,
+        degree/MVAr) have shape (n_bus, len(buses)): rows are all buses in
+        `bus_ids` order, columns the requested buses. Per generator:
+        gen_bus, gen_name, loss_factor, penalty_factor. Raises ValueError
+        without a converged solve, for unknown buses or merged buses."""
+        ...
     def reset_state(self) -> None:
         """Reset the power flow solver state, clearing result vectors and resetting bus injections."""
         ...
+    def enable_trace(self, enable: bool) -> None:
+        """Enable or disable per-iteration convergence tracing (see SolveReport.trace)."""
+        ...
     def enable_iwamoto(self, enable: bool) -> None:
         """Enable or disable the Iwamoto optimal multiplier solver dynamically at runtime."""
         ...
@@ -212,8 +237,43 @@ class PowerGrid:
     @property
     def res_line(self) -> pd.DataFrame: ...
     @property
+    def res_trafo(self) -> pd.DataFrame:
+        """Transformer results (hv_bus, lv_bus, name, pandapower res_trafo columns).
+
+        Like the other element result frames, indexed by element index."""
+        ...
+    @property
+    def res_gen(self) -> pd.DataFrame:
+        """Generator results (bus, name, p_mw, q_mvar, va_degree, vm_pu)."""
+        ...
+    @property
+    def res_ext_grid(self) -> pd.DataFrame:
+        """External grid results (bus, name, p_mw, q_mvar)."""
+        ...
+    @property
+    def res_load(self) -> pd.DataFrame:
+        """Load results (bus, name, p_mw, q_mvar)."""
+        ...
+    @property
+    def res_sgen(self) -> pd.DataFrame:
+        """Static generator results (bus, name, p_mw, q_mvar)."""
+        ...
+    @property
+    def res_shunt(self) -> pd.DataFrame:
+        """Shunt results (bus, name, p_mw, q_mvar, vm_pu)."""
+        ...
+    def violations(self) -> pd.DataFrame:
+        """Limit violations of the last solve (entity, name, kind, value,
+        limit, severity), most severe first. Voltages in p.u., branch
+        loadings and severity in percent. Empty unless the solve converged."""
+        ...
+    @property
+    def bus_ids(self) -> np.ndarray:
+        """Bus ids (int64) in the order of `v`: ascending, gaps allowed."""
+        ...
+    @property
     def v(self) -> np.ndarray:
-        """Complex bus voltages (p.u.). Original bus order."""
+        """Complex bus voltages (p.u.), one entry per bus in `bus_ids` order."""
         ...
     @v.setter
     def v(self, value: np.ndarray) -> None:
@@ -1,9 +1,9 @@
 """
 Focused profiling of PowerGrid.solve() on pegase9241.
 Measures:
-  1. Solver core (newton_pf) via internal DEBUG prints
-  2. Post-processing via internal DEBUG prints
-  3. Total Python-measured wall time per solve()
+  1. Solver core (newton_pf) via the per-iteration convergence trace
+     (Jacobian fill / factorization / solve split)
+  2. Total Python-measured wall time per solve()
 
 Run with: python -u scripts/profile_solve.py
 (requires release build: maturin develop --release)
@@ -22,6 +22,7 @@ print(f"Warmup: {N_WARMUP}, Iterations: {N_ITERS}")
 print()
 
 grid = rustpower.PowerGrid(case_path=case)
+grid.enable_trace(True)
 print("Grid loaded.\n")
 
 # Warmup
@@ -34,6 +35,7 @@ print()
 # Timed runs
 print("--- Timed runs (Python wall clock) ---")
 py_times = []
+core_times = {"fill_ms": [], "factor_ms": [], "solve_ms": []}
 for i in range(N_ITERS):
     sys.stdout.flush()
     t0 = time.perf_counter()
@@ -41,7 +43,8 @@ for i in range(N_ITERS):
     t1 = time.perf_counter()
     dt = (t1 - t0) * 1e3
     py_times.append(dt)
-    # DEBUG lines from Rust are printed automatically
+    for key in core_times:
+        core_times[key].append(sum(rec[key] for rec in r.trace))
 
 print()
 print(f"--- Summary (N={N_ITERS}) ---")
@@ -53,5 +56,9 @@ print(f"    min:    {arr.min():.3f} ms")
 print(f"    max:    {arr.max():.3f} ms")
 print(f"    std:    {arr.std():.3f} ms")
 print()
-print("Compare the Python wall-clock with the Rust-internal timings above")
+print(f"  Newton core per solve() (from SolveReport.trace):")
+for key, vals in core_times.items():
+    print(f"    {key:<10} mean: {np.mean(vals):.3f} ms")
+print()
+print("Compare the Python wall-clock with the Newton core timings above")
 print("to isolate FFI/post-processing overhead.")
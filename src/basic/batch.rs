@@ -0,0 +1,117 @@
+use std::thread;
+
+use nalgebra::{DMatrix, DVector};
+use nalgebra_sparse::CscMatrix;
+use num_complex::Complex64;
+
+use super::new_dsdvbus2::JacobianPattern2;
+use super::newtonpf::newton_pf_with_pattern;
+use super::solver::Solve;
+
+/// `(scenario, voltages, iterations, converged)` as produced by a worker.
+type ScenarioResult = (usize, DVector<Complex64>, usize, bool);
+
+/// Result of a batched power flow: one row per scenario.
+#[derive(Debug, Clone)]
+pub struct BatchPFResult {
+    /// Final voltages (scenarios × buses), columns in the input bus order.
+    pub v: DMatrix<Complex64>,
+    /// Newton iterations taken per scenario.
+    pub iterations: Vec<usize>,
+    /// Convergence flag per scenario.
+    pub converged: Vec<bool>,
+}
+
+/// Solves many injection scenarios on one network topology.
+///
+/// `s_bus` holds one scenario per row, with columns in the same
+/// `[PQ | PV | slack]` order as `Ybus` and `v_init` (see [`newton_pf`]).
+/// The Jacobian pattern is built once and the first scenario is solved before
+/// the workers start; its symbolic factorization is then seeded into every
+/// worker's `Solver` (see [`Solve::seed_symbolic`]), so the pattern is
+/// analyzed once per batch. Every scenario starts from `v_init`, but backends
+/// that refactor with the pivots of an earlier factorization (KLU) may differ
+/// in rounding depending on how the scenarios are split across threads.
+///
+/// `threads` defaults to the available parallelism.
+///
+/// [`newton_pf`]: super::newton_pf
+#[allow(non_snake_case, clippy::too_many_arguments)]
+pub fn newton_pf_batch<Solver: Solve + Default>(
+    Ybus: &CscMatrix<Complex64>,
+    s_bus: &DMatrix<Complex64>,
+    v_init: &DVector<Complex64>,
+    npv: usize,
+    npq: usize,
+    tolerance: Option<f64>,
+    max_iter: Option<usize>,
+    threads: Option<usize>,
+) -> BatchPFResult {
+    let (n_scen, n) = s_bus.shape();
+    let mut result = BatchPFResult {
+        v: DMatrix::zeros(n_scen, n),
+        iterations: vec![0; n_scen],
+        converged: vec![false; n_scen],
+    };
+    if n_scen == 0 {
+        return result;
+    }
+
+    let j_pattern = JacobianPattern2::build_from_permuted(
+        Ybus.col_offsets(),
+        Ybus.row_indices(),
+        npv,
+        npq,
+    );
+    let solve_one = |k: usize, solver: &mut Solver, ap: &mut Vec<usize>, ai: &mut Vec<usize>| {
+        let sbus = s_bus.row(k).transpose();
+        match newton_pf_with_pattern(
+            Ybus, &sbus, v_init, npv, npq, tolerance, max_iter, solver, &j_pattern, ap, ai,
+            None,
+        ) {
+            Ok((v, it)) => (k, v, it, true),
+            Err((_, v, it)) => (k, v, it, false),
+        }
+    };
+
+    // Analyze the pattern once on the first scenario and share the result.
+    let mut solver = Solver::default();
+    let mut ap = j_pattern.j_col_ptrs.clone();
+    let mut ai = j_pattern.j_row_indices.clone();
+    let first = solve_one(0, &mut solver, &mut ap, &mut ai);
+    let symbolic = solver.symbolic();
+
+    let n_rest = n_scen - 1;
+    let n_threads = threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .clamp(1, n_rest.max(1));
+    let chunk = n_rest.div_ceil(n_threads).max(1);
+
+    let solved: Vec<Vec<ScenarioResult>> = thread::scope(|scope| {
+        let (j_pattern, symbolic, solve_one) = (&j_pattern, &symbolic, &solve_one);
+        let workers: Vec<_> = (1..n_scen)
+            .step_by(chunk)
+            .map(|start| {
+                scope.spawn(move || {
+                    let mut solver = Solver::default();
+                    if let Some(symbolic) = symbolic {
+                        solver.seed_symbolic(symbolic.clone());
+                    }
+                    let mut ap = j_pattern.j_col_ptrs.clone();
+                    let mut ai = j_pattern.j_row_indices.clone();
+                    (start..(start + chunk).min(n_scen))
+                        .map(|k| solve_one(k, &mut solver, &mut ap, &mut ai))
+                        .collect()
+                })
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).collect()
+    });
+
+    for (k, v, it, converged) in std::iter::once(first).chain(solved.into_iter().flatten()) {
+        result.v.row_mut(k).copy_from(&v.transpose());
+        result.iterations[k] = it;
+        result.converged[k] = converged;
+    }
+    result
+}
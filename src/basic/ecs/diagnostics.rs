@@ -0,0 +1,754 @@
+//! Network diagnostics before the power flow is initialized.
+//!
+//! In the spirit of pandapower's `diagnostic()`, [`DiagnosticsPlugin`]
+//! checks the loaded elements in [`BeforePFInitStage`], ahead of the node
+//! lookup and element setup, and stores the findings as a
+//! [`DiagnosticReport`] resource:
+//!
+//! * bus references that do not resolve and bus ids used twice,
+//! * in-service elements on out-of-service buses,
+//! * zero, negative or otherwise unusable branch impedances,
+//! * transformer rated voltages that do not fit their buses and lines
+//!   between different voltage levels,
+//! * a missing slack and buses without a path to one,
+//! * implausible values such as non-positive ratings or voltage set points
+//!   outside 0.8–1.2 p.u.
+//!
+//! Elements whose bus does not exist cannot be placed in the grid and
+//! negative bus ids collide with the ground node, so the power flow
+//! initialization is skipped and the report is left to explain why (see
+//! [`pf_init_allowed`]). With [`DiagnosticsConfig::quarantine_missing_buses`]
+//! elements with a missing bus are taken out of service instead (switches are
+//! opened), reported as warnings, and the remaining grid initializes. All
+//! other findings are only reported.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use bevy_app::prelude::*;
+use bevy_ecs::name::Name;
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::{RunSystemOnce, SystemParam};
+use serde::{Serialize, Serializer};
+use tabled::{Table, Tabled, settings::Style};
+
+use super::elements::bus::bus_systems::init_node_lookup;
+use super::elements::*;
+use super::factory::resolve_std_types;
+use super::network::{DataOps, PowerGrid, SolverStage};
+use super::plugin::{AfterPFInitStage, BeforePFInitStage, PFInitStage};
+use crate::io::pandapower::SwitchType;
+
+/// What a diagnostic is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum DiagnosticKind {
+    MissingBus,
+    DuplicateBusId,
+    NegativeBusId,
+    OutOfServiceBus,
+    InvalidImpedance,
+    InconsistentVoltage,
+    NoSlack,
+    DisconnectedBus,
+    ImplausibleParameter,
+}
+
+impl DiagnosticKind {
+    /// Findings of this kind leave no model to initialize while they are
+    /// errors.
+    pub fn blocks_init(self) -> bool {
+        matches!(self, Self::MissingBus | Self::NegativeBusId)
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Errors make the power flow fail or meaningless, warnings are likely
+/// data mistakes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// One finding. `entity` is `None` for findings about the whole network.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    /// Element table (`"bus"`, `"line"`, `"trafo"`, ...).
+    pub element: &'static str,
+    #[serde(serialize_with = "entity_bits")]
+    pub entity: Option<Entity>,
+    pub name: Option<String>,
+    pub message: String,
+}
+
+fn entity_bits<S: Serializer>(e: &Option<Entity>, s: S) -> Result<S::Ok, S::Error> {
+    match e {
+        Some(e) => s.serialize_some(&e.to_bits()),
+        None => s.serialize_none(),
+    }
+}
+
+/// All findings of one check, errors first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Resource)]
+pub struct DiagnosticReport(pub Vec<Diagnostic>);
+
+impl DiagnosticReport {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.0.iter()
+    }
+
+    /// Findings of one kind.
+    pub fn of_kind(&self, kind: DiagnosticKind) -> impl Iterator<Item = &Diagnostic> {
+        self.0.iter().filter(move |d| d.kind == kind)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.0.iter().filter(|d| d.severity == Severity::Error)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Whether an error keeps the power flow from being initialized.
+    pub fn blocks_init(&self) -> bool {
+        self.errors().any(|d| d.kind.blocks_init())
+    }
+}
+
+/// Table row for displaying a diagnostic.
+#[derive(Tabled)]
+struct DiagnosticTable {
+    severity: Severity,
+    kind: DiagnosticKind,
+    element: &'static str,
+    entity: String,
+    name: String,
+    message: String,
+}
+
+impl fmt::Display for DiagnosticReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self.0.iter().map(|d| DiagnosticTable {
+            severity: d.severity,
+            kind: d.kind,
+            element: d.element,
+            entity: d.entity.map(|e| e.to_string()).unwrap_or_default(),
+            name: d.name.clone().unwrap_or_default(),
+            message: d.message.clone(),
+        });
+        write!(f, "{}", Table::new(rows).with(Style::markdown()))
+    }
+}
+
+/// Tolerance of transformer rated voltages against their buses, as in
+/// pandapower's diagnostic.
+const TRAFO_VN_TOLERANCE: f64 = 0.3;
+/// Plausible range of voltage set points (p.u.).
+const VM_RANGE: (f64, f64) = (0.8, 1.2);
+
+#[allow(clippy::type_complexity)]
+#[derive(SystemParam)]
+pub(crate) struct DiagnosticQueries<'w, 's> {
+    buses: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static BusID,
+            &'static VNominal,
+            Has<OutOfService>,
+            Option<&'static Name>,
+        ),
+    >,
+    lines: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static LineParams,
+            &'static FromBus,
+            &'static ToBus,
+            Has<OutOfService>,
+            Option<&'static Name>,
+        ),
+    >,
+    trafos: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static TransformerDevice,
+            &'static FromBus,
+            &'static ToBus,
+            Has<OutOfService>,
+            Option<&'static Name>,
+        ),
+    >,
+    injections: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static TargetBus,
+            Option<&'static TargetVmPu>,
+            Option<&'static ShuntDevice>,
+            (Has<TargetVaDeg>, Has<Slack>, Has<OutOfService>),
+            (Has<LoadCfg>, Has<SGenDevice>, Has<GeneratorCfg>),
+            Option<&'static Name>,
+        ),
+    >,
+    switches: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Switch,
+            &'static SwitchState,
+            Option<&'static Name>,
+        ),
+    >,
+}
+
+struct Collector(Vec<Diagnostic>);
+
+impl Collector {
+    fn push(
+        &mut self,
+        severity: Severity,
+        kind: DiagnosticKind,
+        element: &'static str,
+        entity: Option<Entity>,
+        name: Option<&Name>,
+        message: String,
+    ) {
+        self.0.push(Diagnostic {
+            severity,
+            kind,
+            element,
+            entity,
+            name: name.map(|n| n.to_string()),
+            message,
+        });
+    }
+}
+
+/// Checks the elements of the world.
+pub(crate) fn collect_diagnostics(q: DiagnosticQueries) -> DiagnosticReport {
+    use self::Severity::*;
+    use DiagnosticKind::*;
+    let mut out = Collector(Vec::new());
+
+    // Bus id -> (in service, vn_kv).
+    let mut buses: HashMap<i64, (bool, f64)> = HashMap::new();
+    for (entity, id, vn, oos, name) in &q.buses {
+        if buses.insert(id.0, (!oos, vn.0.0)).is_some() {
+            let msg = format!("bus id {} is used by more than one bus", id.0);
+            out.push(Error, DuplicateBusId, "bus", Some(entity), name, msg);
+        }
+        if id.0 < 0 {
+            let msg = format!("bus id {} is negative; those are reserved for ground", id.0);
+            out.push(Error, NegativeBusId, "bus", Some(entity), name, msg);
+        }
+        if vn.0.0 <= 0.0 {
+            let msg = format!("nominal voltage {} kV is not positive", vn.0.0);
+            out.push(Error, ImplausibleParameter, "bus", Some(entity), name, msg);
+        }
+    }
+
+    // Checks the buses of an in-service element; true if all exist.
+    let connect = |out: &mut Collector,
+                   element: &'static str,
+                   entity: Entity,
+                   name: Option<&Name>,
+                   ids: &[i64]| {
+        let mut found = true;
+        for &id in ids {
+            match buses.get(&id) {
+                None => {
+                    let msg = format!("bus {id} does not exist");
+                    out.push(Error, MissingBus, element, Some(entity), name, msg);
+                    found = false;
+                }
+                Some((false, _)) => {
+                    let msg = format!("connected to out-of-service bus {id}");
+                    out.push(Warning, OutOfServiceBus, element, Some(entity), name, msg);
+                }
+                Some(_) => {}
+            }
+        }
+        found
+    };
+
+    // Edges between in-service buses for the connectivity check.
+    let mut edges = Vec::new();
+
+    for (entity, p, from, to, oos, name) in &q.lines {
+        if oos || !connect(&mut out, "line", entity, name, &[from.0, to.0]) {
+            continue;
+        }
+        edges.push((from.0, to.0));
+        let params = [p.r_ohm_per_km, p.x_ohm_per_km, p.c_nf_per_km, p.g_us_per_km];
+        if params.iter().any(|v| !v.is_finite()) {
+            let msg = "missing parameters and no standard type to take them from".to_string();
+            out.push(Error, InvalidImpedance, "line", Some(entity), name, msg);
+        } else if p.length_km <= 0.0 || p.parallel <= 0 {
+            let msg = format!(
+                "length {} km with {} parallel systems",
+                p.length_km, p.parallel
+            );
+            out.push(Error, InvalidImpedance, "line", Some(entity), name, msg);
+        } else if p.r_ohm_per_km == 0.0 && p.x_ohm_per_km == 0.0 {
+            let msg = "zero series impedance".to_string();
+            out.push(Error, InvalidImpedance, "line", Some(entity), name, msg);
+        } else if p.r_ohm_per_km < 0.0 {
+            let msg = format!("negative resistance {} Ohm/km", p.r_ohm_per_km);
+            out.push(Warning, InvalidImpedance, "line", Some(entity), name, msg);
+        }
+        if p.c_nf_per_km < 0.0 || p.g_us_per_km < 0.0 {
+            let msg = format!(
+                "negative shunt admittance ({} nF/km, {} uS/km)",
+                p.c_nf_per_km, p.g_us_per_km
+            );
+            out.push(
+                Warning,
+                ImplausibleParameter,
+                "line",
+                Some(entity),
+                name,
+                msg,
+            );
+        }
+        let (vn_from, vn_to) = (buses[&from.0].1, buses[&to.0].1);
+        if (vn_from - vn_to).abs() > 1e-3 * vn_from.abs().max(vn_to.abs()) {
+            let msg = format!("connects {vn_from} kV and {vn_to} kV buses");
+            out.push(
+                Warning,
+                InconsistentVoltage,
+                "line",
+                Some(entity),
+                name,
+                msg,
+            );
+        }
+    }
+
+    for (entity, dev, hv, lv, oos, name) in &q.trafos {
+        if oos || !connect(&mut out, "trafo", entity, name, &[hv.0, lv.0]) {
+            continue;
+        }
+        edges.push((hv.0, lv.0));
+        let params = [
+            dev.sn_mva,
+            dev.vn_hv_kv,
+            dev.vn_lv_kv,
+            dev.vk_percent,
+            dev.vkr_percent,
+            dev.pfe_kw,
+            dev.i0_percent,
+            dev.shift_degree,
+        ];
+        if params.iter().any(|v| !v.is_finite()) {
+            let msg = "missing parameters and no standard type to take them from".to_string();
+            out.push(Error, InvalidImpedance, "trafo", Some(entity), name, msg);
+        }
+        if dev.sn_mva <= 0.0 || dev.parallel <= 0 {
+            let msg = format!(
+                "rating {} MVA with {} parallel units",
+                dev.sn_mva, dev.parallel
+            );
+            out.push(
+                Error,
+                ImplausibleParameter,
+                "trafo",
+                Some(entity),
+                name,
+                msg,
+            );
+        }
+        if dev.vk_percent <= 0.0 || dev.vkr_percent < 0.0 || dev.vkr_percent > dev.vk_percent {
+            let msg = format!(
+                "vk {} % and vkr {} % give no valid impedance",
+                dev.vk_percent, dev.vkr_percent
+            );
+            out.push(Error, InvalidImpedance, "trafo", Some(entity), name, msg);
+        }
+        for (side, vn_dev, bus) in [("hv", dev.vn_hv_kv, hv.0), ("lv", dev.vn_lv_kv, lv.0)] {
+            let vn_bus = buses[&bus].1;
+            if (vn_dev - vn_bus).abs() > TRAFO_VN_TOLERANCE * vn_bus.abs() {
+                let msg = format!("{side} rating {vn_dev} kV at a {vn_bus} kV bus");
+                out.push(
+                    Warning,
+                    InconsistentVoltage,
+                    "trafo",
+                    Some(entity),
+                    name,
+                    msg,
+                );
+            }
+        }
+        if dev.vn_hv_kv < dev.vn_lv_kv {
+            let msg = format!(
+                "hv rating {} kV below lv rating {} kV",
+                dev.vn_hv_kv, dev.vn_lv_kv
+            );
+            out.push(
+                Warning,
+                InconsistentVoltage,
+                "trafo",
+                Some(entity),
+                name,
+                msg,
+            );
+        }
+    }
+
+    let mut slack_buses = Vec::new();
+    for (entity, bus, vm, shunt, (ext, slack, oos), (load, sgen, r#gen), name) in &q.injections {
+        let element = match () {
+            _ if ext => "ext_grid",
+            _ if shunt.is_some() => "shunt",
+            _ if sgen => "sgen",
+            _ if load => "load",
+            _ if r#gen => "gen",
+            _ => "element",
+        };
+        if oos || !connect(&mut out, element, entity, name, &[bus.0]) {
+            continue;
+        }
+        if (ext || slack) && buses[&bus.0].0 {
+            slack_buses.push(bus.0);
+        }
+        if let Some(vm) = vm.filter(|vm| vm.0 < VM_RANGE.0 || vm.0 > VM_RANGE.1) {
+            let msg = format!("voltage set point {} p.u.", vm.0);
+            out.push(
+                Warning,
+                ImplausibleParameter,
+                element,
+                Some(entity),
+                name,
+                msg,
+            );
+        }
+        if let Some(dev) = shunt.filter(|s| s.vn_kv <= 0.0) {
+            let msg = format!("rated voltage {} kV is not positive", dev.vn_kv);
+            out.push(
+                Error,
+                ImplausibleParameter,
+                element,
+                Some(entity),
+                name,
+                msg,
+            );
+        }
+    }
+
+    for (entity, sw, state, name) in &q.switches {
+        if sw.et != SwitchType::SwitchTwoBuses {
+            continue;
+        }
+        if connect(&mut out, "switch", entity, name, &[sw.bus, sw.element]) && state.0 {
+            edges.push((sw.bus, sw.element));
+        }
+    }
+
+    if slack_buses.is_empty() {
+        let msg = "no in-service external grid or slack generator".to_string();
+        out.push(Error, NoSlack, "network", None, None, msg);
+    } else {
+        // Buses reachable from a slack over in-service branches.
+        let mut adjacent: HashMap<i64, Vec<i64>> = HashMap::new();
+        for (a, b) in edges {
+            if buses[&a].0 && buses[&b].0 {
+                adjacent.entry(a).or_default().push(b);
+                adjacent.entry(b).or_default().push(a);
+            }
+        }
+        let mut reached = slack_buses.iter().copied().collect::<HashSet<_>>();
+        let mut stack = slack_buses;
+        while let Some(bus) = stack.pop() {
+            for &next in adjacent.get(&bus).into_iter().flatten() {
+                if reached.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        for (entity, id, _, oos, name) in &q.buses {
+            if !oos && !reached.contains(&id.0) {
+                let msg = format!("bus {} has no path to a slack", id.0);
+                out.push(Error, DisconnectedBus, "bus", Some(entity), name, msg);
+            }
+        }
+    }
+
+    let mut report = out.0;
+    report.sort_by_key(|d| std::cmp::Reverse(d.severity));
+    DiagnosticReport(report)
+}
+
+/// Options of the network diagnostics. Insert before the power flow is
+/// initialized.
+#[derive(Debug, Clone, Default, Resource)]
+pub struct DiagnosticsConfig {
+    /// Take elements whose bus does not exist out of service (open
+    /// switches) instead of refusing to initialize the power flow.
+    pub quarantine_missing_buses: bool,
+}
+
+/// Checks the network and stores the report.
+///
+/// Elements with a missing bus are quarantined when
+/// [`DiagnosticsConfig::quarantine_missing_buses`] is set, which turns
+/// their findings into warnings. Otherwise they stay errors and
+/// [`pf_init_allowed`] skips the initialization.
+pub fn diagnose_network(
+    In(mut report): In<DiagnosticReport>,
+    config: Option<Res<DiagnosticsConfig>>,
+    mut cmd: Commands,
+) {
+    if config.is_some_and(|c| c.quarantine_missing_buses) {
+        for d in report.0.iter_mut() {
+            let (DiagnosticKind::MissingBus, Some(entity)) = (d.kind, d.entity) else {
+                continue;
+            };
+            if d.element == "switch" {
+                cmd.entity(entity).insert(SwitchState(false));
+            } else {
+                cmd.entity(entity).insert(OutOfService);
+            }
+            d.severity = Severity::Warning;
+            d.message.push_str("; taken out of service");
+        }
+        report.0.sort_by_key(|d| std::cmp::Reverse(d.severity));
+    }
+    cmd.insert_resource(report);
+}
+
+/// Run condition of the power flow initialization and solve: false once
+/// the stored [`DiagnosticReport`] has an error that
+/// [blocks it](DiagnosticReport::blocks_init).
+pub fn pf_init_allowed(report: Option<Res<DiagnosticReport>>) -> bool {
+    report.is_none_or(|r| !r.blocks_init())
+}
+
+/// Runs the network diagnostics before the power flow is initialized.
+#[derive(Default)]
+pub struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Startup,
+            collect_diagnostics
+                .pipe(diagnose_network)
+                .in_set(BeforePFInitStage)
+                .after(resolve_std_types)
+                .before(init_node_lookup),
+        );
+        app.configure_sets(
+            Startup,
+            (
+                PFInitStage.run_if(pf_init_allowed),
+                AfterPFInitStage.run_if(pf_init_allowed),
+            ),
+        );
+        app.configure_sets(
+            Update,
+            (
+                SolverStage::BeforeSolve.run_if(pf_init_allowed),
+                SolverStage::Solve.run_if(pf_init_allowed),
+                SolverStage::AfterSolve.run_if(pf_init_allowed),
+            ),
+        );
+    }
+}
+
+/// On-demand access to the diagnostics of the current elements.
+pub trait Diagnostics {
+    /// Checks the elements as they are now, without changing them.
+    fn diagnostics(&mut self) -> DiagnosticReport;
+}
+
+impl Diagnostics for World {
+    fn diagnostics(&mut self) -> DiagnosticReport {
+        self.run_system_once(collect_diagnostics).unwrap()
+    }
+}
+
+impl Diagnostics for App {
+    fn diagnostics(&mut self) -> DiagnosticReport {
+        self.world_mut().diagnostics()
+    }
+}
+
+impl Diagnostics for PowerGrid {
+    fn diagnostics(&mut self) -> DiagnosticReport {
+        self.world_mut().diagnostics()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::network::PowerFlow;
+    use crate::basic::ecs::powerflow::systems::{PowerFlowMat, PowerFlowResult};
+    use crate::basic::ecs::post_processing::PostProcessing;
+    use crate::io::pandapower::ecs_net_conv::ToPandapowerNet;
+    use crate::io::pandapower::{Network, load_csv_zip};
+    use std::env;
+
+    fn ieee118() -> Network {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        load_csv_zip(&format!("{dir}/cases/IEEE118/data.zip")).unwrap()
+    }
+
+    fn solve(net: Network) -> PowerGrid {
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.run_pf();
+        grid
+    }
+
+    #[test]
+    fn test_ieee118_is_clean() {
+        let mut grid = solve(ieee118());
+        let report = grid.world().resource::<DiagnosticReport>();
+        assert!(!report.has_errors(), "{report}");
+        assert!(grid.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn test_missing_bus_skips_init() {
+        let mut net = ieee118();
+        net.line.as_mut().unwrap()[0].to_bus = 9999;
+        let grid = solve(net);
+        let world = grid.world();
+        let report = world.resource::<DiagnosticReport>();
+        assert!(report.blocks_init());
+        let missing = report.of_kind(DiagnosticKind::MissingBus).collect::<Vec<_>>();
+        assert_eq!(missing.len(), 1);
+        assert_eq!((missing[0].element, missing[0].severity), ("line", Severity::Error));
+        assert!(missing[0].message.contains("bus 9999 does not exist"));
+        assert!(!world.contains_resource::<PowerFlowMat>());
+        assert!(!world.contains_resource::<PowerFlowResult>());
+    }
+
+    #[test]
+    fn test_missing_bus_in_setup_is_an_error() {
+        use crate::basic::ecs::powerflow::init::p_mw_inj;
+        let mut world = World::new();
+        world.insert_resource(NodeLookup::default());
+        world.insert_resource(PFCommonData {
+            wbase: 1.0,
+            f_hz: 50.0,
+            sbase: 1.0,
+        });
+        world.spawn((TargetBus(7), TargetPMW(1.0)));
+        let err = world
+            .run_system_once::<_, Result, _>(p_mw_inj)
+            .unwrap()
+            .unwrap_err();
+        assert!(err.to_string().contains("bus 7 does not exist"), "{err}");
+    }
+
+    #[test]
+    fn test_negative_bus_id_skips_init() {
+        let mut net = ieee118();
+        let bus = net.bus.iter().position(|b| b.index == 5).unwrap();
+        net.bus[bus].index = -1;
+        for line in net.line.as_mut().unwrap() {
+            for end in [&mut line.from_bus, &mut line.to_bus] {
+                if *end == 5 {
+                    *end = -1;
+                }
+            }
+        }
+        let grid = solve(net);
+        let report = grid.world().resource::<DiagnosticReport>();
+        assert!(report.blocks_init());
+        assert_eq!(report.of_kind(DiagnosticKind::NegativeBusId).count(), 1);
+        assert!(!grid.world().contains_resource::<PowerFlowMat>());
+    }
+
+    #[test]
+    fn test_missing_bus_is_quarantined() {
+        let mut net = ieee118();
+        net.line.as_mut().unwrap()[0].to_bus = 9999;
+        net.load.as_mut().unwrap()[0].bus = 4242;
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.world_mut().insert_resource(DiagnosticsConfig {
+            quarantine_missing_buses: true,
+        });
+        grid.init_pf_net();
+        grid.run_pf();
+        grid.post_process();
+        assert!(grid.pandapower_results().converged);
+
+        let report = grid.world().resource::<DiagnosticReport>().clone();
+        let missing = report
+            .of_kind(DiagnosticKind::MissingBus)
+            .map(|d| (d.element, d.severity))
+            .collect::<Vec<_>>();
+        assert_eq!(missing.len(), 2);
+        assert!(missing.contains(&("line", Severity::Warning)));
+        assert!(missing.contains(&("load", Severity::Warning)));
+        assert!(!report.blocks_init());
+        for d in report.of_kind(DiagnosticKind::MissingBus) {
+            let entity = d.entity.unwrap();
+            assert!(grid.world().entity(entity).contains::<OutOfService>());
+        }
+        assert!(report.to_string().contains("bus 9999 does not exist"));
+    }
+
+    #[test]
+    fn test_checks() {
+        let mut grid = solve(ieee118());
+        let world = grid.world_mut();
+        let mut q = world.query::<&mut TransformerDevice>();
+        q.iter_mut(world).next().unwrap().vk_percent = 0.0;
+        let mut q = world.query_filtered::<&mut TargetVmPu, Without<TargetVaDeg>>();
+        q.iter_mut(world).next().unwrap().0 = 1.5;
+        let mut q = world.query_filtered::<Entity, With<TargetVaDeg>>();
+        let ext_grid = q.iter(world).next().unwrap();
+        world.spawn((BusID(500), VNominal::default()));
+        world.spawn((BusID(500), VNominal::default()));
+
+        let report = grid.diagnostics();
+        let kinds = |kind| report.of_kind(kind).count();
+        assert_eq!(kinds(DiagnosticKind::InvalidImpedance), 1);
+        assert_eq!(kinds(DiagnosticKind::ImplausibleParameter), 1);
+        assert_eq!(kinds(DiagnosticKind::DuplicateBusId), 1);
+        assert_eq!(kinds(DiagnosticKind::DisconnectedBus), 2);
+        assert!(report.0.windows(2).all(|w| w[0].severity >= w[1].severity));
+
+        grid.world_mut().entity_mut(ext_grid).insert(OutOfService);
+        let report = grid.diagnostics();
+        let no_slack = report.of_kind(DiagnosticKind::NoSlack).collect::<Vec<_>>();
+        assert_eq!(no_slack.len(), 1);
+        assert_eq!(no_slack[0].entity, None);
+        assert_eq!(report.of_kind(DiagnosticKind::DisconnectedBus).count(), 0);
+    }
+}
@@ -13,6 +13,7 @@ use crate::io::pandapower;
 
 use bevy_ecs::entity::EntityHash;
 use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryFilter;
 use derive_more::{Deref, DerefMut};
 pub use ele_process::*;
 use nalgebra::Complex;
@@ -73,13 +74,19 @@ pub struct AdmittanceBranch {
 #[derive(Debug, Resource, Deref, DerefMut, serde::Serialize, serde::Deserialize)]
 pub struct PPNetwork(pub pandapower::Network);
 
-/// Resource that maps node indices (i64) to ECS entities.
+/// Resource that maps bus ids (i64) to ECS entities and matrix indices.
 ///
 /// `NodeLookup` helps in quickly finding the ECS entity corresponding to a node in the power flow network.
+/// Bus ids do not have to be contiguous: every bus gets a dense index, its position among
+/// all bus ids in ascending order, which is the row of the bus in the Y-bus, the voltage and
+/// injection vectors and the bus results. For buses numbered `0..n` the index equals the id.
+/// Negative ids are reserved for the ground node.
 #[derive(Default, Debug, Resource)]
 pub struct NodeLookup {
-    /// bus_id → entity 映射
-    pub forward: Vec<Option<Entity>>,
+    /// bus ids in ascending order; the position is the dense index
+    ids: Vec<i64>,
+    /// entity of each dense index
+    entities: Vec<Entity>,
     /// entity → bus_id 映射
     pub reverse: HashMap<Entity, i64, EntityHash>,
 }
@@ -116,62 +123,213 @@ pub struct PFCommonData {
 
 impl NodeLookup {
     pub fn len(&self) -> usize {
-        self.reverse.len()
+        self.ids.len()
     }
     pub fn is_empty(&self) -> bool {
-        self.reverse.is_empty()
+        self.ids.is_empty()
     }
+    /// `(bus_id, entity)` pairs in dense index order.
     pub fn iter(&self) -> impl Iterator<Item = (i64, Entity)> + '_ {
-        self.forward
-            .iter()
-            .enumerate()
-            .filter_map(|(i, v)| v.map(|e| (i as i64, e)))
+        self.ids.iter().copied().zip(self.entities.iter().copied())
+    }
+    /// Bus ids in dense index order.
+    pub fn ids(&self) -> &[i64] {
+        &self.ids
     }
     pub fn insert(&mut self, bus_id: i64, entity: Entity) {
-        let idx = bus_id as usize;
-        if self.forward.len() <= idx {
-            self.forward.resize_with(idx + 1, || None);
+        if self.reverse.contains_key(&entity) {
+            self.remove_entity(entity);
         }
-
-        if let Some(old_id) = self.reverse.insert(entity, bus_id)
-            && let Some(e) = self.forward.get_mut(old_id as usize)
-            && *e == Some(entity)
-        {
-            *e = None;
+        match self.ids.binary_search(&bus_id) {
+            Ok(idx) => {
+                self.reverse.remove(&self.entities[idx]);
+                self.entities[idx] = entity;
+            }
+            Err(idx) => {
+                self.ids.insert(idx, bus_id);
+                self.entities.insert(idx, entity);
+            }
         }
-
-        self.forward[idx] = Some(entity);
+        self.reverse.insert(entity, bus_id);
     }
     pub fn remove_entity(&mut self, entity: Entity) {
         if let Some(id) = self.reverse.remove(&entity)
-            && let Some(slot) = self.forward.get_mut(id as usize)
-            && *slot == Some(entity)
+            && let Ok(idx) = self.ids.binary_search(&id)
+            && self.entities[idx] == entity
         {
-            *slot = None;
+            self.ids.remove(idx);
+            self.entities.remove(idx);
         }
     }
 
     pub fn remove_id(&mut self, bus_id: i64) {
-        if let Some(Some(entity)) = self.forward.get_mut(bus_id as usize) {
-            self.reverse.remove(entity);
+        if let Ok(idx) = self.ids.binary_search(&bus_id) {
+            self.ids.remove(idx);
+            let entity = self.entities.remove(idx);
+            self.reverse.remove(&entity);
         }
     }
 
     pub fn get_entity(&self, bus_id: i64) -> Option<Entity> {
-        self.forward.get(bus_id as usize).and_then(|x| *x)
+        self.index(bus_id).map(|idx| self.entities[idx])
     }
 
     pub fn get_id(&self, entity: Entity) -> Option<i64> {
         self.reverse.get(&entity).copied()
     }
 
+    /// Dense (matrix) index of a bus id.
+    pub fn index(&self, bus_id: i64) -> Option<usize> {
+        self.ids.binary_search(&bus_id).ok()
+    }
+
+    /// Bus id at a dense index.
+    pub fn id_at(&self, index: usize) -> Option<i64> {
+        self.ids.get(index).copied()
+    }
+
     pub fn contains_id(&self, bus_id: i64) -> bool {
-        self.forward
-            .get(bus_id as usize)
-            .is_some_and(|e| e.is_some())
+        self.index(bus_id).is_some()
     }
 
     pub fn contains_entity(&self, entity: Entity) -> bool {
         self.reverse.contains_key(&entity)
     }
 }
+
+impl FromIterator<(i64, Entity)> for NodeLookup {
+    /// Builds the lookup with one sort; like [`NodeLookup::insert`], a later
+    /// pair replaces an earlier one with the same bus id.
+    fn from_iter<I: IntoIterator<Item = (i64, Entity)>>(pairs: I) -> Self {
+        let mut pairs = pairs.into_iter().collect::<Vec<_>>();
+        pairs.sort_by_key(|&(id, _)| id);
+        let mut lookup = NodeLookup::default();
+        for (id, entity) in pairs {
+            if lookup.ids.last() == Some(&id) {
+                let replaced = lookup.entities.pop().unwrap();
+                lookup.ids.pop();
+                lookup.reverse.remove(&replaced);
+            }
+            lookup.ids.push(id);
+            lookup.entities.push(entity);
+            lookup.reverse.insert(entity, id);
+        }
+        lookup
+    }
+}
+
+/// Index of an element in its pandapower table (the row position for tables
+/// read without an index).
+///
+/// Set when a network is loaded and kept in snapshots, so elements keep their
+/// index when other entities are despawned or an archived case is replayed.
+/// Entity indices offer no such guarantee, since Bevy reuses them.
+#[derive(
+    Component,
+    Deref,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct ElementIndex(pub usize);
+
+/// Generators of the pandapower `gen` table: voltage-controlled, without an
+/// angle set point.
+pub type GenFilter = (With<GeneratorCfg>, Without<TargetVaDeg>);
+
+/// External grids: generators with a voltage angle set point.
+pub type ExtGridFilter = (With<GeneratorCfg>, With<TargetVaDeg>);
+
+/// Element table of a pandapower network.
+///
+/// Elements of a kind are addressed by their [`ElementIndex`].
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ElementKind {
+    Line,
+    Trafo,
+    Gen,
+    ExtGrid,
+    Load,
+    SGen,
+    Shunt,
+    Switch,
+}
+
+/// Entities matching `F` ordered by [`ElementIndex`].
+///
+/// Elements without an index (e.g. built by the grid factory) are numbered
+/// after the indexed ones, in spawn order.
+fn indexed_entities<F: QueryFilter>(world: &mut World) -> Vec<Entity> {
+    let mut q = world.query_filtered::<(Entity, Option<&ElementIndex>), F>();
+    let mut rows = q
+        .iter(world)
+        .map(|(e, i)| (e, i.map(|i| i.0)))
+        .collect::<Vec<_>>();
+    rows.sort_by_key(|&(e, i)| (i.is_none(), i, e.index_u32()));
+    let next = rows.iter().filter_map(|r| r.1).max().map_or(0, |i| i + 1);
+    for (index, (e, i)) in (next..).zip(rows.iter_mut().filter(|r| r.1.is_none())) {
+        world.entity_mut(*e).insert(ElementIndex(index));
+        *i = Some(index);
+    }
+    rows.into_iter().map(|(e, _)| e).collect()
+}
+
+impl ElementKind {
+    pub const ALL: [ElementKind; 8] = [
+        ElementKind::Line,
+        ElementKind::Trafo,
+        ElementKind::Gen,
+        ElementKind::ExtGrid,
+        ElementKind::Load,
+        ElementKind::SGen,
+        ElementKind::Shunt,
+        ElementKind::Switch,
+    ];
+
+    /// Entities of all elements of this kind, ordered by [`ElementIndex`].
+    pub fn entities(self, world: &mut World) -> Vec<Entity> {
+        match self {
+            ElementKind::Line => indexed_entities::<With<Line>>(world),
+            ElementKind::Trafo => indexed_entities::<With<TransformerDevice>>(world),
+            ElementKind::Gen => indexed_entities::<GenFilter>(world),
+            ElementKind::ExtGrid => indexed_entities::<ExtGridFilter>(world),
+            ElementKind::Load => indexed_entities::<With<LoadCfg>>(world),
+            ElementKind::SGen => indexed_entities::<With<SGenDevice>>(world),
+            ElementKind::Shunt => indexed_entities::<With<ShuntDevice>>(world),
+            ElementKind::Switch => indexed_entities::<With<Switch>>(world),
+        }
+    }
+
+    /// Entities of all elements of this kind, keyed by [`ElementIndex`].
+    /// Cheaper than repeated [`entity`](Self::entity) calls when many
+    /// elements are looked up.
+    pub fn index_map(self, world: &mut World) -> HashMap<usize, Entity> {
+        self.entities(world)
+            .into_iter()
+            .filter_map(|e| Some((world.get::<ElementIndex>(e)?.0, e)))
+            .collect()
+    }
+
+    /// Entity of the element of this kind with [`ElementIndex`] `index`.
+    pub fn entity(self, world: &mut World, index: usize) -> Option<Entity> {
+        self.entities(world)
+            .into_iter()
+            .find(|&e| world.get::<ElementIndex>(e).is_some_and(|i| i.0 == index))
+    }
+}
+
+/// Gives every element without an [`ElementIndex`] the next free index of
+/// its kind.
+pub fn assign_element_indices(world: &mut World) {
+    for kind in ElementKind::ALL {
+        kind.entities(world);
+    }
+}
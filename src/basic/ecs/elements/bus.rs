@@ -27,7 +27,7 @@ impl Default for VBusPu {
     }
 }
 
-#[derive(Component, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Component, Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[component(storage = "SparseSet")]
 pub struct OutOfService;
 #[derive(Component, Eq, Ord, PartialEq, PartialOrd)]
@@ -135,18 +135,21 @@ impl From<NameWrapper> for Name {
 pub mod bus_systems {
 
     use crate::basic::ecs::elements::NodeLookup;
+    use bevy_ecs::entity::EntityHashSet;
 
     use super::*;
     /// Initializes the [`NodeLookup`] resource from all existing [`BusID`] components.
     ///
     /// Also inserts default values for [`SBusInjPu`] and [`VBusPu`] into each bus entity.
     pub fn init_node_lookup(mut cmd: Commands, bus_ids: Query<(Entity, &BusID)>) {
-        let mut node_lookup = NodeLookup::default();
-        bus_ids.iter().for_each(|(entity, bus_id)| {
-            node_lookup.insert(bus_id.0, entity);
-            cmd.entity(entity)
-                .insert((SBusInjPu::default(), VBusPu::default()));
-        });
+        let node_lookup = bus_ids
+            .iter()
+            .map(|(entity, bus_id)| {
+                cmd.entity(entity)
+                    .insert((SBusInjPu::default(), VBusPu::default()));
+                (bus_id.0, entity)
+            })
+            .collect::<NodeLookup>();
         cmd.insert_resource(node_lookup);
     }
     /// Updates the [`NodeLookup`] in response to changes in [`BusID`] components.
@@ -157,16 +160,17 @@ pub mod bus_systems {
         changed: Query<(Entity, &BusID), Changed<BusID>>,
         mut removed: RemovedComponents<BusID>,
     ) {
-        for entity in removed.read() {
-            lookup.remove_entity(entity);
-        }
-
-        for (entity, bus_id) in changed.iter() {
-            if lookup.contains_entity(entity) {
-                lookup.remove_entity(entity);
-            }
-            lookup.insert(bus_id.0, entity);
+        let stale = removed
+            .read()
+            .chain(changed.iter().map(|(entity, _)| entity))
+            .collect::<EntityHashSet>();
+        if stale.is_empty() {
+            return;
         }
+        // Rebuild in one pass so loading many buses at once stays O(n log n).
+        let kept = lookup.iter().filter(|(_, e)| !stale.contains(e));
+        let added = changed.iter().map(|(entity, bus_id)| (bus_id.0, entity));
+        *lookup = kept.chain(added).collect();
     }
 }
 #[cfg(test)]
@@ -210,4 +214,32 @@ mod tests {
         a.to_file("test_bus.toml", None).unwrap();
         remove_file("test_bus.toml").unwrap();
     }
+
+    #[test]
+    fn test_node_lookup_build_and_update() {
+        use crate::basic::ecs::elements::NodeLookup;
+        use bevy_ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        let a = world.spawn(BusID(7)).id();
+        let b = world.spawn(BusID(-3)).id();
+        let c = world.spawn(BusID(2)).id();
+        world.run_system_once(bus_systems::init_node_lookup).unwrap();
+        let lookup = world.resource::<NodeLookup>();
+        assert_eq!(lookup.ids(), [-3, 2, 7]);
+        assert_eq!(lookup.get_entity(7), Some(a));
+        assert_eq!(lookup.get_id(b), Some(-3));
+
+        // Renumber one bus, remove another and add a new one.
+        world.entity_mut(a).insert(BusID(9));
+        world.despawn(b);
+        let d = world.spawn(BusID(5)).id();
+        world.run_system_once(bus_systems::update_node_lookup).unwrap();
+        let lookup = world.resource::<NodeLookup>();
+        assert_eq!(lookup.ids(), [2, 5, 9]);
+        assert_eq!(lookup.get_entity(9), Some(a));
+        assert_eq!(lookup.get_entity(5), Some(d));
+        assert_eq!(lookup.get_entity(2), Some(c));
+        assert!(!lookup.contains_entity(b));
+    }
 }
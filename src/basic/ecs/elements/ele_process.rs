@@ -13,6 +13,8 @@
 
 use super::switch;
 use crate::basic::ecs::elements::*;
+use crate::basic::ecs::diagnostics::pf_init_allowed;
+use crate::basic::ecs::factory::resolve_std_types;
 use crate::basic::ecs::plugin::BeforePFInitStage;
 use crate::prelude::ecs::network::SolverStage::BeforeSolve;
 
@@ -48,12 +50,15 @@ impl SnaptShotRegGroup for DefaultSnapShotReg {
         ShuntSnapShotReg::register_snap_shot(registry);
         SGenSnapShotReg::register_snap_shot(registry);
         SwitchSnapShotReg::register_snap_shot(registry);
+        registry.register::<ElementIndex>();
     }
 }
 
 /// A Bevy plugin that sets up ECS systems for power grid elements.
 ///
-/// - Initializes bus lookup tables.
+/// - Resolves missing line and transformer parameters from their standard types.
+/// - Numbers elements that were spawned without an [`ElementIndex`].
+/// - Initializes bus lookup tables unless the diagnostics block the power flow.
 /// - Sets up transformers, shunts, and lines during startup.
 /// - Registers dynamic bus update logic during solver stage.
 #[derive(Default)]
@@ -64,12 +69,17 @@ impl bevy_app::Plugin for ElementSetupPlugin {
         app.add_systems(
             Startup,
             (
-                bus::bus_systems::init_node_lookup.in_set(BeforePFInitStage),
+                resolve_std_types.in_set(BeforePFInitStage),
+                assign_element_indices.in_set(BeforePFInitStage),
+                bus::bus_systems::init_node_lookup
+                    .in_set(BeforePFInitStage)
+                    .run_if(pf_init_allowed),
                 (
                     trans::trans_systems::setup_transformer,
                     line::line_systems::setup_line_systems,
                     shunt::shunt_systems::setup_shunt_systems,
-                ),
+                )
+                    .run_if(pf_init_allowed),
             )
                 .chain()
                 .in_set(BeforePFInitStage),
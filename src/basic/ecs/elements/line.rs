@@ -79,12 +79,26 @@ pub struct LineBundle {
     pub std_spec: Option<StandardModelType>,
     /// Optional marker if this line is out of service
     pub out: Option<OutOfService>,
+    /// Optional loading limit (e.g. for OPF)
+    pub max_loading: Option<MaxLoadingPercent>,
+    /// Optional pandapower line type (e.g. "ol" or "cs")
+    pub line_type: Option<LineType>,
 }
 
-/// Standard line model name (e.g. from library or external spec).
+/// Maximum loading of a line (%), kept from the pandapower `max_loading_percent`
+/// column.
+#[derive(Component, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MaxLoadingPercent(pub f64);
+
+/// pandapower line type: `"ol"` for overhead lines, `"cs"` for cables.
+#[derive(Component, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LineType(pub String);
+
+/// Standard line or transformer type name (e.g. "NAYY 4x150 SE").
 ///
-/// This allows referencing a known cable type or vendor model
-/// for reuse of parameter templates. **Currently no use.**
+/// References a type of the [`StdTypeLibrary`](crate::basic::ecs::factory::StdTypeLibrary);
+/// parameters the element leaves open are taken from it by
+/// [`resolve_std_types`](crate::basic::ecs::factory::resolve_std_types).
 #[derive(Component, Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct StandardModelType(pub String);
 /// Registers components relevant to line modeling in the snapshot system.
@@ -111,6 +125,8 @@ impl From<&Line> for LineBundle {
             name: line.name.clone().map(Name::new),
             std_spec: line.std_type.clone().map(StandardModelType),
             out: (!line.in_service).then_some(OutOfService),
+            max_loading: line.max_loading_percent.map(MaxLoadingPercent),
+            line_type: line.type_.clone().map(LineType),
         }
     }
 }
@@ -123,6 +139,8 @@ impl SnaptShotRegGroup for LineSnapshotReg {
         reg.register::<ToBus>();
         reg.register::<LineParams>();
         reg.register::<StandardModelType>();
+        reg.register::<MaxLoadingPercent>();
+        reg.register::<LineType>();
         reg.register::<basic::ecs::elements::Line>();
     }
 }
@@ -133,19 +151,29 @@ pub mod line_systems {
     use crate::basic::ecs::{elements::*, network::GND};
 
     use super::*;
+    #[allow(clippy::type_complexity)]
     pub fn setup_line_systems(
         mut commands: Commands,
-        q: Query<(Entity, &LineParams, &FromBus, &ToBus), Without<OutOfService>>,
+        q: Query<
+            (Entity, &LineParams, &FromBus, &ToBus, Option<&ElementIndex>),
+            Without<OutOfService>,
+        >,
         oos: Query<Entity, (With<LineParams>, With<OutOfService>)>,
+        switches: Query<(&Switch, &SwitchState, Has<OutOfService>)>,
         buses: Query<&VNominal>,
         lut: Res<NodeLookup>,
         common: Res<PFCommonData>,
-    ) {
+    ) -> Result {
         // Out-of-service lines contribute nothing to the Y-bus.
         for entity in &oos {
             commands.entity(entity).despawn_related::<Children>();
         }
-        for (entity, params, from, to) in &q {
+        let open_ends = open_branch_ends(switches);
+        for (entity, params, from, to, index) in &q {
+            let open = |bus: i64| {
+                index.is_some_and(|i| open_ends.contains(&(ElementKind::Line, i.0, bus)))
+            };
+            let (from_open, to_open) = (open(from.0), open(to.0));
             let length = params.length_km;
             let parallel = params.parallel as f64;
             let wbase = common.wbase;
@@ -157,11 +185,29 @@ pub mod line_systems {
             let rl = params.r_ohm_per_km * length / parallel;
             let xl = params.x_ohm_per_km * length / parallel;
             let y_series = 1.0 / Complex::new(rl, xl);
-            let vbase = lut.get_entity(from.0).unwrap();
-            let vbase = buses.get(vbase).unwrap().0.0;
+            let bus = lut
+                .get_entity(from.0)
+                .ok_or_else(|| format!("line {entity}: bus {} does not exist", from.0))?;
+            let vbase = buses.get(bus)?.0.0;
             // Rebuild admittance children from scratch so re-running setup
             // (e.g. a second init_pf) does not duplicate branches.
             commands.entity(entity).despawn_related::<Children>();
+            if from_open || to_open {
+                // Open at one end (or both): the line hangs off the other
+                // end, whose shunt sees the far one through the series branch.
+                commands.entity(entity).insert(Line).with_children(|p| {
+                    let bus = if from_open { to.0 } else { from.0 };
+                    if from_open != to_open && y_shunt != Complex::new(0.0, 0.0) {
+                        let y = y_shunt + y_series * y_shunt / (y_series + y_shunt);
+                        p.spawn(AdmittanceBranch {
+                            y: Admittance(y),
+                            port: Port2(vector![bus, GND]),
+                            v_base: VBase(vbase),
+                        });
+                    }
+                });
+                continue;
+            }
             // Shunt: from and to → GND
             commands.entity(entity).insert(Line).with_children(|p| {
                 if g != 0.0 || b != 0.0 {
@@ -185,5 +231,6 @@ pub mod line_systems {
                 });
             });
         }
+        Ok(())
     }
 }
@@ -56,6 +56,8 @@ pub struct NodeMerge {
 }
 
 /// Represents the mapping of original nodes to their merged nodes after aggregation.
+///
+/// Nodes are dense bus indices ([`NodeLookup::index`]), not bus ids.
 #[derive(
     Default, Debug, Clone, Deref, DerefMut, Resource, serde::Serialize, serde::Deserialize,
 )]
@@ -151,32 +153,35 @@ impl NodeMerge {
 /// Processes the state of switches and updates network components accordingly.
 ///
 /// This function performs node merging or adds admittance branches based on the state of switches.
+/// Out-of-service switches count as open. Re-running it (in the `PFInit`
+/// rebuild) drops the branches of switches that have been opened since.
 #[allow(dead_code)]
 pub fn process_switch_state(
     mut cmd: Commands,
     nodes: Res<NodeLookup>,
     buses: Query<&VNominal>,
-    q: Query<(Entity, &Switch, &SwitchState)>,
-) {
-    let node_idx: Vec<u64> = nodes.reverse.values().map(|&x| x as u64).collect();
+    q: Query<(Entity, &Switch, &SwitchState, Has<OutOfService>)>,
+) -> Result {
+    // Nodes are merged by their dense index, the row of the bus in the Y-bus.
+    let node_idx: Vec<u64> = (0..nodes.len() as u64).collect();
     let mut union_find: Option<NodeMerge> = if q.iter().count() > 0 {
         Some(NodeMerge::new(&node_idx))
     } else {
         None
     };
 
-    q.iter().for_each(|(entity, switch, closed)| {
+    q.iter().try_for_each(|(entity, switch, state, oos)| -> Result {
         let _z_ohm = switch.z_ohm;
+        let closed = **state && !oos;
+        cmd.entity(entity).remove::<AdmittanceBranch>();
         match switch.et {
-            SwitchType::SwitchTwoBuses if **closed && _z_ohm == 0.0 => {
-                union_find
-                    .as_mut()
-                    .unwrap()
-                    .union(switch.bus as u64, switch.element as u64);
+            SwitchType::SwitchTwoBuses if closed && _z_ohm == 0.0 => {
+                if let (Some(a), Some(b)) = (nodes.index(switch.bus), nodes.index(switch.element)) {
+                    union_find.as_mut().unwrap().union(a as u64, b as u64);
+                }
             }
-            SwitchType::SwitchTwoBuses if **closed => {
-                let bus = nodes.get_entity(switch.bus).unwrap();
-                let v_base = *buses.get(bus).unwrap().0;
+            SwitchType::SwitchTwoBuses if closed => {
+                let v_base = switch_v_base(&nodes, &buses, entity, switch)?;
                 cmd.entity(entity).insert(AdmittanceBranch {
                     y: Admittance(Complex::new(_z_ohm, 0.0)),
                     port: Port2(vector![switch.bus, switch.element]),
@@ -185,39 +190,43 @@ pub fn process_switch_state(
             }
             _ => {}
         }
-    });
+        Ok(())
+    })?;
 
     if let Some(union_find) = union_find {
         cmd.insert_resource(NodeMapping(union_find.get_node_mapping(0)));
     }
+    Ok(())
 }
 
 /// Processes the state of switches and updates network components accordingly.
 ///
 /// This function adds admittance branches based on the state of switches, no ideal switch.
+/// Out-of-service switches count as open; like [`process_switch_state`] it
+/// can be re-run after switching.
 #[allow(dead_code)]
 pub fn process_switch_state_admit(
     mut cmd: Commands,
     nodes: Res<NodeLookup>,
     buses: Query<&VNominal>,
-    q: Query<(Entity, &Switch, &SwitchState)>,
-) {
-    q.iter().for_each(|(entity, switch, closed)| {
+    q: Query<(Entity, &Switch, &SwitchState, Has<OutOfService>)>,
+) -> Result {
+    q.iter().try_for_each(|(entity, switch, state, oos)| -> Result {
         let _z_ohm = switch.z_ohm;
+        let closed = **state && !oos;
+        cmd.entity(entity).remove::<AdmittanceBranch>();
         match switch.et {
-            SwitchType::SwitchTwoBuses if **closed && _z_ohm == 0.0 => {
+            SwitchType::SwitchTwoBuses if closed && _z_ohm == 0.0 => {
                 let (node1, node2) = (switch.bus, switch.element);
-                let bus = nodes.get_entity(switch.bus).unwrap();
-                let v_base = *buses.get(bus).unwrap().0;
+                let v_base = switch_v_base(&nodes, &buses, entity, switch)?;
                 cmd.entity(entity).insert(AdmittanceBranch {
                     y: Admittance(Complex::new(1e6, 0.0)),
                     port: Port2(vector![node1, node2]),
                     v_base: VBase(v_base),
                 });
             }
-            SwitchType::SwitchTwoBuses if **closed => {
-                let bus = nodes.get_entity(switch.bus).unwrap();
-                let v_base = *buses.get(bus).unwrap().0;
+            SwitchType::SwitchTwoBuses if closed => {
+                let v_base = switch_v_base(&nodes, &buses, entity, switch)?;
                 cmd.entity(entity).insert(AdmittanceBranch {
                     y: Admittance(Complex::new(_z_ohm, 0.0)),
                     port: Port2(vector![switch.bus, switch.element]),
@@ -226,7 +235,44 @@ pub fn process_switch_state_admit(
             }
             _ => {}
         }
-    });
+        Ok(())
+    })
+}
+
+/// Ends of lines and transformers cut off by open line (`"l"`) and
+/// transformer (`"t"`) switches, as `(kind, element index, bus)`.
+/// Out-of-service switches count as open.
+///
+/// Unlike bus-bus switches these are honoured without a switch plugin: the
+/// element setup leaves the open end floating.
+pub fn open_branch_ends<'a>(
+    switches: impl IntoIterator<Item = (&'a Switch, &'a SwitchState, bool)>,
+) -> HashSet<(ElementKind, usize, i64)> {
+    switches
+        .into_iter()
+        .filter(|(_, state, oos)| !state.0 || *oos)
+        .filter_map(|(switch, _, _)| {
+            let kind = match switch.et {
+                SwitchType::SwitchBusLine => ElementKind::Line,
+                SwitchType::SwitchBusTransformer => ElementKind::Trafo,
+                _ => return None,
+            };
+            Some((kind, usize::try_from(switch.element).ok()?, switch.bus))
+        })
+        .collect()
+}
+
+/// Nominal voltage of the `bus` side of a switch.
+fn switch_v_base(
+    nodes: &NodeLookup,
+    buses: &Query<&VNominal>,
+    entity: Entity,
+    switch: &Switch,
+) -> Result<f64> {
+    let bus = nodes
+        .get_entity(switch.bus)
+        .ok_or_else(|| format!("switch {entity}: bus {} does not exist", switch.bus))?;
+    Ok(*buses.get(bus)?.0)
 }
 
 /// Builds an aggregation matrix based on the provided node mapping.
@@ -514,7 +560,8 @@ fn update_power_flow_matrix(
     mat_v: &CscMatrix<f64>,
     new_total_nodes: usize,
 ) {
-    let permutation_matrix = create_permutation_matrix(&pv, &pq, &ext, new_total_nodes);
+    // Solver ordering is PQ, PV, slack; must agree with `to_perm` below.
+    let permutation_matrix = create_permutation_matrix(&pq, &pv, &ext, new_total_nodes);
     mats.reorder = permutation_matrix;
     
     let mut to_perm = vec![0; new_total_nodes];
@@ -647,7 +694,8 @@ mod tests {
         pf_net.init_pf_net();
         pf_net
             .world_mut()
-            .run_system_once(process_switch_state)
+            .run_system_once::<_, Result, _>(process_switch_state)
+            .unwrap()
             .unwrap();
         // 3. 运行系统并获取结果矩阵 `mat` 和 `mat_v`
         let (mat, mat_v) = pf_net
@@ -715,7 +763,8 @@ mod tests {
         pf_net.init_pf_net();
         pf_net
             .world_mut()
-            .run_system_once(process_switch_state)
+            .run_system_once::<_, Result, _>(process_switch_state)
+            .unwrap()
             .unwrap();
         // Step 3: Run system and retrieve result matrices
         let (mat, mat_v) = pf_net
@@ -22,6 +22,11 @@ pub struct Switch {
 )]
 pub struct SwitchState(pub bool);
 
+/// pandapower switch type, e.g. `"CB"` (circuit breaker), `"LS"` (load
+/// switch) or `"DS"` (disconnector). Informational only.
+#[derive(Debug, Clone, Component, serde::Serialize, serde::Deserialize)]
+pub struct SwitchDeviceType(pub String);
+
 use rustpower_proc_marco::DeferBundle;
 
 #[derive(DeferBundle, Default, Debug, Clone)]
@@ -29,6 +34,7 @@ pub struct SwitchBundle {
     pub switch: Switch,
     pub state: SwitchState,
     pub name: Option<Name>,
+    pub device_type: Option<SwitchDeviceType>,
 }
 use crate::io::pandapower::Switch as PSwitch;
 
@@ -45,6 +51,7 @@ impl From<&PSwitch> for SwitchBundle {
             switch,
             state,
             name: value.name.clone().map(Name::new),
+            device_type: value.type_.clone().map(SwitchDeviceType),
         }
     }
 }
@@ -53,6 +60,7 @@ impl SnaptShotRegGroup for SwitchSnapShotReg {
     fn register_snap_shot(reg: &mut SnapshotRegistry) {
         reg.register::<SwitchState>();
         reg.register::<Switch>();
+        reg.register::<SwitchDeviceType>();
     }
 }
 
@@ -201,11 +201,18 @@ pub mod trans_systems {
     use nalgebra::{Complex, ComplexField};
 
     use super::*;
-    use crate::basic::ecs::elements::OutOfService;
+    use crate::basic::ecs::elements::{
+        ElementIndex, ElementKind, OutOfService, Switch, SwitchState, open_branch_ends,
+    };
+    #[allow(clippy::type_complexity)]
     pub fn setup_transformer(
         mut commands: Commands,
-        q: Query<(Entity, &TransformerDevice), Without<OutOfService>>,
+        q: Query<
+            (Entity, &TransformerDevice, &FromBus, &ToBus, Option<&ElementIndex>),
+            Without<OutOfService>,
+        >,
         oos: Query<Entity, (With<TransformerDevice>, With<OutOfService>)>,
+        switches: Query<(&Switch, &SwitchState, Has<OutOfService>)>,
     ) {
         // Out-of-service transformers contribute nothing to the Y-bus:
         // drop their admittance children and matrix patch.
@@ -213,14 +220,21 @@ pub mod trans_systems {
             commands.entity(entity).despawn_related::<Children>();
             commands.entity(entity).remove::<Port4MatPatch>();
         }
-        q.iter().for_each(|(entity, transformer)| {
-            setup_transformer_admittance(&mut commands, entity, transformer);
+        let open_ends = open_branch_ends(switches);
+        q.iter().for_each(|(entity, transformer, hv, lv, index)| {
+            let open = |bus: i64| {
+                index.is_some_and(|i| open_ends.contains(&(ElementKind::Trafo, i.0, bus)))
+            };
+            let open = (open(hv.0), open(lv.0));
+            setup_transformer_admittance(&mut commands, entity, transformer, open);
         });
     }
+    /// `open` flags the hv and lv ends cut off by open transformer switches.
     fn setup_transformer_admittance(
         commands: &mut Commands,
         parent: Entity,
         dev: &TransformerDevice,
+        open: (bool, bool),
     ) {
         commands.entity(parent).despawn_related::<Children>();
 
@@ -258,6 +272,21 @@ pub mod trans_systems {
         }
 
         let g = t.conjugate() * g * t;
+        // An open end carries no current: eliminate its voltage.
+        let zero = Complex::new(0.0, 0.0);
+        let g = match open {
+            (false, false) => g,
+            (true, true) => {
+                commands.entity(parent).remove::<Port4MatPatch>();
+                return;
+            }
+            (true, false) => {
+                Matrix2::new(zero, zero, zero, g[(1, 1)] - g[(1, 0)] * g[(0, 1)] / g[(0, 0)])
+            }
+            (false, true) => {
+                Matrix2::new(g[(0, 0)] - g[(0, 1)] * g[(1, 0)] / g[(1, 1)], zero, zero, zero)
+            }
+        };
         commands.entity(parent).insert(Port4MatPatch(g));
     }
 }
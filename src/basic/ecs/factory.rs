@@ -4,10 +4,16 @@ use crate::basic::ecs::elements::*;
 use crate::basic::ecs::network::{PowerGrid, DataOps};
 use std::marker::PhantomData;
 use bevy_ecs::name::Name;
-use crate::io::pandapower::SwitchType;
+use crate::io::pandapower::{LineStdType, StdTypes, SwitchType, TrafoStdType, pandapower_std_types};
 use crate::bevy_cmdbuffer::buffer::HarvardCommandBuffer;
 use crate::basic::ecs::elements::units::PerUnit;
 
+/// Catalog of line and transformer standard types, keyed by type name.
+///
+/// [`StdTypeLibrary::pandapower`] starts from pandapower's built-in types;
+/// the `std_types` of a loaded pandapower network and types added through
+/// [`GridFactory`] extend it. Elements carrying a [`StandardModelType`] get
+/// their missing parameters from here, see [`resolve_std_types`].
 #[derive(Resource, Default)]
 pub struct StdTypeLibrary {
     pub lines: HashMap<String, LineTemplate>,
@@ -32,9 +38,188 @@ pub struct TrafoTemplate {
     pub vkr_percent: f64,
     pub pfe_kw: f64,
     pub i0_percent: f64,
+    pub shift_degree: f64,
+    pub tap: TapChanger,
+}
+
+impl From<&LineStdType> for LineTemplate {
+    fn from(t: &LineStdType) -> Self {
+        Self {
+            r_ohm_per_km: t.r_ohm_per_km,
+            x_ohm_per_km: t.x_ohm_per_km,
+            c_nf_per_km: t.c_nf_per_km,
+            g_us_per_km: t.g_us_per_km,
+            max_i_ka: t.max_i_ka,
+        }
+    }
+}
+
+impl From<&TrafoStdType> for TrafoTemplate {
+    fn from(t: &TrafoStdType) -> Self {
+        Self {
+            sn_mva: t.sn_mva,
+            vn_hv_kv: t.vn_hv_kv,
+            vn_lv_kv: t.vn_lv_kv,
+            vk_percent: t.vk_percent,
+            vkr_percent: t.vkr_percent,
+            pfe_kw: t.pfe_kw,
+            i0_percent: t.i0_percent,
+            shift_degree: t.shift_degree,
+            tap: TapChanger {
+                side: t.tap_side.clone(),
+                neutral: t.tap_neutral,
+                max: t.tap_max,
+                min: t.tap_min,
+                pos: t.tap_neutral,
+                step_degree: t.tap_step_degree,
+                step_percent: t.tap_step_percent,
+                is_phase_shifter: t.tap_phase_shifter,
+            },
+        }
+    }
+}
+
+/// A parameter left open, i.e. not finite.
+fn missing(v: f64) -> bool {
+    !v.is_finite()
+}
+
+fn fill(v: &mut f64, template: f64) {
+    if missing(*v) {
+        *v = template;
+    }
+}
+
+fn fill_opt<T: Clone>(v: &mut Option<T>, template: &Option<T>) {
+    if v.is_none() {
+        *v = template.clone();
+    }
+}
+
+impl LineTemplate {
+    /// Line parameters of this type for a single system of `length_km`.
+    pub fn params(&self, length_km: f64) -> LineParams {
+        LineParams {
+            r_ohm_per_km: self.r_ohm_per_km,
+            x_ohm_per_km: self.x_ohm_per_km,
+            c_nf_per_km: self.c_nf_per_km,
+            g_us_per_km: self.g_us_per_km,
+            length_km,
+            df: 1.0,
+            parallel: 1,
+            max_i_ka: self.max_i_ka,
+        }
+    }
+
+    /// Whether `p` has parameters left open that this type provides.
+    pub fn has_missing(p: &LineParams) -> bool {
+        [p.r_ohm_per_km, p.x_ohm_per_km, p.c_nf_per_km, p.g_us_per_km, p.max_i_ka]
+            .into_iter()
+            .any(missing)
+            || p.max_i_ka == 0.0
+    }
+
+    /// Fills the open parameters of `p`; given values are kept. A current
+    /// rating of 0 counts as open.
+    pub fn fill(&self, p: &mut LineParams) {
+        fill(&mut p.r_ohm_per_km, self.r_ohm_per_km);
+        fill(&mut p.x_ohm_per_km, self.x_ohm_per_km);
+        fill(&mut p.c_nf_per_km, self.c_nf_per_km);
+        fill(&mut p.g_us_per_km, self.g_us_per_km);
+        if missing(p.max_i_ka) || p.max_i_ka == 0.0 {
+            p.max_i_ka = self.max_i_ka;
+        }
+    }
+}
+
+impl TrafoTemplate {
+    /// Transformer parameters of this type, with the tap at its neutral
+    /// position.
+    pub fn device(&self) -> TransformerDevice {
+        TransformerDevice {
+            df: 1.0,
+            i0_percent: self.i0_percent,
+            pfe_kw: self.pfe_kw,
+            vk_percent: self.vk_percent,
+            vkr_percent: self.vkr_percent,
+            shift_degree: self.shift_degree,
+            sn_mva: self.sn_mva,
+            vn_hv_kv: self.vn_hv_kv,
+            vn_lv_kv: self.vn_lv_kv,
+            max_loading_percent: None,
+            parallel: 1,
+            tap: Some(self.tap.clone()),
+        }
+    }
+
+    /// Whether `dev` has parameters left open that this type provides.
+    pub fn has_missing(dev: &TransformerDevice) -> bool {
+        let tap_open = dev.tap.as_ref().is_none_or(|t| {
+            t.side.is_none()
+                || t.neutral.is_none()
+                || t.max.is_none()
+                || t.min.is_none()
+                || t.pos.is_none()
+                || t.step_degree.is_none()
+                || t.step_percent.is_none()
+        });
+        [
+            dev.sn_mva,
+            dev.vn_hv_kv,
+            dev.vn_lv_kv,
+            dev.vk_percent,
+            dev.vkr_percent,
+            dev.pfe_kw,
+            dev.i0_percent,
+            dev.shift_degree,
+        ]
+        .into_iter()
+        .any(missing)
+            || tap_open
+    }
+
+    /// Fills the open parameters of `dev`, including unset tap changer
+    /// fields; given values are kept. An unset tap position becomes the
+    /// neutral one.
+    pub fn fill(&self, dev: &mut TransformerDevice) {
+        fill(&mut dev.sn_mva, self.sn_mva);
+        fill(&mut dev.vn_hv_kv, self.vn_hv_kv);
+        fill(&mut dev.vn_lv_kv, self.vn_lv_kv);
+        fill(&mut dev.vk_percent, self.vk_percent);
+        fill(&mut dev.vkr_percent, self.vkr_percent);
+        fill(&mut dev.pfe_kw, self.pfe_kw);
+        fill(&mut dev.i0_percent, self.i0_percent);
+        fill(&mut dev.shift_degree, self.shift_degree);
+        let tap = dev.tap.get_or_insert_with(|| self.tap.clone());
+        fill_opt(&mut tap.side, &self.tap.side);
+        fill_opt(&mut tap.neutral, &self.tap.neutral);
+        fill_opt(&mut tap.max, &self.tap.max);
+        fill_opt(&mut tap.min, &self.tap.min);
+        fill_opt(&mut tap.step_degree, &self.tap.step_degree);
+        fill_opt(&mut tap.step_percent, &self.tap.step_percent);
+        if tap.pos.is_none() {
+            tap.pos = tap.neutral;
+        }
+    }
 }
 
 impl StdTypeLibrary {
+    /// Library of pandapower's built-in line and transformer types.
+    pub fn pandapower() -> Self {
+        let mut lib = Self::default();
+        lib.extend_from(pandapower_std_types());
+        lib
+    }
+
+    /// Adds the types of a pandapower `std_types` section, replacing types
+    /// of the same name.
+    pub fn extend_from(&mut self, types: &StdTypes) {
+        self.lines
+            .extend(types.line.iter().map(|(k, t)| (k.clone(), t.into())));
+        self.trafos
+            .extend(types.trafo.iter().map(|(k, t)| (k.clone(), t.into())));
+    }
+
     pub fn add_line_type(&mut self, name: String, r: f64, x: f64, c: f64, g: f64, max_i: f64) {
         self.lines.insert(name, LineTemplate {
             r_ohm_per_km: r,
@@ -54,10 +239,62 @@ impl StdTypeLibrary {
             vkr_percent: vkr,
             pfe_kw: pfe,
             i0_percent: i0,
+            shift_degree: 0.0,
+            tap: TapChanger {
+                side: Some("hv".to_string()),
+                neutral: Some(0.0),
+                max: Some(10.0),
+                min: Some(-10.0),
+                pos: Some(0.0),
+                step_degree: Some(0.0),
+                step_percent: Some(1.25),
+                is_phase_shifter: false,
+            },
         });
     }
 }
 
+/// Fills the missing parameters of lines and transformers that name a
+/// [`StandardModelType`] from the [`StdTypeLibrary`], or from pandapower's
+/// built-in types when no library is present.
+///
+/// Parameters that are not finite are missing, as is a line current rating
+/// of 0 and any unset tap changer field. Unknown type names are left
+/// alone; the diagnostics report their missing parameters.
+pub fn resolve_std_types(
+    lib: Option<Res<StdTypeLibrary>>,
+    mut lines: Query<(&StandardModelType, &mut LineParams)>,
+    mut trafos: Query<(&StandardModelType, &mut TransformerDevice)>,
+) {
+    let open_lines = lines.iter().any(|(_, p)| LineTemplate::has_missing(p));
+    let open_trafos = trafos.iter().any(|(_, d)| TrafoTemplate::has_missing(d));
+    if !open_lines && !open_trafos {
+        return;
+    }
+    let builtin;
+    let lib = match lib.as_deref() {
+        Some(lib) => lib,
+        None => {
+            builtin = StdTypeLibrary::pandapower();
+            &builtin
+        }
+    };
+    for (st, mut p) in &mut lines {
+        if let Some(t) = lib.lines.get(&st.0)
+            && LineTemplate::has_missing(&p)
+        {
+            t.fill(&mut p);
+        }
+    }
+    for (st, mut dev) in &mut trafos {
+        if let Some(t) = lib.trafos.get(&st.0)
+            && TrafoTemplate::has_missing(&dev)
+        {
+            t.fill(&mut dev);
+        }
+    }
+}
+
 pub trait GridFactory {
     fn set_base(&mut self, f_hz: f64, sn_mva: f64);
     fn add_std_line_type(&mut self, name: String, r: f64, x: f64, c: f64, g: f64, max_i: f64);
@@ -74,6 +311,14 @@ pub trait GridFactory {
     fn add_switch(&mut self, buffer: &mut HarvardCommandBuffer, bus: i64, element: i64, et: String, closed: bool, name: Option<String>, z_ohm: f64) -> Entity;
 }
 
+impl PowerGrid {
+    /// The grid's standard type library, starting from pandapower's
+    /// built-in types if the grid has none yet.
+    fn std_type_library(&mut self) -> Mut<'_, StdTypeLibrary> {
+        self.world_mut().get_resource_or_insert_with(StdTypeLibrary::pandapower)
+    }
+}
+
 impl GridFactory for PowerGrid {
     fn set_base(&mut self, f_hz: f64, sn_mva: f64) {
         self.world_mut().insert_resource(PFCommonData {
@@ -84,17 +329,11 @@ impl GridFactory for PowerGrid {
     }
 
     fn add_std_line_type(&mut self, name: String, r: f64, x: f64, c: f64, g: f64, max_i: f64) {
-        if !self.world().contains_resource::<StdTypeLibrary>() {
-            self.world_mut().init_resource::<StdTypeLibrary>();
-        }
-        self.world_mut().resource_mut::<StdTypeLibrary>().add_line_type(name, r, x, c, g, max_i);
+        self.std_type_library().add_line_type(name, r, x, c, g, max_i);
     }
 
     fn add_std_trafo_type(&mut self, name: String, sn_mva: f64, vn_hv: f64, vn_lv: f64, vk: f64, vkr: f64, pfe: f64, i0: f64) {
-        if !self.world().contains_resource::<StdTypeLibrary>() {
-            self.world_mut().init_resource::<StdTypeLibrary>();
-        }
-        self.world_mut().resource_mut::<StdTypeLibrary>().add_trafo_type(name, sn_mva, vn_hv, vn_lv, vk, vkr, pfe, i0);
+        self.std_type_library().add_trafo_type(name, sn_mva, vn_hv, vn_lv, vk, vkr, pfe, i0);
     }
 
     fn add_bus(&mut self, buffer: &mut HarvardCommandBuffer, id: i64, vn_kv: f64, name: Option<String>, vm_min: f64, vm_max: f64, zone: i64) -> Entity {
@@ -111,19 +350,10 @@ impl GridFactory for PowerGrid {
     }
 
     fn add_line(&mut self, buffer: &mut HarvardCommandBuffer, from_bus: i64, to_bus: i64, length_km: f64, std_type: Option<String>, params: Option<LineParams>, name: Option<String>) -> Entity {
-        let final_params = if let Some(st) = std_type.clone() {
-            let lib = self.world().get_resource::<StdTypeLibrary>().expect("StdTypeLibrary not initialized");
-            let template = lib.lines.get(&st).expect("Line type not found");
-            LineParams {
-                r_ohm_per_km: template.r_ohm_per_km,
-                x_ohm_per_km: template.x_ohm_per_km,
-                c_nf_per_km: template.c_nf_per_km,
-                g_us_per_km: template.g_us_per_km,
-                length_km,
-                df: 1.0,
-                parallel: 1,
-                max_i_ka: template.max_i_ka,
-            }
+        let final_params = if let Some(st) = std_type.as_ref() {
+            let lib = self.std_type_library();
+            let template = lib.lines.get(st).unwrap_or_else(|| panic!("Line type {st} not found"));
+            template.params(length_km)
         } else {
             params.expect("Either std_type or params must be provided")
         };
@@ -188,32 +418,10 @@ impl GridFactory for PowerGrid {
     }
 
     fn add_trafo(&mut self, buffer: &mut HarvardCommandBuffer, hv_bus: i64, lv_bus: i64, std_type: Option<String>, params: Option<TransformerDevice>, name: Option<String>) -> Entity {
-        let final_dev = if let Some(st) = std_type.clone() {
-            let lib = self.world().get_resource::<StdTypeLibrary>().expect("StdTypeLibrary not initialized");
-            let template = lib.trafos.get(&st).expect("Trafo type not found");
-            TransformerDevice {
-                df: 1.0,
-                i0_percent: template.i0_percent,
-                pfe_kw: template.pfe_kw,
-                vk_percent: template.vk_percent,
-                vkr_percent: template.vkr_percent,
-                shift_degree: 0.0,
-                sn_mva: template.sn_mva,
-                vn_hv_kv: template.vn_hv_kv,
-                vn_lv_kv: template.vn_lv_kv,
-                max_loading_percent: None,
-                parallel: 1,
-                tap: Some(TapChanger {
-                    side: Some("hv".to_string()),
-                    neutral: Some(0.0),
-                    max: Some(10.0),
-                    min: Some(-10.0),
-                    pos: Some(0.0),
-                    step_degree: Some(0.0),
-                    step_percent: Some(1.25),
-                    is_phase_shifter: false,
-                }),
-            }
+        let final_dev = if let Some(st) = std_type.as_ref() {
+            let lib = self.std_type_library();
+            let template = lib.trafos.get(st).unwrap_or_else(|| panic!("Trafo type {st} not found"));
+            template.device()
         } else {
             params.expect("Either std_type or params must be provided")
         };
@@ -272,3 +480,123 @@ impl GridFactory for PowerGrid {
         entity
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::network::PowerFlow;
+    use crate::basic::ecs::powerflow::prelude::PowerFlowResult;
+    use crate::io::pandapower::{Bus, ExtGrid, Line, Load, Network, Transformer};
+
+    #[test]
+    fn test_builtin_types() {
+        let lib = StdTypeLibrary::pandapower();
+        assert_eq!(lib.lines.len(), 43);
+        assert_eq!(lib.trafos.len(), 20);
+        let nayy = &lib.lines["NAYY 4x150 SE"];
+        assert_eq!((nayy.r_ohm_per_km, nayy.max_i_ka), (0.208, 0.27));
+        let mv_lv = &lib.trafos["0.4 MVA 20/0.4 kV"];
+        assert_eq!((mv_lv.vk_percent, mv_lv.shift_degree), (6.0, 150.0));
+        assert_eq!(mv_lv.tap.side.as_deref(), Some("hv"));
+        assert_eq!(mv_lv.tap.pos, Some(0.0));
+    }
+
+    /// A 20 kV feeder with a distribution transformer and a 0.4 kV cable
+    /// whose parameters are left to their standard types. The transformer
+    /// keeps a given phase shift of 0 instead of the 150 degrees of its
+    /// Dyn5 type so the flat start converges.
+    fn feeder() -> Network {
+        let bus = |index, vn_kv| Bus { index, in_service: true, vn_kv, ..Default::default() };
+        Network {
+            bus: vec![bus(0, 20.0), bus(1, 0.4), bus(2, 0.4)],
+            ext_grid: Some(vec![ExtGrid { bus: 0, in_service: true, vm_pu: 1.0, ..Default::default() }]),
+            trafo: Some(vec![Transformer {
+                hv_bus: 0,
+                lv_bus: 1,
+                sn_mva: f64::NAN,
+                vn_hv_kv: f64::NAN,
+                vn_lv_kv: f64::NAN,
+                vk_percent: f64::NAN,
+                vkr_percent: f64::NAN,
+                pfe_kw: f64::NAN,
+                i0_percent: f64::NAN,
+                shift_degree: 0.0,
+                in_service: true,
+                parallel: 1,
+                df: 1.0,
+                std_type: Some("0.4 MVA 20/0.4 kV".into()),
+                ..Default::default()
+            }]),
+            line: Some(vec![Line {
+                from_bus: 1,
+                to_bus: 2,
+                length_km: 0.1,
+                r_ohm_per_km: f64::NAN,
+                x_ohm_per_km: f64::NAN,
+                c_nf_per_km: f64::NAN,
+                g_us_per_km: f64::NAN,
+                in_service: true,
+                parallel: 1,
+                df: 1.0,
+                std_type: Some("NAYY 4x150 SE".into()),
+                ..Default::default()
+            }]),
+            load: Some(vec![Load { bus: 2, p_mw: 0.1, q_mvar: 0.02, scaling: 1.0, in_service: true, ..Default::default() }]),
+            f_hz: 50.0,
+            sn_mva: 1.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_missing_parameters() {
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(feeder()));
+        grid.init_pf_net();
+        let world = grid.world_mut();
+        let line = world.query::<&LineParams>().single(world).unwrap().clone();
+        assert_eq!((line.r_ohm_per_km, line.c_nf_per_km, line.g_us_per_km), (0.208, 261.0, 0.0));
+        assert_eq!((line.length_km, line.max_i_ka), (0.1, 0.27));
+        let trafo = world.query::<&TransformerDevice>().single(world).unwrap().clone();
+        assert_eq!((trafo.sn_mva, trafo.vn_lv_kv, trafo.shift_degree), (0.4, 0.4, 0.0));
+        let tap = trafo.tap.unwrap();
+        assert_eq!((tap.side.as_deref(), tap.pos, tap.max), (Some("hv"), Some(0.0), Some(2.0)));
+
+        grid.run_pf();
+        assert!(grid.world().resource::<PowerFlowResult>().converged);
+    }
+
+    #[test]
+    fn test_network_std_types() {
+        // Types of the network replace built-in ones of the same name, and
+        // given parameters are kept.
+        let mut net = feeder();
+        let mut types = StdTypes::default();
+        types.line.insert(
+            "NAYY 4x150 SE".into(),
+            LineStdType { r_ohm_per_km: 0.5, x_ohm_per_km: 0.1, c_nf_per_km: 200.0, max_i_ka: 0.2, ..Default::default() },
+        );
+        net.std_types = Some(types);
+        net.line.as_mut().unwrap()[0].x_ohm_per_km = 0.09;
+
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        let world = grid.world_mut();
+        let line = world.query::<&LineParams>().single(world).unwrap().clone();
+        assert_eq!((line.r_ohm_per_km, line.x_ohm_per_km, line.max_i_ka), (0.5, 0.09, 0.2));
+        assert_eq!(world.resource::<StdTypeLibrary>().lines.len(), 43);
+    }
+
+    #[test]
+    fn test_factory_uses_builtin_types() {
+        let mut grid = PowerGrid::default();
+        let mut buffer = HarvardCommandBuffer::new();
+        grid.add_bus(&mut buffer, 0, 0.4, None, 0.9, 1.1, 0);
+        grid.add_bus(&mut buffer, 1, 0.4, None, 0.9, 1.1, 0);
+        let line = grid.add_line(&mut buffer, 0, 1, 0.2, Some("NAYY 4x150 SE".into()), None, None);
+        buffer.apply(grid.world_mut());
+        let params = grid.world().get::<LineParams>(line).unwrap();
+        assert_eq!((params.x_ohm_per_km, params.length_km), (0.08, 0.2));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod diagnostics;
 pub mod elements;
 pub mod factory;
 pub mod network;
@@ -4,11 +4,15 @@ use std::fmt;
 use bevy_app::prelude::*;
 use bevy_ecs::{component::Mutable, prelude::*, world::error::EntityMutableFetchError};
 
-use crate::basic::{newton_pf, newton_pf_iwamoto, solver::DefaultSolver};
+use crate::basic::{
+    newton_pf_iwamoto_traced, newton_pf_traced,
+    solver::{DefaultSolver, Solve, SymbolicCache},
+};
 
 use super::{
+    elements::{NodeAggRes, NodeLookup},
     plugin::DefaultPlugins,
-    powerflow::{init::BasePFInitPlugins, systems::*},
+    powerflow::{init::BasePFInitPlugins, systems::*, trace::ConvergenceTrace},
 };
 #[derive(Clone, SystemSet, Debug, Hash, PartialEq, Eq)]
 pub enum SolverStage {
@@ -21,6 +25,24 @@ pub struct PowerFlowSolver {
     pub solver: DefaultSolver,
 }
 
+impl PowerFlowSolver {
+    /// Seeds the solver with a symbolic analysis of the same pattern, so the
+    /// next solve only factorizes numerically.
+    pub fn seed(&mut self, symbolic: <DefaultSolver as Solve>::Symbolic) {
+        self.solver.seed_symbolic(symbolic);
+    }
+}
+
+/// Symbolic analyses shared between worlds, keyed by
+/// [`PowerFlowMat::pattern_key`].
+///
+/// Insert a clone of the same cache into every `PowerGrid`/`App` built on a
+/// topology: the first solve of a pattern publishes its analysis, later
+/// solvers (on any thread) are seeded from it and skip ordering and
+/// symbolic factorization.
+#[derive(Default, Clone, Resource)]
+pub struct SharedSymbolicCache(pub SymbolicCache<DefaultSolver>);
+
 /// Represents the ground node in the network.
 pub const GND: i64 = -1;
 
@@ -62,6 +84,7 @@ impl PowerFlow for PowerGrid {
         self.world_mut().insert_resource(PowerFlowConfig {
             max_it: None,
             tol: None,
+            ..Default::default()
         });
 
         self.app_mut()
@@ -120,11 +143,17 @@ fn apply_inversed_permutation(mut mat: ResMut<PowerFlowMat>) {
 /// - `cmd`: Command buffer to insert the result resource.
 /// - `mat`: Power flow matrices resource.
 /// - `cfg`: Power flow configuration resource.
+/// - `trace`: If present, receives the per-iteration [`ConvergenceTrace`].
+#[allow(clippy::too_many_arguments)]
 pub fn ecs_run_pf(
     mut cmd: Commands,
     mat: Res<PowerFlowMat>,
     cfg: Res<PowerFlowConfig>,
     mut solver: ResMut<PowerFlowSolver>,
+    trace: Option<ResMut<ConvergenceTrace>>,
+    node_agg: Option<Res<NodeAggRes>>,
+    cache: Option<Res<SharedSymbolicCache>>,
+    nodes: Option<Res<NodeLookup>>,
 ) {
     // A grid without buses, or without a slack bus (npv + npq == n), has no
     // valid power flow problem; report non-convergence instead of letting the
@@ -141,7 +170,13 @@ pub fn ecs_run_pf(
     let v_init = &mat.v_bus_init;
     let max_it = cfg.max_it;
     let tol = cfg.tol;
-    let v = newton_pf(
+    let mut records = Vec::new();
+    let key = cache.as_ref().map(|c| {
+        let key = mat.pattern_key();
+        c.0.seed(&key, &mut solver.solver);
+        key
+    });
+    let v = newton_pf_traced(
         &mat.y_bus,
         &mat.s_bus,
         v_init,
@@ -150,7 +185,14 @@ pub fn ecs_run_pf(
         tol,
         max_it,
         &mut solver.solver,
+        trace.is_some().then_some(&mut records),
     );
+    if let (Some(cache), Some(key)) = (cache, key) {
+        cache.0.publish(key, &solver.solver);
+    }
+    if let Some(mut trace) = trace {
+        trace.record(&records, &mat, node_agg.as_deref(), nodes.as_deref());
+    }
 
     // Handle the results of the power flow calculation.
     match v {
@@ -174,11 +216,18 @@ pub fn ecs_run_pf(
 }
 
 /// ECS system that runs the power flow calculation using the Iwamoto optimal multiplier method.
+///
+/// Records the [`ConvergenceTrace`] like [`ecs_run_pf`].
+#[allow(clippy::too_many_arguments)]
 pub fn iwamoto_run_pf(
     mut cmd: Commands,
     mat: Res<PowerFlowMat>,
     cfg: Res<PowerFlowConfig>,
     mut solver: ResMut<PowerFlowSolver>,
+    trace: Option<ResMut<ConvergenceTrace>>,
+    node_agg: Option<Res<NodeAggRes>>,
+    cache: Option<Res<SharedSymbolicCache>>,
+    nodes: Option<Res<NodeLookup>>,
 ) {
     if mat.npv + mat.npq >= mat.v_bus_init.len() {
         cmd.insert_resource(PowerFlowResult {
@@ -192,7 +241,13 @@ pub fn iwamoto_run_pf(
     let v_init = &mat.v_bus_init;
     let max_it = cfg.max_it;
     let tol = cfg.tol;
-    let v = newton_pf_iwamoto(
+    let mut records = Vec::new();
+    let key = cache.as_ref().map(|c| {
+        let key = mat.pattern_key();
+        c.0.seed(&key, &mut solver.solver);
+        key
+    });
+    let v = newton_pf_iwamoto_traced(
         &mat.y_bus,
         &mat.s_bus,
         v_init,
@@ -201,7 +256,14 @@ pub fn iwamoto_run_pf(
         tol,
         max_it,
         &mut solver.solver,
+        trace.is_some().then_some(&mut records),
     );
+    if let (Some(cache), Some(key)) = (cache, key) {
+        cache.0.publish(key, &solver.solver);
+    }
+    if let Some(mut trace) = trace {
+        trace.record(&records, &mat, node_agg.as_deref(), nodes.as_deref());
+    }
 
     match v {
         Ok((v, iterations)) => {
@@ -304,4 +366,82 @@ mod tests {
             true
         );
     }
+
+    #[test]
+    fn test_shared_symbolic_cache() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+        let cache = SharedSymbolicCache::default();
+
+        let grids = (0..3)
+            .map(|_| {
+                let mut pf_net = PowerGrid::default();
+                pf_net.world_mut().insert_resource(PPNetwork(net.clone()));
+                pf_net.world_mut().insert_resource(cache.clone());
+                pf_net.init_pf_net();
+                pf_net.run_pf();
+                pf_net
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(cache.0.len(), 1);
+        let v0 = &grids[0].world().resource::<PowerFlowResult>().v;
+        for g in &grids[1..] {
+            let res = g.world().resource::<PowerFlowResult>();
+            assert!(res.converged);
+            assert!((&res.v - v0).camax() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_sparse_bus_ids() {
+        use crate::basic::ecs::post_processing::PostProcessing;
+        use crate::io::pandapower::ecs_net_conv::ToPandapowerNet;
+
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+        // Large ids with gaps, in a different order than the buses.
+        let id = |b: i64| 100_001 + (b * 37 % 118) * 1000;
+        let mut sparse = net.clone();
+        sparse.bus.iter_mut().for_each(|b| b.index = id(b.index));
+        sparse.load.iter_mut().flatten().for_each(|x| x.bus = id(x.bus));
+        sparse.r#gen.iter_mut().flatten().for_each(|x| x.bus = id(x.bus));
+        sparse.sgen.iter_mut().flatten().for_each(|x| x.bus = id(x.bus));
+        sparse.shunt.iter_mut().flatten().for_each(|x| x.bus = id(x.bus));
+        sparse.ext_grid.iter_mut().flatten().for_each(|x| x.bus = id(x.bus));
+        for l in sparse.line.iter_mut().flatten() {
+            (l.from_bus, l.to_bus) = (id(l.from_bus), id(l.to_bus));
+        }
+        for t in sparse.trafo.iter_mut().flatten() {
+            t.hv_bus = id(t.hv_bus as i64) as i32;
+            t.lv_bus = id(t.lv_bus as i64) as i32;
+        }
+
+        let results = [net, sparse].map(|net| {
+            let mut grid = PowerGrid::default();
+            grid.world_mut().insert_resource(PPNetwork(net));
+            grid.init_pf_net();
+            grid.run_pf();
+            grid.post_process();
+            grid.pandapower_results()
+        });
+        let [dense, sparse] = &results;
+        assert!(sparse.converged);
+        assert_eq!(sparse.bus.len(), 118);
+        let by_id = sparse.bus.iter().cloned().collect::<std::collections::HashMap<_, _>>();
+        for (bus, a) in &dense.bus {
+            let b = &by_id[&id(*bus)];
+            assert!((a.vm_pu - b.vm_pu).abs() < 1e-9, "bus {bus}");
+            assert!((a.va_degree - b.va_degree).abs() < 1e-7, "bus {bus}");
+            assert!((a.p_mw - b.p_mw).abs() < 1e-6, "bus {bus}");
+        }
+        for ((_, a), (_, b)) in dense.line.iter().zip(&sparse.line) {
+            assert!((a.p_from_mw - b.p_from_mw).abs() < 1e-6);
+        }
+        for ((_, a), (_, b)) in dense.r#gen.iter().zip(&sparse.r#gen) {
+            assert!((a.q_mvar - b.q_mvar).abs() < 1e-6);
+        }
+    }
 }
@@ -2,8 +2,17 @@ use bevy_app::{plugin_group, prelude::*};
 use bevy_ecs::prelude::*;
 
 use crate::io::pandapower::ecs_net_conv::pandapower_init_system;
-
-use super::{elements::*, network::*, powerflow::prelude::*};
+use bevy_ecs::schedule::ScheduleLabel;
+
+use super::{
+    elements::*,
+    network::*,
+    powerflow::{
+        pf_init::{PFInit, init_pf_init_schedule},
+        prelude::*,
+        voltage_init::{apply_voltage_init, store_converged_voltage},
+    },
+};
 
 #[derive(Debug, SystemSet, Hash, Eq, PartialEq, Clone)]
 pub struct BeforePFInitStage;
@@ -59,6 +68,7 @@ impl Plugin for BasePFPlugin {
         app.world_mut().insert_resource(PowerFlowConfig {
             max_it: None,
             tol: None,
+            ..Default::default()
         });
         app.world_mut().insert_resource(PowerFlowSolver::default());
         app.configure_sets(
@@ -78,6 +88,14 @@ impl Plugin for BasePFPlugin {
                 .in_set(DefaultSolverSet)
                 .in_set(PowerFlowSolverSet),
         );
+        app.add_systems(
+            Update,
+            (
+                apply_voltage_init.before(PowerFlowSolverSet),
+                store_converged_voltage.after(PowerFlowSolverSet),
+            )
+                .in_set(SolverStage::Solve),
+        );
     }
 }
 
@@ -86,22 +104,27 @@ impl Plugin for SwitchPluginTypeA {
     ///
     /// Sets up the systems for processing switch states, aggregating nodes, and handling node merges.
     /// This plugin is suitable for scenarios where node merging and matrix operations are necessary.
+    /// The same systems run in the `PFInit` rebuild, so switching takes effect on the next solve.
     fn build(&self, app: &mut bevy_app::App) {
-        app.add_systems(
-            Startup,
-            (process_switch_state)
-                .chain()
-                .before(init_states)
-                .in_set(PFInitStage),
-        );
-        app.add_systems(
-            Startup,
-            (node_aggregation_system.pipe(handle_node_merge))
-                .chain()
-                .after(init_states)
-                .before(apply_permutation)
-                .in_set(AfterPFInitStage),
-        );
+        init_pf_init_schedule(app);
+        for schedule in [Startup.intern(), PFInit.intern()] {
+            app.add_systems(
+                schedule,
+                (process_switch_state)
+                    .chain()
+                    .before(init_states)
+                    .in_set(PFInitStage),
+            );
+            app.add_systems(
+                schedule,
+                (node_aggregation_system.pipe(handle_node_merge))
+                    .chain()
+                    .after(init_states)
+                    .before(apply_permutation)
+                    .run_if(resource_exists::<NodeMapping>)
+                    .in_set(AfterPFInitStage),
+            );
+        }
     }
 }
 
@@ -110,13 +133,17 @@ impl Plugin for SwitchPluginTypeB {
     ///
     /// Sets up the systems for processing switch states without node aggregation. This approach is more
     /// performant for networks where node merging is not necessary, as it avoids complex matrix operations.
+    /// The same system runs in the `PFInit` rebuild.
     fn build(&self, app: &mut bevy_app::App) {
-        app.add_systems(
-            Startup,
-            (process_switch_state_admit)
-                .before(init_states)
-                .in_set(PFInitStage),
-        );
+        init_pf_init_schedule(app);
+        for schedule in [Startup.intern(), PFInit.intern()] {
+            app.add_systems(
+                schedule,
+                (process_switch_state_admit)
+                    .before(init_states)
+                    .in_set(PFInitStage),
+            );
+        }
     }
 }
 
@@ -151,6 +178,8 @@ plugin_group! {
         :BasePFPlugin,
         // convert the pandapower network to bevy ECS.
         :NewPPLoadPlugin,
+        // check the network before the power flow is initialized.
+        crate::basic::ecs::diagnostics:::DiagnosticsPlugin,
         #[cfg(feature = "archive")]
         crate::io::archive::aurora_format:::ArchivePlugin,
 
@@ -235,6 +264,52 @@ mod test {
         pf_net.print_res_bus();
     }
 
+    #[test]
+    /// Tests `SwitchPluginTypeA` on a network with PV buses: merging a
+    /// spare bus into a load bus of IEEE 118 must leave the solution of the
+    /// original buses unchanged.
+    fn test_ecs_pf_switch_a_pv_buses() {
+        use crate::io::pandapower::{Bus, Switch};
+        use crate::io::pandapower::ecs_net_conv::ToPandapowerNet;
+
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+        let solve = |net, switch: bool| {
+            let mut app = default_app();
+            if switch {
+                app.add_plugins(SwitchPluginTypeA);
+            }
+            app.world_mut().insert_resource(PPNetwork(net));
+            app.update();
+            app.post_process();
+            app.pandapower_results()
+        };
+
+        let mut merged = net.clone();
+        merged.bus.push(Bus {
+            index: 118,
+            in_service: true,
+            vn_kv: net.bus[1].vn_kv,
+            ..Default::default()
+        });
+        merged.switch = Some(vec![Switch {
+            bus: 1,
+            element: 118,
+            closed: true,
+            ..Default::default()
+        }]);
+
+        let plain = solve(net, false);
+        let merged = solve(merged, true);
+        assert!(plain.converged && merged.converged);
+        assert_eq!(merged.bus.len(), 119);
+        for ((bus, a), (_, b)) in plain.bus.iter().zip(&merged.bus) {
+            assert!((a.vm_pu - b.vm_pu).abs() < 1e-9, "bus {bus}");
+            assert!((a.va_degree - b.va_degree).abs() < 1e-9, "bus {bus}");
+        }
+    }
+
     #[test]
     /// Tests the power flow calculation using SwitchPluginTypeB.
     ///
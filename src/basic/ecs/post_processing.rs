@@ -5,11 +5,20 @@ use nalgebra::*;
 use num_complex::{Complex64, ComplexFloat};
 use num_traits::Zero;
 mod res_display;
+pub mod res_elements;
+pub mod violations;
 use res_display::*;
+pub use res_elements::{
+    ExtGridResultData, GenResultData, LoadResultData, SGenResultData, ShuntResultData,
+    TrafoResultData,
+};
+pub use violations::{Violation, ViolationKind, ViolationReport};
 use serde::{Deserialize, Serialize};
 use tabled::{Table, settings::Style};
 
+use crate::basic::solver::DefaultSolver;
 use crate::basic::sparse::cast::Cast;
+use crate::basic::stability::{l_index, modal_analysis};
 
 use super::{elements::*, network::*, powerflow::prelude::*};
 /// Component storing the result of SBus power flow calculation.
@@ -21,6 +30,22 @@ pub struct SBusResult(pub Complex64);
 /// /// The result has a complex number representing the voltage magnitude in p.u.
 #[derive(Debug, Component, Clone, serde::Serialize, serde::Deserialize)]
 pub struct VBusResult(pub Complex64);
+/// Kessel–Glavitsch L-index of a load (PQ) bus: 0 far from voltage
+/// collapse, 1 at the limit.
+#[derive(Debug, Component, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LIndexResult(pub f64);
+
+/// Participation factor of a PQ bus in each mode of [`ModalAnalysisResult`].
+#[derive(Debug, Component, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ModalParticipation(pub Vec<f64>);
+
+/// Smallest eigenvalues of the reduced Q–V Jacobian, ascending; a negative
+/// value means the operating point is voltage unstable.
+#[derive(Debug, Resource, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ModalAnalysisResult {
+    pub eigenvalues: Vec<f64>,
+}
+
 /// Data structure for storing results of power flow calculations for a line.
 #[derive(Component, Debug, Default, Serialize, Deserialize, Clone)]
 pub struct LineResultData {
@@ -74,37 +99,121 @@ fn extract_res_bus(
     //Step 1: restore order before split results to original bus
     let cv = &res.v;
     let mis = &cv.component_mul(&(&mat.y_bus * cv).conjugate());
-    let sbus_res = -mis.clone();
     let inv_order = &mat.reorder.transpose();
-    let sbus_res = inv_order * sbus_res;
+    let mut sbus_res = inv_order * -mis;
     let v = inv_order * &res.v;
-    //Step 2: apply results to original bus
-    let v = match &node_agg {
-        Some(node_agg) => &node_agg.expand_mat_v.cast() * &v,
-        None => v,
-    };
-    let mut sbus_res = match &node_agg {
-        Some(node_agg) => &node_agg.expand_mat.cast() * &sbus_res,
-        None => sbus_res,
-    };
 
+    // Shunts consume at their bus's group voltage; their power is added to
+    // the group's injection before it is split back onto the original buses.
+    let v_group = res_elements::bus_voltages(&mat, &res, node_agg.as_deref());
+    let mut s_shunt = DVector::<Complex64>::zeros(v_group.len());
     shunts.iter().for_each(|(a, b, vb)| {
-        let node = b.0[0] as usize;
+        let Some(node) = nodes.index(b.0[0]) else {
+            return;
+        };
         let z_base = vb.0 * vb.0 / common.sbase;
-        // `node` is an original bus id, so index the un-permuted, expanded `v`
-        // (res.v is in solver ordering and, under aggregation, a different space).
-        let s_shunt = v[node] * (a.0 * z_base * v[node]).conjugate();
-        sbus_res[node] += s_shunt;
+        s_shunt[node] += v_group[node] * (a.0 * z_base * v_group[node]).conjugate();
     });
 
-    for (idx, entity) in nodes.iter() {
+    //Step 2: apply results to original bus. A merged group's voltage and
+    // injection go to its representative bus only, so the injection is
+    // counted once.
+    match &node_agg {
+        Some(node_agg) => {
+            sbus_res += node_agg.expand_mat.transpose().cast() * &s_shunt;
+            sbus_res = &node_agg.expand_mat_v.cast() * &sbus_res;
+        }
+        None => sbus_res += &s_shunt,
+    }
+    let v = match &node_agg {
+        Some(node_agg) => &node_agg.expand_mat_v.cast() * &v,
+        None => v,
+    };
+
+    for (idx, (_, entity)) in nodes.iter().enumerate() {
         cmd.entity(entity).insert((
-            SBusResult(sbus_res[idx as usize] * common.sbase),
-            VBusResult(v[idx as usize]),
+            SBusResult(sbus_res[idx] * common.sbase),
+            VBusResult(v[idx]),
         ));
     }
 }
 
+/// Maps per-PQ-bus columns (solver order) onto original bus indices. Returns
+/// the expanded values and a mask of the buses they apply to.
+fn expand_pq(
+    mat: &PowerFlowMat,
+    node_agg: Option<&NodeAggRes>,
+    pq: &DMatrix<f64>,
+) -> (DMatrix<f64>, DVector<f64>) {
+    let n = mat.from_perm.len();
+    let mut vals = DMatrix::zeros(n, pq.ncols());
+    let mut mask = DVector::zeros(n);
+    for k in 0..pq.nrows() {
+        let bus = mat.inverse_index(k);
+        vals.set_row(bus, &pq.row(k));
+        mask[bus] = 1.0;
+    }
+    match node_agg {
+        Some(agg) => (&agg.expand_mat_v * vals, &agg.expand_mat_v * mask),
+        None => (vals, mask),
+    }
+}
+
+/// Computes the L-index of every load bus.
+fn extract_l_index(
+    mut cmd: Commands,
+    nodes: Res<NodeLookup>,
+    node_agg: Option<Res<NodeAggRes>>,
+    mat: Res<PowerFlowMat>,
+    res: Res<PowerFlowResult>,
+) -> Result<(), String> {
+    if !res.converged {
+        return Err("No converged power flow result".to_string());
+    }
+    let l = l_index(&mat.y_bus, &res.v, mat.npq, &mut DefaultSolver::default())?;
+    let l = DMatrix::from_column_slice(l.len(), 1, l.as_slice());
+    let (l, mask) = expand_pq(&mat, node_agg.as_deref(), &l);
+    for (idx, (_, entity)) in nodes.iter().enumerate() {
+        if mask[idx] > 0.5 {
+            cmd.entity(entity).insert(LIndexResult(l[(idx, 0)]));
+        }
+    }
+    Ok(())
+}
+
+/// Runs modal analysis for the `modes` smallest eigenvalues.
+fn extract_modal_analysis(
+    In(modes): In<usize>,
+    mut cmd: Commands,
+    nodes: Res<NodeLookup>,
+    node_agg: Option<Res<NodeAggRes>>,
+    mat: Res<PowerFlowMat>,
+    res: Res<PowerFlowResult>,
+) -> Result<(), String> {
+    if !res.converged {
+        return Err("No converged power flow result".to_string());
+    }
+    let modal = modal_analysis(
+        &mat.y_bus,
+        &res.v,
+        mat.npv,
+        mat.npq,
+        modes,
+        &mut DefaultSolver::default(),
+    )?;
+    let (p, mask) = expand_pq(&mat, node_agg.as_deref(), &modal.participation);
+    for (idx, (_, entity)) in nodes.iter().enumerate() {
+        if mask[idx] > 0.5 {
+            let factors = p.row(idx).iter().copied().collect();
+            cmd.entity(entity).insert(ModalParticipation(factors));
+        }
+    }
+    cmd.insert_resource(ModalAnalysisResult {
+        eigenvalues: modal.eigenvalues,
+    });
+    Ok(())
+}
+
 /// Prints the results of the power flow for each bus.
 fn print_res_bus(q: Query<(&BusID, &VBusResult, &SBusResult)>) {
     let bus_res_table = q
@@ -147,9 +256,83 @@ fn determine_branch(parent: &Port2, child: &Port2) -> AdmittanceType {
     }
 }
 
+/// Computes the flows of one line from its terminal voltages (p.u.).
+///
+/// `from` and `to` are the bus ids of the line, `branches` the admittance
+/// children of the line entity.
+pub(crate) fn line_result<'a>(
+    (v_from, v_to): (Complex64, Complex64),
+    from: i64,
+    to: i64,
+    max_i_ka: f64,
+    branches: impl IntoIterator<Item = (&'a Admittance, &'a VBase, &'a Port2)>,
+) -> LineResultData {
+    let mut data = LineResultData::default();
+    let p_port = Port2::new(from, to);
+
+    data.vm_from_pu = v_from.modulus();
+    data.va_from_degree = v_from.argument().to_degrees();
+    data.vm_to_pu = v_to.modulus();
+    data.va_to_degree = v_to.argument().to_degrees();
+
+    let mut i_f = Complex64::zero();
+    let mut i_t = Complex64::zero();
+    let mut v_base = 0.0;
+
+    for (a, vbase, pins) in branches {
+        // A line open at one end keeps only a shunt branch.
+        v_base = vbase.0;
+        match determine_branch(&p_port, pins) {
+            AdmittanceType::FromToGround => {
+                i_f += (v_from * vbase.0) * a.0;
+            }
+            AdmittanceType::ToToGround => {
+                i_t -= (v_to * vbase.0) * a.0;
+            }
+            AdmittanceType::BetweenBus => {
+                let vd = v_from - v_to;
+                let i_l = (vd * vbase.0) * a.0;
+                i_f += i_l;
+                i_t += i_l;
+            }
+        }
+    }
+
+    let s_f = v_from * v_base * i_f.conj();
+    let s_t = -v_to * v_base * i_t.conj();
+    data.p_from_mw = s_f.real();
+    data.q_from_mvar = s_f.im();
+    data.p_to_mw = s_t.real();
+    data.q_to_mvar = s_t.im();
+    data.pl_mw = data.p_to_mw + data.p_from_mw;
+    data.ql_mvar = data.q_to_mvar + data.q_from_mvar;
+    data.i_from_ka = i_f.modulus();
+    data.i_to_ka = i_t.modulus();
+    data.i_ka = data.i_from_ka.max(data.i_to_ka);
+
+    if max_i_ka > 0.0 {
+        data.loading_percent = (data.i_ka / max_i_ka) * 100.0;
+    }
+    data
+}
+
+/// Complex power (MVA) flowing into a transformer at its HV and LV bus,
+/// from its terminal voltages (p.u.) and its Y-bus patch.
+pub(crate) fn trafo_flows(
+    (v_hv, v_lv): (Complex64, Complex64),
+    patch: &Port4MatPatch,
+    dev: &TransformerDevice,
+    sbase: f64,
+) -> (Complex64, Complex64) {
+    let p = patch.0.scale(dev.vn_lv_kv * dev.vn_lv_kv / sbase);
+    let i_hv = p[(0, 0)] * v_hv + p[(0, 1)] * v_lv;
+    let i_lv = p[(1, 0)] * v_hv + p[(1, 1)] * v_lv;
+    (v_hv * i_hv.conj() * sbase, v_lv * i_lv.conj() * sbase)
+}
+
 /// Extracts line results after power flow calculation.
-#[allow(unused_assignments)]
-fn extract_res_line(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn extract_res_line(
     mut cmd: Commands,
     node_agg: Option<Res<NodeAggRes>>,
     q: Query<(Entity, &Children, &FromBus, &ToBus, &LineParams), With<Line>>,
@@ -157,67 +340,21 @@ fn extract_res_line(
     results: Res<PowerFlowResult>,
     _common: Res<PFCommonData>,
     mat: Res<PowerFlowMat>,
+    nodes: Res<NodeLookup>,
 ) {
     let v = &mat.reorder.transpose() * &results.v;
+    // Lines at buses merged away by switches see their group's voltage.
     let v = match node_agg {
-        Some(agg) => &agg.expand_mat_v.cast() * v,
+        Some(agg) => &agg.expand_mat.cast() * v,
         None => v,
     };
     
     q.iter().for_each(|(e, children, from, to, params)| {
-        let mut data = LineResultData::default();
-        let p_port = Port2::new(from.0, to.0);
-        
-        let v_from = v[p_port[0] as usize];
-        let v_to = v[p_port[1] as usize];
-
-        data.vm_from_pu = v_from.modulus();
-        data.va_from_degree = v_from.argument().to_degrees();
-        data.vm_to_pu = v_to.modulus();
-        data.va_to_degree = v_to.argument().to_degrees();
-
-        let mut i_f = Complex64::zero();
-        let mut i_t = Complex64::zero();
-        let mut v_base = 0.0;
-
-        for child in children {
-            let (a, vbase, pins) = admit.get(*child).unwrap();
-            match determine_branch(&p_port, pins) {
-                AdmittanceType::FromToGround => {
-                    i_f += (v_from * vbase.0) * a.0;
-                }
-                AdmittanceType::ToToGround => {
-                    i_t -= (v_to * vbase.0) * a.0;
-                }
-                AdmittanceType::BetweenBus => {
-                    let vd = v_from - v_to;
-                    let i_l = (vd * vbase.0) * a.0;
-                    let s = (vd * vbase.0) * i_l.conj();
-                    data.pl_mw += s.re();
-                    data.ql_mvar += s.im();
-                    i_f += i_l;
-                    i_t += i_l;
-                    v_base = vbase.0;
-                }
-            }
-        }
-
-        let s_f = v_from * v_base * i_f.conj();
-        let s_t = -v_to * v_base * i_t.conj();
-        data.p_from_mw = s_f.real();
-        data.q_from_mvar = s_f.im();
-        data.p_to_mw = s_t.real();
-        data.q_to_mvar = s_t.im();
-        data.pl_mw = data.p_to_mw + data.p_from_mw;
-        data.ql_mvar = data.q_to_mvar + data.q_from_mvar;
-        data.i_from_ka = i_f.modulus();
-        data.i_to_ka = i_t.modulus();
-        data.i_ka = data.i_from_ka.max(data.i_to_ka);
-
-        if params.max_i_ka > 0.0 {
-            data.loading_percent = (data.i_ka / params.max_i_ka) * 100.0;
-        }
-
+        let (Some(f), Some(t)) = (nodes.index(from.0), nodes.index(to.0)) else {
+            return;
+        };
+        let branches = children.iter().map(|child| admit.get(child).unwrap());
+        let data = line_result((v[f], v[t]), from.0, to.0, params.max_i_ka, branches);
         cmd.entity(e).insert(data);
     });
 }
@@ -245,9 +382,53 @@ pub trait PostProcessing {
 
     /// Processes and prints the line results.
     fn print_res_line(&mut self);
+
+    /// Prints the transformer results.
+    fn print_res_trafo(&mut self);
+
+    /// Prints the generator results.
+    fn print_res_gen(&mut self);
+
+    /// Prints the external grid results.
+    fn print_res_ext_grid(&mut self);
+
+    /// Prints the load results.
+    fn print_res_load(&mut self);
+
+    /// Prints the static generator results.
+    fn print_res_sgen(&mut self);
+
+    /// Prints the shunt results.
+    fn print_res_shunt(&mut self);
+
+    /// Kessel–Glavitsch L-index of every load bus, stored as
+    /// [`LIndexResult`]. Factors the load-bus admittance block; not part of
+    /// `post_process`.
+    fn stability_indices(&mut self) -> Result<(), String>;
+
+    /// Modal analysis of the `modes` smallest reduced-Jacobian eigenvalues,
+    /// stored as [`ModalAnalysisResult`] and per-bus [`ModalParticipation`].
+    /// Iterative solves with one Jacobian factorization; not part of
+    /// `post_process`.
+    fn modal_analysis(&mut self, modes: usize) -> Result<(), String>;
+
+    /// Checks the post-processed results against bus voltage, line and
+    /// transformer limits. Empty unless the last solve converged.
+    fn violations(&mut self) -> ViolationReport;
+
+    /// Prints the limit violations, most severe first.
+    fn print_violations(&mut self);
 }
 
 impl PostProcessing for PowerGrid {
+    fn violations(&mut self) -> ViolationReport {
+        self.world_mut().run_system_once(violations::collect_violations).unwrap()
+    }
+
+    fn print_violations(&mut self) {
+        println!("{}", self.violations());
+    }
+
     fn print_res_bus(&mut self) {
         self.world_mut().run_system_once(print_res_bus).unwrap();
     }
@@ -256,13 +437,59 @@ impl PostProcessing for PowerGrid {
         self.world_mut().run_system_once(print_res_line).unwrap();
     }
 
+    fn print_res_trafo(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_trafo).unwrap();
+    }
+
+    fn print_res_gen(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_gen).unwrap();
+    }
+
+    fn print_res_ext_grid(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_ext_grid).unwrap();
+    }
+
+    fn print_res_load(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_load).unwrap();
+    }
+
+    fn print_res_sgen(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_sgen).unwrap();
+    }
+
+    fn print_res_shunt(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_shunt).unwrap();
+    }
+
     fn post_process(&mut self) {
         self.world_mut().run_system_once(extract_res_bus).unwrap();
         self.world_mut().run_system_once(extract_res_line).unwrap();
+        self.world_mut().run_system_once(res_elements::extract_res_trafo).unwrap();
+        self.world_mut().run_system_once(res_elements::extract_res_devices).unwrap();
+    }
+
+    fn stability_indices(&mut self) -> Result<(), String> {
+        self.world_mut()
+            .run_system_once(extract_l_index)
+            .map_err(|e| e.to_string())?
+    }
+
+    fn modal_analysis(&mut self, modes: usize) -> Result<(), String> {
+        self.world_mut()
+            .run_system_once_with(extract_modal_analysis, modes)
+            .map_err(|e| e.to_string())?
     }
 }
 
 impl PostProcessing for App {
+    fn violations(&mut self) -> ViolationReport {
+        self.world_mut().run_system_once(violations::collect_violations).unwrap()
+    }
+
+    fn print_violations(&mut self) {
+        println!("{}", self.violations());
+    }
+
     fn print_res_bus(&mut self) {
         self.world_mut().run_system_once(print_res_bus).unwrap();
     }
@@ -271,9 +498,47 @@ impl PostProcessing for App {
         self.world_mut().run_system_once(print_res_line).unwrap();
     }
 
+    fn print_res_trafo(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_trafo).unwrap();
+    }
+
+    fn print_res_gen(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_gen).unwrap();
+    }
+
+    fn print_res_ext_grid(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_ext_grid).unwrap();
+    }
+
+    fn print_res_load(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_load).unwrap();
+    }
+
+    fn print_res_sgen(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_sgen).unwrap();
+    }
+
+    fn print_res_shunt(&mut self) {
+        self.world_mut().run_system_once(res_elements::print_res_shunt).unwrap();
+    }
+
     fn post_process(&mut self) {
         self.world_mut().run_system_once(extract_res_bus).unwrap();
         self.world_mut().run_system_once(extract_res_line).unwrap();
+        self.world_mut().run_system_once(res_elements::extract_res_trafo).unwrap();
+        self.world_mut().run_system_once(res_elements::extract_res_devices).unwrap();
+    }
+
+    fn stability_indices(&mut self) -> Result<(), String> {
+        self.world_mut()
+            .run_system_once(extract_l_index)
+            .map_err(|e| e.to_string())?
+    }
+
+    fn modal_analysis(&mut self, modes: usize) -> Result<(), String> {
+        self.world_mut()
+            .run_system_once_with(extract_modal_analysis, modes)
+            .map_err(|e| e.to_string())?
     }
 }
 
@@ -312,5 +577,20 @@ mod tests {
         pf_net.post_process();
         pf_net.print_res_bus();
         pf_net.print_res_line();
+
+        // The L-index is opt-in.
+        let mut l_q = pf_net.world_mut().query::<&LIndexResult>();
+        assert_eq!(l_q.iter(pf_net.world()).count(), 0);
+        pf_net.stability_indices().unwrap();
+        let npq = pf_net.world().resource::<PowerFlowMat>().npq;
+        let mut l_q = pf_net.world_mut().query::<&LIndexResult>();
+        assert_eq!(l_q.iter(pf_net.world()).count(), npq);
+
+        pf_net.modal_analysis(2).unwrap();
+        let modal = pf_net.world().resource::<ModalAnalysisResult>();
+        assert_eq!(modal.eigenvalues.len(), 2);
+        let mut p_q = pf_net.world_mut().query::<&ModalParticipation>();
+        let total: f64 = p_q.iter(pf_net.world()).map(|p| p.0[0]).sum();
+        assert!((total - 1.0).abs() < 1e-9);
     }
 }
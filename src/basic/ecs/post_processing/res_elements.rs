@@ -0,0 +1,638 @@
+//! Per-element results, the equivalents of pandapower's `res_trafo`,
+//! `res_gen`, `res_ext_grid`, `res_load`, `res_sgen` and `res_shunt`.
+//!
+//! Powers follow pandapower's sign conventions: generators, external grids
+//! and static generators are positive when injecting, loads and shunts when
+//! consuming, and transformer flows when entering the transformer.
+//! Out-of-service elements get all-zero results.
+
+use std::collections::HashMap;
+
+use bevy_ecs::name::Name;
+use bevy_ecs::prelude::*;
+use nalgebra::DVector;
+use nalgebra_sparse::CsrMatrix;
+use num_complex::Complex64;
+use serde::{Deserialize, Serialize};
+use tabled::{Table, Tabled, settings::Style};
+
+use super::res_display::FloatWrapper;
+use super::trafo_flows;
+use crate::basic::ecs::elements::*;
+use crate::basic::ecs::powerflow::prelude::*;
+use crate::basic::sparse::cast::Cast;
+
+/// Results of a two-winding transformer.
+#[derive(Component, Debug, Default, Serialize, Deserialize, Clone)]
+pub struct TrafoResultData {
+    pub p_hv_mw: f64,         // Active power into the HV side (MW)
+    pub q_hv_mvar: f64,       // Reactive power into the HV side (MVAr)
+    pub p_lv_mw: f64,         // Active power into the LV side (MW)
+    pub q_lv_mvar: f64,       // Reactive power into the LV side (MVAr)
+    pub pl_mw: f64,           // Active power loss (MW)
+    pub ql_mvar: f64,         // Reactive power loss (MVAr)
+    pub i_hv_ka: f64,         // Current at the HV side (kA)
+    pub i_lv_ka: f64,         // Current at the LV side (kA)
+    pub vm_hv_pu: f64,        // Voltage magnitude at the HV bus (p.u.)
+    pub va_hv_degree: f64,    // Voltage angle at the HV bus (degrees)
+    pub vm_lv_pu: f64,        // Voltage magnitude at the LV bus (p.u.)
+    pub va_lv_degree: f64,    // Voltage angle at the LV bus (degrees)
+    pub loading_percent: f64, // Loading relative to sn_mva (%)
+}
+
+/// Results of a voltage-controlling generator.
+#[derive(Component, Debug, Default, Serialize, Deserialize, Clone)]
+pub struct GenResultData {
+    pub p_mw: f64,
+    pub q_mvar: f64,
+    pub va_degree: f64,
+    pub vm_pu: f64,
+}
+
+/// Results of an external grid.
+#[derive(Component, Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ExtGridResultData {
+    pub p_mw: f64,
+    pub q_mvar: f64,
+}
+
+/// Results of a load.
+#[derive(Component, Debug, Default, Serialize, Deserialize, Clone)]
+pub struct LoadResultData {
+    pub p_mw: f64,
+    pub q_mvar: f64,
+}
+
+/// Results of a static generator.
+#[derive(Component, Debug, Default, Serialize, Deserialize, Clone)]
+pub struct SGenResultData {
+    pub p_mw: f64,
+    pub q_mvar: f64,
+}
+
+/// Results of a shunt.
+#[derive(Component, Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ShuntResultData {
+    pub p_mw: f64,
+    pub q_mvar: f64,
+    pub vm_pu: f64,
+}
+
+/// Bus voltages (p.u.) in bus-id order. Every bus of a merged group gets
+/// the group's voltage, unlike `VBusResult` which only fills the
+/// representative bus.
+pub(crate) fn bus_voltages(
+    mat: &PowerFlowMat,
+    res: &PowerFlowResult,
+    node_agg: Option<&NodeAggRes>,
+) -> DVector<Complex64> {
+    let v = &mat.reorder.transpose() * &res.v;
+    match node_agg {
+        Some(agg) => &agg.expand_mat.cast() * v,
+        None => v,
+    }
+}
+
+/// Extracts transformer results after power flow calculation.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub(crate) fn extract_res_trafo(
+    mut cmd: Commands,
+    q: Query<(
+        Entity,
+        &FromBus,
+        &ToBus,
+        Option<&Port4MatPatch>,
+        &TransformerDevice,
+        Has<OutOfService>,
+    )>,
+    buses: Query<&VNominal>,
+    nodes: Res<NodeLookup>,
+    node_agg: Option<Res<NodeAggRes>>,
+    mat: Res<PowerFlowMat>,
+    res: Res<PowerFlowResult>,
+    common: Res<PFCommonData>,
+) {
+    let v = bus_voltages(&mat, &res, node_agg.as_deref());
+    let vn_kv = |bus: i64, default: f64| {
+        nodes
+            .get_entity(bus)
+            .and_then(|e| buses.get(e).ok())
+            .map_or(default, |vn| vn.0.0)
+    };
+
+    for (entity, from, to, patch, dev, oos) in &q {
+        let ends = (nodes.index(from.0), nodes.index(to.0));
+        let (Some(patch), (Some(hv), Some(lv))) = (patch.filter(|_| !oos), ends) else {
+            cmd.entity(entity).insert(TrafoResultData::default());
+            continue;
+        };
+        let (v_hv, v_lv) = (v[hv], v[lv]);
+        let (s_hv, s_lv) = trafo_flows((v_hv, v_lv), patch, dev, common.sbase);
+        let (vm_hv, vm_lv) = (v_hv.norm(), v_lv.norm());
+        let mut data = TrafoResultData {
+            p_hv_mw: s_hv.re,
+            q_hv_mvar: s_hv.im,
+            p_lv_mw: s_lv.re,
+            q_lv_mvar: s_lv.im,
+            pl_mw: s_hv.re + s_lv.re,
+            ql_mvar: s_hv.im + s_lv.im,
+            i_hv_ka: s_hv.norm() / (3f64.sqrt() * vm_hv * vn_kv(from.0, dev.vn_hv_kv)),
+            i_lv_ka: s_lv.norm() / (3f64.sqrt() * vm_lv * vn_kv(to.0, dev.vn_lv_kv)),
+            vm_hv_pu: vm_hv,
+            va_hv_degree: v_hv.arg().to_degrees(),
+            vm_lv_pu: vm_lv,
+            va_lv_degree: v_lv.arg().to_degrees(),
+            loading_percent: 0.0,
+        };
+        if dev.sn_mva > 0.0 {
+            // Current-based, as in pandapower: S / (|V| S_rated).
+            data.loading_percent = (s_hv.norm() / vm_hv).max(s_lv.norm() / vm_lv)
+                / (dev.sn_mva * dev.parallel.max(1) as f64)
+                * 100.0;
+        }
+        cmd.entity(entity).insert(data);
+    }
+}
+
+/// Extracts generator, external grid, load, static generator and shunt
+/// results after power flow calculation.
+///
+/// Loads and static generators return their set points. What the
+/// voltage-controlling elements (gens and external grids) deliver follows
+/// from the bus balance: the solved injection minus the set points of all
+/// other elements at the bus. Reactive power is shared equally among the
+/// controlling elements of a bus, the active power left after the gens'
+/// set points equally among its slack elements.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub(crate) fn extract_res_devices(
+    mut cmd: Commands,
+    gens: Query<
+        (
+            Entity,
+            &TargetBus,
+            Option<&TargetPMW>,
+            Has<Slack>,
+            Has<OutOfService>,
+            Has<TargetVaDeg>,
+        ),
+        With<GeneratorCfg>,
+    >,
+    others: Query<
+        (
+            Entity,
+            &TargetBus,
+            Option<&TargetPMW>,
+            Option<&TargetQMVar>,
+            Has<LoadCfg>,
+            Has<SGenDevice>,
+            Has<OutOfService>,
+        ),
+        Without<GeneratorCfg>,
+    >,
+    shunts: Query<(Entity, &TargetBus, &ShuntDevice, Has<OutOfService>)>,
+    nodes: Res<NodeLookup>,
+    node_agg: Option<Res<NodeAggRes>>,
+    mat: Res<PowerFlowMat>,
+    res: Res<PowerFlowResult>,
+    common: Res<PFCommonData>,
+) {
+    let v = bus_voltages(&mat, &res, node_agg.as_deref());
+
+    // Solved injection (MVA) per solver node, in bus-id order of the
+    // (possibly merged) network, and the node each bus belongs to.
+    let s_calc = res.v.component_mul(&(&mat.y_bus * &res.v).conjugate());
+    let s_calc = (&mat.reorder.transpose() * s_calc) * Complex64::from(common.sbase);
+    let merged = node_agg
+        .as_deref()
+        .map(|agg| CsrMatrix::from(&agg.expand_mat));
+    // Out-of-service elements may refer to buses that do not exist.
+    let node_of = |bus: i64| {
+        let idx = nodes.index(bus)?;
+        Some(match &merged {
+            Some(m) => m.row(idx).col_indices()[0],
+            None => idx,
+        })
+    };
+    let v_at = |bus: i64| nodes.index(bus).map(|i| v[i]).unwrap_or_default();
+
+    let mut residual = s_calc.clone();
+    for (entity, bus, p, q, is_load, is_sgen, oos) in &others {
+        let s = if oos {
+            Complex64::default()
+        } else {
+            Complex64::new(p.map_or(0.0, |p| p.0), q.map_or(0.0, |q| q.0))
+        };
+        if let Some(node) = node_of(bus.0).filter(|_| !oos) {
+            residual[node] -= s;
+        }
+        if is_load {
+            cmd.entity(entity).insert(LoadResultData {
+                p_mw: -s.re,
+                q_mvar: -s.im,
+            });
+        } else if is_sgen {
+            cmd.entity(entity).insert(SGenResultData {
+                p_mw: s.re,
+                q_mvar: s.im,
+            });
+        }
+    }
+
+    // Per node: controlling elements, slack elements and fixed gen output.
+    let mut share: HashMap<usize, (usize, usize, f64)> = HashMap::new();
+    for (_, bus, p, slack, oos, _) in &gens {
+        let Some(node) = node_of(bus.0).filter(|_| !oos) else {
+            continue;
+        };
+        let entry = share.entry(node).or_default();
+        entry.0 += 1;
+        match p {
+            Some(p) if !slack => entry.2 += p.0,
+            _ => entry.1 += 1,
+        }
+    }
+    for (entity, bus, p, slack, oos, ext_grid) in &gens {
+        let vb = v_at(bus.0);
+        let (p_mw, q_mvar) = if let Some(node) = node_of(bus.0).filter(|_| !oos) {
+            let (n_ctrl, n_slack, p_fixed) = share[&node];
+            let p_mw = match p {
+                Some(p) if !slack => p.0,
+                _ => (residual[node].re - p_fixed) / n_slack as f64,
+            };
+            (p_mw, residual[node].im / n_ctrl as f64)
+        } else {
+            (0.0, 0.0)
+        };
+        // Told apart like `GenFilter` and `ExtGridFilter`.
+        if ext_grid {
+            cmd.entity(entity).insert(ExtGridResultData { p_mw, q_mvar });
+        } else {
+            cmd.entity(entity).insert(GenResultData {
+                p_mw,
+                q_mvar,
+                va_degree: vb.arg().to_degrees(),
+                vm_pu: vb.norm(),
+            });
+        }
+    }
+
+    for (entity, bus, dev, oos) in &shunts {
+        let vm = v_at(bus.0).norm();
+        let scale = if oos { 0.0 } else { dev.step as f64 * vm * vm };
+        cmd.entity(entity).insert(ShuntResultData {
+            p_mw: dev.p_mw * scale,
+            q_mvar: dev.q_mvar * scale,
+            vm_pu: vm,
+        });
+    }
+}
+
+/// Table row for transformer results.
+#[derive(Tabled)]
+struct TrafoResTable {
+    hv_bus: i64,
+    lv_bus: i64,
+    p_hv_mw: FloatWrapper,
+    q_hv_mvar: FloatWrapper,
+    p_lv_mw: FloatWrapper,
+    q_lv_mvar: FloatWrapper,
+    pl_mw: FloatWrapper,
+    ql_mvar: FloatWrapper,
+    i_hv_ka: FloatWrapper,
+    i_lv_ka: FloatWrapper,
+    vm_hv_pu: FloatWrapper,
+    va_hv_degree: FloatWrapper,
+    vm_lv_pu: FloatWrapper,
+    va_lv_degree: FloatWrapper,
+    loading_percent: FloatWrapper,
+}
+
+/// Table row for single-bus element results; `vm_pu` and `va_degree` are
+/// left empty for elements that do not report them.
+#[derive(Tabled)]
+struct DeviceResTable {
+    bus: i64,
+    name: String,
+    p_mw: FloatWrapper,
+    q_mvar: FloatWrapper,
+    #[tabled(display_with = "display_opt")]
+    vm_pu: Option<FloatWrapper>,
+    #[tabled(display_with = "display_opt")]
+    va_degree: Option<FloatWrapper>,
+}
+
+fn display_opt(value: &Option<FloatWrapper>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+impl DeviceResTable {
+    fn new(bus: &TargetBus, name: Option<&Name>, p_mw: f64, q_mvar: f64) -> Self {
+        Self {
+            bus: bus.0,
+            name: name.map(|n| n.to_string()).unwrap_or_default(),
+            p_mw: FloatWrapper::new(p_mw, 3),
+            q_mvar: FloatWrapper::new(q_mvar, 3),
+            vm_pu: None,
+            va_degree: None,
+        }
+    }
+}
+
+fn print_rows<T: Tabled>(rows: impl IntoIterator<Item = T>) {
+    let table = Table::new(rows).with(Style::markdown()).to_string();
+    println!("{table}");
+}
+
+/// Prints the results of the power flow for each transformer.
+pub(crate) fn print_res_trafo(q: Query<(&FromBus, &ToBus, &TrafoResultData)>) {
+    print_rows(
+        q.iter()
+            .sort_by_key::<&FromBus, _>(|b| b.0)
+            .map(|(from, to, r)| TrafoResTable {
+                hv_bus: from.0,
+                lv_bus: to.0,
+                p_hv_mw: FloatWrapper::new(r.p_hv_mw, 3),
+                q_hv_mvar: FloatWrapper::new(r.q_hv_mvar, 3),
+                p_lv_mw: FloatWrapper::new(r.p_lv_mw, 3),
+                q_lv_mvar: FloatWrapper::new(r.q_lv_mvar, 3),
+                pl_mw: FloatWrapper::new(r.pl_mw, 3),
+                ql_mvar: FloatWrapper::new(r.ql_mvar, 3),
+                i_hv_ka: FloatWrapper::new(r.i_hv_ka, 3),
+                i_lv_ka: FloatWrapper::new(r.i_lv_ka, 3),
+                vm_hv_pu: FloatWrapper::new(r.vm_hv_pu, 2),
+                va_hv_degree: FloatWrapper::new(r.va_hv_degree, 2),
+                vm_lv_pu: FloatWrapper::new(r.vm_lv_pu, 2),
+                va_lv_degree: FloatWrapper::new(r.va_lv_degree, 2),
+                loading_percent: FloatWrapper::new(r.loading_percent, 1),
+            }),
+    );
+}
+
+/// Prints the results of the power flow for each generator.
+pub(crate) fn print_res_gen(q: Query<(&TargetBus, Option<&Name>, &GenResultData)>) {
+    print_rows(
+        q.iter()
+            .sort_by_key::<&TargetBus, _>(|b| b.0)
+            .map(|(bus, name, r)| DeviceResTable {
+                vm_pu: Some(FloatWrapper::new(r.vm_pu, 5)),
+                va_degree: Some(FloatWrapper::new(r.va_degree, 5)),
+                ..DeviceResTable::new(bus, name, r.p_mw, r.q_mvar)
+            }),
+    );
+}
+
+/// Prints the results of the power flow for each external grid.
+pub(crate) fn print_res_ext_grid(q: Query<(&TargetBus, Option<&Name>, &ExtGridResultData)>) {
+    print_rows(
+        q.iter()
+            .sort_by_key::<&TargetBus, _>(|b| b.0)
+            .map(|(bus, name, r)| DeviceResTable::new(bus, name, r.p_mw, r.q_mvar)),
+    );
+}
+
+/// Prints the results of the power flow for each load.
+pub(crate) fn print_res_load(q: Query<(&TargetBus, Option<&Name>, &LoadResultData)>) {
+    print_rows(
+        q.iter()
+            .sort_by_key::<&TargetBus, _>(|b| b.0)
+            .map(|(bus, name, r)| DeviceResTable::new(bus, name, r.p_mw, r.q_mvar)),
+    );
+}
+
+/// Prints the results of the power flow for each static generator.
+pub(crate) fn print_res_sgen(q: Query<(&TargetBus, Option<&Name>, &SGenResultData)>) {
+    print_rows(
+        q.iter()
+            .sort_by_key::<&TargetBus, _>(|b| b.0)
+            .map(|(bus, name, r)| DeviceResTable::new(bus, name, r.p_mw, r.q_mvar)),
+    );
+}
+
+/// Prints the results of the power flow for each shunt.
+pub(crate) fn print_res_shunt(q: Query<(&TargetBus, Option<&Name>, &ShuntResultData)>) {
+    print_rows(
+        q.iter()
+            .sort_by_key::<&TargetBus, _>(|b| b.0)
+            .map(|(bus, name, r)| DeviceResTable {
+                vm_pu: Some(FloatWrapper::new(r.vm_pu, 5)),
+                ..DeviceResTable::new(bus, name, r.p_mw, r.q_mvar)
+            }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::network::{DataOps, PowerFlow, PowerGrid};
+    use crate::basic::ecs::post_processing::{LineResultData, PostProcessing, SBusResult};
+    use crate::io::pandapower::load_csv_zip;
+    use std::env;
+
+    #[test]
+    fn test_element_results_118() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.run_pf();
+        grid.post_process();
+        grid.print_res_trafo();
+        grid.print_res_gen();
+        grid.print_res_ext_grid();
+        grid.print_res_load();
+        grid.print_res_shunt();
+
+        let world = grid.world_mut();
+        let n_bus = world.query::<&BusID>().iter(world).count();
+        let mut bus_sum = vec![Complex64::default(); n_bus];
+        let mut add =
+            |bus: &TargetBus, p: f64, q: f64| bus_sum[bus.0 as usize] += Complex64::new(p, q);
+        for (b, r) in world.query::<(&TargetBus, &GenResultData)>().iter(world) {
+            add(b, r.p_mw, r.q_mvar);
+        }
+        for (b, r) in world
+            .query::<(&TargetBus, &ExtGridResultData)>()
+            .iter(world)
+        {
+            add(b, r.p_mw, r.q_mvar);
+        }
+        for (b, r) in world.query::<(&TargetBus, &SGenResultData)>().iter(world) {
+            add(b, r.p_mw, r.q_mvar);
+        }
+        for (b, r) in world.query::<(&TargetBus, &LoadResultData)>().iter(world) {
+            add(b, -r.p_mw, -r.q_mvar);
+        }
+        for (b, r) in world.query::<(&TargetBus, &ShuntResultData)>().iter(world) {
+            add(b, -r.p_mw, -r.q_mvar);
+        }
+
+        // Element results add up to the bus results (demand convention).
+        for (id, s) in world.query::<(&BusID, &SBusResult)>().iter(world) {
+            let err = (bus_sum[id.0 as usize] + s.0).norm();
+            assert!(err < 1e-6, "bus {}: {err}", id.0);
+        }
+
+        // Generation minus demand is what lines and transformers lose.
+        let trafos = world
+            .query::<&TrafoResultData>()
+            .iter(world)
+            .cloned()
+            .collect::<Vec<_>>();
+        let line_losses: f64 = world
+            .query::<&LineResultData>()
+            .iter(world)
+            .map(|r| r.pl_mw)
+            .sum();
+        let losses = line_losses + trafos.iter().map(|t| t.pl_mw).sum::<f64>();
+        let balance: f64 = bus_sum.iter().map(|s| s.re).sum();
+        assert!((balance - losses).abs() < 1e-6, "{balance} vs {losses}");
+
+        assert!(!trafos.is_empty());
+        for t in &trafos {
+            assert!(t.pl_mw > -1e-9 && t.loading_percent > 0.0, "{t:?}");
+            assert!(t.i_hv_ka > 0.0 && t.i_lv_ka > 0.0);
+        }
+        let gens = world
+            .query::<(&TargetPMW, &GenResultData)>()
+            .iter(world)
+            .count();
+        assert!(gens > 0);
+        for (p, r) in world.query::<(&TargetPMW, &GenResultData)>().iter(world) {
+            assert_eq!(p.0, r.p_mw);
+        }
+
+        // Gens and external grids are split like their element tables.
+        let n_gen = world.query_filtered::<(), GenFilter>().iter(world).count();
+        let n_res = world
+            .query_filtered::<(), (GenFilter, With<GenResultData>)>()
+            .iter(world)
+            .count();
+        assert_eq!(n_gen, n_res);
+        let n_ext = world.query_filtered::<(), ExtGridFilter>().iter(world).count();
+        let n_res = world
+            .query_filtered::<(), (ExtGridFilter, With<ExtGridResultData>)>()
+            .iter(world)
+            .count();
+        assert_eq!(n_ext, n_res);
+        assert!(n_ext > 0);
+    }
+
+    #[test]
+    fn test_out_of_service_loads_not_in_balance() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let net = load_csv_zip(&format!("{}/cases/IEEE118/data.zip", dir)).unwrap();
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.run_pf();
+        grid.post_process();
+        let ext_grid = |grid: &mut PowerGrid| {
+            let world = grid.world_mut();
+            let mut q = world.query::<(&TargetBus, &ExtGridResultData)>();
+            let (bus, r) = q.single(world).unwrap();
+            (bus.0, r.clone())
+        };
+        let (bus, before) = ext_grid(&mut grid);
+
+        // Out-of-service loads draw nothing, also when their bus does not
+        // exist (e.g. quarantined by the diagnostics).
+        let loads = [bus, 99_999].map(|bus| {
+            grid.world_mut()
+                .spawn((
+                    TargetBus(bus),
+                    TargetPMW(-50.0),
+                    TargetQMVar(-20.0),
+                    LoadCfg::default(),
+                    OutOfService,
+                ))
+                .id()
+        });
+        grid.post_process();
+        let (_, after) = ext_grid(&mut grid);
+        assert!(
+            (before.p_mw - after.p_mw).abs() < 1e-9,
+            "{before:?} vs {after:?}"
+        );
+        assert!((before.q_mvar - after.q_mvar).abs() < 1e-9);
+        for load in loads {
+            let r = grid.world().get::<LoadResultData>(load).unwrap();
+            assert_eq!((r.p_mw, r.q_mvar), (0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_element_results_merged_buses() {
+        use crate::basic::ecs::plugin::SwitchPluginTypeA;
+        use crate::basic::ecs::plugin::default_app;
+        use crate::io::pandapower::load_pandapower_json_obj;
+
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/test/new_input_PFLV_modified.json", dir);
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(name).unwrap()).unwrap();
+        let net = load_pandapower_json_obj(json["pp_network"].as_object().unwrap());
+        let mut app = default_app();
+        app.add_plugins(SwitchPluginTypeA);
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+        app.post_process();
+
+        // Buses merged away by closed switches still see their group's voltage.
+        let world = app.world_mut();
+        let trafos = world.query::<&TrafoResultData>().iter(world).count();
+        assert!(trafos > 0);
+        for t in world.query::<&TrafoResultData>().iter(world) {
+            assert!(t.vm_hv_pu > 0.5 && t.vm_lv_pu > 0.5, "{t:?}");
+        }
+        for s in world.query::<&ShuntResultData>().iter(world) {
+            assert!(s.vm_pu > 0.5, "{s:?}");
+        }
+        let ext = world
+            .query::<&ExtGridResultData>()
+            .iter(world)
+            .map(|r| r.p_mw)
+            .collect::<Vec<_>>();
+        let loads: f64 = world
+            .query::<&LoadResultData>()
+            .iter(world)
+            .map(|r| r.p_mw)
+            .sum();
+        assert_eq!(ext.len(), 1);
+        assert!(
+            ext[0] > loads * 0.9 && ext[0] < loads * 1.2,
+            "{ext:?} vs {loads}"
+        );
+
+        // Lines see the group voltage too, so their flows close the balance.
+        let lines = world
+            .query::<&LineResultData>()
+            .iter(world)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert!(!lines.is_empty());
+        for l in &lines {
+            assert!(l.vm_from_pu > 0.5 && l.vm_to_pu > 0.5, "{l:?}");
+            assert!(l.pl_mw > -1e-9, "{l:?}");
+        }
+        let sgens: f64 = world
+            .query::<&SGenResultData>()
+            .iter(world)
+            .map(|r| r.p_mw)
+            .sum();
+        let shunts: f64 = world
+            .query::<&ShuntResultData>()
+            .iter(world)
+            .map(|r| r.p_mw)
+            .sum();
+        let trafo_losses: f64 = world
+            .query::<&TrafoResultData>()
+            .iter(world)
+            .map(|r| r.pl_mw)
+            .sum();
+        let losses = lines.iter().map(|l| l.pl_mw).sum::<f64>() + trafo_losses;
+        let balance = ext[0] + sgens - loads - shunts;
+        assert!((balance - losses).abs() < 1e-6, "{balance} vs {losses}");
+    }
+}
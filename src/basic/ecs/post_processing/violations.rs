@@ -0,0 +1,350 @@
+//! Limit violation analysis on post-processed results.
+//!
+//! Bus voltages are checked against [`VmLimit`], line loadings (relative to
+//! the `max_i_ka` rating) against [`MaxLoadingPercent`] and transformer
+//! loadings against `TransformerDevice::max_loading_percent`, both 100 % if
+//! unset.
+
+use std::fmt;
+use std::io::Write;
+
+use bevy_ecs::name::Name;
+use bevy_ecs::prelude::*;
+use serde::{Serialize, Serializer};
+use tabled::{Table, Tabled, settings::Style};
+
+use super::res_display::FloatWrapper;
+use super::res_elements::bus_voltages;
+use super::{LineResultData, TrafoResultData};
+use crate::basic::ecs::elements::*;
+use crate::basic::ecs::powerflow::prelude::*;
+
+/// What limit an element violates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum ViolationKind {
+    Undervoltage,
+    Overvoltage,
+    LineOverload,
+    TrafoOverload,
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// One limit violation.
+///
+/// `value` and `limit` are in p.u. for voltages and in percent loading for
+/// branches. `severity` is the exceedance relative to the limit in percent,
+/// so violations of different kinds rank on one scale.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    #[serde(serialize_with = "entity_bits")]
+    pub entity: Entity,
+    pub name: Option<String>,
+    pub kind: ViolationKind,
+    pub value: f64,
+    pub limit: f64,
+    pub severity: f64,
+}
+
+fn entity_bits<S: Serializer>(e: &Entity, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u64(e.to_bits())
+}
+
+impl Violation {
+    fn over(
+        entity: Entity,
+        name: Option<&Name>,
+        kind: ViolationKind,
+        value: f64,
+        limit: f64,
+    ) -> Self {
+        Self {
+            entity,
+            name: name.map(|n| n.to_string()),
+            kind,
+            value,
+            limit,
+            severity: (value - limit) / limit * 100.0,
+        }
+    }
+
+    fn under(
+        entity: Entity,
+        name: Option<&Name>,
+        kind: ViolationKind,
+        value: f64,
+        limit: f64,
+    ) -> Self {
+        Self {
+            severity: (limit - value) / limit * 100.0,
+            ..Self::over(entity, name, kind, value, limit)
+        }
+    }
+}
+
+/// All violations of one solution, most severe first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ViolationReport(pub Vec<Violation>);
+
+impl ViolationReport {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Violation> {
+        self.0.iter()
+    }
+
+    /// Violations of one kind.
+    pub fn of_kind(&self, kind: ViolationKind) -> impl Iterator<Item = &Violation> {
+        self.0.iter().filter(move |v| v.kind == kind)
+    }
+
+    /// Writes the report as CSV with a header row.
+    pub fn write_csv<W: Write>(&self, writer: W) -> csv::Result<()> {
+        let mut w = csv::Writer::from_writer(writer);
+        for v in &self.0 {
+            w.serialize(v)?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+}
+
+/// Table row for displaying a violation.
+#[derive(Tabled)]
+struct ViolationTable {
+    entity: Entity,
+    name: String,
+    kind: ViolationKind,
+    value: FloatWrapper,
+    limit: FloatWrapper,
+    severity_percent: FloatWrapper,
+}
+
+impl fmt::Display for ViolationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self.0.iter().map(|v| ViolationTable {
+            entity: v.entity,
+            name: v.name.clone().unwrap_or_default(),
+            kind: v.kind,
+            value: FloatWrapper::new(v.value, 4),
+            limit: FloatWrapper::new(v.limit, 4),
+            severity_percent: FloatWrapper::new(v.severity, 2),
+        });
+        write!(f, "{}", Table::new(rows).with(Style::markdown()))
+    }
+}
+
+/// Collects the violations of the current post-processed results.
+///
+/// Without a converged solve the results are meaningless and the report is
+/// empty. Bus voltages are taken from the solution with merged buses
+/// expanded, so buses folded into a switch group see the group's voltage.
+#[allow(clippy::type_complexity)]
+pub(crate) fn collect_violations(
+    res: Option<Res<PowerFlowResult>>,
+    mat: Option<Res<PowerFlowMat>>,
+    nodes: Option<Res<NodeLookup>>,
+    node_agg: Option<Res<NodeAggRes>>,
+    buses: Query<(Entity, &BusID, &VmLimit<PerUnit>, Option<&Name>), Without<OutOfService>>,
+    lines: Query<
+        (
+            Entity,
+            &LineResultData,
+            &LineParams,
+            Option<&MaxLoadingPercent>,
+            Option<&Name>,
+        ),
+        Without<OutOfService>,
+    >,
+    trafos: Query<
+        (Entity, &TrafoResultData, &TransformerDevice, Option<&Name>),
+        Without<OutOfService>,
+    >,
+) -> ViolationReport {
+    let (Some(res), Some(mat), Some(nodes)) = (res, mat, nodes) else {
+        return ViolationReport::default();
+    };
+    if !res.converged {
+        return ViolationReport::default();
+    }
+    let v = bus_voltages(&mat, &res, node_agg.as_deref());
+    let mut report = Vec::new();
+
+    for (entity, id, lim, name) in &buses {
+        let Some(vm) = nodes.index(id.0).map(|i| v[i].norm()) else {
+            continue;
+        };
+        if vm < lim.min() {
+            report.push(Violation::under(
+                entity,
+                name,
+                ViolationKind::Undervoltage,
+                vm,
+                lim.min(),
+            ));
+        } else if vm > lim.max() {
+            report.push(Violation::over(
+                entity,
+                name,
+                ViolationKind::Overvoltage,
+                vm,
+                lim.max(),
+            ));
+        }
+    }
+
+    for (entity, data, params, max_loading, name) in &lines {
+        let limit = max_loading.map_or(100.0, |m| m.0);
+        if params.max_i_ka > 0.0 && data.loading_percent > limit {
+            report.push(Violation::over(
+                entity,
+                name,
+                ViolationKind::LineOverload,
+                data.loading_percent,
+                limit,
+            ));
+        }
+    }
+
+    for (entity, data, dev, name) in &trafos {
+        let limit = dev.max_loading_percent.unwrap_or(100.0);
+        if dev.sn_mva > 0.0 && data.loading_percent > limit {
+            report.push(Violation::over(
+                entity,
+                name,
+                ViolationKind::TrafoOverload,
+                data.loading_percent,
+                limit,
+            ));
+        }
+    }
+
+    report.sort_by(|a, b| b.severity.total_cmp(&a.severity));
+    ViolationReport(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::network::{DataOps, PowerFlow, PowerGrid};
+    use crate::basic::ecs::post_processing::PostProcessing;
+    use crate::io::pandapower::load_csv_zip;
+    use std::env;
+
+    #[test]
+    fn test_violations_118() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.run_pf();
+        grid.post_process();
+
+        // Tighten every voltage band so the case must violate it.
+        let mut q = grid.world_mut().query::<&mut VmLimit<PerUnit>>();
+        for mut lim in q.iter_mut(grid.world_mut()) {
+            *lim = VmLimit::new(0.99, 1.01);
+        }
+        let mut q = grid.world_mut().query::<&mut TransformerDevice>();
+        let mut n_trafo = 0;
+        for mut dev in q.iter_mut(grid.world_mut()) {
+            dev.max_loading_percent = Some(1e-6);
+            n_trafo += 1;
+        }
+        let world = grid.world_mut();
+        let lines = world
+            .query_filtered::<Entity, With<LineParams>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        for e in lines {
+            world.entity_mut(e).insert(MaxLoadingPercent(1e-6));
+        }
+        let report = grid.violations();
+        let trafo = report
+            .of_kind(ViolationKind::TrafoOverload)
+            .collect::<Vec<_>>();
+        assert!(n_trafo > 0);
+        assert_eq!(trafo.len(), n_trafo);
+        assert!(trafo.iter().all(|v| v.value < 200.0), "{trafo:?}");
+        let line = report
+            .of_kind(ViolationKind::LineOverload)
+            .collect::<Vec<_>>();
+        assert!(!line.is_empty());
+        assert!(line.iter().all(|v| v.limit == 1e-6), "{line:?}");
+        assert!(report.of_kind(ViolationKind::Overvoltage).count() > 0);
+        assert!(report.of_kind(ViolationKind::Undervoltage).count() > 0);
+        assert!(report.0.windows(2).all(|w| w[0].severity >= w[1].severity));
+        for v in report.iter() {
+            assert!(v.severity > 0.0);
+        }
+
+        let mut csv = Vec::new();
+        report.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("entity,name,kind,value,limit,severity"));
+        assert_eq!(csv.lines().count(), report.len() + 1);
+        assert!(report.to_string().contains("Overvoltage"));
+
+        // A diverged solve reports nothing, not stale violations.
+        grid.world_mut().resource_mut::<PowerFlowResult>().converged = false;
+        assert!(grid.violations().is_empty());
+    }
+
+    #[test]
+    fn test_violations_merged_buses() {
+        use crate::basic::ecs::plugin::{SwitchPluginTypeA, default_app};
+        use crate::basic::ecs::post_processing::VBusResult;
+        use crate::io::pandapower::load_pandapower_json_obj;
+
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/test/new_input_PFLV_modified.json", dir);
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(name).unwrap()).unwrap();
+        let net = load_pandapower_json_obj(json["pp_network"].as_object().unwrap());
+        let mut app = default_app();
+        app.add_plugins(SwitchPluginTypeA);
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+        app.post_process();
+
+        // `VBusResult` only fills the representative bus of a merged group.
+        let world = app.world_mut();
+        let merged = world
+            .query::<(Entity, &VBusResult)>()
+            .iter(world)
+            .filter(|(_, v)| v.0.norm() == 0.0)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        assert!(!merged.is_empty());
+
+        let report = app.violations();
+        for v in report.of_kind(ViolationKind::Undervoltage) {
+            assert!(v.value > 0.5, "{v:?}");
+        }
+        assert!(report.iter().all(|v| !merged.contains(&v.entity)));
+
+        // A band around the solution flags the merged buses like any other.
+        let world = app.world_mut();
+        for mut lim in world.query::<&mut VmLimit<PerUnit>>().iter_mut(world) {
+            *lim = VmLimit::new(1.5, 2.0);
+        }
+        let report = app.violations();
+        for e in &merged {
+            let v = report.iter().find(|v| v.entity == *e).unwrap();
+            assert_eq!(v.kind, ViolationKind::Undervoltage);
+            assert!(v.value > 0.5 && v.severity < 100.0, "{v:?}");
+        }
+    }
+}
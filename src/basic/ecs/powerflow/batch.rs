@@ -0,0 +1,160 @@
+use bevy_app::App;
+use bevy_ecs::prelude::*;
+use nalgebra::DMatrix;
+use num_complex::Complex64;
+
+use crate::basic::batch::{BatchPFResult, newton_pf_batch};
+use crate::basic::ecs::elements::NodeAggRes;
+use crate::basic::ecs::network::{DataOps, PowerGrid};
+use crate::basic::solver::DefaultSolver;
+
+use super::systems::{PowerFlowConfig, PowerFlowMat};
+
+/// Batched multi-scenario power flow on an initialized network.
+///
+/// The scenarios share the current topology, Y-bus and start voltages; only
+/// the injections differ. No ECS state is touched, so the network can keep
+/// being used for ordinary solves afterwards.
+pub trait BatchPowerFlow {
+    /// Solves every row of `s_bus` (scenarios × buses, complex p.u.
+    /// injections in bus order, i.e. the unpermuted `PowerFlowMat` order).
+    /// Returned voltages use the same bus order. Networks with buses merged
+    /// by closed switches are rejected.
+    fn run_pf_batch(
+        &self,
+        s_bus: &DMatrix<Complex64>,
+        threads: Option<usize>,
+    ) -> Result<BatchPFResult, String>;
+}
+
+impl BatchPowerFlow for World {
+    fn run_pf_batch(
+        &self,
+        s_bus: &DMatrix<Complex64>,
+        threads: Option<usize>,
+    ) -> Result<BatchPFResult, String> {
+        if self.contains_resource::<NodeAggRes>() {
+            return Err("Batch power flow does not support merged buses".to_string());
+        }
+        let mat = self
+            .get_resource::<PowerFlowMat>()
+            .ok_or("Power flow matrices are not initialized")?;
+        let cfg = self.get_resource::<PowerFlowConfig>().cloned().unwrap_or_default();
+        let n = mat.v_bus_init.len();
+        if s_bus.ncols() != n {
+            return Err(format!(
+                "Expected {} bus columns in s_bus, got {}",
+                n,
+                s_bus.ncols()
+            ));
+        }
+        if mat.npv + mat.npq >= n {
+            return Err("No slack bus in the network".to_string());
+        }
+
+        let mut s_perm = DMatrix::zeros(s_bus.nrows(), n);
+        for (new_idx, &old_idx) in mat.from_perm.iter().enumerate() {
+            s_perm.column_mut(new_idx).copy_from(&s_bus.column(old_idx));
+        }
+        let mut res = newton_pf_batch::<DefaultSolver>(
+            &mat.y_bus,
+            &s_perm,
+            &mat.v_bus_init,
+            mat.npv,
+            mat.npq,
+            cfg.tol,
+            cfg.max_it,
+            threads,
+        );
+        let v_perm = std::mem::replace(&mut res.v, DMatrix::zeros(s_bus.nrows(), n));
+        for (new_idx, &old_idx) in mat.from_perm.iter().enumerate() {
+            res.v.column_mut(old_idx).copy_from(&v_perm.column(new_idx));
+        }
+        Ok(res)
+    }
+}
+
+impl BatchPowerFlow for App {
+    fn run_pf_batch(
+        &self,
+        s_bus: &DMatrix<Complex64>,
+        threads: Option<usize>,
+    ) -> Result<BatchPFResult, String> {
+        self.world().run_pf_batch(s_bus, threads)
+    }
+}
+
+impl BatchPowerFlow for PowerGrid {
+    fn run_pf_batch(
+        &self,
+        s_bus: &DMatrix<Complex64>,
+        threads: Option<usize>,
+    ) -> Result<BatchPFResult, String> {
+        self.world().run_pf_batch(s_bus, threads)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::plugin::default_app;
+    use crate::basic::ecs::powerflow::systems::PowerFlowResult;
+    use crate::io::pandapower::load_csv_zip;
+    use crate::prelude::PPNetwork;
+    use std::env;
+
+    #[test]
+    fn test_batch_118() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+
+        let mut app = default_app();
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+
+        let mat = app.world().resource::<PowerFlowMat>();
+        let n = mat.s_bus.len();
+        let mut base = mat.s_bus.clone();
+        let mut v_ref = app.world().resource::<PowerFlowResult>().v.clone();
+        for (new_idx, &old_idx) in mat.from_perm.iter().enumerate() {
+            base[old_idx] = mat.s_bus[new_idx];
+            v_ref[old_idx] = app.world().resource::<PowerFlowResult>().v[new_idx];
+        }
+
+        let scales = [0.9, 0.95, 1.0, 1.05, 1.1];
+        let mut s_bus = DMatrix::zeros(scales.len(), n);
+        for (k, scale) in scales.iter().enumerate() {
+            s_bus.row_mut(k).copy_from(&(&base * Complex64::from(*scale)).transpose());
+        }
+
+        let res = app.run_pf_batch(&s_bus, Some(2)).unwrap();
+        assert!(res.converged.iter().all(|&c| c));
+        let diff = (res.v.row(2).transpose() - &v_ref).camax();
+        assert!(diff < 1e-9, "base scenario differs from single solve: {diff}");
+
+        let serial = app.run_pf_batch(&s_bus, Some(1)).unwrap();
+        assert_eq!(serial.v, res.v);
+    }
+
+    #[test]
+    fn test_batch_rejects_merged_buses() {
+        use crate::basic::ecs::plugin::SwitchPluginTypeA;
+        use crate::io::pandapower::load_pandapower_json_obj;
+
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/test/new_input_PFLV_modified.json", dir);
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(name).unwrap()).unwrap();
+        let net = load_pandapower_json_obj(json["pp_network"].as_object().unwrap());
+        let mut app = default_app();
+        app.add_plugins(SwitchPluginTypeA);
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+
+        let n = app.world().resource::<PowerFlowMat>().s_bus.len();
+        let s_bus = DMatrix::zeros(1, n);
+        let err = app.run_pf_batch(&s_bus, None).unwrap_err();
+        assert!(err.contains("merged buses"), "{err}");
+    }
+}
@@ -51,19 +51,30 @@ impl<'w, 's, T: Component, T1: Component<Mutability = Mutable>> NodeOp<'w, 's, T
     ///
     /// # Arguments
     /// * `f` - Closure receiving the source data, the target mutable state, and base scaling.
-    pub fn inject<F>(&mut self, mut f: F)
+    ///
+    /// # Errors
+    /// If an element's bus is not in the [`NodeLookup`].
+    pub fn inject<F>(&mut self, mut f: F) -> Result
     where
         F: FnMut(&T, &mut T1, f64),
     {
         let s_base_frac = 1.0 / self.common.sbase;
         for (target_bus, val) in self.elements.iter() {
-            let entity = self.node.get_entity(target_bus.0).unwrap();
-            let mut target = self.buses.get_mut(entity).unwrap();
+            let entity = bus_entity(&self.node, target_bus)?;
+            let mut target = self.buses.get_mut(entity)?;
             f(val, &mut target, s_base_frac);
         }
+        Ok(())
     }
 }
 
+/// Bus entity of `target`, or an error naming the missing bus id.
+fn bus_entity(nodes: &NodeLookup, target: &TargetBus) -> Result<Entity> {
+    Ok(nodes
+        .get_entity(target.0)
+        .ok_or_else(|| format!("bus {} does not exist", target.0))?)
+}
+
 /// Labels all non-out-of-service, untagged buses as PQ buses by default.
 /// Excludes PV, Slack, and out-of-service nodes.
 pub fn label_pq_nodes(
@@ -112,43 +123,38 @@ pub fn label_slack_nodes(
 }
 
 /// Injects active power (P in MW) into the system as per-unit complex real part at SBus nodes.
-pub fn p_mw_inj(mut target_p: NodeOp<TargetPMW, SBusInjPu>) {
+pub fn p_mw_inj(mut target_p: NodeOp<TargetPMW, SBusInjPu>) -> Result {
     target_p.inject(|val, state, sbase_frac| {
         state.0.re += val.0 * sbase_frac;
-    });
+    })
 }
 
 /// Injects voltage magnitude and angle into VBus nodes,
 /// reconstructing the complex per-unit voltage vector from separate magnitude and angle components.
-pub fn v_inj(mut v: ParamSet<(NodeOp<TargetVmPu, VBusPu>, NodeOp<TargetVaDeg, VBusPu>)>) {
+pub fn v_inj(mut v: ParamSet<(NodeOp<TargetVmPu, VBusPu>, NodeOp<TargetVaDeg, VBusPu>)>) -> Result {
     let target_vm = v.p0();
     let mut buses = target_vm.buses;
-    target_vm
-        .elements
-        .iter()
-        .for_each(|(target_bus, target_vm_pu)| {
-            let entity = target_vm.node.get_entity(target_bus.0).unwrap();
-            let mut data = buses.get_mut(entity).unwrap();
-            data.0 = data.0.simd_signum() * Complex::new(target_vm_pu.0, 0.0);
-        });
+    for (target_bus, target_vm_pu) in target_vm.elements.iter() {
+        let entity = bus_entity(&target_vm.node, target_bus)?;
+        let mut data = buses.get_mut(entity)?;
+        data.0 = data.0.simd_signum() * Complex::new(target_vm_pu.0, 0.0);
+    }
 
     let target_va = v.p1();
     let mut buses = target_va.buses;
-    target_va
-        .elements
-        .iter()
-        .for_each(|(target_bus, target_va_deg)| {
-            let entity = target_va.node.get_entity(target_bus.0).unwrap();
-            let mut data = buses.get_mut(entity).unwrap();
-            data.0 = data.0.simd_modulus() * Complex::from_polar(1.0, target_va_deg.0.to_radians());
-        });
+    for (target_bus, target_va_deg) in target_va.elements.iter() {
+        let entity = bus_entity(&target_va.node, target_bus)?;
+        let mut data = buses.get_mut(entity)?;
+        data.0 = data.0.simd_modulus() * Complex::from_polar(1.0, target_va_deg.0.to_radians());
+    }
+    Ok(())
 }
 
 /// Injects reactive power (Q in MVar) into the system as per-unit complex imaginary part at SBus nodes.
-pub fn q_mvar_inj(mut target_q: NodeOp<TargetQMVar, SBusInjPu>) {
+pub fn q_mvar_inj(mut target_q: NodeOp<TargetQMVar, SBusInjPu>) -> Result {
     target_q.inject(|val, state, sbase_frac| {
         state.0.im += val.0 * sbase_frac;
-    });
+    })
 }
 
 /// Plugin for tagging buses based on their operational role (PQ, PV, Slack).
@@ -7,6 +7,8 @@
 /// This module is a key part of the simulation backend, handling the Newton-Raphson iteration
 /// and constraint scheduling mechanisms in coordination with ECS world data.
 pub mod init; // System and resource initialization logic
+pub mod batch; // Batched multi-scenario solves on a fixed topology
+pub mod sensitivity; // dV/dP, dV/dQ and loss factors from the converged Jacobian
 pub mod mutation; // Standardized parameter-mutation pipeline (message bus + consumer)
 pub mod pf_init; // Re-runnable full-initialization schedule (PFInit)
 pub mod nonlinear_schedule;
@@ -14,6 +16,8 @@ pub mod qlim; // Generator reactive power limit handling
 pub mod branch_data; // Incremental branch analysis data
 pub mod result_extract; // Snapshot and result extraction into simulation state
 pub mod structure_update; // Dynamic structural updates triggered by simulation stages
+pub mod voltage_init; // Newton start-point strategies (flat, DC, warm, user)
+pub mod trace; // Opt-in per-iteration convergence trace
 pub mod systems; // Core system stages for power flow iteration // Scheduler for non-linear solve steps (e.g., Q-limit enforcement)
 
 /// Re-exports commonly used symbols from `init` and `systems` for easy access.
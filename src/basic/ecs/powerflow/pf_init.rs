@@ -6,15 +6,24 @@
 //! zeroes `SBusInjPu`/`VBusPu`, then the injection systems re-accumulate from
 //! the case data — i.e. "zero, then consume all diffs from scratch". The
 //! incremental path is the same accumulation without the zeroing.
+//!
+//! Plugins join the rebuild through the same sets they use at startup:
+//! [`PFInitStage`] runs after the injections and before the Y-bus is built,
+//! [`AfterPFInitStage`] between building the Y-bus and permuting it (e.g.
+//! the switch plugins' node merging).
 
+use bevy_app::App;
 use bevy_ecs::prelude::*;
 use bevy_ecs::schedule::ScheduleLabel;
 
 use crate::basic::ecs::elements::bus::bus_systems::init_node_lookup;
+use crate::basic::ecs::elements::{NodeAggRes, NodeMapping, assign_element_indices};
 use crate::basic::ecs::elements::line::line_systems::setup_line_systems;
 use crate::basic::ecs::elements::shunt::shunt_systems::setup_shunt_systems;
 use crate::basic::ecs::elements::trans::trans_systems::setup_transformer;
+use crate::basic::ecs::factory::resolve_std_types;
 use crate::basic::ecs::network::apply_permutation;
+use crate::basic::ecs::plugin::{AfterPFInitStage, PFInitStage};
 use crate::io::pandapower::ecs_net_conv::pandapower_init_system;
 
 use super::init::{
@@ -31,10 +40,12 @@ pub struct PFInit;
 
 /// Drop stale projections and pending diff messages; the rebuild re-derives
 /// everything from the case data, so applying old diffs afterwards would
-/// double-count.
+/// double-count. Node merging by closed switches is derived again as well.
 pub fn cleanup_solver_state(world: &mut World) {
     world.remove_resource::<PowerFlowMat>();
     world.remove_resource::<PowerFlowResult>();
+    world.remove_resource::<NodeMapping>();
+    world.remove_resource::<NodeAggRes>();
     if let Some(mut msgs) = world.get_resource_mut::<bevy_ecs::message::Messages<ParamDiff>>() {
         msgs.clear();
     }
@@ -57,8 +68,11 @@ pub fn build_pf_init_schedule() -> Schedule {
     let mut s = Schedule::new(PFInit);
     s.add_systems(
         (
-            // 0. Ingest a pending pandapower network, if any (consumed once)
+            // 0. Ingest a pending pandapower network, if any (consumed once),
+            //    and fill parameters left to standard types
             pandapower_init_system,
+            resolve_std_types,
+            assign_element_indices,
             // 1. Invalidate projections + pending diffs
             cleanup_solver_state,
             // 2. Node lookup; zeroes SBusInjPu / VBusPu ("0 启动")
@@ -82,5 +96,17 @@ pub fn build_pf_init_schedule() -> Schedule {
         )
             .chain(),
     );
+    s.configure_sets((
+        PFInitStage.after(v_inj).before(init_states),
+        AfterPFInitStage.after(init_states).before(apply_permutation),
+    ));
     s
 }
+
+/// Adds the `PFInit` schedule to `app` unless it is there already, so
+/// plugins can add systems to it regardless of the order they are added in.
+pub fn init_pf_init_schedule(app: &mut App) {
+    if app.get_schedule(PFInit).is_none() {
+        app.add_schedule(build_pf_init_schedule());
+    }
+}
@@ -50,7 +50,7 @@ pub struct QLimEnv<'w, 's> {
 /// # Notes:
 /// - Assumes only **one generator per bus**, or at least uses the first found.
 /// - Requires consistent ordering with matrix reordering / aggregation structure.
-fn modify_qlim_system(
+pub(crate) fn modify_qlim_system(
     mut cmd: Commands,
     mut event: MessageWriter<NodeTypeChangeEvent>,
     env: QLimEnv,
@@ -79,17 +79,16 @@ fn modify_qlim_system(
     let mut structure_change = false;
     generators
         .iter()
-        .map(|d| {
+        .filter_map(|d| {
             let bus = d.0.0;
-            let e = buses.get_entity(bus).unwrap();
-            (e, bus, d.1)
+            Some((buses.get_entity(bus)?, buses.index(bus)?, d.1))
         })
-        .for_each(|(e, bus, lim)| {
+        .for_each(|(e, idx, lim)| {
             if !pf_bus.contains(e) {
                 return;
             }
             let mut q_target = pf_bus.get_mut(e).unwrap();
-            let q_mvar = (sbus_res[bus as usize].im - q_target.0.im) * common.sbase;
+            let q_mvar = (sbus_res[idx].im - q_target.0.im) * common.sbase;
             let qlim = &lim.q;
             // SBusInjPu at a PV bus holds the NON-generator injection (e.g.
             // -Q_load); the generator's Q is a free variable and not part of
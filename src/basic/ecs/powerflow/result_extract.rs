@@ -29,8 +29,7 @@ pub fn extract_powerflow_results(
         Some(node_agg) => &node_agg.expand_mat_v.cast() * &v,
         None => v,
     };
-    for i in 0..v.len() {
-        let entity = buses.get_entity(i as i64).unwrap();
+    for (i, (_, entity)) in buses.iter().enumerate().take(v.len()) {
         if let Ok(mut bus) = q.get_mut(entity) {
             bus.0 = v[i];
         }
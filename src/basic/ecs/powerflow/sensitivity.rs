@@ -0,0 +1,168 @@
+use bevy_app::App;
+use bevy_ecs::name::Name;
+use bevy_ecs::prelude::*;
+use nalgebra::DMatrix;
+
+use crate::basic::ecs::elements::*;
+use crate::basic::ecs::network::{DataOps, PowerGrid};
+use crate::basic::sensitivity::jacobian_sensitivities;
+use crate::basic::solver::DefaultSolver;
+
+use super::systems::{PowerFlowMat, PowerFlowResult};
+
+/// Loss sensitivity of one generator (gen, ext_grid or sgen).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenLossFactor {
+    pub entity: Entity,
+    pub bus: i64,
+    pub name: Option<String>,
+    /// `dP_loss / dP_gen` for an increase balanced by the slack.
+    pub loss_factor: f64,
+    /// `1 / (1 - loss_factor)`.
+    pub penalty_factor: f64,
+}
+
+/// Sensitivities of the last converged solution, in bus-id order.
+///
+/// Matrix rows are all buses in ascending id order (row `i` is
+/// [`NodeLookup::id_at`]`(i)`), columns the requested `buses`. Units are
+/// p.u. per MW/MVAr for `dvm_*` and degrees per MW/MVAr for `dva_*`.
+#[derive(Debug, Clone)]
+pub struct SensitivityReport {
+    pub buses: Vec<i64>,
+    pub dvm_dp: DMatrix<f64>,
+    pub dvm_dq: DMatrix<f64>,
+    pub dva_dp: DMatrix<f64>,
+    pub dva_dq: DMatrix<f64>,
+    /// Per generator in service, sorted by entity.
+    pub gen_loss_factors: Vec<GenLossFactor>,
+}
+
+/// Voltage and loss sensitivity analysis at the converged operating point.
+///
+/// The Jacobian is rebuilt from the stored result in the solver's
+/// `[PQ | PV | slack]` order and the answer mapped back to bus ids, so
+/// callers never see the permutation. The network is not modified.
+pub trait SensitivityAnalysis {
+    fn sensitivities(&mut self, buses: &[i64]) -> Result<SensitivityReport, String>;
+}
+
+impl SensitivityAnalysis for World {
+    fn sensitivities(&mut self, buses: &[i64]) -> Result<SensitivityReport, String> {
+        if self.contains_resource::<NodeAggRes>() {
+            return Err("Sensitivity analysis does not support merged buses".to_string());
+        }
+        let mut gen_q = self.query_filtered::<(Entity, &TargetBus, Option<&Name>), (
+            Or<(With<GeneratorCfg>, With<SGenDevice>)>,
+            Without<OutOfService>,
+        )>();
+        let world: &World = self;
+
+        let mat = world
+            .get_resource::<PowerFlowMat>()
+            .ok_or("Power flow matrices are not initialized")?;
+        let res = world
+            .get_resource::<PowerFlowResult>()
+            .filter(|r| r.converged)
+            .ok_or("No converged power flow result")?;
+        let sbase = world
+            .get_resource::<PFCommonData>()
+            .ok_or("Missing PFCommonData")?
+            .sbase;
+        let lookup = world
+            .get_resource::<NodeLookup>()
+            .ok_or("Missing NodeLookup")?;
+        let n = mat.v_bus_init.len();
+        let selected = buses
+            .iter()
+            .map(|&b| {
+                lookup
+                    .index(b)
+                    .map(|i| mat.reorder_index(i))
+                    .ok_or(format!("Unknown bus {b}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let sens = jacobian_sensitivities(
+            &mat.y_bus,
+            &res.v,
+            mat.npv,
+            mat.npq,
+            &selected,
+            &mut DefaultSolver::default(),
+        )?;
+        let unpermute = |m: &DMatrix<f64>, scale: f64| {
+            DMatrix::from_fn(n, buses.len(), |i, k| m[(mat.reorder_index(i), k)] * scale)
+        };
+        let deg = 180.0 / std::f64::consts::PI;
+
+        let mut gens = gen_q
+            .iter(world)
+            .filter_map(|(entity, bus, name)| Some((entity, bus, name, lookup.index(bus.0)?)))
+            .map(|(entity, bus, name, idx)| {
+                let loss_factor = sens.loss_factors[mat.reorder_index(idx)];
+                GenLossFactor {
+                    entity,
+                    bus: bus.0,
+                    name: name.map(|n| n.to_string()),
+                    loss_factor,
+                    penalty_factor: 1.0 / (1.0 - loss_factor),
+                }
+            })
+            .collect::<Vec<_>>();
+        gens.sort_by_key(|g| g.entity);
+
+        Ok(SensitivityReport {
+            buses: buses.to_vec(),
+            dvm_dp: unpermute(&sens.dvm_dp, 1.0 / sbase),
+            dvm_dq: unpermute(&sens.dvm_dq, 1.0 / sbase),
+            dva_dp: unpermute(&sens.dva_dp, deg / sbase),
+            dva_dq: unpermute(&sens.dva_dq, deg / sbase),
+            gen_loss_factors: gens,
+        })
+    }
+}
+
+impl SensitivityAnalysis for App {
+    fn sensitivities(&mut self, buses: &[i64]) -> Result<SensitivityReport, String> {
+        self.world_mut().sensitivities(buses)
+    }
+}
+
+impl SensitivityAnalysis for PowerGrid {
+    fn sensitivities(&mut self, buses: &[i64]) -> Result<SensitivityReport, String> {
+        self.world_mut().sensitivities(buses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::plugin::default_app;
+    use crate::io::pandapower::load_csv_zip;
+    use crate::prelude::PPNetwork;
+    use std::env;
+
+    #[test]
+    fn test_report_118() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+        let mut app = default_app();
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+
+        let report = app.sensitivities(&[1, 2]).unwrap();
+        assert_eq!(report.dvm_dq.shape(), (118, 2));
+        // More reactive injection at a PQ bus raises its own voltage.
+        let row = app.world().resource::<NodeLookup>().index(2).unwrap();
+        assert_eq!(report.buses[1], 2);
+        assert!(report.dvm_dq[(row, 1)] > 0.0);
+        assert!(!report.gen_loss_factors.is_empty());
+        for g in &report.gen_loss_factors {
+            assert!(g.loss_factor.abs() < 0.5, "{g:?}");
+            assert!((g.penalty_factor * (1.0 - g.loss_factor) - 1.0).abs() < 1e-12);
+        }
+        assert!(app.sensitivities(&[1000]).is_err());
+    }
+}
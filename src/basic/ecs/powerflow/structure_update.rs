@@ -97,18 +97,32 @@ pub fn reset_solvers(world: &mut World) {
 /// e.g. time series, update the components themselves and fire the event).
 /// Deliberately tick-free: a full O(n) copy is cheap and always correct,
 /// whereas `Changed<T>` semantics depend on observer tick bookkeeping.
-pub fn sbus_pu_update(mut pfmat: ResMut<PowerFlowMat>, sbus: Query<(&BusID, &SBusInjPu)>) {
+pub fn sbus_pu_update(
+    mut pfmat: ResMut<PowerFlowMat>,
+    nodes: Res<NodeLookup>,
+    sbus: Query<(&BusID, &SBusInjPu)>,
+) {
     for (bus_id, s) in sbus {
-        let idx = pfmat.reorder_index(bus_id.0 as usize);
+        let Some(idx) = nodes.index(bus_id.0) else {
+            continue;
+        };
+        let idx = pfmat.reorder_index(idx);
         pfmat.s_bus[idx] = s.0;
     }
 }
 /// Re-syncs the full `v_bus` vector in [`PowerFlowMat`] from the `VBusPu`
 /// components. Triggered by the coarse [`VoltageChangeEvent`]. Tick-free for
 /// the same reason as [`sbus_pu_update`].
-pub fn vbus_pu_update(mut pfmat: ResMut<PowerFlowMat>, vbus: Query<(&BusID, &VBusPu)>) {
+pub fn vbus_pu_update(
+    mut pfmat: ResMut<PowerFlowMat>,
+    nodes: Res<NodeLookup>,
+    vbus: Query<(&BusID, &VBusPu)>,
+) {
     for (bus_id, s) in vbus {
-        let idx = pfmat.reorder_index(bus_id.0 as usize);
+        let Some(idx) = nodes.index(bus_id.0) else {
+            continue;
+        };
+        let idx = pfmat.reorder_index(idx);
         pfmat.v_bus_init[idx] = s.0;
     }
 }
@@ -170,7 +184,7 @@ impl Plugin for StructureUpdatePlugin {
         app.add_message::<super::mutation::ParamDiff>();
         app.init_resource::<LastStructureAction>();
         // The single definition of "full rebuild", runnable on demand.
-        app.add_schedule(super::pf_init::build_pf_init_schedule());
+        super::pf_init::init_pf_init_schedule(app);
         // The mutation-bus consumer runs as an ordinary system right before
         // structure_update, so the change events it emits are seen this frame.
         app.add_systems(
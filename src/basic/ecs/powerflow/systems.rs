@@ -7,6 +7,7 @@ use num_traits::One;
 use crate::basic::ecs::elements::*;
 
 use super::init::*;
+pub use super::voltage_init::VoltageInit;
 // /// Resource that wraps the power flow network (PFNetwork).
 // #[derive(Debug, Resource, Clone, serde::Serialize, serde::Deserialize)]
 // pub struct ResPFNetwork(pub PFNetwork);
@@ -19,6 +20,8 @@ pub struct PowerFlowConfig {
     pub max_it: Option<usize>, // Maximum number of iterations
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tol: Option<f64>, // Tolerance for convergence
+    #[serde(default, skip_serializing_if = "VoltageInit::is_auto")]
+    pub init: VoltageInit, // Newton start-point strategy
 }
 
 /// Resource for storing the results of power flow calculation, including the final voltage vector,
@@ -51,6 +54,16 @@ impl PowerFlowMat {
     pub fn inverse_index(&self, perm: usize) -> usize {
         self.from_perm[perm]
     }
+
+    /// Key of the Jacobian sparsity pattern: the permuted Y-bus pattern
+    /// together with the PV/PQ split.
+    pub fn pattern_key(&self) -> crate::basic::solver::PatternKey {
+        crate::basic::solver::PatternKey::new(
+            self.y_bus.col_offsets(),
+            self.y_bus.row_indices(),
+            &[self.npv, self.npq],
+        )
+    }
 }
 /// Creates a permutation matrix for reordering buses in the power flow network.
 ///
@@ -130,12 +143,12 @@ pub(crate) fn create_y_bus(
         // Compute branch admittance in per-unit system
         admit_br[idx] = ad.0 * (vbase.0 * vbase.0) / s_base;
 
-        // Build incidence matrix
-        if topo.0[0] >= 0 {
-            incidence_matrix.push(topo.0[0] as usize, idx, Complex64::one());
+        // Build incidence matrix (ground and unknown buses have no row)
+        if let Some(i) = node_lookup.index(topo.0[0]) {
+            incidence_matrix.push(i, idx, Complex64::one());
         }
-        if topo.0[1] >= 0 {
-            incidence_matrix.push(topo.0[1] as usize, idx, -Complex64::one());
+        if let Some(i) = node_lookup.index(topo.0[1]) {
+            incidence_matrix.push(i, idx, -Complex64::one());
         }
     }
 
@@ -153,15 +166,16 @@ pub(crate) fn create_y_bus(
         // Compute branch admittance in per-unit system
         let p = patch.0.scale((vbase * vbase) / s_base);
         // Build incidence matrix
-        if from.0 >= 0 {
-            trans_patch_matrix.push(from.0 as usize, from.0 as usize, p[(0, 0)]);
+        let (from, to) = (node_lookup.index(from.0), node_lookup.index(to.0));
+        if let Some(f) = from {
+            trans_patch_matrix.push(f, f, p[(0, 0)]);
         }
-        if to.0 >= 0 {
-            trans_patch_matrix.push(to.0 as usize, to.0 as usize, p[(1, 1)]);
+        if let Some(t) = to {
+            trans_patch_matrix.push(t, t, p[(1, 1)]);
         }
-        if from.0 >= 0 && to.0 >= 0 {
-            trans_patch_matrix.push(from.0 as usize, to.0 as usize, p[(0, 1)]);
-            trans_patch_matrix.push(to.0 as usize, from.0 as usize, p[(1, 0)]);
+        if let (Some(f), Some(t)) = (from, to) {
+            trans_patch_matrix.push(f, t, p[(0, 1)]);
+            trans_patch_matrix.push(t, f, p[(1, 0)]);
         }
     }
     let y_bus = y_bus + CsrMatrix::from(&trans_patch_matrix);
@@ -247,17 +261,21 @@ pub(crate) fn init_bus_status(
     // Initialize power injections and voltage vectors
     let mut s_bus = DVector::zeros(nodes);
     let mut v_bus_init = DVector::from_element(nodes, Complex64::one());
-    let mut pq_only: Vec<_> = pq.iter().map(|x| x.0.0).collect();
-    let mut pv_only: Vec<_> = pv.iter().map(|x| x.0.0).collect();
-    let mut exts: Vec<_> = ext.iter().map(|x| x.0.0).collect();
+    // Dense indices of the buses; buses missing from the lookup are skipped.
+    let index = |bus_id: &BusID| node_lookup.index(bus_id.0).map(|i| i as i64);
+    let mut pq_only: Vec<_> = pq.iter().filter_map(|x| index(x.0)).collect();
+    let mut pv_only: Vec<_> = pv.iter().filter_map(|x| index(x.0)).collect();
+    let mut exts: Vec<_> = ext.iter().filter_map(|x| index(x.0)).collect();
 
     sbus.iter().for_each(|(bus_id, s)| {
-        let idx = bus_id.0 as usize;
-        s_bus[idx] = s.0;
+        if let Some(idx) = node_lookup.index(bus_id.0) {
+            s_bus[idx] = s.0;
+        }
     });
     vbus.iter().for_each(|(bus_id, s)| {
-        let idx = bus_id.0 as usize;
-        v_bus_init[idx] = s.0;
+        if let Some(idx) = node_lookup.index(bus_id.0) {
+            v_bus_init[idx] = s.0;
+        }
     });
 
     let npv = pv_only.len();
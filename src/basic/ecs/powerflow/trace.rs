@@ -0,0 +1,152 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+
+use crate::basic::{
+    ecs::elements::{NodeAggRes, NodeLookup},
+    newtonpf::NewtonIterRecord,
+};
+
+use super::systems::PowerFlowMat;
+
+/// One Newton iteration of the last solve. Buses are reported as the
+/// original `BusID`, not the solver's permuted index.
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IterationTrace {
+    pub iteration: usize,           // 0 = start point, before any step
+    pub mismatch_norm: f64,         // ||F||_2 after this iteration
+    pub max_p_bus: Option<i64>,     // BusID with the largest |ΔP|
+    pub max_p_mismatch: f64,        // signed ΔP at that bus (p.u.)
+    pub max_q_bus: Option<i64>,     // BusID with the largest |ΔQ|
+    pub max_q_mismatch: f64,        // signed ΔQ at that bus (p.u.)
+    pub step_norm: f64,             // ||dx||_2 of the Newton step
+    pub fill_ms: f64,               // Jacobian fill time
+    pub factor_ms: f64,             // factorization time
+    pub solve_ms: f64,              // triangular solve time
+}
+
+/// Opt-in per-iteration convergence trace of the last Newton-Raphson solve.
+///
+/// The default solver system only records when this resource exists, so
+/// tracing costs nothing unless [`ConvergenceTracePlugin`] is added (or the
+/// resource inserted by hand). Each solve overwrites the previous trace.
+#[derive(Debug, Default, Resource, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConvergenceTrace {
+    pub records: Vec<IterationTrace>,
+}
+
+impl ConvergenceTrace {
+    /// Replaces the trace with `raw`, mapping permuted solver indices back
+    /// to `BusID` through the permutation (and node aggregation, if any)
+    /// and the dense bus index of `nodes`.
+    pub fn record(
+        &mut self,
+        raw: &[NewtonIterRecord],
+        mat: &PowerFlowMat,
+        node_agg: Option<&NodeAggRes>,
+        nodes: Option<&NodeLookup>,
+    ) {
+        let bus_id = |perm: usize| -> i64 {
+            let idx = mat.inverse_index(perm);
+            let idx = match node_agg {
+                // A merged node stands for several buses; report the first.
+                Some(agg) => agg
+                    .expand_mat
+                    .col(idx)
+                    .row_indices()
+                    .first()
+                    .map_or(idx, |&r| r),
+                None => idx,
+            };
+            nodes.and_then(|n| n.id_at(idx)).unwrap_or(idx as i64)
+        };
+        let ms = |d: std::time::Duration| d.as_secs_f64() * 1e3;
+
+        self.records.clear();
+        self.records.extend(raw.iter().map(|r| IterationTrace {
+            iteration: r.iteration,
+            mismatch_norm: r.mismatch_norm,
+            max_p_bus: r.max_p.map(|(i, _)| bus_id(i)),
+            max_p_mismatch: r.max_p.map_or(0.0, |(_, v)| v),
+            max_q_bus: r.max_q.map(|(i, _)| bus_id(i)),
+            max_q_mismatch: r.max_q.map_or(0.0, |(_, v)| v),
+            step_norm: r.step_norm,
+            fill_ms: ms(r.fill),
+            factor_ms: ms(r.timing.factor),
+            solve_ms: ms(r.timing.solve),
+        }));
+    }
+
+    /// Total time spent in Jacobian fill, factorization and solve (ms).
+    pub fn totals_ms(&self) -> (f64, f64, f64) {
+        self.records.iter().fold((0.0, 0.0, 0.0), |acc, r| {
+            (acc.0 + r.fill_ms, acc.1 + r.factor_ms, acc.2 + r.solve_ms)
+        })
+    }
+}
+
+/// Enables [`ConvergenceTrace`] recording for the default NR solver.
+#[derive(Default)]
+pub struct ConvergenceTracePlugin;
+
+impl Plugin for ConvergenceTracePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConvergenceTrace>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::plugin::default_app;
+    use crate::basic::ecs::powerflow::systems::PowerFlowResult;
+    use crate::io::pandapower::load_csv_zip;
+    use crate::prelude::PPNetwork;
+    use std::env;
+
+    #[test]
+    fn test_trace_118() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+
+        let mut app = default_app();
+        app.add_plugins(ConvergenceTracePlugin);
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+
+        let res = app.world().resource::<PowerFlowResult>();
+        let trace = app.world().resource::<ConvergenceTrace>();
+        assert!(res.converged);
+        assert_eq!(trace.records.len(), res.iterations + 1);
+        assert_eq!(trace.records[0].iteration, 0);
+        assert_eq!(trace.records[0].step_norm, 0.0);
+
+        let last = trace.records.last().unwrap();
+        assert!(last.mismatch_norm < 1e-6);
+        assert!(last.mismatch_norm < trace.records[0].mismatch_norm);
+        // Mismatch buses are reported as original bus ids.
+        for r in &trace.records {
+            let bus = r.max_p_bus.unwrap();
+            assert!((0..118).contains(&bus));
+        }
+    }
+
+    #[test]
+    fn test_trace_iwamoto_118() {
+        use crate::basic::ecs::plugin::IwamotoPlugin;
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+
+        let mut app = default_app();
+        app.add_plugins((ConvergenceTracePlugin, IwamotoPlugin));
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+
+        let res = app.world().resource::<PowerFlowResult>();
+        let trace = app.world().resource::<ConvergenceTrace>();
+        assert!(res.converged);
+        assert_eq!(trace.records.len(), res.iterations + 1);
+        assert!(trace.records.last().unwrap().mismatch_norm < 1e-6);
+    }
+}
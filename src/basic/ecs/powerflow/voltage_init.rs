@@ -0,0 +1,286 @@
+//! Newton start-point strategies, selected by [`PowerFlowConfig::init`].
+//!
+//! The start vector is written into `PowerFlowMat::v_bus_init` right before
+//! the solver system runs. Every strategy only touches the free variables:
+//! PQ magnitudes and non-slack angles. PV magnitudes and the slack voltage
+//! stay at their setpoints, so the choice never changes the physics.
+
+use std::collections::BTreeMap;
+
+use bevy_ecs::prelude::*;
+use nalgebra::{ComplexField, DVector};
+use nalgebra_sparse::{CooMatrix, CscMatrix};
+use num_complex::Complex64;
+
+use crate::basic::ecs::elements::{NodeAggRes, NodeLookup};
+use crate::basic::solver::{DefaultSolver, Solve};
+
+use super::systems::{PowerFlowConfig, PowerFlowMat, PowerFlowResult};
+
+/// Initial voltage strategy for the Newton-Raphson solve.
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum VoltageInit {
+    /// Keep the voltages derived from the bus state (`VBusPu`): a flat start
+    /// with generator setpoints after a rebuild, and the previous solution
+    /// once `VBusUpdatePlugin` writes it back.
+    #[default]
+    Auto,
+    /// |V| = 1 p.u. at PQ buses, all angles equal to the slack angle.
+    Flat,
+    /// Flat magnitudes with angles from a DC power flow. The DC step is
+    /// taken from the flat point, so phase-shifter angles are included.
+    Dc,
+    /// The last converged solution; falls back to `Auto` when there is none
+    /// and to `Flat` when the buses or their solver order changed since.
+    WarmStart,
+    /// User-provided `(vm_pu, va_degree)` keyed by `BusID`. Buses that are
+    /// not listed keep their `Auto` value.
+    User(BTreeMap<i64, (f64, f64)>),
+}
+
+impl VoltageInit {
+    pub fn is_auto(&self) -> bool {
+        matches!(self, VoltageInit::Auto)
+    }
+}
+
+/// Last converged voltage vector in unpermuted (bus) order. Only maintained
+/// while [`VoltageInit::WarmStart`] is selected.
+#[derive(Debug, Default, Resource, Clone)]
+pub struct LastConvergedVoltage {
+    pub v: DVector<Complex64>,
+    /// `PowerFlowMat::from_perm` of the solve that produced `v`.
+    pub from_perm: Vec<usize>,
+    /// `NodeLookup` bus ids of that solve.
+    pub bus_ids: Vec<i64>,
+}
+
+/// Writes the start vector selected by [`PowerFlowConfig::init`] into
+/// `PowerFlowMat::v_bus_init`. Runs right before the solver system.
+pub fn apply_voltage_init(
+    cfg: Res<PowerFlowConfig>,
+    mat: Option<ResMut<PowerFlowMat>>,
+    last: Option<Res<LastConvergedVoltage>>,
+    node_agg: Option<Res<NodeAggRes>>,
+    nodes: Option<Res<NodeLookup>>,
+) {
+    let Some(mut mat) = mat else { return };
+    match &cfg.init {
+        VoltageInit::Auto => {}
+        VoltageInit::Flat => flat_start(&mut mat),
+        VoltageInit::Dc => {
+            flat_start(&mut mat);
+            dc_angles(&mut mat);
+        }
+        VoltageInit::WarmStart => {
+            let Some(last) = last else { return };
+            let bus_ids = nodes.as_deref().map_or(&[][..], |n| n.ids());
+            if last.from_perm != mat.from_perm || last.bus_ids != bus_ids {
+                flat_start(&mut mat);
+                return;
+            }
+            for i in 0..mat.v_bus_init.len() {
+                let v = last.v[mat.inverse_index(i)];
+                set_start(&mut mat, i, v);
+            }
+        }
+        VoltageInit::User(values) => {
+            for (&bus, &(vm, va)) in values {
+                let bus = nodes.as_deref().and_then(|n| n.index(bus));
+                if let Some(i) = bus.and_then(|b| solver_index(&mat, node_agg.as_deref(), b)) {
+                    set_start(&mut mat, i, Complex64::from_polar(vm, va.to_radians()));
+                }
+            }
+        }
+    }
+}
+
+/// Caches the converged voltage for [`VoltageInit::WarmStart`].
+pub fn store_converged_voltage(
+    mut cmd: Commands,
+    cfg: Res<PowerFlowConfig>,
+    mat: Option<Res<PowerFlowMat>>,
+    res: Option<Res<PowerFlowResult>>,
+    nodes: Option<Res<NodeLookup>>,
+) {
+    if cfg.init != VoltageInit::WarmStart {
+        return;
+    }
+    let (Some(mat), Some(res)) = (mat, res) else { return };
+    if !res.converged || res.v.len() != mat.from_perm.len() {
+        return;
+    }
+    let mut v = DVector::zeros(res.v.len());
+    for (i, &orig) in mat.from_perm.iter().enumerate() {
+        v[orig] = res.v[i];
+    }
+    cmd.insert_resource(LastConvergedVoltage {
+        v,
+        from_perm: mat.from_perm.clone(),
+        bus_ids: nodes.map_or_else(Vec::new, |n| n.ids().to_vec()),
+    });
+}
+
+/// Maps a dense bus index to its index in the permuted solver vectors.
+pub(crate) fn solver_index(mat: &PowerFlowMat, node_agg: Option<&NodeAggRes>, bus: usize) -> Option<usize> {
+    let idx = match node_agg {
+        Some(agg) => agg
+            .expand_mat
+            .triplet_iter()
+            .find(|(r, _, _)| *r == bus)
+            .map(|(_, c, _)| c)?,
+        None => bus,
+    };
+    mat.to_perm.get(idx).copied()
+}
+
+/// Sets the start value of permuted bus `i`, keeping setpoints pinned:
+/// PQ buses take `v`, PV buses only its angle, the slack is untouched.
+fn set_start(mat: &mut PowerFlowMat, i: usize, v: Complex64) {
+    let n_bus = mat.npv + mat.npq;
+    if i < mat.npq {
+        mat.v_bus_init[i] = v;
+    } else if i < n_bus {
+        let vm = mat.v_bus_init[i].modulus();
+        mat.v_bus_init[i] = Complex64::from_polar(vm, v.arg());
+    }
+}
+
+/// Flat start referenced to the (first) slack angle.
+fn flat_start(mat: &mut PowerFlowMat) {
+    let n_bus = mat.npv + mat.npq;
+    let va_ref = mat.v_bus_init.rows_range(n_bus..).iter().next().map_or(0.0, |v| v.arg());
+    for i in 0..n_bus {
+        set_start(mat, i, Complex64::from_polar(1.0, va_ref));
+    }
+}
+
+/// One DC (B'θ = P) angle step from the current start point.
+///
+/// B' is the weighted Laplacian built from the off-diagonal susceptances of
+/// the permuted Y-bus, reduced to the non-slack buses `[0, npv + npq)`. The
+/// right-hand side is the active power mismatch at the start point, which
+/// carries the phase-shifter flows of a flat start.
+fn dc_angles(mat: &mut PowerFlowMat) {
+    let n_bus = mat.npv + mat.npq;
+    if n_bus == 0 {
+        return;
+    }
+    let v0 = &mat.v_bus_init;
+    let mis = v0.component_mul(&(&mat.y_bus * v0).conjugate()) - &mat.s_bus;
+
+    let mut coo = CooMatrix::new(n_bus, n_bus);
+    let mut diag = vec![0.0; n_bus];
+    for (r, c, y) in mat.y_bus.triplet_iter() {
+        if r == c || r >= n_bus {
+            continue;
+        }
+        let b = y.im;
+        diag[r] += b;
+        if c < n_bus {
+            coo.push(r, c, -b);
+        }
+    }
+    for (i, d) in diag.into_iter().enumerate() {
+        coo.push(i, i, d);
+    }
+    let (mut ap, mut ai, mut ax) = CscMatrix::from(&coo).disassemble();
+    let mut dtheta: Vec<f64> = mis.rows_range(0..n_bus).iter().map(|s| -s.re).collect();
+
+    let mut solver = DefaultSolver::default();
+    if solver.solve(&mut ap, &mut ai, &mut ax, &mut dtheta, n_bus).is_err() {
+        return; // singular B' (islanded subnetwork): keep the flat start
+    }
+    for (i, dt) in dtheta.into_iter().enumerate() {
+        let v = mat.v_bus_init[i];
+        mat.v_bus_init[i] = Complex64::from_polar(v.modulus(), v.arg() + dt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::plugin::default_app;
+    use crate::io::pandapower::load_csv_zip;
+    use crate::prelude::PPNetwork;
+    use bevy_app::App;
+    use std::env;
+
+    fn app_118(init: VoltageInit) -> App {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+
+        let mut app = default_app();
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.world_mut().resource_mut::<PowerFlowConfig>().init = init;
+        app
+    }
+
+    #[test]
+    fn test_dc_init_118() {
+        let mut flat = app_118(VoltageInit::Flat);
+        flat.update();
+        let flat_res = flat.world().resource::<PowerFlowResult>();
+
+        let mut dc = app_118(VoltageInit::Dc);
+        dc.update();
+        let dc_res = dc.world().resource::<PowerFlowResult>();
+
+        assert!(flat_res.converged && dc_res.converged);
+        assert!(dc_res.iterations <= flat_res.iterations);
+        assert!((&dc_res.v - &flat_res.v).camax() < 1e-5);
+    }
+
+    #[test]
+    fn test_warm_start_118() {
+        let mut app = app_118(VoltageInit::WarmStart);
+        app.update();
+        assert!(app.world().contains_resource::<LastConvergedVoltage>());
+        app.update();
+        let res = app.world().resource::<PowerFlowResult>();
+        assert!(res.converged);
+        assert_eq!(res.iterations, 0);
+    }
+
+    #[test]
+    fn test_warm_start_order_changed_118() {
+        let mut flat = app_118(VoltageInit::Flat);
+        flat.update();
+        let flat_iterations = flat.world().resource::<PowerFlowResult>().iterations;
+
+        let mut app = app_118(VoltageInit::WarmStart);
+        app.update();
+        // Same length, different solver order: the cache must not be used.
+        let mut last = app.world().resource::<LastConvergedVoltage>().clone();
+        last.from_perm.reverse();
+        last.v.fill(Complex64::new(0.5, 0.0));
+        app.world_mut().insert_resource(last);
+        app.update();
+        let res = app.world().resource::<PowerFlowResult>();
+        assert!(res.converged);
+        assert_eq!(res.iterations, flat_iterations);
+    }
+
+    #[test]
+    fn test_user_init_118() {
+        let mut app = app_118(VoltageInit::Auto);
+        app.update();
+        let v = {
+            let mat = app.world().resource::<PowerFlowMat>();
+            let res = app.world().resource::<PowerFlowResult>();
+            (0..res.v.len())
+                .map(|i| {
+                    let v = res.v[i];
+                    (mat.inverse_index(i) as i64, (v.modulus(), v.arg().to_degrees()))
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let mut user = app_118(VoltageInit::User(v));
+        user.update();
+        let res = user.world().resource::<PowerFlowResult>();
+        assert!(res.converged);
+        assert_eq!(res.iterations, 0);
+    }
+}
@@ -1,4 +1,5 @@
 use std::f64::consts::PI;
+use std::time::{Duration, Instant};
 
 use nalgebra::{DVector, ComplexField, SimdComplexField};
 use nalgebra_sparse::CscMatrix;
@@ -6,8 +7,8 @@ use num_complex::Complex64;
 use num_traits::Zero;
 
 use super::new_dsdvbus2::{fill_jacobian_v2, JacobianPattern2};
-use super::newtonpf::assemble_f_v2;
-use super::solver::Solve;
+use super::newtonpf::{assemble_f_v2, max_abs, NewtonFailure, NewtonIterRecord};
+use super::solver::{Solve, SolveTiming};
 
 /// Newton-Raphson power flow with Iwamoto optimal multiplier step size control.
 #[allow(non_snake_case, clippy::too_many_arguments)]
@@ -20,7 +21,24 @@ pub fn newton_pf_iwamoto<Solver: Solve>(
     tolerance: Option<f64>,
     max_iter: Option<usize>,
     solver: &mut Solver,
-) -> Result<(DVector<Complex64>, usize), (String, DVector<Complex64>, usize)> {
+) -> Result<(DVector<Complex64>, usize), NewtonFailure> {
+    newton_pf_iwamoto_traced(Ybus, Sbus, v_init, npv, npq, tolerance, max_iter, solver, None)
+}
+
+/// [`newton_pf_iwamoto`] that optionally appends a [`NewtonIterRecord`] per
+/// iteration to `trace`; `step_norm` is the norm of the scaled step.
+#[allow(non_snake_case, clippy::too_many_arguments)]
+pub fn newton_pf_iwamoto_traced<Solver: Solve>(
+    Ybus: &CscMatrix<Complex64>,
+    Sbus: &DVector<Complex64>,
+    v_init: &DVector<Complex64>,
+    npv: usize,
+    npq: usize,
+    tolerance: Option<f64>,
+    max_iter: Option<usize>,
+    solver: &mut Solver,
+    mut trace: Option<&mut Vec<NewtonIterRecord>>,
+) -> Result<(DVector<Complex64>, usize), NewtonFailure> {
     let mut v = v_init.clone();
     let max_iter = max_iter.unwrap_or(100);
     let tol = tolerance.unwrap_or(1e-6);
@@ -38,6 +56,15 @@ pub fn newton_pf_iwamoto<Solver: Solve>(
     let mut mis = &v.component_mul(&(Ybus * &v).conjugate()) - Sbus;
     let mut F = DVector::zeros(n_state);
     assemble_f_v2(&mut F, n_bus, &mis, n_state, npq);
+    if let Some(trace) = trace.as_deref_mut() {
+        trace.push(NewtonIterRecord {
+            iteration: 0,
+            mismatch_norm: F.norm(),
+            max_p: max_abs(F.rows_range(0..n_bus).as_slice()),
+            max_q: max_abs(F.rows_range(n_bus..n_state).as_slice()),
+            ..Default::default()
+        });
+    }
     if F.norm() < tol {
         return Ok((v, 0));
     }
@@ -59,9 +86,12 @@ pub fn newton_pf_iwamoto<Solver: Solve>(
         )
     };
 
-    for it in 0..max_iter {
+    // Newton steps taken, reported on success and failure alike.
+    let mut steps = 0;
+    while steps < max_iter {
         let ibus = Ybus * &v;
 
+        let t_fill = Instant::now();
         fill_jacobian_v2(
             Ybus,
             v.as_slice(),
@@ -73,6 +103,9 @@ pub fn newton_pf_iwamoto<Solver: Solve>(
             &mut j_values,
         );
 
+        let t_solve = Instant::now();
+        let fill = t_solve - t_fill;
+
         // Save original mismatch vector a before solver.solve overwrites it
         let a = F.clone();
 
@@ -83,6 +116,10 @@ pub fn newton_pf_iwamoto<Solver: Solve>(
             F.data.as_mut_slice(),
             n_state,
         );
+        let timing = solver.last_timing().unwrap_or(SolveTiming {
+            factor: Duration::ZERO,
+            solve: t_solve.elapsed(),
+        });
 
         let dx = &F;
 
@@ -113,6 +150,7 @@ pub fn newton_pf_iwamoto<Solver: Solve>(
 
         // Find the optimal multiplier mu
         let mu = solve_iwamoto_multiplier(&a, &c);
+        let step_norm = if trace.is_some() { mu * dx.norm() } else { 0.0 };
 
         // Angle update: all non-slack buses.
         v_a.rows_range_mut(0..n_bus)
@@ -131,12 +169,26 @@ pub fn newton_pf_iwamoto<Solver: Solve>(
             .sub_to(Sbus, &mut mis);
         assemble_f_v2(&mut F, n_bus, &mis, n_state, npq);
 
+        steps += 1;
+
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.push(NewtonIterRecord {
+                iteration: steps,
+                mismatch_norm: F.norm(),
+                max_p: max_abs(F.rows_range(0..n_bus).as_slice()),
+                max_q: max_abs(F.rows_range(n_bus..n_state).as_slice()),
+                step_norm,
+                fill,
+                timing,
+            });
+        }
+
         if F.norm() < tol {
-            return Ok((v, it + 1));
+            return Ok((v, steps));
         }
     }
 
-    Err((String::from("Did not converge!"), v, max_iter))
+    Err((String::from("Did not converge!"), v, steps))
 }
 
 #[allow(non_snake_case)]
@@ -5,12 +5,18 @@ pub(crate) mod new_dsdvbus2;
 pub(crate) mod pf_old_impl;
 pub mod newtonpf;
 pub mod iwamoto;
+pub mod batch;
+pub mod sensitivity;
+pub mod stability;
 
 pub mod ecs;
 pub mod solver;
 pub(crate) mod sparse;
-pub use newtonpf::newton_pf;
-pub use iwamoto::newton_pf_iwamoto;
+pub use newtonpf::{newton_pf, newton_pf_traced};
+pub use iwamoto::{newton_pf_iwamoto, newton_pf_iwamoto_traced};
+pub use batch::{BatchPFResult, newton_pf_batch};
+pub use sensitivity::{Sensitivities, jacobian_sensitivities};
+pub use stability::{ModalResult, l_index, modal_analysis};
 
 #[cfg(test)]
 mod test_jacobian_pattern;
@@ -1,8 +1,9 @@
 #![allow(unused)]
 use std::f64::consts::PI;
+use std::time::{Duration, Instant};
 
 use super::new_dsdvbus2::{fill_jacobian_v2, JacobianPattern2};
-use super::solver::Solve;
+use super::solver::{Solve, SolveTiming};
 use super::sparse::slice::*;
 use nalgebra::*;
 use nalgebra_sparse::*;
@@ -41,6 +42,33 @@ impl<T: Clone + Zero + Scalar + ClosedAddAssign> Slice for CscMatrix<T> {
 
 // ─── Default solver: newton_pf ────────────────────────────────────────────────
 
+/// Per-iteration diagnostics collected by [`newton_pf_traced`].
+///
+/// Bus indices refer to the permuted `[PQ | PV | slack]` order; mapping them
+/// back to `BusID` is the caller's job. Record `0` describes the start point
+/// (no step, no timings).
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NewtonIterRecord {
+    pub iteration: usize,
+    /// 2-norm of the mismatch vector `F` after this iteration's update.
+    pub mismatch_norm: f64,
+    /// Permuted bus index and signed value of the largest |ΔP| (p.u.).
+    pub max_p: Option<(usize, f64)>,
+    /// Permuted bus index and signed value of the largest |ΔQ| (p.u.).
+    pub max_q: Option<(usize, f64)>,
+    /// 2-norm of the Newton step `dx`.
+    pub step_norm: f64,
+    /// Time spent filling the Jacobian values.
+    pub fill: Duration,
+    /// Time spent in the solver call. Backends without
+    /// [`Solve::last_timing`] report the whole call as `solve`.
+    pub timing: SolveTiming,
+}
+
+/// Error of the Newton-Raphson solvers: a message, the last iterate and the
+/// number of Newton steps taken.
+pub type NewtonFailure = (String, DVector<Complex64>, usize);
+
 /// Newton-Raphson power flow under the `[PQ | PV | slack]` bus ordering.
 /// Branch-free Jacobian assembly via `JacobianPattern2` + `fill_jacobian_v2`.
 ///
@@ -56,17 +84,63 @@ pub fn newton_pf<Solver: Solve>(
     tolerance: Option<f64>,
     max_iter: Option<usize>,
     solver: &mut Solver,
-) -> Result<(DVector<Complex64>, usize), (String, DVector<Complex64>, usize)> {
-    let mut v = v_init.clone();
-    let max_iter = max_iter.unwrap_or(100);
-    let tol = tolerance.unwrap_or(1e-6);
+) -> Result<(DVector<Complex64>, usize), NewtonFailure> {
+    newton_pf_traced(Ybus, Sbus, v_init, npv, npq, tolerance, max_iter, solver, None)
+}
 
+/// [`newton_pf`] that optionally appends a [`NewtonIterRecord`] per
+/// iteration to `trace`. With `trace == None` the two are identical.
+#[allow(non_snake_case, clippy::too_many_arguments)]
+pub fn newton_pf_traced<Solver: Solve>(
+    Ybus: &CscMatrix<Complex64>,
+    Sbus: &DVector<Complex64>,
+    v_init: &DVector<Complex64>,
+    npv: usize,
+    npq: usize,
+    tolerance: Option<f64>,
+    max_iter: Option<usize>,
+    solver: &mut Solver,
+    trace: Option<&mut Vec<NewtonIterRecord>>,
+) -> Result<(DVector<Complex64>, usize), NewtonFailure> {
     let j_pattern = JacobianPattern2::build_from_permuted(
         Ybus.col_offsets(),
         Ybus.row_indices(),
         npv,
         npq,
     );
+    let mut Ap = j_pattern.j_col_ptrs.clone();
+    let mut Ai = j_pattern.j_row_indices.clone();
+    newton_pf_with_pattern(
+        Ybus, Sbus, v_init, npv, npq, tolerance, max_iter, solver, &j_pattern, &mut Ap, &mut Ai,
+        trace,
+    )
+}
+
+/// Newton-Raphson kernel on a prebuilt [`JacobianPattern2`].
+///
+/// `Ap`/`Ai` are the pattern's column pointers and row indices as handed to
+/// the solver; callers that share one pattern across threads pass their own
+/// copies. Keeping the pattern fixed lets `solver` keep its symbolic
+/// factorization across calls.
+#[allow(non_snake_case, clippy::too_many_arguments)]
+pub(crate) fn newton_pf_with_pattern<Solver: Solve>(
+    Ybus: &CscMatrix<Complex64>,
+    Sbus: &DVector<Complex64>,
+    v_init: &DVector<Complex64>,
+    npv: usize,
+    npq: usize,
+    tolerance: Option<f64>,
+    max_iter: Option<usize>,
+    solver: &mut Solver,
+    j_pattern: &JacobianPattern2,
+    Ap: &mut [usize],
+    Ai: &mut [usize],
+    mut trace: Option<&mut Vec<NewtonIterRecord>>,
+) -> Result<(DVector<Complex64>, usize), NewtonFailure> {
+    let mut v = v_init.clone();
+    let max_iter = max_iter.unwrap_or(100);
+    let tol = tolerance.unwrap_or(1e-6);
+
     let n_state = npv + 2 * npq;
     let mut j_values = vec![0.0; j_pattern.nnz_j];
 
@@ -74,7 +148,17 @@ pub fn newton_pf<Solver: Solve>(
     let mut mis = &v.component_mul(&(Ybus * &v).conjugate()) - Sbus;
     let mut F = DVector::zeros(n_state);
     assemble_f_v2(&mut F, n_bus, &mis, n_state, npq);
-    if F.norm() < tol {
+    let f_norm = F.norm();
+    if let Some(trace) = trace.as_deref_mut() {
+        trace.push(NewtonIterRecord {
+            iteration: 0,
+            mismatch_norm: f_norm,
+            max_p: max_abs(F.rows_range(0..n_bus).as_slice()),
+            max_q: max_abs(F.rows_range(n_bus..n_state).as_slice()),
+            ..Default::default()
+        });
+    }
+    if f_norm < tol {
         return Ok((v, 0));
     }
 
@@ -82,32 +166,24 @@ pub fn newton_pf<Solver: Solve>(
     let mut v_a = v.map(|e| e.simd_argument());
     let mut v_norm = v.map(|e| e.simd_signum());
 
-    let Ap = unsafe {
-        std::slice::from_raw_parts_mut(
-            j_pattern.j_col_ptrs.as_ptr() as *mut usize,
-            j_pattern.j_col_ptrs.len(),
-        )
-    };
-    let Ai = unsafe {
-        std::slice::from_raw_parts_mut(
-            j_pattern.j_row_indices.as_ptr() as *mut usize,
-            j_pattern.j_row_indices.len(),
-        )
-    };
-
-    for it in 0..max_iter {
+    // Newton steps taken, reported on success and failure alike.
+    let mut steps = 0;
+    while steps < max_iter {
         let ibus = Ybus * &v;
 
+        let t_fill = Instant::now();
         fill_jacobian_v2(
             Ybus,
             v.as_slice(),
             v_norm.as_slice(),
             ibus.as_slice(),
-            &j_pattern,
+            j_pattern,
             npv,
             npq,
             &mut j_values,
         );
+        let t_solve = Instant::now();
+        let fill = t_solve - t_fill;
 
         let _ = solver.solve(
             Ap,
@@ -116,8 +192,13 @@ pub fn newton_pf<Solver: Solve>(
             F.data.as_mut_slice(),
             n_state,
         );
+        let timing = solver.last_timing().unwrap_or(SolveTiming {
+            factor: Duration::ZERO,
+            solve: t_solve.elapsed(),
+        });
 
         let dx = &F;
+        let step_norm = if trace.is_some() { dx.norm() } else { 0.0 };
 
         // Angle update: all non-slack buses.
         v_a.rows_range_mut(0..n_bus)
@@ -135,17 +216,40 @@ pub fn newton_pf<Solver: Solve>(
         v.component_mul(&(Ybus * &v).conjugate())
             .sub_to(Sbus, &mut mis);
         assemble_f_v2(&mut F, n_bus, &mis, n_state, npq);
+        let f_norm = F.norm();
 
-        if F.norm() < tol {
-            return Ok((v, it));
+        steps += 1;
+
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.push(NewtonIterRecord {
+                iteration: steps,
+                mismatch_norm: f_norm,
+                max_p: max_abs(F.rows_range(0..n_bus).as_slice()),
+                max_q: max_abs(F.rows_range(n_bus..n_state).as_slice()),
+                step_norm,
+                fill,
+                timing,
+            });
+        }
+
+        if f_norm < tol {
+            return Ok((v, steps));
         }
     }
 
-    Err((String::from("Did not converge!"), v, max_iter))
+    Err((String::from("Did not converge!"), v, steps))
 }
 
 // ─── helpers ─────────────────────────────────────────────────────────────────
 
+/// Index and signed value of the entry with the largest magnitude.
+pub(crate) fn max_abs(x: &[f64]) -> Option<(usize, f64)> {
+    x.iter()
+        .copied()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+}
+
 /// Mismatch RHS under `[PQ | PV | slack]` ordering.
 ///
 /// `F[0..n_bus]`      = Re(mis[0..n_bus])
@@ -163,3 +267,80 @@ pub(crate) fn assemble_f_v2(
     f.rows_range_mut(n_bus..num_state)
         .zip_apply(&mis.rows_range(0..npq), |a, b| *a = b.simd_imaginary());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::plugin::default_app;
+    use crate::basic::ecs::powerflow::systems::PowerFlowMat;
+    use crate::basic::solver::DefaultSolver;
+    use crate::io::pandapower::load_csv_zip;
+    use crate::prelude::PPNetwork;
+    use std::env;
+
+    /// Counts the linear solves, one per Newton step.
+    #[derive(Default)]
+    struct CountingSolver(DefaultSolver, usize);
+
+    impl Solve for CountingSolver {
+        type Symbolic = <DefaultSolver as Solve>::Symbolic;
+
+        fn solve(
+            &mut self,
+            ap: &mut [usize],
+            ai: &mut [usize],
+            ax: &mut [f64],
+            b: &mut [f64],
+            n: usize,
+        ) -> Result<(), &'static str> {
+            self.1 += 1;
+            self.0.solve(ap, ai, ax, b, n)
+        }
+
+        fn reset(&mut self) {
+            self.0.reset();
+        }
+    }
+
+    #[test]
+    fn test_iterations_count_newton_steps() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+        let mut app = default_app();
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+
+        let mat = app.world().resource::<PowerFlowMat>();
+        let mut solver = CountingSolver::default();
+        let (_, iterations) = newton_pf(
+            &mat.y_bus,
+            &mat.s_bus,
+            &mat.v_bus_init,
+            mat.npv,
+            mat.npq,
+            None,
+            None,
+            &mut solver,
+        )
+        .unwrap();
+        assert!(iterations > 0);
+        assert_eq!(iterations, solver.1);
+
+        // A tolerance that cannot be met reports the steps taken as well.
+        let mut solver = CountingSolver::default();
+        let (_, _, iterations) = newton_pf(
+            &mat.y_bus,
+            &mat.s_bus,
+            &mat.v_bus_init,
+            mat.npv,
+            mat.npq,
+            Some(0.0),
+            Some(3),
+            &mut solver,
+        )
+        .unwrap_err();
+        assert_eq!(iterations, 3);
+        assert_eq!(iterations, solver.1);
+    }
+}
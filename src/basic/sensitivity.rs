@@ -0,0 +1,246 @@
+use nalgebra::{DMatrix, DVector};
+use nalgebra_sparse::CscMatrix;
+use num_complex::Complex64;
+
+use super::new_dsdvbus2::{JacobianPattern2, fill_jacobian_v2};
+use super::solver::Solve;
+
+/// Linear sensitivities of a converged operating point.
+///
+/// Rows are buses and columns the selected injection buses, all indices in
+/// the `[PQ | PV | slack]` order of the inputs; values are per unit (angles
+/// in radians). Entries that a bus type pins are zero: `|V|` of PV and slack
+/// buses, angles of the slack bus, and the columns of injections that are
+/// not Newton equations (`P` at the slack, `Q` at PV and slack buses).
+#[derive(Debug, Clone)]
+pub struct Sensitivities {
+    /// Selected injection buses, one column each.
+    pub buses: Vec<usize>,
+    pub dva_dp: DMatrix<f64>,
+    pub dvm_dp: DMatrix<f64>,
+    pub dva_dq: DMatrix<f64>,
+    pub dvm_dq: DMatrix<f64>,
+    /// Incremental loss factor `dP_loss / dP_k` per bus for an injection
+    /// balanced by the slack bus; zero at the slack itself.
+    pub loss_factors: DVector<f64>,
+}
+
+/// Computes voltage and loss sensitivities from the Jacobian at `v`.
+///
+/// The Jacobian is filled with [`fill_jacobian_v2`] on the same pattern the
+/// Newton solver uses and factored once; the voltage sensitivities of all
+/// selected buses are one multi-RHS solve and the loss factors one transpose
+/// solve. `v` must be a converged solution, otherwise the result describes
+/// a non-physical point.
+#[allow(non_snake_case)]
+pub fn jacobian_sensitivities<Solver: Solve>(
+    Ybus: &CscMatrix<Complex64>,
+    v: &DVector<Complex64>,
+    npv: usize,
+    npq: usize,
+    buses: &[usize],
+    solver: &mut Solver,
+) -> Result<Sensitivities, String> {
+    let n = v.len();
+    let n_bus = npv + npq;
+    let n_state = n_bus + npq;
+    if let Some(&bad) = buses.iter().find(|&&b| b >= n) {
+        return Err(format!("Bus index {bad} out of range for {n} buses"));
+    }
+
+    factor_jacobian(Ybus, v, npv, npq, solver)?;
+    let v_norm = v.map(|e| e / e.norm());
+    let ibus = Ybus * v;
+
+    // Columns 2k / 2k + 1 are the unit P / Q injection at buses[k].
+    let m = buses.len();
+    let mut rhs = DMatrix::<f64>::zeros(n_state, 2 * m);
+    for (k, &b) in buses.iter().enumerate() {
+        if b < n_bus {
+            rhs[(b, 2 * k)] = 1.0;
+        }
+        if b < npq {
+            rhs[(n_bus + b, 2 * k + 1)] = 1.0;
+        }
+    }
+    if m > 0 {
+        solver.solve_many(rhs.as_mut_slice(), n_state, 2 * m)?;
+    }
+
+    let mut sens = Sensitivities {
+        buses: buses.to_vec(),
+        dva_dp: DMatrix::zeros(n, m),
+        dvm_dp: DMatrix::zeros(n, m),
+        dva_dq: DMatrix::zeros(n, m),
+        dvm_dq: DMatrix::zeros(n, m),
+        loss_factors: DVector::zeros(n),
+    };
+    for k in 0..m {
+        let (dp, dq) = (rhs.column(2 * k), rhs.column(2 * k + 1));
+        sens.dva_dp
+            .view_mut((0, k), (n_bus, 1))
+            .copy_from(&dp.rows(0, n_bus));
+        sens.dvm_dp
+            .view_mut((0, k), (npq, 1))
+            .copy_from(&dp.rows(n_bus, npq));
+        sens.dva_dq
+            .view_mut((0, k), (n_bus, 1))
+            .copy_from(&dq.rows(0, n_bus));
+        sens.dvm_dq
+            .view_mut((0, k), (npq, 1))
+            .copy_from(&dq.rows(n_bus, npq));
+    }
+
+    // Gradient of P_loss = Re(sum_i V_i conj(I_i)) over the Newton state:
+    //   d/dθ_k  = Re(j (V_k conj(I_k) - conj(V_k) w_k))
+    //   d/d|V|_k = Re(conj(Vn_k) w_k + Vn_k conj(I_k))
+    // with w_k = sum_i V_i conj(Y_ik), i.e. a sweep over Ybus column k.
+    let mut grad = vec![0.0; n_state];
+    for k in 0..n_bus {
+        let col = Ybus.col(k);
+        let w: Complex64 = col
+            .row_indices()
+            .iter()
+            .zip(col.values())
+            .map(|(&i, y)| v[i] * y.conj())
+            .sum();
+        let vi = v[k] * ibus[k].conj();
+        grad[k] = (Complex64::i() * (vi - v[k].conj() * w)).re;
+        if k < npq {
+            grad[n_bus + k] = (v_norm[k].conj() * w + v_norm[k] * ibus[k].conj()).re;
+        }
+    }
+    solver.solve_transpose_many(&mut grad, n_state, 1)?;
+    sens.loss_factors
+        .rows_mut(0, n_bus)
+        .copy_from_slice(&grad[..n_bus]);
+    Ok(sens)
+}
+
+/// Fills the `[PQ | PV | slack]` Newton Jacobian at `v` and factors it into
+/// `solver`, ready for `solve_many` / `solve_transpose_many`.
+#[allow(non_snake_case)]
+pub(crate) fn factor_jacobian<Solver: Solve>(
+    Ybus: &CscMatrix<Complex64>,
+    v: &DVector<Complex64>,
+    npv: usize,
+    npq: usize,
+    solver: &mut Solver,
+) -> Result<(), String> {
+    let pattern =
+        JacobianPattern2::build_from_permuted(Ybus.col_offsets(), Ybus.row_indices(), npv, npq);
+    let mut ap = pattern.j_col_ptrs.clone();
+    let mut ai = pattern.j_row_indices.clone();
+    let mut j_values = vec![0.0; pattern.nnz_j];
+    let v_norm = v.map(|e| e / e.norm());
+    let ibus = Ybus * v;
+    fill_jacobian_v2(
+        Ybus,
+        v.as_slice(),
+        v_norm.as_slice(),
+        ibus.as_slice(),
+        &pattern,
+        npv,
+        npq,
+        &mut j_values,
+    );
+    solver.factor(&mut ap, &mut ai, &mut j_values, npv + 2 * npq)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::plugin::default_app;
+    use crate::basic::ecs::powerflow::systems::{PowerFlowMat, PowerFlowResult};
+    use crate::basic::newton_pf;
+    use crate::basic::solver::DefaultSolver;
+    use crate::io::pandapower::load_csv_zip;
+    use crate::prelude::PPNetwork;
+    use std::env;
+
+    #[test]
+    fn test_against_finite_differences() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+        let mut app = default_app();
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+
+        let mat = app.world().resource::<PowerFlowMat>();
+        let v0 = &app.world().resource::<PowerFlowResult>().v;
+        let (npv, npq) = (mat.npv, mat.npq);
+        let pq_bus = 3;
+        let pv_bus = npq + 2;
+        let sens = jacobian_sensitivities(
+            &mat.y_bus,
+            v0,
+            npv,
+            npq,
+            &[pq_bus, pv_bus],
+            &mut DefaultSolver::default(),
+        )
+        .unwrap();
+
+        let loss = |v: &DVector<Complex64>| {
+            v.component_mul(&(&mat.y_bus * v).conjugate())
+                .iter()
+                .map(|s| s.re)
+                .sum::<f64>()
+        };
+        let solve = |s_bus: DVector<Complex64>| {
+            newton_pf(
+                &mat.y_bus,
+                &s_bus,
+                v0,
+                npv,
+                npq,
+                Some(1e-12),
+                None,
+                &mut DefaultSolver::default(),
+            )
+            .unwrap()
+            .0
+        };
+        let h = 1e-5;
+        for (k, bus, dir, dva, dvm) in [
+            (
+                0,
+                pq_bus,
+                Complex64::new(h, 0.0),
+                &sens.dva_dp,
+                &sens.dvm_dp,
+            ),
+            (
+                0,
+                pq_bus,
+                Complex64::new(0.0, h),
+                &sens.dva_dq,
+                &sens.dvm_dq,
+            ),
+            (
+                1,
+                pv_bus,
+                Complex64::new(h, 0.0),
+                &sens.dva_dp,
+                &sens.dvm_dp,
+            ),
+        ] {
+            let mut s_bus = mat.s_bus.clone();
+            s_bus[bus] += dir;
+            let v1 = solve(s_bus);
+            for i in 0..v0.len() {
+                let fd_va = (v1[i] / v0[i]).arg() / h;
+                let fd_vm = (v1[i].norm() - v0[i].norm()) / h;
+                assert!((fd_va - dva[(i, k)]).abs() < 1e-4, "dva bus {i}");
+                assert!((fd_vm - dvm[(i, k)]).abs() < 1e-4, "dvm bus {i}");
+            }
+            if dir.re != 0.0 {
+                let fd_loss = (loss(&v1) - loss(v0)) / h;
+                assert!((fd_loss - sens.loss_factors[bus]).abs() < 1e-4);
+            }
+        }
+        assert_eq!(sens.loss_factors[v0.len() - 1], 0.0);
+    }
+}
@@ -1,3 +1,8 @@
+use std::collections::{HashMap, hash_map::Entry};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 #[cfg(feature = "faer")]
 mod faer;
 #[cfg(feature = "faer")]
@@ -8,6 +13,11 @@ mod klu;
 #[cfg(any(feature = "klu", feature = "klu_dyn"))]
 pub use klu::*;
 
+#[cfg(feature = "krylov")]
+mod krylov;
+#[cfg(feature = "krylov")]
+pub use krylov::*;
+
 #[cfg(feature = "rsparse")]
 mod rsparse;
 #[cfg(feature = "rsparse")]
@@ -17,6 +27,7 @@ pub use rsparse::*;
     not(feature = "klu"),
     not(feature = "klu_dyn"),
     not(feature = "faer"),
+    not(feature = "krylov"),
     feature = "rsparse"
 ))]
 pub type DefaultSolver = RSparseSolver;
@@ -27,9 +38,29 @@ pub type DefaultSolver = KLUSolver;
 #[cfg(all(not(feature = "klu"), not(feature = "klu_dyn"), feature = "faer"))]
 pub type DefaultSolver = FaerSolver;
 
+#[cfg(all(
+    not(feature = "klu"),
+    not(feature = "klu_dyn"),
+    not(feature = "faer"),
+    feature = "krylov"
+))]
+pub type DefaultSolver = KrylovSolver;
+
+/// Wall-clock split of the most recent [`Solve::solve`] call.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SolveTiming {
+    /// Time spent in (symbolic + numeric) factorization.
+    pub factor: Duration,
+    /// Time spent in the triangular solves.
+    pub solve: Duration,
+}
+
 #[allow(non_snake_case)]
 /// A trait for solving sparse linear systems.
 pub trait Solve {
+    /// Shareable handle to the backend's symbolic analysis.
+    type Symbolic: Clone + Send + Sync + 'static;
+
     /// Solves the sparse linear system.
     ///
     /// # Parameters
@@ -53,4 +84,217 @@ pub trait Solve {
     ) -> Result<(), &'static str>;
 
     fn reset(&mut self);
+
+    /// Factorizes the matrix (reusing the symbolic analysis) without
+    /// solving, for later [`Solve::solve_many`] /
+    /// [`Solve::solve_transpose_many`] calls.
+    fn factor(
+        &mut self,
+        _Ap: &mut [usize],
+        _Ai: &mut [usize],
+        _Ax: &mut [f64],
+        _n: usize,
+    ) -> Result<(), &'static str> {
+        Err("factor-only is not supported by this backend")
+    }
+
+    /// Solves `A X = B` with the last factorization. `b` holds `nrhs`
+    /// right-hand sides column-major (`n × nrhs`) and is overwritten by `X`.
+    fn solve_many(&mut self, _b: &mut [f64], _n: usize, _nrhs: usize) -> Result<(), &'static str> {
+        Err("multi-RHS solves are not supported by this backend")
+    }
+
+    /// Solves `Aᵀ X = B` with the last factorization; layout as in
+    /// [`Solve::solve_many`].
+    fn solve_transpose_many(
+        &mut self,
+        _b: &mut [f64],
+        _n: usize,
+        _nrhs: usize,
+    ) -> Result<(), &'static str> {
+        Err("transpose solves are not supported by this backend")
+    }
+
+    /// Timing of the last `solve` call, if the backend records it.
+    fn last_timing(&self) -> Option<SolveTiming> {
+        None
+    }
+
+    /// Symbolic analysis of the current pattern, if one has been computed.
+    fn symbolic(&self) -> Option<Self::Symbolic> {
+        None
+    }
+
+    /// Seeds the solver with a symbolic analysis computed for the same
+    /// pattern elsewhere, so the next `solve` only factorizes numerically.
+    fn seed_symbolic(&mut self, _symbolic: Self::Symbolic) {}
+}
+
+/// Hash of a CSC sparsity pattern plus any extra discriminator (e.g. the
+/// PV/PQ split that shapes the Jacobian).
+pub fn pattern_hash(col_ptrs: &[usize], row_indices: &[usize], extra: &[usize]) -> u64 {
+    let mut h = DefaultHasher::new();
+    col_ptrs.hash(&mut h);
+    row_indices.hash(&mut h);
+    extra.hash(&mut h);
+    h.finish()
+}
+
+/// [`SymbolicCache`] key: a CSC sparsity pattern plus any extra
+/// discriminator. Only the [`pattern_hash`] selects the bucket; equality
+/// compares the full pattern, so a hash collision is a cache miss rather
+/// than a symbolic analysis applied to the wrong matrix.
+#[derive(Debug, Clone)]
+pub struct PatternKey {
+    hash: u64,
+    col_ptrs: Vec<usize>,
+    row_indices: Vec<usize>,
+    extra: Vec<usize>,
+}
+
+impl PatternKey {
+    pub fn new(col_ptrs: &[usize], row_indices: &[usize], extra: &[usize]) -> Self {
+        Self {
+            hash: pattern_hash(col_ptrs, row_indices, extra),
+            col_ptrs: col_ptrs.to_vec(),
+            row_indices: row_indices.to_vec(),
+            extra: extra.to_vec(),
+        }
+    }
+
+    pub fn hash_value(&self) -> u64 {
+        self.hash
+    }
+}
+
+impl PartialEq for PatternKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+            && self.extra == other.extra
+            && self.col_ptrs == other.col_ptrs
+            && self.row_indices == other.row_indices
+    }
+}
+
+impl Eq for PatternKey {}
+
+impl Hash for PatternKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+/// Thread-safe, reference-counted map from a sparsity pattern to a symbolic
+/// analysis. Clones share the same map, so solvers on many threads (or in
+/// many `PowerGrid`s) with the same topology analyze it only once.
+pub struct SymbolicCache<S: Solve>(Arc<Mutex<HashMap<PatternKey, S::Symbolic>>>);
+
+impl<S: Solve> Clone for SymbolicCache<S> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<S: Solve> Default for SymbolicCache<S> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<S: Solve> SymbolicCache<S> {
+    pub fn get(&self, key: &PatternKey) -> Option<S::Symbolic> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    /// Stores `symbolic` unless another solver already published one.
+    pub fn insert(&self, key: PatternKey, symbolic: S::Symbolic) {
+        self.0.lock().unwrap().entry(key).or_insert(symbolic);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Seeds `solver` from the cache if it has no analysis yet.
+    pub fn seed(&self, key: &PatternKey, solver: &mut S) {
+        if solver.symbolic().is_none()
+            && let Some(symbolic) = self.get(key)
+        {
+            solver.seed_symbolic(symbolic);
+        }
+    }
+
+    /// Publishes the analysis of `solver` under `key`.
+    pub fn publish(&self, key: PatternKey, solver: &S) {
+        if let Entry::Vacant(entry) = self.0.lock().unwrap().entry(key)
+            && let Some(symbolic) = solver.symbolic()
+        {
+            entry.insert(symbolic);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_key_collision_is_miss() {
+        let cache = SymbolicCache::<DefaultSolver>::default();
+        let a = PatternKey::new(&[0, 1, 2], &[0, 1], &[0, 1]);
+        let mut b = PatternKey::new(&[0, 2, 2], &[0, 1], &[0, 1]);
+        // Force a collision of the bucket hash.
+        b.hash = a.hash;
+        assert_ne!(a, b);
+        assert_eq!(a, PatternKey::new(&[0, 1, 2], &[0, 1], &[0, 1]));
+
+        let mut solver = DefaultSolver::default();
+        let (mut ap, mut ai, mut ax) = (vec![0, 1, 2], vec![0, 1], vec![2.0, 3.0]);
+        solver.factor(&mut ap, &mut ai, &mut ax, 2).unwrap();
+        cache.publish(a.clone(), &solver);
+        assert!(cache.get(&a).is_some());
+        assert!(cache.get(&b).is_none());
+    }
+
+    #[test]
+    fn test_solve_many_and_transpose() {
+        // A = [4 1 0; 2 3 1; 0 1 2], nonsymmetric.
+        let a = [[4.0, 1.0, 0.0], [2.0, 3.0, 1.0], [0.0, 1.0, 2.0]];
+        let mut ap = vec![0, 2, 5, 7];
+        let mut ai = vec![0, 1, 0, 1, 2, 1, 2];
+        let mut ax = vec![4.0, 2.0, 1.0, 3.0, 1.0, 1.0, 2.0];
+        let rhs = [1.0, 2.0, 3.0, -1.0, 0.5, 4.0];
+
+        let mut solver = DefaultSolver::default();
+        solver.factor(&mut ap, &mut ai, &mut ax, 3).unwrap();
+        let mut x = rhs;
+        solver.solve_many(&mut x, 3, 2).unwrap();
+        let mut xt = rhs;
+        solver.solve_transpose_many(&mut xt, 3, 2).unwrap();
+
+        for k in 0..2 {
+            for i in 0..3 {
+                let ax_i: f64 = (0..3).map(|j| a[i][j] * x[3 * k + j]).sum();
+                let atx_i: f64 = (0..3).map(|j| a[j][i] * xt[3 * k + j]).sum();
+                assert!((ax_i - rhs[3 * k + i]).abs() < 1e-9);
+                assert!((atx_i - rhs[3 * k + i]).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[cfg(feature = "rsparse")]
+    #[test]
+    fn test_rsparse_solve_many_checks_rhs() {
+        let mut solver = RSparseSolver::default();
+        let (mut ap, mut ai, mut ax) = (vec![0, 1, 2], vec![0, 1], vec![2.0, 3.0]);
+        solver.factor(&mut ap, &mut ai, &mut ax, 2).unwrap();
+        let mut b = [1.0; 3];
+        assert!(solver.solve_many(&mut b, 2, 2).is_err());
+        assert!(solver.solve_transpose_many(&mut b, 2, 2).is_err());
+        assert!(solver.solve_many(&mut b, 3, 1).is_err());
+    }
 }
@@ -1,21 +1,75 @@
 use faer::{
-    MatMut,
-    linalg::solvers::Solve,
+    Conj, MatMut,
+    dyn_stack::{MemBuffer, MemStack, StackReq},
+    get_global_parallelism,
     sparse::{
         SparseColMatRef, SymbolicSparseColMatRef,
-        linalg::solvers::{Lu, SymbolicLu},
+        linalg::lu::{LuRef, NumericLu, SymbolicLu, factorize_symbolic_lu},
     },
 };
 
-use super::Solve as PoSolve;
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::{Solve as PoSolve, SolveTiming};
+
+/// Sparse LU backend on faer.
+///
+/// The symbolic analysis (ordering, elimination tree, supernodes) and the
+/// workspace are computed on the first call and reused afterwards; later
+/// calls only redo the numeric factorization into the same `NumericLu`
+/// storage. Call [`PoSolve::reset`] whenever the sparsity pattern changes.
 #[derive(Default)]
 pub struct FaerSolver {
-    lu: Option<Lu<usize, f64>>,
-    symbolic: Option<SymbolicLu<usize>>,
+    symbolic: Option<Arc<SymbolicLu<usize>>>,
+    numeric: Option<NumericLu<usize, f64>>,
+    scratch: Option<MemBuffer>,
+    factored: bool,
+    timing: SolveTiming,
+}
+
+impl FaerSolver {
+    fn solve_impl(
+        &mut self,
+        b: &mut [f64],
+        n: usize,
+        nrhs: usize,
+        transpose: bool,
+    ) -> Result<(), &'static str> {
+        let (Some(symbolic), Some(numeric), true) = (&self.symbolic, &self.numeric, self.factored)
+        else {
+            return Err("Matrix is not factorized");
+        };
+        let t0 = Instant::now();
+        let par = get_global_parallelism();
+        // The cached workspace covers one right-hand side.
+        let mut extra = (nrhs > 1).then(|| {
+            MemBuffer::new(StackReq::or(
+                symbolic.solve_in_place_scratch::<f64>(nrhs, par),
+                symbolic.solve_transpose_in_place_scratch::<f64>(nrhs, par),
+            ))
+        });
+        let stack = MemStack::new(match extra.as_mut() {
+            Some(buf) => buf,
+            None => self.scratch.as_mut().unwrap(),
+        });
+        // `numeric` was produced by `factorize_numeric_lu` on `symbolic`.
+        let lu = LuRef::new_unchecked(symbolic, numeric);
+        let rhs = MatMut::from_column_major_slice_mut(&mut b[..n * nrhs], n, nrhs);
+        if transpose {
+            lu.solve_transpose_in_place_with_conj(Conj::No, rhs, par, stack);
+        } else {
+            lu.solve_in_place_with_conj(Conj::No, rhs, par, stack);
+        }
+        self.timing.solve = t0.elapsed();
+        Ok(())
+    }
 }
 
 #[allow(non_snake_case)]
 impl PoSolve for FaerSolver {
+    type Symbolic = Arc<SymbolicLu<usize>>;
+
     #[allow(unused)]
     /// Solves the sparse linear system using the Faer solver.
     ///
@@ -37,22 +91,119 @@ impl PoSolve for FaerSolver {
         Ax: &mut [f64],
         b: &mut [f64],
         n: usize,
+    ) -> Result<(), &'static str> {
+        PoSolve::factor(self, Ap, Ai, Ax, n)?;
+        self.solve_impl(b, n, 1, false)
+    }
+
+    fn factor(
+        &mut self,
+        Ap: &mut [usize],
+        Ai: &mut [usize],
+        Ax: &mut [f64],
+        n: usize,
     ) -> Result<(), &'static str> {
         let s = unsafe { SymbolicSparseColMatRef::new_unchecked(n, n, Ap, None, Ai) };
         let mat = SparseColMatRef::new(s, Ax);
+        let par = get_global_parallelism();
+        let t0 = Instant::now();
         if self.symbolic.is_none() {
-            self.symbolic = Some(SymbolicLu::try_new(s).map_err(|_| "Faer symbolic error")?);
+            let symbolic =
+                factorize_symbolic_lu(s, Default::default()).map_err(|_| "Faer symbolic error")?;
+            self.symbolic = Some(Arc::new(symbolic));
+            self.scratch = None;
+        }
+        let symbolic = self.symbolic.as_ref().unwrap();
+        if self.scratch.is_none() {
+            let req = symbolic
+                .factorize_numeric_lu_scratch::<f64>(par, Default::default())
+                .or(symbolic.solve_in_place_scratch::<f64>(1, par))
+                .or(symbolic.solve_transpose_in_place_scratch::<f64>(1, par));
+            self.scratch = Some(MemBuffer::new(req));
+            self.numeric = Some(NumericLu::new());
         }
 
-        self.lu = Some(
-            Lu::try_new_with_symbolic(self.symbolic.as_ref().unwrap().clone(), mat)
-                .map_err(|_| "Faer numerical error")?,
-        );
-        let mat_ref = MatMut::from_column_major_slice_mut(b, n, 1);
-        self.lu.as_ref().unwrap().solve_in_place(mat_ref);
+        self.factored = false;
+        let stack = MemStack::new(self.scratch.as_mut().unwrap());
+        symbolic
+            .factorize_numeric_lu(
+                self.numeric.as_mut().unwrap(),
+                mat,
+                par,
+                stack,
+                Default::default(),
+            )
+            .map_err(|_| "Faer numerical error")?;
+        self.factored = true;
+        self.timing.factor = t0.elapsed();
         Ok(())
     }
+
+    fn solve_many(&mut self, b: &mut [f64], n: usize, nrhs: usize) -> Result<(), &'static str> {
+        self.solve_impl(b, n, nrhs, false)
+    }
+
+    fn solve_transpose_many(
+        &mut self,
+        b: &mut [f64],
+        n: usize,
+        nrhs: usize,
+    ) -> Result<(), &'static str> {
+        self.solve_impl(b, n, nrhs, true)
+    }
+
     fn reset(&mut self) {
         self.symbolic = None;
+        self.numeric = None;
+        self.scratch = None;
+        self.factored = false;
+    }
+
+    fn last_timing(&self) -> Option<SolveTiming> {
+        Some(self.timing)
+    }
+
+    fn symbolic(&self) -> Option<Arc<SymbolicLu<usize>>> {
+        self.symbolic.clone()
+    }
+
+    fn seed_symbolic(&mut self, symbolic: Arc<SymbolicLu<usize>>) {
+        self.symbolic = Some(symbolic);
+        self.numeric = None;
+        self.scratch = None;
+        self.factored = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refactor_and_reset() {
+        // [4 1 0; 1 3 1; 0 1 2]
+        let mut ap = vec![0, 2, 5, 7];
+        let mut ai = vec![0, 1, 0, 1, 2, 1, 2];
+        let mut ax = vec![4.0, 1.0, 1.0, 3.0, 1.0, 1.0, 2.0];
+        let mut solver = FaerSolver::default();
+        for scale in [1.0, 2.0] {
+            let mut scaled: Vec<f64> = ax.iter().map(|v| v * scale).collect();
+            let mut b = vec![scale, 2.0 * scale, 3.0 * scale];
+            solver
+                .solve(&mut ap, &mut ai, &mut scaled, &mut b, 3)
+                .unwrap();
+            for (x, e) in b.iter().zip([2.0 / 9.0, 1.0 / 9.0, 13.0 / 9.0]) {
+                assert!((x - e).abs() < 1e-12);
+            }
+        }
+
+        // New pattern: diagonal only.
+        solver.reset();
+        let (mut ap, mut ai) = (vec![0, 1, 2, 3], vec![0, 1, 2]);
+        ax.truncate(3);
+        ax.copy_from_slice(&[2.0, 4.0, 8.0]);
+        let mut b = vec![1.0, 1.0, 1.0];
+        solver.solve(&mut ap, &mut ai, &mut ax, &mut b, 3).unwrap();
+        assert_eq!(b, vec![0.5, 0.25, 0.125]);
     }
 }
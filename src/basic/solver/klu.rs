@@ -1,11 +1,15 @@
-use super::Solve;
+use std::time::Instant;
+
+use super::{Solve, SolveTiming};
 use rustpower_sol_klu as klu_rs;
 
 #[derive(Default)]
-pub struct KLUSolver(pub klu_rs::KLUSolver);
+pub struct KLUSolver(pub klu_rs::KLUSolver, SolveTiming);
 
 #[allow(non_snake_case)]
 impl Solve for KLUSolver {
+    type Symbolic = klu_rs::SharedSymbolic;
+
     #[allow(unused)]
     /// Solves the sparse linear system using the KLU solver.
     ///
@@ -28,7 +32,19 @@ impl Solve for KLUSolver {
         b: &mut [f64],
         n: usize,
     ) -> Result<(), &'static str> {
-        unsafe {
+        self.factor(Ap, Ai, Ax, n)?;
+        self.solve_many(b, n, 1)
+    }
+
+    fn factor(
+        &mut self,
+        Ap: &mut [usize],
+        Ai: &mut [usize],
+        Ax: &mut [f64],
+        n: usize,
+    ) -> Result<(), &'static str> {
+        let t0 = Instant::now();
+        let ret = unsafe {
             if self.0.symbolic.is_null() {
                 self.0.solve_sym(
                     Ap.as_mut_ptr() as *mut i64,
@@ -36,8 +52,8 @@ impl Solve for KLUSolver {
                     n as i64,
                 );
             }
-            
-            let mut ret = if self.0.numeric.is_null() {
+
+            if self.0.numeric.is_null() {
                 self.0.factor(
                     Ap.as_mut_ptr() as *mut i64,
                     Ai.as_mut_ptr() as *mut i64,
@@ -51,7 +67,7 @@ impl Solve for KLUSolver {
                     Ax.as_mut_ptr(),
                     n as i64,
                 );
-                // status > 0 means singular, status < 0 means error. 
+                // status > 0 means singular, status < 0 means error.
                 // In both cases, we try a full factor.
                 if status != 0 {
                     self.0.factor(
@@ -62,18 +78,61 @@ impl Solve for KLUSolver {
                 } else {
                     0
                 }
-            };
-
-            ret |= self.0.solve(b.as_mut_ptr(), n as i64, 1);
-            if ret != 0 {
-                return Err("error occurred when calling KLU routines!");
             }
+        };
+        self.1.factor = t0.elapsed();
+        if ret != 0 {
+            return Err("error occurred when calling KLU routines!");
+        }
+        Ok(())
+    }
+
+    fn solve_many(&mut self, b: &mut [f64], n: usize, nrhs: usize) -> Result<(), &'static str> {
+        if self.0.numeric.is_null() || b.len() < n * nrhs {
+            return Err("Matrix is not factorized");
+        }
+        let t0 = Instant::now();
+        let ret = unsafe { self.0.solve(b.as_mut_ptr(), n as i64, nrhs as i64) };
+        self.1.solve = t0.elapsed();
+        if ret != 0 {
+            return Err("error occurred when calling KLU routines!");
         }
         Ok(())
     }
+
+    fn solve_transpose_many(
+        &mut self,
+        b: &mut [f64],
+        n: usize,
+        nrhs: usize,
+    ) -> Result<(), &'static str> {
+        if self.0.numeric.is_null() || b.len() < n * nrhs {
+            return Err("Matrix is not factorized");
+        }
+        let t0 = Instant::now();
+        let ret = unsafe { self.0.tsolve(b.as_mut_ptr(), n as i64, nrhs as i64) };
+        self.1.solve = t0.elapsed();
+        if ret != 0 {
+            return Err("error occurred when calling KLU routines!");
+        }
+        Ok(())
+    }
+
     fn reset(&mut self) {
         self.0.reset();
     }
+
+    fn last_timing(&self) -> Option<SolveTiming> {
+        Some(self.1)
+    }
+
+    fn symbolic(&self) -> Option<klu_rs::SharedSymbolic> {
+        self.0.shared_symbolic()
+    }
+
+    fn seed_symbolic(&mut self, symbolic: klu_rs::SharedSymbolic) {
+        self.0.set_symbolic(symbolic);
+    }
 }
 
 #[cfg(feature = "klu")]
@@ -0,0 +1,734 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::{Solve, SolveTiming};
+
+/// Krylov iteration used by [`KrylovSolver`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KrylovMethod {
+    /// Restarted GMRES(m) with right preconditioning.
+    Gmres { restart: usize },
+    /// Right-preconditioned BiCGStab.
+    BiCgStab,
+}
+
+impl Default for KrylovMethod {
+    fn default() -> Self {
+        KrylovMethod::Gmres { restart: 50 }
+    }
+}
+
+/// Pure-Rust iterative backend: GMRES or BiCGStab preconditioned with ILU(k).
+///
+/// This is a linear solver for the assembled Jacobian, not a Jacobian-free
+/// method: the Newton loop still builds `J` and the Krylov iteration and the
+/// preconditioner both use it.
+///
+/// The reverse Cuthill-McKee ordering, the ILU(k) fill pattern and the
+/// CSC→CSR mapping are computed once (until [`Solve::reset`]). The ILU
+/// factors are kept across Newton iterations and
+/// only recomputed when the previous solve needed more than
+/// `refactor_threshold` Krylov iterations, or when a solve with stale factors
+/// fails to converge.
+pub struct KrylovSolver {
+    pub method: KrylovMethod,
+    /// Relative residual tolerance `||b - Ax|| <= tol * ||b||`.
+    pub tol: f64,
+    /// Maximum Krylov iterations per solve.
+    pub max_iter: usize,
+    /// Refactor the preconditioner once a solve takes more iterations.
+    pub refactor_threshold: usize,
+    /// Level of fill of the ILU(k) preconditioner (default 2). Fill grows
+    /// quickly with the level on large networks.
+    pub fill_level: usize,
+    pattern: Option<Arc<IluPattern>>,
+    values: Vec<f64>, // matrix values in pattern order
+    ilu: Option<Vec<f64>>,
+    stale: bool,
+    fresh: bool,
+    iterations: usize,
+    timing: SolveTiming,
+}
+
+impl Default for KrylovSolver {
+    fn default() -> Self {
+        Self {
+            method: KrylovMethod::default(),
+            tol: 1e-10,
+            max_iter: 500,
+            refactor_threshold: 40,
+            fill_level: 2,
+            pattern: None,
+            values: Vec::new(),
+            ilu: None,
+            stale: false,
+            fresh: false,
+            iterations: 0,
+            timing: SolveTiming::default(),
+        }
+    }
+}
+
+impl KrylovSolver {
+    pub fn new(method: KrylovMethod) -> Self {
+        Self {
+            method,
+            ..Default::default()
+        }
+    }
+
+    /// Krylov iterations taken by the last `solve` call.
+    pub fn last_iterations(&self) -> usize {
+        self.iterations
+    }
+
+    fn refresh_ilu(&mut self) {
+        let lu = self.ilu.get_or_insert_with(Vec::new);
+        lu.clone_from(&self.values);
+        self.pattern.as_ref().unwrap().ilu(lu);
+        self.fresh = true;
+    }
+
+    fn run(&self, rhs: &[f64], x: &mut [f64], transpose: bool) -> (bool, usize) {
+        x.fill(0.0);
+        let op = Operator {
+            pattern: self.pattern.as_ref().unwrap(),
+            a: &self.values,
+            lu: self.ilu.as_ref().unwrap(),
+            transpose,
+        };
+        match self.method {
+            KrylovMethod::Gmres { restart } => gmres(&op, rhs, x, self.tol, self.max_iter, restart),
+            KrylovMethod::BiCgStab => bicgstab(&op, rhs, x, self.tol, self.max_iter),
+        }
+    }
+
+    /// Solves one right-hand side in place, refreshing stale ILU factors
+    /// once if the iteration fails.
+    fn solve_column(&mut self, b: &mut [f64], transpose: bool) -> Result<(), &'static str> {
+        let pattern = self.pattern.clone().unwrap();
+        let rhs: Vec<f64> = pattern.perm.iter().map(|&old| b[old]).collect();
+        let mut x = vec![0.0; pattern.n];
+        let t0 = Instant::now();
+        let (mut converged, mut iterations) = self.run(&rhs, &mut x, transpose);
+        self.timing.solve += t0.elapsed();
+        if !converged && !self.fresh {
+            let t1 = Instant::now();
+            self.refresh_ilu();
+            self.timing.factor += t1.elapsed();
+            let t2 = Instant::now();
+            (converged, iterations) = self.run(&rhs, &mut x, transpose);
+            self.timing.solve += t2.elapsed();
+        }
+        for (&old, xi) in pattern.perm.iter().zip(x) {
+            b[old] = xi;
+        }
+        self.iterations = iterations;
+        self.stale = iterations > self.refactor_threshold;
+        if converged {
+            Ok(())
+        } else {
+            Err("Krylov solver did not converge")
+        }
+    }
+
+    fn solve_columns(
+        &mut self,
+        b: &mut [f64],
+        n: usize,
+        nrhs: usize,
+        transpose: bool,
+    ) -> Result<(), &'static str> {
+        if self.ilu.is_none() || self.pattern.as_ref().is_none_or(|p| p.n != n) {
+            return Err("Matrix is not factorized");
+        }
+        if b.len() < n * nrhs {
+            return Err("Right-hand side is too short");
+        }
+        self.timing.solve = Default::default();
+        for col in b.chunks_exact_mut(n).take(nrhs) {
+            self.solve_column(col, transpose)?;
+        }
+        Ok(())
+    }
+}
+
+/// CSR pattern of `P A Pᵀ` extended with the ILU(k) fill, where `P` is a
+/// reverse Cuthill-McKee ordering. Columns are sorted within each row.
+/// This is the shareable symbolic analysis of [`KrylovSolver`].
+pub struct IluPattern {
+    n: usize,
+    a_col_ptrs: Vec<usize>, // CSC pattern of A the analysis was built for
+    a_row_indices: Vec<usize>,
+    row_ptr: Vec<usize>,
+    col: Vec<usize>,
+    diag: Vec<usize>,     // position of (i, i) in row i
+    from_csc: Vec<usize>, // CSC position of A -> position in the pattern
+    perm: Vec<usize>,     // new -> old index
+}
+
+impl IluPattern {
+    #[allow(non_snake_case)]
+    fn new(Ap: &[usize], Ai: &[usize], n: usize, fill_level: usize) -> Result<Self, &'static str> {
+        let nnz_a = Ap[n];
+        let perm = rcm(Ap, Ai, n);
+        let mut iperm = vec![0; n];
+        for (new, &old) in perm.iter().enumerate() {
+            iperm[old] = new;
+        }
+
+        let mut a_rows = vec![Vec::new(); n];
+        for j in 0..n {
+            for &r in &Ai[Ap[j]..Ap[j + 1]] {
+                a_rows[iperm[r]].push(iperm[j]);
+            }
+        }
+        let rows = ilu_symbolic(a_rows, fill_level);
+
+        let mut row_ptr = Vec::with_capacity(n + 1);
+        row_ptr.push(0);
+        let mut col = Vec::new();
+        for r in &rows {
+            col.extend_from_slice(r);
+            row_ptr.push(col.len());
+        }
+        let find = |i: usize, j: usize| {
+            col[row_ptr[i]..row_ptr[i + 1]]
+                .binary_search(&j)
+                .map(|p| row_ptr[i] + p)
+                .ok()
+        };
+        let diag = (0..n)
+            .map(|i| find(i, i).ok_or("ILU needs a structurally nonzero diagonal"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut from_csc = vec![0; nnz_a];
+        for j in 0..n {
+            for p in Ap[j]..Ap[j + 1] {
+                from_csc[p] = find(iperm[Ai[p]], iperm[j]).unwrap();
+            }
+        }
+        Ok(Self {
+            n,
+            a_col_ptrs: Ap[..=n].to_vec(),
+            a_row_indices: Ai[..nnz_a].to_vec(),
+            row_ptr,
+            col,
+            diag,
+            from_csc,
+            perm,
+        })
+    }
+
+    /// Whether the analysis was built for the CSC pattern `Ap`/`Ai`.
+    #[allow(non_snake_case)]
+    fn matches(&self, Ap: &[usize], Ai: &[usize], n: usize) -> bool {
+        self.n == n
+            && Ap.get(..=n) == Some(&self.a_col_ptrs[..])
+            && Ai.get(..self.a_row_indices.len()) == Some(&self.a_row_indices[..])
+    }
+
+    fn matvec(&self, a: &[f64], x: &[f64], y: &mut [f64]) {
+        for (i, yi) in y.iter_mut().enumerate() {
+            *yi = (self.row_ptr[i]..self.row_ptr[i + 1])
+                .map(|p| a[p] * x[self.col[p]])
+                .sum();
+        }
+    }
+
+    /// In-place ILU (IKJ variant): `lu` holds the matrix values on entry and
+    /// unit-lower L / upper U on the (fill-extended) pattern on exit.
+    fn ilu(&self, lu: &mut [f64]) {
+        let mut pos = vec![usize::MAX; self.n];
+        for i in 0..self.n {
+            let row = self.row_ptr[i]..self.row_ptr[i + 1];
+            for p in row.clone() {
+                pos[self.col[p]] = p;
+            }
+            for kp in self.row_ptr[i]..self.diag[i] {
+                let k = self.col[kp];
+                lu[kp] /= lu[self.diag[k]];
+                for up in self.diag[k] + 1..self.row_ptr[k + 1] {
+                    let q = pos[self.col[up]];
+                    if q != usize::MAX {
+                        lu[q] -= lu[kp] * lu[up];
+                    }
+                }
+            }
+            // Guard against (near-)zero pivots instead of failing outright.
+            let d = &mut lu[self.diag[i]];
+            if d.abs() < 1e-12 {
+                *d = if *d < 0.0 { -1e-12 } else { 1e-12 };
+            }
+            for p in row {
+                pos[self.col[p]] = usize::MAX;
+            }
+        }
+    }
+
+    /// `y = Aᵀ x`.
+    fn matvec_t(&self, a: &[f64], x: &[f64], y: &mut [f64]) {
+        y.fill(0.0);
+        for (i, xi) in x.iter().enumerate() {
+            for p in self.row_ptr[i]..self.row_ptr[i + 1] {
+                y[self.col[p]] += a[p] * xi;
+            }
+        }
+    }
+
+    /// `x = (LU)^-T x`: solves with `Uᵀ`, then with the unit `Lᵀ`.
+    fn precond_t(&self, lu: &[f64], x: &mut [f64]) {
+        for i in 0..self.n {
+            x[i] /= lu[self.diag[i]];
+            for p in self.diag[i] + 1..self.row_ptr[i + 1] {
+                x[self.col[p]] -= lu[p] * x[i];
+            }
+        }
+        for i in (0..self.n).rev() {
+            for p in self.row_ptr[i]..self.diag[i] {
+                x[self.col[p]] -= lu[p] * x[i];
+            }
+        }
+    }
+
+    /// `x = (LU)^-1 x` with the factors from [`Self::ilu`].
+    fn precond(&self, lu: &[f64], x: &mut [f64]) {
+        for i in 0..self.n {
+            let s: f64 = (self.row_ptr[i]..self.diag[i])
+                .map(|p| lu[p] * x[self.col[p]])
+                .sum();
+            x[i] -= s;
+        }
+        for i in (0..self.n).rev() {
+            let s: f64 = (self.diag[i] + 1..self.row_ptr[i + 1])
+                .map(|p| lu[p] * x[self.col[p]])
+                .sum();
+            x[i] = (x[i] - s) / lu[self.diag[i]];
+        }
+    }
+}
+
+/// Reverse Cuthill-McKee ordering (new -> old) of the symmetrized pattern.
+#[allow(non_snake_case)]
+fn rcm(Ap: &[usize], Ai: &[usize], n: usize) -> Vec<usize> {
+    let mut adj = vec![Vec::new(); n];
+    for j in 0..n {
+        for &r in &Ai[Ap[j]..Ap[j + 1]] {
+            if r != j {
+                adj[r].push(j);
+                adj[j].push(r);
+            }
+        }
+    }
+    for a in adj.iter_mut() {
+        a.sort_unstable();
+        a.dedup();
+    }
+    let mut by_degree: Vec<usize> = (0..n).collect();
+    by_degree.sort_by_key(|&i| adj[i].len());
+
+    let mut order = Vec::with_capacity(n);
+    let mut visited = vec![false; n];
+    for &start in &by_degree {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut head = order.len();
+        order.push(start);
+        while head < order.len() {
+            let mut next: Vec<usize> = adj[order[head]]
+                .iter()
+                .copied()
+                .filter(|&k| !visited[k])
+                .collect();
+            next.sort_by_key(|&k| adj[k].len());
+            for k in next {
+                visited[k] = true;
+                order.push(k);
+            }
+            head += 1;
+        }
+    }
+    order.reverse();
+    order
+}
+
+/// Level-of-fill ILU(k) pattern. `rows` holds the column indices of each
+/// row of A; returns the sorted, fill-extended rows.
+fn ilu_symbolic(rows: Vec<Vec<usize>>, fill_level: usize) -> Vec<Vec<usize>> {
+    let n = rows.len();
+    let mut out: Vec<Vec<(usize, usize)>> = Vec::with_capacity(n); // (col, level)
+    let mut level = vec![usize::MAX; n];
+    for (i, row) in rows.into_iter().enumerate() {
+        let mut cols = std::collections::BTreeSet::new();
+        for j in row {
+            level[j] = 0;
+            cols.insert(j);
+        }
+        let mut cursor = 0;
+        while let Some(&k) = cols.range(cursor..i).next() {
+            cursor = k + 1;
+            for &(j, lkj) in out[k].iter().filter(|(j, _)| *j > k) {
+                let l = level[k] + lkj + 1;
+                if l <= fill_level && l < level[j] {
+                    level[j] = l;
+                    cols.insert(j);
+                }
+            }
+        }
+        out.push(cols.iter().map(|&j| (j, level[j])).collect());
+        for &j in &cols {
+            level[j] = usize::MAX;
+        }
+    }
+    out.into_iter()
+        .map(|r| r.into_iter().map(|(j, _)| j).collect())
+        .collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn norm(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+/// Preconditioned linear operator seen by the Krylov loops.
+struct Operator<'a> {
+    pattern: &'a IluPattern,
+    a: &'a [f64],
+    lu: &'a [f64],
+    /// Work with `Aᵀ` and `Mᵀ` instead.
+    transpose: bool,
+}
+
+impl Operator<'_> {
+    fn matvec(&self, x: &[f64], y: &mut [f64]) {
+        if self.transpose {
+            self.pattern.matvec_t(self.a, x, y);
+        } else {
+            self.pattern.matvec(self.a, x, y);
+        }
+    }
+
+    fn precond(&self, x: &mut [f64]) {
+        if self.transpose {
+            self.pattern.precond_t(self.lu, x);
+        } else {
+            self.pattern.precond(self.lu, x);
+        }
+    }
+
+    /// `y = A M^-1 x`, leaving `M^-1 x` in `z`.
+    fn apply(&self, x: &[f64], z: &mut [f64], y: &mut [f64]) {
+        z.copy_from_slice(x);
+        self.precond(z);
+        self.matvec(z, y);
+    }
+
+    fn residual(&self, b: &[f64], x: &[f64], r: &mut [f64]) {
+        self.matvec(x, r);
+        r.iter_mut().zip(b).for_each(|(ri, bi)| *ri = bi - *ri);
+    }
+}
+
+/// Restarted GMRES(m); returns `(converged, iterations)`.
+fn gmres(
+    op: &Operator,
+    b: &[f64],
+    x: &mut [f64],
+    tol: f64,
+    max_iter: usize,
+    restart: usize,
+) -> (bool, usize) {
+    let n = b.len();
+    let m = restart.clamp(1, n.max(1));
+    let target = tol * norm(b);
+    let mut r = vec![0.0; n];
+    let mut z = vec![0.0; n];
+    let mut w = vec![0.0; n];
+    let mut total = 0;
+    loop {
+        op.residual(b, x, &mut r);
+        let beta = norm(&r);
+        if beta <= target {
+            return (true, total);
+        }
+        if total >= max_iter {
+            return (false, total);
+        }
+
+        let mut v = vec![r.iter().map(|ri| ri / beta).collect::<Vec<_>>()];
+        let mut h = vec![vec![0.0; m]; m + 1];
+        let (mut cs, mut sn) = (vec![0.0; m], vec![0.0; m]);
+        let mut g = vec![0.0; m + 1];
+        g[0] = beta;
+        let mut k_done = 0;
+        for k in 0..m {
+            op.apply(&v[k], &mut z, &mut w);
+            for (i, vi) in v.iter().enumerate() {
+                h[i][k] = dot(&w, vi);
+                w.iter_mut()
+                    .zip(vi)
+                    .for_each(|(wj, vj)| *wj -= h[i][k] * vj);
+            }
+            h[k + 1][k] = norm(&w);
+            let breakdown = h[k + 1][k] == 0.0;
+            if !breakdown {
+                v.push(w.iter().map(|wj| wj / h[k + 1][k]).collect());
+            }
+
+            for i in 0..k {
+                let t = cs[i] * h[i][k] + sn[i] * h[i + 1][k];
+                h[i + 1][k] = -sn[i] * h[i][k] + cs[i] * h[i + 1][k];
+                h[i][k] = t;
+            }
+            let d = h[k][k].hypot(h[k + 1][k]);
+            (cs[k], sn[k]) = if d == 0.0 {
+                (1.0, 0.0)
+            } else {
+                (h[k][k] / d, h[k + 1][k] / d)
+            };
+            h[k][k] = d;
+            h[k + 1][k] = 0.0;
+            g[k + 1] = -sn[k] * g[k];
+            g[k] *= cs[k];
+
+            total += 1;
+            k_done = k + 1;
+            if breakdown || g[k + 1].abs() <= target || total >= max_iter {
+                break;
+            }
+        }
+
+        let mut y = g[..k_done].to_vec();
+        for i in (0..k_done).rev() {
+            y[i] = (y[i] - (i + 1..k_done).map(|j| h[i][j] * y[j]).sum::<f64>()) / h[i][i];
+        }
+        w.fill(0.0);
+        for (yi, vi) in y.iter().zip(&v) {
+            w.iter_mut().zip(vi).for_each(|(wj, vj)| *wj += yi * vj);
+        }
+        op.precond(&mut w);
+        x.iter_mut().zip(&w).for_each(|(xj, dj)| *xj += dj);
+    }
+}
+
+/// Right-preconditioned BiCGStab; returns `(converged, iterations)`.
+fn bicgstab(op: &Operator, b: &[f64], x: &mut [f64], tol: f64, max_iter: usize) -> (bool, usize) {
+    let n = b.len();
+    let target = tol * norm(b);
+    let mut r = vec![0.0; n];
+    op.residual(b, x, &mut r);
+    if norm(&r) <= target {
+        return (true, 0);
+    }
+    let r_hat = r.clone();
+    let (mut rho, mut alpha, mut omega) = (1.0, 1.0, 1.0);
+    let (mut p, mut v) = (vec![0.0; n], vec![0.0; n]);
+    let (mut y, mut z, mut s, mut t) = (vec![0.0; n], vec![0.0; n], vec![0.0; n], vec![0.0; n]);
+    for it in 1..=max_iter {
+        let rho_new = dot(&r_hat, &r);
+        if rho_new == 0.0 || omega == 0.0 {
+            return (false, it);
+        }
+        let beta = (rho_new / rho) * (alpha / omega);
+        for j in 0..n {
+            p[j] = r[j] + beta * (p[j] - omega * v[j]);
+        }
+        op.apply(&p, &mut y, &mut v);
+        alpha = rho_new / dot(&r_hat, &v);
+        for j in 0..n {
+            s[j] = r[j] - alpha * v[j];
+        }
+        if norm(&s) <= target {
+            x.iter_mut().zip(&y).for_each(|(xj, yj)| *xj += alpha * yj);
+            return (true, it);
+        }
+        op.apply(&s, &mut z, &mut t);
+        omega = dot(&t, &s) / dot(&t, &t);
+        for j in 0..n {
+            x[j] += alpha * y[j] + omega * z[j];
+            r[j] = s[j] - omega * t[j];
+        }
+        if norm(&r) <= target {
+            return (true, it);
+        }
+        rho = rho_new;
+    }
+    (false, max_iter)
+}
+
+#[allow(non_snake_case)]
+impl Solve for KrylovSolver {
+    type Symbolic = Arc<IluPattern>;
+
+    /// Solves the sparse linear system with preconditioned GMRES/BiCGStab.
+    ///
+    /// # Parameters
+    ///
+    /// * `Ap` - Column pointers of the matrix.
+    /// * `Ai` - Row indices of the matrix.
+    /// * `Ax` - Non-zero values of the matrix.
+    /// * `b` - Right-hand side vector, overwritten with the solution.
+    /// * `n` - Dimension of the system.
+    ///
+    /// # Returns
+    ///
+    /// A result indicating success or failure.
+    fn solve(
+        &mut self,
+        Ap: &mut [usize],
+        Ai: &mut [usize],
+        Ax: &mut [f64],
+        b: &mut [f64],
+        n: usize,
+    ) -> Result<(), &'static str> {
+        self.factor(Ap, Ai, Ax, n)?;
+        self.solve_columns(b, n, 1, false)
+    }
+
+    fn factor(
+        &mut self,
+        Ap: &mut [usize],
+        Ai: &mut [usize],
+        Ax: &mut [f64],
+        n: usize,
+    ) -> Result<(), &'static str> {
+        let t0 = Instant::now();
+        if !self
+            .pattern
+            .as_ref()
+            .is_some_and(|p| p.matches(Ap, Ai, n))
+        {
+            self.pattern = Some(Arc::new(IluPattern::new(Ap, Ai, n, self.fill_level)?));
+            self.ilu = None;
+        }
+        let pattern = self.pattern.as_ref().unwrap();
+        self.values.clear();
+        self.values.resize(pattern.col.len(), 0.0);
+        for (p, &v) in Ax.iter().enumerate() {
+            self.values[pattern.from_csc[p]] = v;
+        }
+        self.fresh = false;
+        if self.ilu.is_none() || self.stale {
+            self.refresh_ilu();
+        }
+        self.timing.factor = t0.elapsed();
+        Ok(())
+    }
+
+    /// Solves column by column; each column keeps the stale-factor retry of
+    /// [`Solve::solve`].
+    fn solve_many(&mut self, b: &mut [f64], n: usize, nrhs: usize) -> Result<(), &'static str> {
+        self.solve_columns(b, n, nrhs, false)
+    }
+
+    fn solve_transpose_many(
+        &mut self,
+        b: &mut [f64],
+        n: usize,
+        nrhs: usize,
+    ) -> Result<(), &'static str> {
+        self.solve_columns(b, n, nrhs, true)
+    }
+
+    fn reset(&mut self) {
+        self.pattern = None;
+        self.values.clear();
+        self.ilu = None;
+        self.stale = false;
+    }
+
+    fn last_timing(&self) -> Option<SolveTiming> {
+        Some(self.timing)
+    }
+
+    fn symbolic(&self) -> Option<Arc<IluPattern>> {
+        self.pattern.clone()
+    }
+
+    fn seed_symbolic(&mut self, symbolic: Arc<IluPattern>) {
+        self.pattern = Some(symbolic);
+        self.ilu = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::plugin::default_app;
+    use crate::basic::ecs::powerflow::systems::{PowerFlowMat, PowerFlowResult};
+    use crate::basic::newton_pf;
+    use crate::io::pandapower::load_csv_zip;
+    use crate::prelude::PPNetwork;
+    use std::env;
+
+    #[test]
+    fn test_small_system() {
+        // [4 1 0; 1 3 1; 0 1 2] x = [1 2 3]
+        let mut ap = vec![0, 2, 5, 7];
+        let mut ai = vec![0, 1, 0, 1, 2, 1, 2];
+        let mut ax = vec![4.0, 1.0, 1.0, 3.0, 1.0, 1.0, 2.0];
+        for method in [KrylovMethod::Gmres { restart: 2 }, KrylovMethod::BiCgStab] {
+            let mut b = vec![1.0, 2.0, 3.0];
+            let mut solver = KrylovSolver::new(method);
+            solver.solve(&mut ap, &mut ai, &mut ax, &mut b, 3).unwrap();
+            let expected = [2.0 / 9.0, 1.0 / 9.0, 13.0 / 9.0];
+            for (x, e) in b.iter().zip(expected) {
+                assert!((x - e).abs() < 1e-9, "{method:?}: {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_newton_118() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+        let mut app = default_app();
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+
+        let mat = app.world().resource::<PowerFlowMat>();
+        let v_ref = &app.world().resource::<PowerFlowResult>().v;
+        for method in [KrylovMethod::default(), KrylovMethod::BiCgStab] {
+            let mut solver = KrylovSolver::new(method);
+            let (v, _) = newton_pf(
+                &mat.y_bus,
+                &mat.s_bus,
+                &mat.v_bus_init,
+                mat.npv,
+                mat.npq,
+                None,
+                None,
+                &mut solver,
+            )
+            .unwrap();
+            assert!((&v - v_ref).camax() < 1e-6, "{method:?}");
+        }
+    }
+
+    #[test]
+    fn test_pattern_change_rebuilds_analysis() {
+        // Same size and nonzero count as `test_small_system`, other pattern.
+        let mut ap = vec![0, 2, 4, 7];
+        let mut ai = vec![0, 2, 0, 1, 0, 1, 2];
+        let mut ax = vec![4.0, 1.0, 1.0, 3.0, 0.0, 1.0, 2.0];
+        let mut solver = KrylovSolver::default();
+        let mut b = vec![1.0, 2.0, 3.0];
+        solver.solve(&mut ap, &mut ai, &mut ax, &mut b, 3).unwrap();
+
+        // The system of `test_small_system` on the same solver.
+        let mut ap = vec![0, 2, 5, 7];
+        let mut ai = vec![0, 1, 0, 1, 2, 1, 2];
+        let mut ax = vec![4.0, 1.0, 1.0, 3.0, 1.0, 1.0, 2.0];
+        let mut b = vec![1.0, 2.0, 3.0];
+        solver.solve(&mut ap, &mut ai, &mut ax, &mut b, 3).unwrap();
+        let expected = [2.0 / 9.0, 1.0 / 9.0, 13.0 / 9.0];
+        for (x, e) in b.iter().zip(expected) {
+            assert!((x - e).abs() < 1e-9, "{b:?}");
+        }
+    }
+}
@@ -1,18 +1,25 @@
 use rsparse::{
     self,
-    data::{self, Numeric, Symb},
-    lsolve, lu, sqr, usolve,
+    data::{self, Nmrc, Numeric, Symb},
+    lsolve, ltsolve, lu, sqr, usolve, utsolve,
 };
 
-use super::Solve;
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::{Solve, SolveTiming};
 
 #[derive(Default)]
 pub struct RSparseSolver {
     x: Option<Vec<f64>>,
-    symbolic: Option<Symb>,
+    symbolic: Option<Arc<Symb>>,
+    numeric: Option<Nmrc<f64>>,
+    timing: SolveTiming,
 }
 #[allow(non_snake_case)]
 impl Solve for RSparseSolver {
+    type Symbolic = Arc<Symb>;
+
     #[allow(unused)]
     /// Solves the sparse linear system using the RSparse solver.
     ///
@@ -34,10 +41,21 @@ impl Solve for RSparseSolver {
         Ax: &mut [f64],
         b: &mut [f64],
         n: usize,
+    ) -> Result<(), &'static str> {
+        self.factor(Ap, Ai, Ax, n)?;
+        self.solve_many(b, n, 1)
+    }
+
+    fn factor(
+        &mut self,
+        Ap: &mut [usize],
+        Ai: &mut [usize],
+        Ax: &mut [f64],
+        _n: usize,
     ) -> Result<(), &'static str> {
         let n = Ap.len() - 1;
         let p: Vec<isize> = Ap.iter().map(|&v| v as isize).collect();
-        let mut a = data::Sprs {
+        let a = data::Sprs {
             m: n,
             n,
             i: Ai.to_vec(),
@@ -45,23 +63,77 @@ impl Solve for RSparseSolver {
             x: Ax.to_vec(),
             nzmax: Ax.len(),
         };
+        let t0 = Instant::now();
         if self.symbolic.is_none() {
-            self.symbolic = Some(sqr(&a, 1, false));
+            self.symbolic = Some(Arc::new(sqr(&a, 1, false)));
+        }
+        if self.x.as_ref().is_none_or(|x| x.len() != n) {
             self.x = Some(vec![0.0; n]);
         }
-        let mut x = self.x.as_mut().unwrap();
-        let mut s = self.symbolic.as_mut().unwrap();
-        let n_lu = lu(&a, s, 1e-6).map_err(|_| "LU factorization failed")?; // numeric LU factorization
-        ipvec(&n_lu.pinv, b, &mut x[..]); // x = P*b
-        lsolve(&n_lu.l, x); // x = L\x
-        usolve(&n_lu.u, x); // x = U\x
-        ipvec(&s.q, x, &mut b[..]); // b = Q*x
+        // `lu` updates the fill counts, so a shared analysis is copied once.
+        let s = Arc::make_mut(self.symbolic.as_mut().unwrap());
+        self.numeric = None;
+        self.numeric = Some(lu(&a, s, 1e-6).map_err(|_| "LU factorization failed")?);
+        self.timing.factor = t0.elapsed();
+        Ok(())
+    }
 
+    fn solve_many(&mut self, b: &mut [f64], n: usize, nrhs: usize) -> Result<(), &'static str> {
+        let t0 = Instant::now();
+        let (Some(n_lu), Some(s), Some(x)) = (&self.numeric, &self.symbolic, &mut self.x) else {
+            return Err("Matrix is not factorized");
+        };
+        if x.len() != n || b.len() < n * nrhs {
+            return Err("Right-hand side does not match the factored matrix");
+        }
+        for b in b.chunks_exact_mut(n).take(nrhs) {
+            ipvec(&n_lu.pinv, b, &mut x[..]); // x = P*b
+            lsolve(&n_lu.l, x); // x = L\x
+            usolve(&n_lu.u, x); // x = U\x
+            ipvec(&s.q, x, b); // b = Q*x
+        }
+        self.timing.solve = t0.elapsed();
+        Ok(())
+    }
+
+    fn solve_transpose_many(
+        &mut self,
+        b: &mut [f64],
+        n: usize,
+        nrhs: usize,
+    ) -> Result<(), &'static str> {
+        let t0 = Instant::now();
+        let (Some(n_lu), Some(s), Some(x)) = (&self.numeric, &self.symbolic, &mut self.x) else {
+            return Err("Matrix is not factorized");
+        };
+        if x.len() != n || b.len() < n * nrhs {
+            return Err("Right-hand side does not match the factored matrix");
+        }
+        for b in b.chunks_exact_mut(n).take(nrhs) {
+            pvec(&s.q, b, &mut x[..]); // x = Q'*b
+            utsolve(&n_lu.u, x); // x = U'\x
+            ltsolve(&n_lu.l, x); // x = L'\x
+            pvec(&n_lu.pinv, x, b); // b = P'*x
+        }
+        self.timing.solve = t0.elapsed();
         Ok(())
     }
 
     fn reset(&mut self) {
         self.symbolic = None;
+        self.numeric = None;
+    }
+
+    fn last_timing(&self) -> Option<SolveTiming> {
+        Some(self.timing)
+    }
+
+    fn symbolic(&self) -> Option<Arc<Symb>> {
+        self.symbolic.clone()
+    }
+
+    fn seed_symbolic(&mut self, symbolic: Arc<Symb>) {
+        self.symbolic = Some(symbolic);
     }
 }
 
@@ -81,3 +153,15 @@ fn ipvec<T: Numeric<T>>(p: &Option<Vec<isize>>, b: &[T], x: &mut [T]) {
         None => ipvec_identity(b, x),
     }
 }
+
+/// `x = P'*b`, the inverse of [`ipvec`].
+fn pvec<T: Numeric<T>>(p: &Option<Vec<isize>>, b: &[T], x: &mut [T]) {
+    match p {
+        Some(pvec) => {
+            for k in 0..x.len() {
+                x[k] = b[pvec[k] as usize];
+            }
+        }
+        None => ipvec_identity(b, x),
+    }
+}
@@ -0,0 +1,352 @@
+use nalgebra::{DMatrix, DVector};
+use nalgebra_sparse::CscMatrix;
+use num_complex::Complex64;
+
+use super::sensitivity::factor_jacobian;
+use super::solver::Solve;
+
+/// Kessel–Glavitsch L-index of every PQ bus (the first `npq` entries of
+/// the `[PQ | PV | slack]` order).
+///
+/// With the Y-bus split into load (`L`, PQ) and generator (`G`, PV and
+/// slack) buses, `L_j = |1 + z_j / V_j|` where `Y_LL z = Y_LG V_G`, which
+/// is `|1 - sum_i F_ji V_i / V_j|` for `F_LG = -Y_LL^-1 Y_LG` without
+/// forming `F_LG`. Values near 0 are far from collapse, 1 is the limit.
+/// The complex solve runs on the real `2 npq` equivalent of `Y_LL`.
+#[allow(non_snake_case)]
+pub fn l_index<Solver: Solve>(
+    Ybus: &CscMatrix<Complex64>,
+    v: &DVector<Complex64>,
+    npq: usize,
+    solver: &mut Solver,
+) -> Result<DVector<f64>, String> {
+    if npq == 0 {
+        return Ok(DVector::zeros(0));
+    }
+    let n = v.len();
+
+    // Each y = a + jb of Y_LL becomes the 2x2 block [a -b; b a].
+    let mut ap = vec![0];
+    let mut ai = Vec::new();
+    let mut ax = Vec::new();
+    let mut rhs = vec![0.0; 2 * npq];
+    for j in 0..n {
+        let col = Ybus.col(j);
+        let rows = col.row_indices().iter().zip(col.values());
+        if j >= npq {
+            for (&i, y) in rows.take_while(|(i, _)| **i < npq) {
+                let u = y * v[j];
+                rhs[2 * i] += u.re;
+                rhs[2 * i + 1] += u.im;
+            }
+            continue;
+        }
+        let block = rows.take_while(|(i, _)| **i < npq).collect::<Vec<_>>();
+        for imag in [false, true] {
+            for &(&i, y) in &block {
+                let (top, bottom) = if imag { (-y.im, y.re) } else { (y.re, y.im) };
+                ai.extend([2 * i, 2 * i + 1]);
+                ax.extend([top, bottom]);
+            }
+            ap.push(ai.len());
+        }
+    }
+    solver.solve(&mut ap, &mut ai, &mut ax, &mut rhs, 2 * npq)?;
+
+    Ok(DVector::from_fn(npq, |j, _| {
+        let z = Complex64::new(rhs[2 * j], rhs[2 * j + 1]);
+        (1.0 + z / v[j]).norm()
+    }))
+}
+
+/// Critical modes of the reduced Q–V Jacobian `J_R = J22 - J21 J11^-1 J12`.
+#[derive(Debug, Clone)]
+pub struct ModalResult {
+    /// Eigenvalues of `J_R`, ascending. Small positive values flag weak
+    /// modes; a negative one means the operating point is voltage unstable.
+    pub eigenvalues: Vec<f64>,
+    /// Bus participation factors (`npq` × modes, PQ buses in permuted
+    /// order); each column sums to one.
+    pub participation: DMatrix<f64>,
+}
+
+/// Modal analysis (Gao, Morison, Kundur) of the `modes` smallest
+/// eigenvalues of the reduced Jacobian at `v`.
+///
+/// `J_R^-1` is the `|V|`–`Q` block of `J^-1`, so applying it (or its
+/// transpose) is one solve with a single sparse factorization of `J`, and
+/// `J_R` is never formed. Its largest eigenvalues, the smallest of `J_R`,
+/// come from Arnoldi iterations; left eigenvectors from a second run on the
+/// transpose. `J_R` is close to symmetric and its critical modes are real,
+/// so complex pairs are skipped and fewer than `modes` eigenvalues may be
+/// returned.
+#[allow(non_snake_case)]
+pub fn modal_analysis<Solver: Solve>(
+    Ybus: &CscMatrix<Complex64>,
+    v: &DVector<Complex64>,
+    npv: usize,
+    npq: usize,
+    modes: usize,
+    solver: &mut Solver,
+) -> Result<ModalResult, String> {
+    let n_bus = npv + npq;
+    let n_state = n_bus + npq;
+    let modes = modes.min(npq);
+    if modes == 0 {
+        return Ok(ModalResult {
+            eigenvalues: Vec::new(),
+            participation: DMatrix::zeros(npq, 0),
+        });
+    }
+
+    factor_jacobian(Ybus, v, npv, npq, solver)?;
+    let mut apply = |x: &DVector<f64>, transpose: bool| -> Result<DVector<f64>, String> {
+        let mut rhs = DVector::zeros(n_state);
+        rhs.rows_mut(n_bus, npq).copy_from(x);
+        if transpose {
+            solver.solve_transpose_many(rhs.as_mut_slice(), n_state, 1)?;
+        } else {
+            solver.solve_many(rhs.as_mut_slice(), n_state, 1)?;
+        }
+        Ok(rhs.rows(n_bus, npq).into_owned())
+    };
+    // Largest |mu| of J_R^-1 are the smallest |lambda| of J_R.
+    let right = arnoldi(npq, modes, |x| apply(x, false))?;
+    let left = arnoldi(npq, modes, |x| apply(x, true))?;
+
+    let mut eigen = right
+        .into_iter()
+        .map(|(mu, r)| {
+            let (_, l) = left
+                .iter()
+                .min_by(|a, b| (a.0 - mu).abs().total_cmp(&(b.0 - mu).abs()))
+                .ok_or("No left eigenvector in modal analysis")?;
+            let mut p = r.component_mul(l);
+            let sum = p.sum();
+            if sum != 0.0 {
+                p /= sum;
+            }
+            Ok((1.0 / mu, p))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    eigen.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut participation = DMatrix::zeros(npq, eigen.len());
+    for (k, (_, p)) in eigen.iter().enumerate() {
+        participation.set_column(k, p);
+    }
+    Ok(ModalResult {
+        eigenvalues: eigen.into_iter().map(|(l, _)| l).collect(),
+        participation,
+    })
+}
+
+/// Up to `modes` eigenvalues of largest magnitude of the linear operator
+/// `apply` on `R^n`, with unit eigenvectors, by explicitly restarted
+/// Arnoldi. Only real eigenvalues are reported; complex pairs are skipped.
+///
+/// Only the small Hessenberg matrix is dense; eigenvectors of it come from
+/// [`inverse_iteration`].
+fn arnoldi(
+    n: usize,
+    modes: usize,
+    mut apply: impl FnMut(&DVector<f64>) -> Result<DVector<f64>, String>,
+) -> Result<Vec<(f64, DVector<f64>)>, String> {
+    const MAX_RESTARTS: usize = 100;
+    let m = n.min((2 * modes + 1).max(30));
+    let mut start = DVector::from_element(n, 1.0 / (n as f64).sqrt());
+    let mut last: Vec<Complex64> = Vec::new();
+
+    for _ in 0..MAX_RESTARTS {
+        let mut basis = vec![start.clone()];
+        let mut h = DMatrix::<f64>::zeros(m + 1, m);
+        let mut k = 0;
+        let mut invariant = false;
+        while k < m {
+            let mut w = apply(&basis[k])?;
+            let scale = w.norm();
+            // Modified Gram-Schmidt, twice for orthogonality.
+            for _ in 0..2 {
+                for (i, q) in basis.iter().enumerate() {
+                    let c = q.dot(&w);
+                    h[(i, k)] += c;
+                    w.axpy(-c, q, 1.0);
+                }
+            }
+            let norm = w.norm();
+            h[(k + 1, k)] = norm;
+            k += 1;
+            if norm <= 1e-12 * scale {
+                invariant = true;
+                break;
+            }
+            if k < m {
+                basis.push(w / norm);
+            }
+        }
+        let hk = h.view((0, 0), (k, k)).into_owned();
+
+        let mut ritz = hk
+            .complex_eigenvalues()
+            .iter()
+            .copied()
+            .filter(|mu| mu.im.abs() <= 1e-10 * mu.norm() && mu.re != 0.0)
+            .collect::<Vec<_>>();
+        ritz.sort_by(|a, b| b.norm().total_cmp(&a.norm()));
+        ritz.truncate(modes);
+
+        let pairs = ritz
+            .iter()
+            .map(|mu| {
+                let y = inverse_iteration(&hk, mu.re)?;
+                let mut x = DVector::zeros(n);
+                for (yi, q) in y.iter().zip(&basis) {
+                    x.axpy(*yi, q, 1.0);
+                }
+                Ok((mu.re, x.normalize()))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let scale = ritz.first().map_or(0.0, |mu| mu.norm());
+        let settled = last.len() == ritz.len()
+            && last
+                .iter()
+                .zip(&ritz)
+                .all(|(a, b)| (a - b).norm() <= 1e-10 * scale);
+        if invariant || k == n || settled {
+            return Ok(pairs);
+        }
+        last = ritz;
+        start = pairs
+            .iter()
+            .fold(DVector::zeros(n), |acc, (_, x)| acc + x)
+            .normalize();
+    }
+    Err("Modal analysis did not converge".to_string())
+}
+
+/// Eigenvector of `a` for the (real) eigenvalue `mu`.
+fn inverse_iteration(a: &DMatrix<f64>, mu: f64) -> Result<DVector<f64>, String> {
+    let n = a.nrows();
+    // Shift slightly off the eigenvalue so the LU stays regular.
+    let shift = mu * (1.0 + 1e-9) + 1e-14;
+    let lu = (a - DMatrix::identity(n, n) * shift).lu();
+    let mut x = DVector::from_element(n, 1.0 / (n as f64).sqrt());
+    for _ in 0..4 {
+        x = lu
+            .solve(&x)
+            .ok_or("Singular shifted matrix in modal analysis")?;
+        x.normalize_mut();
+    }
+    Ok(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::plugin::default_app;
+    use crate::basic::ecs::powerflow::systems::{PowerFlowMat, PowerFlowResult};
+    use crate::basic::solver::DefaultSolver;
+    use crate::io::pandapower::load_csv_zip;
+    use crate::prelude::PPNetwork;
+    use std::env;
+
+    #[test]
+    fn test_indices_118() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+        let mut app = default_app();
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+
+        let mat = app.world().resource::<PowerFlowMat>();
+        let v = &app.world().resource::<PowerFlowResult>().v;
+        let l = l_index(&mat.y_bus, v, mat.npq, &mut DefaultSolver::default()).unwrap();
+        assert_eq!(l.len(), mat.npq);
+        assert!(l.iter().all(|x| (0.0..1.0).contains(x)), "{l}");
+
+        // Same index from the dense F_LG = -Y_LL^-1 Y_LG definition.
+        let (n, npq) = (v.len(), mat.npq);
+        let y = DMatrix::from_fn(n, n, |i, j| {
+            let col = mat.y_bus.col(j);
+            col.row_indices()
+                .iter()
+                .position(|&r| r == i)
+                .map_or(Complex64::default(), |p| col.values()[p])
+        });
+        let f = -y
+            .view((0, 0), (npq, npq))
+            .lu()
+            .solve(&y.view((0, npq), (npq, n - npq)).into_owned())
+            .unwrap();
+        let fv = f * v.rows(npq, n - npq);
+        for j in 0..npq {
+            assert!(((Complex64::new(1.0, 0.0) - fv[j] / v[j]).norm() - l[j]).abs() < 1e-10);
+        }
+
+        let modal = modal_analysis(
+            &mat.y_bus,
+            v,
+            mat.npv,
+            mat.npq,
+            3,
+            &mut DefaultSolver::default(),
+        )
+        .unwrap();
+        assert_eq!(modal.eigenvalues.len(), 3);
+        assert!(modal.eigenvalues.windows(2).all(|w| w[0] <= w[1]));
+        assert!(modal.eigenvalues[0] > 0.0);
+        for k in 0..3 {
+            assert!((modal.participation.column(k).sum() - 1.0).abs() < 1e-9);
+        }
+
+        // Same eigenvalues from the dense J_R^-1.
+        let (n_bus, n_state) = (mat.npv + mat.npq, mat.npv + 2 * mat.npq);
+        let mut solver = DefaultSolver::default();
+        factor_jacobian(&mat.y_bus, v, mat.npv, mat.npq, &mut solver).unwrap();
+        let mut rhs = DMatrix::<f64>::zeros(n_state, mat.npq);
+        for j in 0..mat.npq {
+            rhs[(n_bus + j, j)] = 1.0;
+        }
+        solver
+            .solve_many(rhs.as_mut_slice(), n_state, mat.npq)
+            .unwrap();
+        let s = rhs.rows(n_bus, mat.npq).into_owned();
+        let mut mu = s
+            .complex_eigenvalues()
+            .iter()
+            .map(|m| m.norm())
+            .collect::<Vec<_>>();
+        mu.sort_by(|a, b| b.total_cmp(a));
+        for (lambda, mu) in modal.eigenvalues.iter().zip(&mu) {
+            assert!((lambda - 1.0 / mu).abs() < 1e-8 * lambda, "{lambda} {mu}");
+        }
+    }
+
+    #[test]
+    fn test_arnoldi_skips_conjugate_pair() {
+        // Eigenvalues 1 ± 2i, 0.5, 0.1 and 0.05.
+        let a = DMatrix::from_row_slice(
+            5,
+            5,
+            &[
+                1.0, -2.0, 0.0, 0.0, 0.0, //
+                2.0, 1.0, 0.0, 0.0, 0.0, //
+                0.0, 0.0, 0.5, 0.0, 0.0, //
+                0.0, 0.0, 0.0, 0.1, 0.0, //
+                0.0, 0.0, 0.0, 0.0, 0.05,
+            ],
+        );
+        let pairs = arnoldi(5, 2, |x| Ok(&a * x)).unwrap();
+        let mu = pairs.iter().map(|(mu, _)| *mu).collect::<Vec<_>>();
+        assert_eq!(mu.len(), 2);
+        assert!(
+            (mu[0] - 0.5).abs() < 1e-9 && (mu[1] - 0.1).abs() < 1e-9,
+            "{mu:?}"
+        );
+        for (mu, x) in &pairs {
+            assert!((&a * x - x * *mu).norm() < 1e-9);
+        }
+    }
+}
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use crate::basic::ecs::post_processing::SBusResult;
 use crate::basic::ecs::post_processing::VBusResult;
 use crate::basic::ecs::powerflow::systems::PowerFlowConfig;
+use crate::basic::ecs::powerflow::trace::ConvergenceTrace;
 use crate::prelude::default_app;
 use bevy_app::prelude::*;
 pub use bevy_archive::archetype_archive::load_world_resource;
@@ -168,6 +169,7 @@ impl Plugin for ArchivePlugin {
         let out_reg = Arc::new({
             let mut out_reg = SnapshotRegistry::default();
             register_all!(out_reg, [BusID, VBusResult, SBusResult, crate::basic::ecs::post_processing::LineResultData]);
+            register_res_all!(out_reg, [ConvergenceTrace]);
             out_reg
         });
         reg.pf_state_reg = pf_reg;
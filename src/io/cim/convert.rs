@@ -0,0 +1,949 @@
+//! Conversion of CGMES models into pandapower [`Network`]s.
+//!
+//! Buses are the connectivity nodes of a node-breaker model, or the
+//! topological nodes (TP profile) when the model has no connectivity nodes.
+//! Breakers, disconnectors and other switches become bus-bus switches, so a
+//! switch plugin (`SwitchPluginTypeA`) collapses the node-breaker topology
+//! into electrical buses. Operating values (load and machine set points,
+//! switch and tap positions, shunt sections, terminal connection) come from
+//! the SSH profile, with the EQ normal values as fallback.
+//!
+//! * `ACLineSegment` becomes a line with its total parameters on 1 km.
+//! * `EnergyConsumer` (and its conform/non-conform subclasses) becomes a
+//!   load, `LinearShuntCompensator` a shunt with its sections as steps.
+//! * `SynchronousMachine` and `ExternalNetworkInjection` with an enabled
+//!   voltage control become generators regulating to their target voltage;
+//!   the one with the lowest reference priority (or, without priorities,
+//!   the first external injection or the largest machine) becomes the
+//!   external grid. Machines without voltage control become static
+//!   generators.
+//! * `PowerTransformer` ends are mapped onto the tap-on-hv transformer
+//!   model: end 1 is `hv_bus`, the series impedances of all ends are
+//!   referred to end 1 at rated voltages, and the rated voltages, bus base
+//!   voltages and `RatioTapChanger` positions give a single off-nominal
+//!   ratio. `PhaseTapChangerLinear` steps and the ends' phase angle clocks
+//!   give the phase shift. Magnetizing admittance becomes a shunt at the
+//!   end 1 bus. Three-winding transformers get a star bus at the end 1
+//!   rated voltage and one transformer per end.
+//!
+//! Missing machine limits are NaN, pandapower's missing value, which no
+//! limit check trips. The nominal frequency is the model's `BaseFrequency`,
+//! 50 Hz without one, or given to [`convert_network`].
+//!
+//! Operational limits, other tap changer types and controller data are not
+//! read.
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::io::Error;
+use std::path::Path;
+
+use super::model::*;
+use crate::io::pandapower::*;
+
+const LOAD_CLASSES: &[&str] = &[
+    "EnergyConsumer",
+    "ConformLoad",
+    "NonConformLoad",
+    "StationSupply",
+];
+const SWITCH_CLASSES: &[&str] = &[
+    "Switch",
+    "Breaker",
+    "Disconnector",
+    "LoadBreakSwitch",
+    "Fuse",
+    "Jumper",
+];
+const MACHINE_CLASSES: &[&str] = &["SynchronousMachine", "ExternalNetworkInjection"];
+
+fn non_empty<T>(rows: Vec<T>) -> Option<Vec<T>> {
+    (!rows.is_empty()).then_some(rows)
+}
+
+/// Terminals and buses of the model.
+struct Topology<'a> {
+    model: &'a CimModel,
+    /// Terminals of each conducting equipment, by sequence number.
+    terminals: HashMap<&'a str, Vec<&'a CimObject>>,
+    /// Bus index of each connectivity or topological node.
+    bus: HashMap<&'a str, i64>,
+    /// Terminal property naming the node a terminal connects to.
+    node_prop: &'static str,
+}
+
+impl<'a> Topology<'a> {
+    fn new(model: &'a CimModel) -> (Self, Vec<Bus>) {
+        let node_breaker = model.of_class(&["ConnectivityNode"]).next().is_some();
+        let (class, node_prop) = if node_breaker {
+            ("ConnectivityNode", "ConnectivityNode")
+        } else {
+            ("TopologicalNode", "TopologicalNode")
+        };
+
+        let mut terminals: HashMap<&str, Vec<&CimObject>> = HashMap::new();
+        for (_, t) in model.of_class(&["Terminal"]) {
+            if let Some(eq) = t.get("ConductingEquipment") {
+                terminals.entry(eq).or_default().push(t);
+            }
+        }
+        for list in terminals.values_mut() {
+            list.sort_by_key(|t| t.f64("sequenceNumber").unwrap_or(0.0) as i64);
+        }
+
+        let mut topo = Self {
+            model,
+            terminals,
+            bus: HashMap::new(),
+            node_prop,
+        };
+        let vn = topo.node_voltages();
+        let buses = model
+            .of_class(&[class])
+            .enumerate()
+            .map(|(i, (id, node))| {
+                topo.bus.insert(id, i as i64);
+                Bus {
+                    index: i as i64,
+                    in_service: true,
+                    name: node.name(),
+                    r#type: Some("b".into()),
+                    vn_kv: vn.get(id).copied().unwrap_or(1.0),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        (topo, buses)
+    }
+
+    /// Nominal voltage of a base voltage, voltage level or bay.
+    fn container_kv(&self, obj: &CimObject) -> Option<f64> {
+        match obj.class.as_str() {
+            "BaseVoltage" => obj.f64("nominalVoltage"),
+            "Bay" => self.container_kv(self.model.follow(obj, "VoltageLevel")?),
+            _ => self.container_kv(self.model.follow(obj, "BaseVoltage")?),
+        }
+    }
+
+    /// Base voltage of every node: from its container or base voltage,
+    /// else from the base voltage or rated voltage of connected equipment.
+    fn node_voltages(&self) -> HashMap<&'a str, f64> {
+        let model = self.model;
+        let mut vn = HashMap::new();
+        let class = self.node_prop;
+        for (id, node) in model.of_class(&[class]) {
+            let kv = [
+                "BaseVoltage",
+                "ConnectivityNodeContainer",
+                "TopologicalNode",
+            ]
+            .iter()
+            .find_map(|p| {
+                let obj = model.follow(node, p)?;
+                match obj.class.as_str() {
+                    "TopologicalNode" => self.container_kv(model.follow(obj, "BaseVoltage")?),
+                    _ => self.container_kv(obj),
+                }
+            });
+            if let Some(kv) = kv {
+                vn.insert(id, kv);
+            }
+        }
+        for (_, end) in model.of_class(&["PowerTransformerEnd"]) {
+            let node = model
+                .follow(end, "Terminal")
+                .and_then(|t| t.get(self.node_prop));
+            if let (Some(node), Some(kv)) = (node, end.f64("ratedU")) {
+                vn.entry(node).or_insert(kv);
+            }
+        }
+        for (eq_id, terminals) in &self.terminals {
+            let Some(kv) = model
+                .get(eq_id)
+                .and_then(|eq| self.container_kv(model.follow(eq, "BaseVoltage")?))
+            else {
+                continue;
+            };
+            for t in terminals {
+                if let Some(node) = t.get(self.node_prop) {
+                    vn.entry(node).or_insert(kv);
+                }
+            }
+        }
+        vn
+    }
+
+    fn terminals(&self, eq: &str) -> &[&'a CimObject] {
+        self.terminals
+            .get(eq)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn bus_of(&self, terminal: &CimObject) -> Option<i64> {
+        self.bus.get(terminal.get(self.node_prop)?).copied()
+    }
+
+    /// Buses of the first `n` terminals of `eq`.
+    fn buses(&self, eq: &str, n: usize) -> Option<Vec<i64>> {
+        let terminals = self.terminals(eq);
+        if terminals.len() < n {
+            return None;
+        }
+        terminals[..n].iter().map(|t| self.bus_of(t)).collect()
+    }
+
+    /// In service unless switched out (`Equipment.inService`) or one of its
+    /// terminals is disconnected.
+    fn in_service(&self, id: &str, eq: &CimObject) -> bool {
+        eq.bool("inService").unwrap_or(true)
+            && self
+                .terminals(id)
+                .iter()
+                .all(|t| t.bool("connected").unwrap_or(true))
+    }
+}
+
+/// One transformer end with its tap position applied.
+struct End<'a> {
+    obj: &'a CimObject,
+    bus: i64,
+    rated_kv: f64,
+    /// Rated voltage scaled by the ratio tap changer.
+    tapped_kv: f64,
+    /// Phase shift of the end in degrees (clock and phase tap changer).
+    angle: f64,
+}
+
+fn tap_steps(tc: &CimObject) -> f64 {
+    let neutral = tc.f64("neutralStep").unwrap_or(0.0);
+    let step = tc
+        .f64("step")
+        .or_else(|| tc.f64("normalStep"))
+        .unwrap_or(neutral);
+    step - neutral
+}
+
+struct Builder<'a> {
+    model: &'a CimModel,
+    topo: Topology<'a>,
+    sbase: f64,
+    bus: Vec<Bus>,
+    shunt: Vec<Shunt>,
+    trafo: Vec<Transformer>,
+}
+
+impl<'a> Builder<'a> {
+    fn vn_kv(&self, bus: i64) -> f64 {
+        self.bus[bus as usize].vn_kv
+    }
+
+    /// Adds a transformer from `hv` to `lv` whose series impedance `z_ohm`
+    /// is referred to the hv winding at `hv_kv`; `hv_kv`/`lv_kv` are the
+    /// (tapped) winding voltages.
+    #[allow(clippy::too_many_arguments)]
+    fn add_branch(
+        &mut self,
+        (hv, hv_kv): (i64, f64),
+        (lv, lv_kv): (i64, f64),
+        (r, x): (f64, f64),
+        shift: f64,
+        sn_mva: f64,
+        in_service: bool,
+        name: Option<String>,
+    ) {
+        let (vn_hv, vn_lv) = (self.vn_kv(hv), self.vn_kv(lv));
+        // hv -- t:1 -- z -- lv with the impedance moved behind the tap.
+        let ratio = (hv_kv / vn_hv) / (lv_kv / vn_lv);
+        let scale = sn_mva / (vn_hv * vn_hv * ratio * ratio);
+        self.trafo.push(Transformer {
+            hv_bus: hv as i32,
+            lv_bus: lv as i32,
+            sn_mva,
+            vn_hv_kv: vn_hv,
+            vn_lv_kv: vn_lv,
+            vk_percent: r.hypot(x) * scale * 100.0,
+            vkr_percent: r * scale * 100.0,
+            pfe_kw: 0.0,
+            i0_percent: 0.0,
+            shift_degree: shift,
+            tap_side: Some("hv".into()),
+            tap_neutral: Some(0.0),
+            tap_pos: Some(1.0),
+            tap_step_percent: Some((ratio - 1.0) * 100.0),
+            tap_phase_shifter: false,
+            df: 1.0,
+            parallel: 1,
+            in_service,
+            name,
+            ..Default::default()
+        });
+    }
+
+    fn ends(&self, id: &str) -> Option<Vec<End<'a>>> {
+        let model = self.model;
+        let mut ends = model
+            .of_class(&["PowerTransformerEnd"])
+            .filter(|(_, e)| e.get("PowerTransformer") == Some(id))
+            .collect::<Vec<_>>();
+        ends.sort_by_key(|(_, e)| e.f64("endNumber").unwrap_or(0.0) as i64);
+        ends.into_iter()
+            .map(|(end_id, obj)| {
+                let bus = self.topo.bus_of(model.follow(obj, "Terminal")?)?;
+                let rated_kv = obj.f64("ratedU").unwrap_or_else(|| self.vn_kv(bus));
+                let mut tapped_kv = rated_kv;
+                let mut angle = -30.0 * obj.f64("phaseAngleClock").unwrap_or(0.0);
+                for (_, tc) in model.of_class(&["RatioTapChanger"]) {
+                    if tc.get("TransformerEnd") == Some(end_id) {
+                        let step = tc.f64("stepVoltageIncrement").unwrap_or(0.0);
+                        tapped_kv *= 1.0 + tap_steps(tc) * step / 100.0;
+                    }
+                }
+                for (_, tc) in model.of_class(&["PhaseTapChangerLinear"]) {
+                    if tc.get("TransformerEnd") == Some(end_id) {
+                        angle += tap_steps(tc) * tc.f64("stepPhaseShiftIncrement").unwrap_or(0.0);
+                    }
+                }
+                Some(End {
+                    obj,
+                    bus,
+                    rated_kv,
+                    tapped_kv,
+                    angle,
+                })
+            })
+            .collect()
+    }
+
+    fn add_transformer(&mut self, id: &str, pt: &CimObject) {
+        let Some(ends) = self.ends(id) else { return };
+        if !(2..=3).contains(&ends.len()) {
+            return;
+        }
+        let in_service = self.topo.in_service(id, pt);
+        let name = pt.name();
+        let e1 = &ends[0];
+        let sn_mva = |e: &End| e.obj.f64("ratedS").filter(|s| *s > 0.0);
+        let sn1 = sn_mva(e1).unwrap_or(self.sbase);
+        let prop = |e: &End, p: &str| e.obj.f64(p).unwrap_or(0.0);
+
+        // Magnetizing admittance of all ends, referred to end 1.
+        let (mut g, mut b) = (0.0, 0.0);
+        for e in &ends {
+            let k = (e.rated_kv / e1.rated_kv).powi(2);
+            g += prop(e, "g") * k;
+            b += prop(e, "b") * k;
+        }
+        if g != 0.0 || b != 0.0 {
+            let vn = self.vn_kv(e1.bus);
+            self.shunt.push(Shunt {
+                index: None,
+                bus: e1.bus,
+                p_mw: g * vn * vn,
+                q_mvar: -b * vn * vn,
+                vn_kv: vn,
+                step: 1,
+                max_step: 1,
+                in_service,
+                name: name.clone(),
+            });
+        }
+
+        if ends.len() == 2 {
+            let e2 = &ends[1];
+            let k = (e1.rated_kv / e2.rated_kv).powi(2);
+            let z = (
+                prop(e1, "r") + prop(e2, "r") * k,
+                prop(e1, "x") + prop(e2, "x") * k,
+            );
+            self.add_branch(
+                (e1.bus, e1.tapped_kv),
+                (e2.bus, e2.tapped_kv),
+                z,
+                e1.angle - e2.angle,
+                sn1,
+                in_service,
+                name,
+            );
+            return;
+        }
+
+        let star = self.bus.len() as i64;
+        self.bus.push(Bus {
+            index: star,
+            in_service,
+            name: Some(format!("{} star", name.as_deref().unwrap_or(id))),
+            r#type: Some("n".into()),
+            vn_kv: e1.rated_kv,
+            ..Default::default()
+        });
+        for e in &ends {
+            self.add_branch(
+                (e.bus, e.tapped_kv),
+                (star, e1.rated_kv),
+                (prop(e, "r"), prop(e, "x")),
+                e.angle + 30.0 * e1.obj.f64("phaseAngleClock").unwrap_or(0.0),
+                sn_mva(e).unwrap_or(sn1),
+                in_service,
+                name.clone(),
+            );
+        }
+    }
+}
+
+/// A voltage-controlling machine or injection before the slack is chosen.
+struct Machine<'a> {
+    obj: &'a CimObject,
+    bus: i64,
+    in_service: bool,
+    vm_pu: Option<f64>,
+}
+
+/// Nominal frequency (Hz) used when a model defines no `BaseFrequency`.
+pub const DEFAULT_F_HZ: f64 = 50.0;
+
+/// Nominal frequency (Hz) of the first `BaseFrequency` of a model.
+pub fn base_frequency(model: &CimModel) -> Option<f64> {
+    model
+        .of_class(&["BaseFrequency"])
+        .find_map(|(_, bf)| bf.f64("frequency"))
+        .filter(|f| *f > 0.0)
+}
+
+impl From<&CimModel> for Network {
+    /// Converts at the model's [`base_frequency`], or [`DEFAULT_F_HZ`].
+    fn from(model: &CimModel) -> Self {
+        convert_network(model, base_frequency(model).unwrap_or(DEFAULT_F_HZ))
+    }
+}
+
+/// Converts a model at a nominal frequency `f_hz`, which sets the line
+/// capacitances and the network's `f_hz`.
+pub fn convert_network(model: &CimModel, f_hz: f64) -> Network {
+    let (topo, bus) = Topology::new(model);
+    let mut m = Builder {
+        model,
+        topo,
+        sbase: 100.0,
+        bus,
+        shunt: Vec::new(),
+        trafo: Vec::new(),
+    };
+
+    let mut line = Vec::new();
+    for (id, seg) in model.of_class(&["ACLineSegment"]) {
+        let Some(b) = m.topo.buses(id, 2) else {
+            continue;
+        };
+        let p = |n: &str| seg.f64(n).unwrap_or(0.0);
+        line.push(Line {
+            from_bus: b[0],
+            to_bus: b[1],
+            length_km: 1.0,
+            r_ohm_per_km: p("r"),
+            x_ohm_per_km: p("x"),
+            c_nf_per_km: p("bch") / (2.0 * PI * f_hz) * 1e9,
+            g_us_per_km: p("gch") * 1e6,
+            max_i_ka: None,
+            df: 1.0,
+            parallel: 1,
+            in_service: m.topo.in_service(id, seg),
+            name: seg.name(),
+            ..Default::default()
+        });
+    }
+
+    let mut load = Vec::new();
+    for (id, ec) in model.of_class(LOAD_CLASSES) {
+        let Some(b) = m.topo.buses(id, 1) else {
+            continue;
+        };
+        load.push(Load {
+            bus: b[0],
+            p_mw: ec.f64("p").unwrap_or(0.0),
+            q_mvar: ec.f64("q").unwrap_or(0.0),
+            in_service: m.topo.in_service(id, ec),
+            scaling: 1.0,
+            name: ec.name(),
+            ..Default::default()
+        });
+    }
+
+    for (id, sc) in model.of_class(&["LinearShuntCompensator"]) {
+        let Some(b) = m.topo.buses(id, 1) else {
+            continue;
+        };
+        let vn = sc.f64("nomU").unwrap_or_else(|| m.vn_kv(b[0]));
+        let sections = sc
+            .f64("sections")
+            .or_else(|| sc.f64("normalSections"))
+            .unwrap_or(0.0);
+        m.shunt.push(Shunt {
+            index: None,
+            bus: b[0],
+            p_mw: sc.f64("gPerSection").unwrap_or(0.0) * vn * vn,
+            q_mvar: -sc.f64("bPerSection").unwrap_or(0.0) * vn * vn,
+            vn_kv: vn,
+            step: sections.round() as i32,
+            max_step: sc.f64("maximumSections").unwrap_or(1.0) as i32,
+            in_service: m.topo.in_service(id, sc),
+            name: sc.name(),
+        });
+    }
+
+    let mut switch = Vec::new();
+    for (id, sw) in model.of_class(SWITCH_CLASSES) {
+        let Some(b) = m.topo.buses(id, 2) else {
+            continue;
+        };
+        if b[0] == b[1] {
+            continue;
+        }
+        let open = sw
+            .bool("open")
+            .or_else(|| sw.bool("normalOpen"))
+            .unwrap_or(false);
+        switch.push(Switch {
+            index: None,
+            bus: b[0],
+            element: b[1],
+            et: SwitchType::SwitchTwoBuses,
+            type_: match sw.class.as_str() {
+                "Breaker" => Some("CB".into()),
+                "Disconnector" => Some("DS".into()),
+                "LoadBreakSwitch" => Some("LBS".into()),
+                _ => None,
+            },
+            closed: !open && m.topo.in_service(id, sw),
+            name: sw.name(),
+            z_ohm: 0.0,
+        });
+    }
+
+    for (id, pt) in model.of_class(&["PowerTransformer"]) {
+        m.add_transformer(id, pt);
+    }
+
+    // Machines: regulating ones are generator candidates, the others
+    // static generators. Injections use the load sign convention.
+    let mut machines = Vec::new();
+    let mut sgen = Vec::new();
+    for (id, sm) in model.of_class(MACHINE_CLASSES) {
+        let Some(b) = m.topo.buses(id, 1) else {
+            continue;
+        };
+        let in_service = m.topo.in_service(id, sm);
+        let control = model.follow(sm, "RegulatingControl");
+        let regulating = sm
+            .bool("controlEnabled")
+            .or_else(|| sm.bool("regulationStatus"))
+            .unwrap_or(true)
+            && control.is_some_and(|c| {
+                c.bool("enabled").unwrap_or(true)
+                    && c.get("mode").is_none_or(|mode| mode == "voltage")
+            });
+        let vm_pu = control.filter(|_| regulating).and_then(|c| {
+            let target = c.f64("targetValue")?;
+            let bus = model
+                .follow(c, "Terminal")
+                .and_then(|t| m.topo.bus_of(t))
+                .unwrap_or(b[0]);
+            Some(target / m.vn_kv(bus))
+        });
+        if vm_pu.is_some() || sm.class == "ExternalNetworkInjection" {
+            machines.push(Machine {
+                obj: sm,
+                bus: b[0],
+                in_service,
+                vm_pu,
+            });
+        } else {
+            sgen.push(SGen {
+                bus: b[0],
+                name: sm.name(),
+                p_mw: -sm.f64("p").unwrap_or(0.0),
+                q_mvar: -sm.f64("q").unwrap_or(0.0),
+                sn_mva: sm.f64("ratedS"),
+                scaling: 1.0,
+                in_service,
+                controllable: Some(false),
+                ..Default::default()
+            });
+        }
+    }
+
+    let priority = |mc: &Machine| {
+        mc.obj
+            .f64("referencePriority")
+            .filter(|p| *p > 0.0)
+            .unwrap_or(f64::INFINITY)
+    };
+    let slack = machines
+        .iter()
+        .enumerate()
+        .filter(|(_, mc)| mc.in_service)
+        .min_by(|(_, a), (_, b)| {
+            let eni = |mc: &Machine| mc.obj.class != "ExternalNetworkInjection";
+            let size = |mc: &Machine| -mc.obj.f64("ratedS").unwrap_or(0.0);
+            priority(a)
+                .total_cmp(&priority(b))
+                .then(eni(a).cmp(&eni(b)))
+                .then(size(a).total_cmp(&size(b)))
+        })
+        .map(|(i, _)| i);
+
+    let mut ext_grid = Vec::new();
+    let mut r#gen = Vec::new();
+    for (i, mc) in machines.iter().enumerate() {
+        let sm = mc.obj;
+        let limit = |a: &str, b: &str| sm.f64(a).or_else(|| sm.f64(b));
+        let unit = model.follow(sm, "GeneratingUnit");
+        let unit_p = |p: &str| unit.and_then(|u| u.f64(p));
+        let max_p = limit("maxP", "maxOperatingP").or_else(|| unit_p("maxOperatingP"));
+        let min_p = limit("minP", "minOperatingP").or_else(|| unit_p("minOperatingP"));
+        let vm_pu = mc.vm_pu.unwrap_or(1.0);
+        if Some(i) == slack {
+            ext_grid.push(ExtGrid {
+                index: None,
+                bus: mc.bus,
+                in_service: mc.in_service,
+                va_degree: 0.0,
+                vm_pu,
+                max_p_mw: max_p,
+                min_p_mw: min_p,
+                max_q_mvar: sm.f64("maxQ"),
+                min_q_mvar: sm.f64("minQ"),
+                slack_weight: 1.0,
+                name: sm.name(),
+            });
+        } else if mc.vm_pu.is_some() {
+            r#gen.push(Gen {
+                index: None,
+                bus: mc.bus,
+                controllable: Some(true),
+                in_service: mc.in_service,
+                name: sm.name(),
+                p_mw: -sm.f64("p").unwrap_or(0.0),
+                scaling: 1.0,
+                sn_mva: sm.f64("ratedS"),
+                type_: None,
+                vm_pu,
+                slack: false,
+                max_p_mw: max_p.unwrap_or(f64::NAN),
+                min_p_mw: min_p.unwrap_or(f64::NAN),
+                max_q_mvar: sm.f64("maxQ").unwrap_or(f64::NAN),
+                min_q_mvar: sm.f64("minQ").unwrap_or(f64::NAN),
+                slack_weight: 0.0,
+            });
+        } else {
+            sgen.push(SGen {
+                bus: mc.bus,
+                name: sm.name(),
+                p_mw: -sm.f64("p").unwrap_or(0.0),
+                q_mvar: -sm.f64("q").unwrap_or(0.0),
+                scaling: 1.0,
+                in_service: mc.in_service,
+                controllable: Some(false),
+                ..Default::default()
+            });
+        }
+    }
+
+    let Builder {
+        bus, shunt, trafo, ..
+    } = m;
+    Network {
+        bus,
+        load: non_empty(load),
+        line: non_empty(line),
+        trafo: non_empty(trafo),
+        shunt: non_empty(shunt),
+        ext_grid: non_empty(ext_grid),
+        sgen: non_empty(sgen),
+        switch: non_empty(switch),
+        r#gen: non_empty(r#gen),
+        f_hz,
+        sn_mva: 100.0,
+        std_types: None,
+    }
+}
+
+/// Reads CGMES profile files (or zip archives of them) as a [`Network`],
+/// ready to be inserted as
+/// [`PPNetwork`](crate::basic::ecs::elements::PPNetwork).
+pub fn load_cgmes_network<P: AsRef<Path>>(paths: &[P]) -> Result<Network, Error> {
+    Ok(Network::from(&load_cgmes(paths)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::elements::PPNetwork;
+    use crate::basic::ecs::plugin::{SwitchPluginTypeA, default_app};
+    use crate::basic::ecs::post_processing::PostProcessing;
+    use crate::io::pandapower::NetworkResults;
+    use crate::io::pandapower::ecs_net_conv::ToPandapowerNet;
+    use std::env;
+
+    fn solve(net: Network) -> NetworkResults {
+        let mut app = default_app();
+        if net.switch.is_some() {
+            app.add_plugins(SwitchPluginTypeA);
+        }
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+        app.post_process();
+        let res = app.pandapower_results();
+        assert!(res.converged);
+        res
+    }
+
+    /// EQ, SSH and TP documents of the two-substation node-breaker case.
+    fn profiles() -> Vec<String> {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        ["EQ", "SSH", "TP"]
+            .iter()
+            .map(|p| {
+                let path = format!("{dir}/cases/cim/two_substations_{p}.xml");
+                std::fs::read_to_string(path).unwrap()
+            })
+            .collect()
+    }
+
+    fn network(profiles: &[String]) -> Network {
+        let docs = profiles.iter().map(String::as_str).collect::<Vec<_>>();
+        Network::from(&CimModel::parse(&docs).unwrap())
+    }
+
+    #[test]
+    fn test_node_breaker_elements() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let paths = ["EQ", "SSH", "TP"].map(|p| format!("{dir}/cases/cim/two_substations_{p}.xml"));
+        let net = load_cgmes_network(&paths).unwrap();
+
+        assert_eq!(net.bus.len(), 9);
+        assert_eq!(net.bus[7].name.as_deref(), Some("S2 trafo bay"));
+        assert_eq!(net.bus[7].vn_kv, 110.0);
+        assert_eq!(net.bus[8].vn_kv, 20.0);
+
+        // The load break switch with both terminals on one node is dropped.
+        let switch = net.switch.as_ref().unwrap();
+        assert_eq!(switch.len(), 6);
+        assert_eq!(switch.iter().filter(|s| !s.closed).count(), 1);
+        assert_eq!(switch[2].type_.as_deref(), Some("DS"));
+        assert!(!switch[2].closed);
+
+        let line = &net.line.as_ref().unwrap()[0];
+        assert_eq!((line.from_bus, line.to_bus), (2, 5));
+        assert_eq!(line.x_ohm_per_km, 12.0);
+
+        let shunt = &net.shunt.as_ref().unwrap()[0];
+        assert_eq!((shunt.step, shunt.max_step), (1, 2));
+        assert!((shunt.q_mvar + 5.0).abs() < 1e-12);
+
+        let ext_grid = &net.ext_grid.as_ref().unwrap()[0];
+        assert_eq!(ext_grid.bus, 1);
+        assert!((ext_grid.vm_pu - 115.0 / 110.0).abs() < 1e-12);
+        assert_eq!(ext_grid.max_p_mw, Some(100.0));
+
+        let sgen = &net.sgen.as_ref().unwrap()[0];
+        assert_eq!((sgen.bus, sgen.p_mw, sgen.q_mvar), (8, 8.0, 2.0));
+        assert!(net.r#gen.is_none());
+    }
+
+    #[test]
+    fn test_node_breaker_power_flow() {
+        let res = solve(network(&profiles()));
+        // Line 2 is open at S1 behind the disconnector.
+        let l2 = &res.line[1].1;
+        assert!(l2.p_from_mw.abs() < 1e-6 && l2.q_from_mvar.abs() < 1e-6);
+        let p_slack = res.ext_grid[0].1.p_mw;
+        assert!(p_slack > 22.0 && p_slack < 23.0, "{p_slack}");
+        let l1 = &res.line[0].1;
+        assert!(l1.p_from_mw > 22.0 && l1.vm_from_pu > 1.0);
+        // S1 busbar merged with the generator bay at the set point.
+        assert!((res.bus[1].1.vm_pu - 115.0 / 110.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_transformer_tap_ratio() {
+        // Without load the 20 kV busbar sits at the tapped ratio
+        // 115 * (1 + 2 * 1.25 %) : 21 below the S2 busbar.
+        let mut docs = profiles();
+        docs[1] = docs[1]
+            .replace(">30</cim:EnergyConsumer.p>", ">0</cim:EnergyConsumer.p>")
+            .replace(">10</cim:EnergyConsumer.q>", ">0</cim:EnergyConsumer.q>")
+            .replace(">-8</cim:RotatingMachine.p>", ">0</cim:RotatingMachine.p>")
+            .replace(">-2</cim:RotatingMachine.q>", ">0</cim:RotatingMachine.q>")
+            .replace(
+                ">1</cim:ShuntCompensator.sections>",
+                ">0</cim:ShuntCompensator.sections>",
+            );
+        let res = solve(network(&docs));
+        let kv_hv = res.bus[4].1.vm_pu * 110.0;
+        let kv_lv = res.bus[8].1.vm_pu * 20.0;
+        assert!((kv_lv - kv_hv * 21.0 / (115.0 * 1.025)).abs() < 1e-9);
+        assert!((res.bus[8].1.va_degree - res.bus[4].1.va_degree).abs() < 1e-9);
+    }
+
+    const BUS_BRANCH_EQ: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<rdf:RDF xmlns:cim="http://iec.ch/TC57/2013/CIM-schema-cim16#"
+    xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <cim:BaseVoltage rdf:ID="_BV">
+    <cim:BaseVoltage.nominalVoltage>110</cim:BaseVoltage.nominalVoltage>
+  </cim:BaseVoltage>
+  <cim:ExternalNetworkInjection rdf:ID="_X">
+    <cim:RegulatingCondEq.RegulatingControl rdf:resource="#_RC"/>
+    <cim:ExternalNetworkInjection.maxP>500</cim:ExternalNetworkInjection.maxP>
+  </cim:ExternalNetworkInjection>
+  <cim:RegulatingControl rdf:ID="_RC">
+    <cim:RegulatingControl.Terminal rdf:resource="#_X_T"/>
+    <cim:RegulatingControl.mode rdf:resource="http://iec.ch/TC57/2013/CIM-schema-cim16#RegulatingControlModeKind.voltage"/>
+  </cim:RegulatingControl>
+  <cim:ACLineSegment rdf:ID="_L">
+    <cim:ConductingEquipment.BaseVoltage rdf:resource="#_BV"/>
+    <cim:ACLineSegment.r>5</cim:ACLineSegment.r>
+    <cim:ACLineSegment.x>20</cim:ACLineSegment.x>
+    <cim:ACLineSegment.bch>0.0001</cim:ACLineSegment.bch>
+  </cim:ACLineSegment>
+  <cim:ConformLoad rdf:ID="_LD"/>
+  <cim:Terminal rdf:ID="_X_T">
+    <cim:Terminal.ConductingEquipment rdf:resource="#_X"/>
+  </cim:Terminal>
+  <cim:Terminal rdf:ID="_L_T1">
+    <cim:ACDCTerminal.sequenceNumber>1</cim:ACDCTerminal.sequenceNumber>
+    <cim:Terminal.ConductingEquipment rdf:resource="#_L"/>
+  </cim:Terminal>
+  <cim:Terminal rdf:ID="_L_T2">
+    <cim:ACDCTerminal.sequenceNumber>2</cim:ACDCTerminal.sequenceNumber>
+    <cim:Terminal.ConductingEquipment rdf:resource="#_L"/>
+  </cim:Terminal>
+  <cim:Terminal rdf:ID="_LD_T">
+    <cim:Terminal.ConductingEquipment rdf:resource="#_LD"/>
+  </cim:Terminal>
+</rdf:RDF>"##;
+
+    const BUS_BRANCH_TP: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<rdf:RDF xmlns:cim="http://iec.ch/TC57/2013/CIM-schema-cim16#"
+    xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <cim:TopologicalNode rdf:ID="_N1">
+    <cim:TopologicalNode.BaseVoltage rdf:resource="#_BV"/>
+  </cim:TopologicalNode>
+  <cim:TopologicalNode rdf:ID="_N2">
+    <cim:TopologicalNode.BaseVoltage rdf:resource="#_BV"/>
+  </cim:TopologicalNode>
+  <cim:Terminal rdf:about="#_X_T">
+    <cim:Terminal.TopologicalNode rdf:resource="#_N1"/>
+  </cim:Terminal>
+  <cim:Terminal rdf:about="#_L_T1">
+    <cim:Terminal.TopologicalNode rdf:resource="#_N1"/>
+  </cim:Terminal>
+  <cim:Terminal rdf:about="#_L_T2">
+    <cim:Terminal.TopologicalNode rdf:resource="#_N2"/>
+  </cim:Terminal>
+  <cim:Terminal rdf:about="#_LD_T">
+    <cim:Terminal.TopologicalNode rdf:resource="#_N2"/>
+  </cim:Terminal>
+</rdf:RDF>"##;
+
+    const BUS_BRANCH_SSH: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<rdf:RDF xmlns:cim="http://iec.ch/TC57/2013/CIM-schema-cim16#"
+    xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <cim:ExternalNetworkInjection rdf:about="#_X">
+    <cim:RegulatingCondEq.controlEnabled>true</cim:RegulatingCondEq.controlEnabled>
+    <cim:ExternalNetworkInjection.referencePriority>0</cim:ExternalNetworkInjection.referencePriority>
+  </cim:ExternalNetworkInjection>
+  <cim:RegulatingControl rdf:about="#_RC">
+    <cim:RegulatingControl.enabled>true</cim:RegulatingControl.enabled>
+    <cim:RegulatingControl.targetValue>112.2</cim:RegulatingControl.targetValue>
+  </cim:RegulatingControl>
+  <cim:ConformLoad rdf:about="#_LD">
+    <cim:EnergyConsumer.p>50</cim:EnergyConsumer.p>
+    <cim:EnergyConsumer.q>20</cim:EnergyConsumer.q>
+  </cim:ConformLoad>
+</rdf:RDF>"##;
+
+    #[test]
+    fn test_bus_branch_topology() {
+        let model = CimModel::parse(&[BUS_BRANCH_EQ, BUS_BRANCH_TP, BUS_BRANCH_SSH]).unwrap();
+        let net = Network::from(&model);
+        assert_eq!(net.bus.len(), 2);
+        assert!(net.bus.iter().all(|b| b.vn_kv == 110.0));
+        assert!(net.switch.is_none());
+
+        let line = &net.line.as_ref().unwrap()[0];
+        assert_eq!((line.from_bus, line.to_bus), (0, 1));
+        assert!((line.c_nf_per_km - 1e5 / (2.0 * PI * 50.0)).abs() < 1e-9);
+        assert_eq!(net.load.as_ref().unwrap()[0].p_mw, 50.0);
+
+        // The only injection is the slack even without a reference priority.
+        let ext_grid = &net.ext_grid.as_ref().unwrap()[0];
+        assert!((ext_grid.vm_pu - 1.02).abs() < 1e-12);
+        assert_eq!(ext_grid.max_p_mw, Some(500.0));
+
+        let res = solve(net);
+        assert!((res.bus[0].1.vm_pu - 1.02).abs() < 1e-12);
+        assert!(res.ext_grid[0].1.p_mw > 50.0);
+    }
+
+    #[test]
+    fn test_base_frequency_and_missing_gen_limits() {
+        // A 60 Hz model with a voltage-controlling machine without limits.
+        const GEN_60HZ: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<rdf:RDF xmlns:cim="http://iec.ch/TC57/2013/CIM-schema-cim16#"
+    xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <cim:BaseFrequency rdf:ID="_BF">
+    <cim:BaseFrequency.frequency>60</cim:BaseFrequency.frequency>
+  </cim:BaseFrequency>
+  <cim:SynchronousMachine rdf:ID="_G">
+    <cim:RegulatingCondEq.RegulatingControl rdf:resource="#_RC2"/>
+    <cim:SynchronousMachine.p>-10</cim:SynchronousMachine.p>
+  </cim:SynchronousMachine>
+  <cim:RegulatingControl rdf:ID="_RC2">
+    <cim:RegulatingControl.Terminal rdf:resource="#_G_T"/>
+    <cim:RegulatingControl.targetValue>110</cim:RegulatingControl.targetValue>
+  </cim:RegulatingControl>
+  <cim:Terminal rdf:ID="_G_T">
+    <cim:Terminal.ConductingEquipment rdf:resource="#_G"/>
+    <cim:Terminal.TopologicalNode rdf:resource="#_N2"/>
+  </cim:Terminal>
+</rdf:RDF>"##;
+        let docs = [BUS_BRANCH_EQ, BUS_BRANCH_TP, BUS_BRANCH_SSH, GEN_60HZ];
+        let model = CimModel::parse(&docs).unwrap();
+        assert_eq!(base_frequency(&model), Some(60.0));
+        let net = Network::from(&model);
+        assert_eq!(net.f_hz, 60.0);
+        let line = &net.line.as_ref().unwrap()[0];
+        assert!((line.c_nf_per_km - 1e5 / (2.0 * PI * 60.0)).abs() < 1e-9);
+        assert_eq!(convert_network(&model, 50.0).f_hz, 50.0);
+
+        let g = &net.r#gen.as_ref().unwrap()[0];
+        assert!(g.max_p_mw.is_nan() && g.min_p_mw.is_nan());
+        assert!(g.max_q_mvar.is_nan() && g.min_q_mvar.is_nan());
+
+        // Missing limits are written as null and read back as missing.
+        let json = crate::io::pandapower::to_pandapower_json(&net, None).unwrap();
+        let reloaded = load_pandapower_json_obj(json.as_object().unwrap());
+        let g = &reloaded.r#gen.as_ref().unwrap()[0];
+        assert!(g.max_q_mvar.is_nan() && g.min_p_mw.is_nan());
+    }
+
+    #[test]
+    fn test_slack_selection_nan_rating() {
+        // A second injection with a NaN rating must not panic the ordering.
+        const NAN_ENI: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<rdf:RDF xmlns:cim="http://iec.ch/TC57/2013/CIM-schema-cim16#"
+    xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <cim:ExternalNetworkInjection rdf:ID="_Y">
+    <cim:ExternalNetworkInjection.ratedS>NaN</cim:ExternalNetworkInjection.ratedS>
+  </cim:ExternalNetworkInjection>
+  <cim:Terminal rdf:ID="_Y_T">
+    <cim:Terminal.ConductingEquipment rdf:resource="#_Y"/>
+    <cim:Terminal.TopologicalNode rdf:resource="#_N2"/>
+  </cim:Terminal>
+</rdf:RDF>"##;
+        let docs = [BUS_BRANCH_EQ, BUS_BRANCH_TP, BUS_BRANCH_SSH, NAN_ENI];
+        let net = Network::from(&CimModel::parse(&docs).unwrap());
+        assert_eq!(net.ext_grid.as_ref().map_or(0, Vec::len), 1);
+    }
+}
@@ -0,0 +1,7 @@
+//! CGMES (CIM) network import.
+
+pub mod convert;
+pub mod model;
+
+pub use convert::*;
+pub use model::*;
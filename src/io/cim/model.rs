@@ -0,0 +1,230 @@
+//! Generic store for CGMES RDF/XML documents.
+//!
+//! Every object of every profile is kept as a class name plus a map of its
+//! properties. Objects are keyed by their mRID, so the attributes an SSH or
+//! TP document adds to an EQ object (`rdf:about`) are merged into the
+//! object declared in EQ (`rdf:ID`). Property names are shortened to the
+//! part after the last `.` (`ACLineSegment.r` becomes `r`), references to
+//! the referenced mRID and enumeration values to their literal
+//! (`...#WindingConnection.D` becomes `D`). Namespaces are ignored, which
+//! makes CGMES 2.4.15 (CIM16) and 3.0 (CIM100) documents look the same.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read};
+use std::path::Path;
+
+/// One CIM object with its merged properties.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CimObject {
+    pub class: String,
+    pub props: HashMap<String, String>,
+}
+
+impl CimObject {
+    pub fn get(&self, prop: &str) -> Option<&str> {
+        self.props.get(prop).map(String::as_str)
+    }
+
+    /// Numeric property; missing or malformed values give `None`.
+    pub fn f64(&self, prop: &str) -> Option<f64> {
+        self.get(prop)?.trim().parse().ok()
+    }
+
+    /// Boolean property (`true`/`false`).
+    pub fn bool(&self, prop: &str) -> Option<bool> {
+        self.get(prop)?.trim().parse().ok()
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.get("name").map(str::to_string)
+    }
+}
+
+/// Objects of a set of CGMES profile documents.
+#[derive(Debug, Clone, Default)]
+pub struct CimModel {
+    objects: HashMap<String, CimObject>,
+    /// mRIDs in order of first appearance.
+    order: Vec<String>,
+}
+
+/// Normalizes an `rdf:ID`, `rdf:about` or `rdf:resource` value to a plain
+/// mRID: `#_abc`, `_abc` and `urn:uuid:abc` all become `abc`.
+fn mrid(s: &str) -> &str {
+    let s = s.trim();
+    let s = s.strip_prefix('#').unwrap_or(s);
+    let s = s.strip_prefix("urn:uuid:").unwrap_or(s);
+    s.strip_prefix('_').unwrap_or(s)
+}
+
+/// Value of an `rdf:resource`: an enumeration literal for schema URIs,
+/// otherwise the referenced mRID.
+fn resource(s: &str) -> &str {
+    match s.split_once('#') {
+        Some((uri, lit)) if !uri.is_empty() => lit.rsplit('.').next().unwrap_or(lit),
+        _ => mrid(s),
+    }
+}
+
+impl CimModel {
+    pub fn get(&self, mrid: &str) -> Option<&CimObject> {
+        self.objects.get(mrid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Objects of one of the `classes`, in document order.
+    pub fn of_class<'a, 'c>(
+        &'a self,
+        classes: &'c [&'c str],
+    ) -> impl Iterator<Item = (&'a str, &'a CimObject)> + use<'a, 'c> {
+        self.order.iter().filter_map(move |id| {
+            let obj = &self.objects[id];
+            classes
+                .contains(&obj.class.as_str())
+                .then_some((id.as_str(), obj))
+        })
+    }
+
+    /// The object referenced by property `prop` of `obj`.
+    pub fn follow(&self, obj: &CimObject, prop: &str) -> Option<&CimObject> {
+        self.get(obj.get(prop)?)
+    }
+
+    /// Adds the objects of one RDF/XML document.
+    pub fn add_document(&mut self, text: &str) -> Result<(), Error> {
+        let doc = roxmltree::Document::parse(text)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+        let root = doc.root_element();
+        if root.tag_name().name() != "RDF" {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "not an RDF/XML document",
+            ));
+        }
+        for node in root.children().filter(|n| n.is_element()) {
+            let class = node.tag_name().name();
+            if class == "FullModel" || class == "DifferenceModel" {
+                continue;
+            }
+            let Some(id) = node
+                .attributes()
+                .find(|a| a.name() == "ID" || a.name() == "about")
+                .map(|a| mrid(a.value()).to_string())
+            else {
+                continue;
+            };
+            let declared = node.attributes().any(|a| a.name() == "ID");
+            let obj = self.objects.entry(id.clone()).or_insert_with(|| {
+                self.order.push(id);
+                CimObject::default()
+            });
+            if declared || obj.class.is_empty() {
+                obj.class = class.to_string();
+            }
+            for prop in node.children().filter(|n| n.is_element()) {
+                let name = prop.tag_name().name();
+                let name = name.rsplit('.').next().unwrap_or(name);
+                let value = match prop.attributes().find(|a| a.name() == "resource") {
+                    Some(r) => resource(r.value()).to_string(),
+                    None => prop.text().unwrap_or_default().trim().to_string(),
+                };
+                obj.props.insert(name.to_string(), value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds a model from the texts of several profile documents.
+    pub fn parse(documents: &[&str]) -> Result<Self, Error> {
+        let mut model = Self::default();
+        for text in documents {
+            model.add_document(text)?;
+        }
+        Ok(model)
+    }
+}
+
+/// Reads CGMES profile files. Zip archives are searched for `.xml` files,
+/// as CGMES exchanges usually ship one archive per profile or per model.
+pub fn load_cgmes<P: AsRef<Path>>(paths: &[P]) -> Result<CimModel, Error> {
+    let mut model = CimModel::default();
+    for path in paths {
+        let path = path.as_ref();
+        let is_zip = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("zip"));
+        if !is_zip {
+            model.add_document(&std::fs::read_to_string(path)?)?;
+            continue;
+        }
+        let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i)?;
+            if file.is_file() && file.name().to_ascii_lowercase().ends_with(".xml") {
+                let mut text = String::with_capacity(file.size() as usize);
+                file.read_to_string(&mut text)?;
+                model.add_document(&text)?;
+            }
+        }
+    }
+    Ok(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EQ: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<rdf:RDF xmlns:cim="http://iec.ch/TC57/2013/CIM-schema-cim16#"
+    xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+    xmlns:md="http://iec.ch/TC57/61970-552/ModelDescription/1#">
+  <md:FullModel rdf:about="urn:uuid:model">
+    <md:Model.profile>http://entsoe.eu/CIM/EquipmentCore/3/1</md:Model.profile>
+  </md:FullModel>
+  <cim:Breaker rdf:ID="_brk">
+    <cim:IdentifiedObject.name>BRK 1</cim:IdentifiedObject.name>
+    <cim:Switch.normalOpen>false</cim:Switch.normalOpen>
+  </cim:Breaker>
+  <cim:PowerTransformerEnd rdf:ID="_end">
+    <cim:PowerTransformerEnd.connectionKind rdf:resource="http://iec.ch/TC57/2013/CIM-schema-cim16#WindingConnection.D"/>
+    <cim:PowerTransformerEnd.r>0.5</cim:PowerTransformerEnd.r>
+    <cim:TransformerEnd.Terminal rdf:resource="#_t1"/>
+  </cim:PowerTransformerEnd>
+</rdf:RDF>"##;
+
+    const SSH: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<rdf:RDF xmlns:cim="http://iec.ch/TC57/CIM100#"
+    xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <cim:Breaker rdf:about="#_brk">
+    <cim:Switch.open>true</cim:Switch.open>
+  </cim:Breaker>
+</rdf:RDF>"##;
+
+    #[test]
+    fn test_merge_profiles() {
+        let model = CimModel::parse(&[EQ, SSH]).unwrap();
+        assert_eq!(model.len(), 2);
+
+        let brk = model.get("brk").unwrap();
+        assert_eq!(brk.class, "Breaker");
+        assert_eq!(brk.name().as_deref(), Some("BRK 1"));
+        assert_eq!(brk.bool("normalOpen"), Some(false));
+        assert_eq!(brk.bool("open"), Some(true));
+
+        let end = model.get("end").unwrap();
+        assert_eq!(end.get("connectionKind"), Some("D"));
+        assert_eq!(end.get("Terminal"), Some("t1"));
+        assert_eq!(end.f64("r"), Some(0.5));
+        assert_eq!(model.of_class(&["Breaker"]).count(), 1);
+
+        assert!(CimModel::parse(&["<a/>"]).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+//! IEEE Common Data Format (CDF) import.
+//!
+//! The bus and branch sections of a CDF file are read into a
+//! [`MatpowerCase`], which then converts into a [`Network`] the same way
+//! MATPOWER cases do. Loss zones, interchange and tie line data are
+//! ignored.
+//!
+//! Identifiers, names and card types are read from their fixed columns;
+//! the numeric fields after them are split at whitespace, which also
+//! accepts the many files whose numbers drifted out of their columns.
+//! Bus types 0 and 1 become PQ buses, 2 PV and 3 the reference bus.
+//! Generators are created at PV and reference buses and wherever the bus
+//! card carries a generation. The `max`/`min` columns of a bus card are
+//! reactive power limits only at PV and reference buses.
+
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use crate::io::matpower::*;
+use crate::io::pandapower::Network;
+
+/// Generator limit used where a CDF card gives none.
+const NO_LIMIT: f64 = 9999.0;
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+/// Trimmed text of the 1-based, inclusive columns `from..=to`.
+fn col(line: &str, from: usize, to: usize) -> &str {
+    let to = to.min(line.len());
+    line.get(from - 1..to).unwrap_or_default().trim()
+}
+
+fn int(line: &str, from: usize, to: usize) -> Result<i64, Error> {
+    let s = col(line, from, to);
+    if s.is_empty() {
+        return Ok(0);
+    }
+    s.parse()
+        .map_err(|_| invalid(format!("invalid integer '{s}' in '{line}'")))
+}
+
+/// Whitespace separated numbers from column `from` on; missing trailing
+/// fields are 0.
+fn numbers<const N: usize>(line: &str, from: usize) -> Result<[f64; N], Error> {
+    let mut out = [0.0; N];
+    let rest = line.get(from - 1..).unwrap_or_default();
+    for (v, s) in out.iter_mut().zip(rest.split_whitespace()) {
+        *v = s
+            .parse()
+            .map_err(|_| invalid(format!("invalid number '{s}' in '{line}'")))?;
+    }
+    Ok(out)
+}
+
+fn parse_bus(line: &str, case: &mut MatpowerCase, names: &mut Vec<String>) -> Result<(), Error> {
+    let bus_i = int(line, 1, 4)?;
+    let cdf_type = int(line, 25, 26)?;
+    let [vm, va, pd, qd, pg, qg, base_kv, v_set, max, min, gs, bs] = numbers(line, 27)?;
+    let bus_type = match cdf_type {
+        2 => 2,
+        3 => 3,
+        _ => 1,
+    };
+    let base = case.base_mva;
+    case.bus.push(MpBus {
+        bus_i,
+        bus_type,
+        pd,
+        qd,
+        gs: gs * base,
+        bs: bs * base,
+        area: int(line, 19, 20)?,
+        vm,
+        va,
+        base_kv,
+        zone: int(line, 21, 23)?,
+        vmax: 1.1,
+        vmin: 0.9,
+        extra: Vec::new(),
+    });
+    names.push(col(line, 6, 17).to_string());
+
+    if bus_type == 1 && pg == 0.0 && qg == 0.0 {
+        return Ok(());
+    }
+    let (qmax, qmin) = if bus_type == 1 || (max == 0.0 && min == 0.0) {
+        (NO_LIMIT, -NO_LIMIT)
+    } else {
+        (max, min)
+    };
+    case.r#gen.push(MpGen {
+        bus: bus_i,
+        pg,
+        qg,
+        qmax,
+        qmin,
+        vg: if v_set > 0.0 { v_set } else { vm },
+        mbase: base,
+        status: 1,
+        pmax: NO_LIMIT,
+        pmin: 0.0,
+        extra: Vec::new(),
+    });
+    Ok(())
+}
+
+fn parse_branch(line: &str) -> Result<MpBranch, Error> {
+    let [
+        r,
+        x,
+        b,
+        rate_a,
+        rate_b,
+        rate_c,
+        _control,
+        _side,
+        ratio,
+        angle,
+    ] = numbers(line, 20)?;
+    Ok(MpBranch {
+        fbus: int(line, 1, 4)?,
+        tbus: int(line, 6, 9)?,
+        r,
+        x,
+        b,
+        rate_a,
+        rate_b,
+        rate_c,
+        ratio,
+        angle,
+        status: 1,
+        angmin: -360.0,
+        angmax: 360.0,
+        extra: Vec::new(),
+    })
+}
+
+/// Parses the text of an IEEE CDF file.
+pub fn parse_ieee_cdf(text: &str) -> Result<MatpowerCase, Error> {
+    let mut lines = text.lines();
+    let title = lines.next().ok_or_else(|| invalid("empty CDF file"))?;
+    let mut case = MatpowerCase {
+        name: col(title, 46, title.len()).to_string(),
+        ..Default::default()
+    };
+    if let Ok(base) = col(title, 32, 37).parse() {
+        case.base_mva = base;
+    }
+
+    let mut names = Vec::new();
+    let mut has_bus = false;
+    while let Some(line) = lines.next() {
+        let header = line.trim_start();
+        let bus = header.starts_with("BUS DATA FOLLOWS");
+        if !bus && !header.starts_with("BRANCH DATA FOLLOWS") {
+            continue;
+        }
+        has_bus |= bus;
+        loop {
+            let line = lines
+                .next()
+                .ok_or_else(|| invalid("unterminated CDF section"))?;
+            if line.trim_start().starts_with("-999") {
+                break;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            if bus {
+                parse_bus(line, &mut case, &mut names)?;
+            } else {
+                case.branch.push(parse_branch(line)?);
+            }
+        }
+    }
+    if !has_bus {
+        return Err(invalid("CDF file has no bus data"));
+    }
+    if names.iter().all(|n| !n.is_empty()) {
+        case.bus_name = Some(names);
+    }
+    Ok(case)
+}
+
+/// Reads an IEEE CDF file.
+pub fn load_ieee_cdf(path: impl AsRef<Path>) -> Result<MatpowerCase, Error> {
+    parse_ieee_cdf(&fs::read_to_string(path)?)
+}
+
+/// Reads an IEEE CDF file as a [`Network`], ready to be inserted as
+/// [`PPNetwork`](crate::basic::ecs::elements::PPNetwork).
+pub fn load_ieee_cdf_network(path: impl AsRef<Path>) -> Result<Network, Error> {
+    Ok(Network::from(&load_ieee_cdf(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn cdf_case() -> MatpowerCase {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        load_ieee_cdf(format!("{dir}/cases/ieee_cdf/ieee14cdf.txt")).unwrap()
+    }
+
+    #[test]
+    fn test_ieee14_cards() {
+        let case = cdf_case();
+        assert_eq!(case.base_mva, 100.0);
+        assert_eq!(case.name, "IEEE 14 Bus Test Case");
+        assert_eq!(case.bus.len(), 14);
+        assert_eq!(case.bus_name.as_ref().unwrap()[5], "Bus 6     LV");
+        assert_eq!(case.bus[8].bs, 19.0);
+        let types = case.bus.iter().map(|b| b.bus_type).collect::<Vec<_>>();
+        assert_eq!(types[..4], [3, 2, 2, 1]);
+        assert_eq!(case.r#gen.len(), 5);
+        assert_eq!((case.r#gen[1].qmax, case.r#gen[1].qmin), (50.0, -40.0));
+        assert_eq!(case.branch.len(), 20);
+        assert_eq!(case.branch[7].ratio, 0.978);
+        assert_eq!(case.branch[0].b, 0.0528);
+
+        assert!(parse_ieee_cdf("title\nBUS DATA FOLLOWS\n   1 A").is_err());
+    }
+
+    #[test]
+    fn test_ieee14_matches_matpower_case() {
+        // MATPOWER's case14 was converted from this file, so the power
+        // flow data agrees exactly.
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let mpc = load_matpower(format!("{dir}/cases/matpower/case14.m")).unwrap();
+        let case = cdf_case();
+        for (a, b) in case.bus.iter().zip(&mpc.bus) {
+            assert_eq!(
+                (a.bus_i, a.bus_type, a.pd, a.qd),
+                (b.bus_i, b.bus_type, b.pd, b.qd)
+            );
+            assert_eq!((a.gs, a.bs), (b.gs, b.bs));
+        }
+        for (a, b) in case.branch.iter().zip(&mpc.branch) {
+            assert_eq!(
+                (a.fbus, a.tbus, a.r, a.x, a.b),
+                (b.fbus, b.tbus, b.r, b.x, b.b)
+            );
+            assert_eq!((a.ratio, a.angle), (b.ratio, b.angle));
+        }
+        for (a, b) in case.r#gen.iter().zip(&mpc.r#gen) {
+            assert_eq!((a.bus, a.pg, a.vg), (b.bus, b.pg, b.vg));
+        }
+        let net = Network::from(&case);
+        assert_eq!(net.trafo.as_ref().unwrap().len(), 3);
+        assert_eq!(net.r#gen.as_ref().unwrap().len(), 4);
+    }
+}
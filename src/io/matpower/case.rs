@@ -0,0 +1,666 @@
+//! Parser and writer for MATPOWER `.m` case files (format version 2).
+//!
+//! Only the data assignments of a case are read: `mpc.version`,
+//! `mpc.baseMVA`, the `bus`, `gen`, `branch` and `gencost` matrices and the
+//! optional `bus_name` cell array. Other statements are ignored. Columns
+//! beyond the standard ones (OPF results, ramp limits, ...) are kept in
+//! `extra` so a case is written back unchanged.
+
+use std::fs;
+use std::io::{Error, ErrorKind, Write};
+use std::path::Path;
+
+/// One row of `mpc.bus`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MpBus {
+    pub bus_i: i64,
+    /// 1 = PQ, 2 = PV, 3 = reference, 4 = isolated.
+    pub bus_type: i32,
+    pub pd: f64,
+    pub qd: f64,
+    pub gs: f64,
+    pub bs: f64,
+    pub area: i64,
+    pub vm: f64,
+    pub va: f64,
+    pub base_kv: f64,
+    pub zone: i64,
+    pub vmax: f64,
+    pub vmin: f64,
+    pub extra: Vec<f64>,
+}
+
+/// One row of `mpc.gen`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MpGen {
+    pub bus: i64,
+    pub pg: f64,
+    pub qg: f64,
+    pub qmax: f64,
+    pub qmin: f64,
+    pub vg: f64,
+    pub mbase: f64,
+    pub status: i32,
+    pub pmax: f64,
+    pub pmin: f64,
+    pub extra: Vec<f64>,
+}
+
+/// One row of `mpc.branch`. A `ratio` of 0 means a line (no transformer);
+/// otherwise the off-nominal tap sits at the from bus.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MpBranch {
+    pub fbus: i64,
+    pub tbus: i64,
+    pub r: f64,
+    pub x: f64,
+    pub b: f64,
+    pub rate_a: f64,
+    pub rate_b: f64,
+    pub rate_c: f64,
+    pub ratio: f64,
+    pub angle: f64,
+    pub status: i32,
+    pub angmin: f64,
+    pub angmax: f64,
+    pub extra: Vec<f64>,
+}
+
+/// One row of `mpc.gencost`: model 1 is piecewise linear (`cost` holds
+/// `x1, y1, ...`), model 2 is polynomial (`cost` holds the coefficients,
+/// highest order first).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MpGenCost {
+    pub model: i32,
+    pub startup: f64,
+    pub shutdown: f64,
+    pub ncost: usize,
+    pub cost: Vec<f64>,
+}
+
+/// A MATPOWER case. All quantities are in MATPOWER units (MW, MVAr, p.u.
+/// on `base_mva`, kV, degrees).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatpowerCase {
+    /// Function name of the case file.
+    pub name: String,
+    pub version: String,
+    pub base_mva: f64,
+    pub bus: Vec<MpBus>,
+    pub r#gen: Vec<MpGen>,
+    pub branch: Vec<MpBranch>,
+    pub gencost: Vec<MpGenCost>,
+    pub bus_name: Option<Vec<String>>,
+}
+
+impl Default for MatpowerCase {
+    fn default() -> Self {
+        Self {
+            name: "case".into(),
+            version: "2".into(),
+            base_mva: 100.0,
+            bus: Vec::new(),
+            r#gen: Vec::new(),
+            branch: Vec::new(),
+            gencost: Vec::new(),
+            bus_name: None,
+        }
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+/// Removes `%` comments and `...` continuations, leaving quoted strings
+/// intact.
+fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        let mut quoted = false;
+        let mut end = line.len();
+        for (i, c) in line.char_indices() {
+            match c {
+                '\'' => quoted = !quoted,
+                '%' if !quoted => {
+                    end = i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let line = &line[..end];
+        match line.trim_end().strip_suffix("...") {
+            Some(rest) => {
+                out.push_str(rest);
+                out.push(' ');
+            }
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Right-hand side of an `mpc.<field> = ...` assignment.
+enum Rhs<'a> {
+    Matrix(&'a str),
+    Cell(&'a str),
+    Scalar(&'a str),
+}
+
+/// Splits the statements of a case into `(field, value)` pairs.
+fn assignments(text: &str) -> Result<Vec<(&str, Rhs<'_>)>, Error> {
+    let mut out = Vec::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find("mpc.") {
+        let after = &rest[pos + 4..];
+        let name_len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        let name = &after[..name_len];
+        let after = after[name_len..].trim_start();
+        let Some(after) = after.strip_prefix('=') else {
+            rest = &rest[pos + 4..];
+            continue;
+        };
+        let after = after.trim_start();
+        let (rhs, tail) = match after.chars().next() {
+            Some(open @ ('[' | '{')) => {
+                let close = if open == '[' { ']' } else { '}' };
+                let end = after
+                    .find(close)
+                    .ok_or_else(|| invalid(format!("unterminated mpc.{name}")))?;
+                let body = &after[1..end];
+                let rhs = if open == '[' {
+                    Rhs::Matrix(body)
+                } else {
+                    Rhs::Cell(body)
+                };
+                (rhs, &after[end + 1..])
+            }
+            _ => {
+                let end = after.find([';', '\n']).unwrap_or(after.len());
+                (Rhs::Scalar(after[..end].trim()), &after[end..])
+            }
+        };
+        out.push((name, rhs));
+        rest = tail;
+    }
+    Ok(out)
+}
+
+fn parse_number(s: &str) -> Result<f64, Error> {
+    s.parse::<f64>()
+        .map_err(|_| invalid(format!("invalid number '{s}'")))
+}
+
+/// Rows of a matrix literal; rows end at `;` or a line break, entries are
+/// separated by whitespace or commas.
+fn parse_matrix(body: &str) -> Result<Vec<Vec<f64>>, Error> {
+    body.split([';', '\n'])
+        .map(|row| {
+            row.split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|s| !s.is_empty())
+                .map(parse_number)
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .filter(|row| !matches!(row, Ok(r) if r.is_empty()))
+        .collect()
+}
+
+/// Quoted strings of a cell array literal.
+fn parse_cell(body: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            continue;
+        }
+        let mut s = String::new();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                    s.push('\'');
+                } else {
+                    break;
+                }
+            } else {
+                s.push(c);
+            }
+        }
+        out.push(s);
+    }
+    out
+}
+
+fn unquote(s: &str) -> &str {
+    s.trim_matches(|c| c == '\'' || c == '"')
+}
+
+/// Reads `N` standard columns of a row and keeps the rest.
+fn columns<const N: usize>(
+    table: &str,
+    row: &[f64],
+    required: usize,
+    defaults: [f64; N],
+) -> Result<([f64; N], Vec<f64>), Error> {
+    if row.len() < required {
+        return Err(invalid(format!(
+            "mpc.{table} row has {} columns, expected at least {required}",
+            row.len()
+        )));
+    }
+    let mut cols = defaults;
+    let n = row.len().min(N);
+    cols[..n].copy_from_slice(&row[..n]);
+    Ok((cols, row.get(N..).unwrap_or_default().to_vec()))
+}
+
+impl MpBus {
+    fn from_row(row: &[f64]) -> Result<Self, Error> {
+        let (
+            [
+                bus_i,
+                ty,
+                pd,
+                qd,
+                gs,
+                bs,
+                area,
+                vm,
+                va,
+                base_kv,
+                zone,
+                vmax,
+                vmin,
+            ],
+            extra,
+        ) = columns("bus", row, 13, [0.0; 13])?;
+        Ok(Self {
+            bus_i: bus_i as i64,
+            bus_type: ty as i32,
+            pd,
+            qd,
+            gs,
+            bs,
+            area: area as i64,
+            vm,
+            va,
+            base_kv,
+            zone: zone as i64,
+            vmax,
+            vmin,
+            extra,
+        })
+    }
+
+    fn row(&self) -> Vec<f64> {
+        let mut row = vec![
+            self.bus_i as f64,
+            self.bus_type as f64,
+            self.pd,
+            self.qd,
+            self.gs,
+            self.bs,
+            self.area as f64,
+            self.vm,
+            self.va,
+            self.base_kv,
+            self.zone as f64,
+            self.vmax,
+            self.vmin,
+        ];
+        row.extend(&self.extra);
+        row
+    }
+}
+
+impl MpGen {
+    fn from_row(row: &[f64]) -> Result<Self, Error> {
+        let ([bus, pg, qg, qmax, qmin, vg, mbase, status, pmax, pmin], extra) =
+            columns("gen", row, 8, [0.0; 10])?;
+        Ok(Self {
+            bus: bus as i64,
+            pg,
+            qg,
+            qmax,
+            qmin,
+            vg,
+            mbase,
+            status: status as i32,
+            pmax,
+            pmin,
+            extra,
+        })
+    }
+
+    fn row(&self) -> Vec<f64> {
+        let mut row = vec![
+            self.bus as f64,
+            self.pg,
+            self.qg,
+            self.qmax,
+            self.qmin,
+            self.vg,
+            self.mbase,
+            self.status as f64,
+            self.pmax,
+            self.pmin,
+        ];
+        row.extend(&self.extra);
+        row
+    }
+}
+
+impl MpBranch {
+    fn from_row(row: &[f64]) -> Result<Self, Error> {
+        let mut defaults = [0.0; 13];
+        defaults[10] = 1.0;
+        defaults[11] = -360.0;
+        defaults[12] = 360.0;
+        let (
+            [
+                fbus,
+                tbus,
+                r,
+                x,
+                b,
+                rate_a,
+                rate_b,
+                rate_c,
+                ratio,
+                angle,
+                status,
+                angmin,
+                angmax,
+            ],
+            extra,
+        ) = columns("branch", row, 10, defaults)?;
+        Ok(Self {
+            fbus: fbus as i64,
+            tbus: tbus as i64,
+            r,
+            x,
+            b,
+            rate_a,
+            rate_b,
+            rate_c,
+            ratio,
+            angle,
+            status: status as i32,
+            angmin,
+            angmax,
+            extra,
+        })
+    }
+
+    fn row(&self) -> Vec<f64> {
+        let mut row = vec![
+            self.fbus as f64,
+            self.tbus as f64,
+            self.r,
+            self.x,
+            self.b,
+            self.rate_a,
+            self.rate_b,
+            self.rate_c,
+            self.ratio,
+            self.angle,
+            self.status as f64,
+            self.angmin,
+            self.angmax,
+        ];
+        row.extend(&self.extra);
+        row
+    }
+}
+
+impl MpGenCost {
+    fn from_row(row: &[f64]) -> Result<Self, Error> {
+        let ([model, startup, shutdown, ncost], cost) = columns("gencost", row, 4, [0.0; 4])?;
+        Ok(Self {
+            model: model as i32,
+            startup,
+            shutdown,
+            ncost: ncost as usize,
+            cost,
+        })
+    }
+
+    fn row(&self) -> Vec<f64> {
+        let mut row = vec![
+            self.model as f64,
+            self.startup,
+            self.shutdown,
+            self.ncost as f64,
+        ];
+        row.extend(&self.cost);
+        row
+    }
+}
+
+fn rows<T>(
+    table: &str,
+    rhs: &Rhs<'_>,
+    from_row: fn(&[f64]) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    let Rhs::Matrix(body) = rhs else {
+        return Err(invalid(format!("mpc.{table} is not a matrix")));
+    };
+    parse_matrix(body)?.iter().map(|r| from_row(r)).collect()
+}
+
+/// Parses the text of a MATPOWER case file.
+pub fn parse_matpower(text: &str) -> Result<MatpowerCase, Error> {
+    let text = strip_comments(text);
+    let mut case = MatpowerCase::default();
+    let header = text
+        .lines()
+        .find(|l| l.trim_start().starts_with("function"));
+    if let Some((_, name)) = header.and_then(|h| h.split_once('=')) {
+        case.name = name.trim().trim_end_matches(';').to_string();
+    }
+    let mut has_bus = false;
+    for (field, rhs) in assignments(&text)? {
+        match field {
+            "version" => {
+                if let Rhs::Scalar(s) = rhs {
+                    case.version = unquote(s).to_string();
+                }
+            }
+            "baseMVA" => {
+                let Rhs::Scalar(s) = rhs else {
+                    return Err(invalid("mpc.baseMVA is not a scalar"));
+                };
+                case.base_mva = parse_number(s)?;
+            }
+            "bus" => {
+                case.bus = rows(field, &rhs, MpBus::from_row)?;
+                has_bus = true;
+            }
+            "gen" => case.r#gen = rows(field, &rhs, MpGen::from_row)?,
+            "branch" => case.branch = rows(field, &rhs, MpBranch::from_row)?,
+            "gencost" => case.gencost = rows(field, &rhs, MpGenCost::from_row)?,
+            "bus_name" => {
+                if let Rhs::Cell(body) = rhs {
+                    case.bus_name = Some(parse_cell(body));
+                }
+            }
+            _ => {}
+        }
+    }
+    if !has_bus {
+        return Err(invalid("case has no mpc.bus"));
+    }
+    if let Some(names) = &case.bus_name
+        && names.len() != case.bus.len()
+    {
+        return Err(invalid(format!(
+            "mpc.bus_name has {} entries for {} buses",
+            names.len(),
+            case.bus.len()
+        )));
+    }
+    Ok(case)
+}
+
+/// Reads a MATPOWER case file.
+pub fn load_matpower(path: impl AsRef<Path>) -> Result<MatpowerCase, Error> {
+    parse_matpower(&fs::read_to_string(path)?)
+}
+
+/// Formats a number the way MATPOWER reads it back exactly.
+fn number(v: f64) -> String {
+    if v.is_nan() {
+        "NaN".into()
+    } else if v.is_infinite() {
+        if v > 0.0 { "Inf".into() } else { "-Inf".into() }
+    } else {
+        v.to_string()
+    }
+}
+
+fn write_matrix<W: Write>(
+    w: &mut W,
+    field: &str,
+    header: &str,
+    rows: impl Iterator<Item = Vec<f64>>,
+) -> std::io::Result<()> {
+    writeln!(w, "%\t{header}")?;
+    writeln!(w, "mpc.{field} = [")?;
+    for row in rows {
+        let row = row.into_iter().map(number).collect::<Vec<_>>();
+        writeln!(w, "\t{};", row.join("\t"))?;
+    }
+    writeln!(w, "];")
+}
+
+/// Writes a case in MATPOWER format version 2.
+pub fn write_matpower<W: Write>(case: &MatpowerCase, mut w: W) -> std::io::Result<()> {
+    let w = &mut w;
+    writeln!(w, "function mpc = {}", case.name)?;
+    writeln!(w, "%{}", case.name.to_uppercase())?;
+    writeln!(w)?;
+    writeln!(w, "%% MATPOWER Case Format : Version {}", case.version)?;
+    writeln!(w, "mpc.version = '{}';", case.version)?;
+    writeln!(w)?;
+    writeln!(w, "%%-----  Power Flow Data  -----%%")?;
+    writeln!(w, "%% system MVA base")?;
+    writeln!(w, "mpc.baseMVA = {};", number(case.base_mva))?;
+    writeln!(w)?;
+    writeln!(w, "%% bus data")?;
+    write_matrix(
+        w,
+        "bus",
+        "bus_i\ttype\tPd\tQd\tGs\tBs\tarea\tVm\tVa\tbaseKV\tzone\tVmax\tVmin",
+        case.bus.iter().map(MpBus::row),
+    )?;
+    writeln!(w)?;
+    writeln!(w, "%% generator data")?;
+    write_matrix(
+        w,
+        "gen",
+        "bus\tPg\tQg\tQmax\tQmin\tVg\tmBase\tstatus\tPmax\tPmin",
+        case.r#gen.iter().map(MpGen::row),
+    )?;
+    writeln!(w)?;
+    writeln!(w, "%% branch data")?;
+    write_matrix(
+        w,
+        "branch",
+        "fbus\ttbus\tr\tx\tb\trateA\trateB\trateC\tratio\tangle\tstatus\tangmin\tangmax",
+        case.branch.iter().map(MpBranch::row),
+    )?;
+    if !case.gencost.is_empty() {
+        writeln!(w)?;
+        writeln!(w, "%%-----  OPF Data  -----%%")?;
+        writeln!(w, "%% generator cost data")?;
+        write_matrix(
+            w,
+            "gencost",
+            "model\tstartup\tshutdown\tn\tc(n-1)\t...\tc0",
+            case.gencost.iter().map(MpGenCost::row),
+        )?;
+    }
+    if let Some(names) = &case.bus_name {
+        writeln!(w)?;
+        writeln!(w, "%% bus names")?;
+        writeln!(w, "mpc.bus_name = {{")?;
+        for name in names {
+            writeln!(w, "\t'{}';", name.replace('\'', "''"))?;
+        }
+        writeln!(w, "}};")?;
+    }
+    Ok(())
+}
+
+/// Writes a case to a `.m` file.
+pub fn save_matpower(case: &MatpowerCase, path: impl AsRef<Path>) -> std::io::Result<()> {
+    let mut out = Vec::new();
+    write_matpower(case, &mut out)?;
+    fs::write(path, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_parse_write_round_trip() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let case = load_matpower(format!("{}/cases/matpower/case9.m", dir)).unwrap();
+        assert_eq!(case.name, "case9");
+        assert_eq!(case.base_mva, 100.0);
+        assert_eq!(
+            (case.bus.len(), case.r#gen.len(), case.branch.len()),
+            (9, 3, 9)
+        );
+        assert_eq!(case.r#gen[1].pg, 163.0);
+        assert_eq!(case.r#gen[1].extra.len(), 11);
+        assert_eq!(case.branch[2].b, 0.358);
+        assert_eq!(case.gencost[2].cost, vec![0.1225, 1.0, 335.0]);
+
+        let mut text = Vec::new();
+        write_matpower(&case, &mut text).unwrap();
+        let back = parse_matpower(&String::from_utf8(text).unwrap()).unwrap();
+        assert_eq!(case, back);
+    }
+
+    #[test]
+    fn test_parse_syntax() {
+        let text = "function mpc = tiny\n\
+            mpc.version = '2';\n\
+            mpc.baseMVA = 10; % comment\n\
+            mpc.bus = [1, 3, 0, 0, 0, 0, 1, 1, 0, 20, 1, 1.1, 0.9; ...\n\
+            \t2 1 5 1 0 0 1 1 0 20 1 1.1 0.9];\n\
+            mpc.gen = [1 0 0 Inf -Inf 1 10 1 100 0];\n\
+            mpc.branch = [1 2 0.01 0.1 0 0 0 0 0 0 1];\n\
+            mpc.bus_name = { 'Bus ''A'''; 'B % 2' };\n";
+        let case = parse_matpower(text).unwrap();
+        assert_eq!(case.name, "tiny");
+        assert_eq!(case.base_mva, 10.0);
+        assert_eq!(case.bus.len(), 2);
+        assert_eq!(case.bus[1].pd, 5.0);
+        assert_eq!(case.r#gen[0].qmax, f64::INFINITY);
+        assert_eq!(case.branch[0].angmax, 360.0);
+        assert_eq!(
+            case.bus_name,
+            Some(vec!["Bus 'A'".to_string(), "B % 2".to_string()])
+        );
+
+        let mut text = Vec::new();
+        write_matpower(&case, &mut text).unwrap();
+        let mut back = parse_matpower(&String::from_utf8(text).unwrap()).unwrap();
+        // Optional branch columns are written out in full.
+        back.branch[0].extra.clear();
+        assert_eq!(case, back);
+
+        assert!(parse_matpower("mpc.baseMVA = 100;").is_err());
+        assert!(parse_matpower("mpc.bus = [1 2 3];").is_err());
+    }
+}
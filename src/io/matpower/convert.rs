@@ -0,0 +1,525 @@
+//! Conversion between MATPOWER cases and pandapower [`Network`]s.
+//!
+//! The mapping follows pandapower's `from_ppc`: branches with a tap ratio,
+//! a phase shift or different terminal voltages become transformers (with
+//! the tap on the from bus, which becomes `hv_bus`), the first generator at
+//! a reference bus becomes the external grid, and generators at PQ buses
+//! become static generators. Buses keep their MATPOWER bus number as index
+//! and, unless the case names them, as name.
+//!
+//! MATPOWER cannot express line conductance, transformer iron losses,
+//! ZIP loads or switches, so those are dropped on export. Generator costs
+//! have no counterpart in a [`Network`] and are not imported.
+
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+use std::io::Error;
+use std::path::Path;
+
+use super::case::*;
+use crate::io::pandapower::*;
+
+/// Placeholder limit for unbounded generator ratings.
+const NO_LIMIT: f64 = 9999.0;
+
+/// A generator limit, or `default` for pandapower's missing value (NaN).
+fn limit(v: f64, default: f64) -> f64 {
+    if v.is_nan() { default } else { v }
+}
+
+fn non_empty<T>(rows: Vec<T>) -> Option<Vec<T>> {
+    (!rows.is_empty()).then_some(rows)
+}
+
+impl From<&MatpowerCase> for Network {
+    fn from(case: &MatpowerCase) -> Self {
+        let mut net = Network {
+            sn_mva: case.base_mva,
+            ..Default::default()
+        };
+        let base = case.base_mva;
+        let pos = case
+            .bus
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (b.bus_i, i))
+            .collect::<HashMap<_, _>>();
+        // MATPOWER allows baseKV = 0 (per-unit only cases); use 1 kV so the
+        // ohmic parameters stay finite.
+        let vn_kv = |b: &MpBus| if b.base_kv > 0.0 { b.base_kv } else { 1.0 };
+
+        net.bus = case
+            .bus
+            .iter()
+            .enumerate()
+            .map(|(i, b)| Bus {
+                index: b.bus_i,
+                in_service: b.bus_type != 4,
+                max_vm_pu: Some(b.vmax),
+                min_vm_pu: Some(b.vmin),
+                name: Some(match &case.bus_name {
+                    Some(names) => names[i].clone(),
+                    None => b.bus_i.to_string(),
+                }),
+                r#type: Some("b".into()),
+                vn_kv: vn_kv(b),
+                zone: Some(b.zone),
+            })
+            .collect();
+
+        let mut load = Vec::new();
+        let mut shunt = Vec::new();
+        for b in &case.bus {
+            if b.pd != 0.0 || b.qd != 0.0 {
+                load.push(Load {
+                    bus: b.bus_i,
+                    in_service: true,
+                    p_mw: b.pd,
+                    q_mvar: b.qd,
+                    scaling: 1.0,
+                    ..Default::default()
+                });
+            }
+            if b.gs != 0.0 || b.bs != 0.0 {
+                shunt.push(Shunt {
+                    index: None,
+                    bus: b.bus_i,
+                    p_mw: b.gs,
+                    q_mvar: -b.bs,
+                    vn_kv: vn_kv(b),
+                    step: 1,
+                    max_step: 1,
+                    in_service: true,
+                    name: None,
+                });
+            }
+        }
+
+        let mut r#gen = Vec::new();
+        let mut ext_grid = Vec::new();
+        let mut sgen = Vec::new();
+        let mut slack_buses = HashSet::new();
+        for g in &case.r#gen {
+            let bus = g.bus;
+            let Some(b) = pos.get(&bus).map(|&i| &case.bus[i]) else {
+                continue;
+            };
+            let in_service = g.status > 0;
+            match b.bus_type {
+                3 if slack_buses.insert(bus) => ext_grid.push(ExtGrid {
+                    index: None,
+                    bus,
+                    in_service,
+                    va_degree: b.va,
+                    vm_pu: g.vg,
+                    max_p_mw: Some(g.pmax),
+                    min_p_mw: Some(g.pmin),
+                    max_q_mvar: Some(g.qmax),
+                    min_q_mvar: Some(g.qmin),
+                    slack_weight: 1.0,
+                    name: None,
+                }),
+                1 => sgen.push(SGen {
+                    bus,
+                    p_mw: g.pg,
+                    q_mvar: g.qg,
+                    sn_mva: Some(g.mbase),
+                    scaling: 1.0,
+                    in_service,
+                    controllable: Some(true),
+                    ..Default::default()
+                }),
+                _ => r#gen.push(Gen {
+                    index: None,
+                    bus,
+                    controllable: Some(true),
+                    in_service,
+                    name: None,
+                    p_mw: g.pg,
+                    scaling: 1.0,
+                    sn_mva: Some(g.mbase),
+                    type_: None,
+                    vm_pu: g.vg,
+                    slack: false,
+                    max_p_mw: g.pmax,
+                    min_p_mw: g.pmin,
+                    max_q_mvar: g.qmax,
+                    min_q_mvar: g.qmin,
+                    slack_weight: 0.0,
+                }),
+            }
+        }
+
+        let mut line = Vec::new();
+        let mut trafo = Vec::new();
+        for br in &case.branch {
+            let (from, to) = (br.fbus, br.tbus);
+            let (Some(&f), Some(&t)) = (pos.get(&from), pos.get(&to)) else {
+                continue;
+            };
+            let (vn_from, vn_to) = (net.bus[f].vn_kv, net.bus[t].vn_kv);
+            let in_service = br.status > 0;
+            let is_trafo = br.ratio != 0.0 || br.angle != 0.0 || vn_from != vn_to;
+            if !is_trafo {
+                let z_base = vn_from * vn_from / base;
+                line.push(Line {
+                    from_bus: from,
+                    to_bus: to,
+                    length_km: 1.0,
+                    r_ohm_per_km: br.r * z_base,
+                    x_ohm_per_km: br.x * z_base,
+                    c_nf_per_km: br.b / z_base / (2.0 * PI * net.f_hz) * 1e9,
+                    g_us_per_km: 0.0,
+                    max_i_ka: Some(br.rate_a / (3f64.sqrt() * vn_from)),
+                    df: 1.0,
+                    parallel: 1,
+                    in_service,
+                    type_: Some("ol".into()),
+                    ..Default::default()
+                });
+                continue;
+            }
+            let sn_mva = if br.rate_a > 0.0 { br.rate_a } else { base };
+            let ratio = if br.ratio == 0.0 { 1.0 } else { br.ratio };
+            let z = br.r.hypot(br.x);
+            trafo.push(Transformer {
+                hv_bus: from as i32,
+                lv_bus: to as i32,
+                sn_mva,
+                vn_hv_kv: vn_from,
+                vn_lv_kv: vn_to,
+                vk_percent: z * 100.0 * sn_mva / base,
+                vkr_percent: br.r * 100.0 * sn_mva / base,
+                pfe_kw: 0.0,
+                i0_percent: -br.b * 100.0 * base / sn_mva,
+                shift_degree: br.angle,
+                tap_side: Some("hv".into()),
+                tap_neutral: Some(0.0),
+                tap_pos: Some(1.0),
+                tap_step_percent: Some((ratio - 1.0) * 100.0),
+                tap_phase_shifter: false,
+                df: 1.0,
+                parallel: 1,
+                in_service,
+                ..Default::default()
+            });
+        }
+
+        net.load = non_empty(load);
+        net.shunt = non_empty(shunt);
+        net.r#gen = non_empty(r#gen);
+        net.ext_grid = non_empty(ext_grid);
+        net.sgen = non_empty(sgen);
+        net.line = non_empty(line);
+        net.trafo = non_empty(trafo);
+        net
+    }
+}
+
+/// MATPOWER bus numbers for the buses of `net`: their indices when all are
+/// positive, else their names when all of them are distinct positive
+/// integers, otherwise `1..=n`.
+fn bus_numbers(net: &Network) -> Vec<i64> {
+    if net.bus.iter().all(|b| b.index > 0) {
+        return net.bus.iter().map(|b| b.index).collect();
+    }
+    let names = net
+        .bus
+        .iter()
+        .map(|b| b.name.as_deref()?.parse::<i64>().ok().filter(|n| *n > 0))
+        .collect::<Option<Vec<_>>>();
+    match names {
+        Some(ids) if ids.iter().collect::<HashSet<_>>().len() == ids.len() => ids,
+        _ => (1..=net.bus.len() as i64).collect(),
+    }
+}
+
+impl From<&Network> for MatpowerCase {
+    fn from(net: &Network) -> Self {
+        let base = net.sn_mva;
+        let ids = bus_numbers(net);
+        let pos = net
+            .bus
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (b.index, i))
+            .collect::<HashMap<_, _>>();
+        let mut bus = net
+            .bus
+            .iter()
+            .zip(&ids)
+            .map(|(b, id)| MpBus {
+                bus_i: *id,
+                bus_type: if b.in_service { 1 } else { 4 },
+                area: 1,
+                vm: 1.0,
+                base_kv: b.vn_kv,
+                zone: b.zone.unwrap_or(1),
+                vmax: b.max_vm_pu.unwrap_or(1.1),
+                vmin: b.min_vm_pu.unwrap_or(0.9),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let at = |index: i64| pos.get(&index).copied();
+
+        for l in net.load.iter().flatten().filter(|l| l.in_service) {
+            if let Some(b) = at(l.bus).map(|i| &mut bus[i]) {
+                b.pd += l.p_mw * l.scaling;
+                b.qd += l.q_mvar * l.scaling;
+            }
+        }
+        for s in net.sgen.iter().flatten().filter(|s| s.in_service) {
+            if let Some(b) = at(s.bus).map(|i| &mut bus[i]) {
+                b.pd -= s.p_mw * s.scaling;
+                b.qd -= s.q_mvar * s.scaling;
+            }
+        }
+        for s in net.shunt.iter().flatten().filter(|s| s.in_service) {
+            if let Some(b) = at(s.bus).map(|i| &mut bus[i]) {
+                b.gs += s.p_mw * s.step as f64;
+                b.bs -= s.q_mvar * s.step as f64;
+            }
+        }
+
+        let mut r#gen = Vec::new();
+        for e in net.ext_grid.iter().flatten() {
+            if let Some(b) = at(e.bus).map(|i| &mut bus[i]) {
+                if e.in_service && b.bus_type != 4 {
+                    b.bus_type = 3;
+                    b.vm = e.vm_pu;
+                    b.va = e.va_degree;
+                }
+                r#gen.push(MpGen {
+                    bus: b.bus_i,
+                    qmax: e.max_q_mvar.unwrap_or(NO_LIMIT),
+                    qmin: e.min_q_mvar.unwrap_or(-NO_LIMIT),
+                    vg: e.vm_pu,
+                    mbase: base,
+                    status: e.in_service as i32,
+                    pmax: e.max_p_mw.unwrap_or(NO_LIMIT),
+                    pmin: e.min_p_mw.unwrap_or(-NO_LIMIT),
+                    extra: vec![0.0; 11],
+                    ..Default::default()
+                });
+            }
+        }
+        for g in net.r#gen.iter().flatten() {
+            if let Some(b) = at(g.bus).map(|i| &mut bus[i]) {
+                if g.in_service && b.bus_type != 4 {
+                    b.bus_type = if g.slack { 3 } else { b.bus_type.max(2) };
+                    b.vm = g.vm_pu;
+                }
+                r#gen.push(MpGen {
+                    bus: b.bus_i,
+                    pg: g.p_mw * g.scaling,
+                    qg: 0.0,
+                    qmax: limit(g.max_q_mvar, NO_LIMIT),
+                    qmin: limit(g.min_q_mvar, -NO_LIMIT),
+                    vg: g.vm_pu,
+                    mbase: g.sn_mva.unwrap_or(base),
+                    status: g.in_service as i32,
+                    pmax: limit(g.max_p_mw, NO_LIMIT),
+                    pmin: limit(g.min_p_mw, -NO_LIMIT),
+                    extra: vec![0.0; 11],
+                });
+            }
+        }
+
+        let mut branch = Vec::new();
+        let number = |bus: i64| pos.get(&bus).map(|&i| ids[i]);
+        for l in net.line.iter().flatten() {
+            let (Some(fbus), Some(tbus)) = (number(l.from_bus), number(l.to_bus)) else {
+                continue;
+            };
+            let vn = net.bus[pos[&l.from_bus]].vn_kv;
+            let z_base = vn * vn / base;
+            let parallel = l.parallel as f64;
+            let rate = l.max_i_ka.unwrap_or(0.0) * l.df * parallel * vn * 3f64.sqrt();
+            branch.push(MpBranch {
+                fbus,
+                tbus,
+                r: l.r_ohm_per_km * l.length_km / parallel / z_base,
+                x: l.x_ohm_per_km * l.length_km / parallel / z_base,
+                b: 2.0 * PI * net.f_hz * l.c_nf_per_km * 1e-9 * l.length_km * parallel * z_base,
+                rate_a: rate,
+                rate_b: rate,
+                rate_c: rate,
+                status: l.in_service as i32,
+                angmin: -360.0,
+                angmax: 360.0,
+                ..Default::default()
+            });
+        }
+        for t in net.trafo.iter().flatten() {
+            let (Some(fbus), Some(tbus)) = (number(t.hv_bus as i64), number(t.lv_bus as i64))
+            else {
+                continue;
+            };
+            // Same per-unit model as the transformer admittance setup: the
+            // tap acts on the hv side and the impedance is on `sn_mva`.
+            let parallel = t.parallel as f64;
+            let scale = base / t.sn_mva / parallel;
+            let z = t.vk_percent * 0.01 * scale;
+            let r = t.vkr_percent * 0.01 * scale;
+            let steps = t.tap_pos.unwrap_or(0.0) - t.tap_neutral.unwrap_or(0.0);
+            let rate = t.sn_mva * t.df * parallel;
+            branch.push(MpBranch {
+                fbus,
+                tbus,
+                r,
+                x: (z * z - r * r).sqrt(),
+                b: -t.i0_percent * 0.01 / scale,
+                rate_a: rate,
+                rate_b: rate,
+                rate_c: rate,
+                ratio: 1.0 + steps * 0.01 * t.tap_step_percent.unwrap_or(0.0),
+                angle: t.shift_degree,
+                status: t.in_service as i32,
+                angmin: -360.0,
+                angmax: 360.0,
+                extra: Vec::new(),
+            });
+        }
+
+        let bus_name = net
+            .bus
+            .iter()
+            .zip(&ids)
+            .any(|(b, id)| b.name.as_deref() != Some(&*id.to_string()))
+            .then(|| {
+                net.bus
+                    .iter()
+                    .map(|b| b.name.clone().unwrap_or_default())
+                    .collect()
+            });
+
+        MatpowerCase {
+            base_mva: base,
+            bus,
+            r#gen,
+            branch,
+            bus_name,
+            ..Default::default()
+        }
+    }
+}
+
+/// Reads a MATPOWER case file as a [`Network`], ready to be inserted as
+/// [`PPNetwork`](crate::basic::ecs::elements::PPNetwork).
+pub fn load_matpower_network(path: impl AsRef<Path>) -> Result<Network, Error> {
+    Ok(Network::from(&load_matpower(path)?))
+}
+
+/// Writes a [`Network`] as a MATPOWER case file.
+pub fn save_matpower_network(net: &Network, path: impl AsRef<Path>) -> Result<(), Error> {
+    save_matpower(&MatpowerCase::from(net), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::elements::PPNetwork;
+    use crate::basic::ecs::network::{DataOps, PowerFlow, PowerGrid};
+    use crate::basic::ecs::post_processing::PostProcessing;
+    use crate::io::pandapower::ecs_net_conv::ToPandapowerNet;
+    use std::env;
+
+    /// Solved bus voltages `(vm_pu, va_degree)` in bus order.
+    fn solve(net: Network) -> Vec<(f64, f64)> {
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.run_pf();
+        grid.post_process();
+        let res = grid.pandapower_results();
+        assert!(res.converged);
+        res.bus
+            .iter()
+            .map(|(_, b)| (b.vm_pu, b.va_degree))
+            .collect()
+    }
+
+    fn case(name: &str) -> MatpowerCase {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        load_matpower(format!("{}/cases/matpower/{}.m", dir, name)).unwrap()
+    }
+
+    #[test]
+    fn test_case9_matches_matpower() {
+        let net = Network::from(&case("case9"));
+        assert_eq!(net.line.as_ref().unwrap().len(), 9);
+        assert_eq!(net.ext_grid.as_ref().unwrap().len(), 1);
+        assert_eq!(net.r#gen.as_ref().unwrap().len(), 2);
+        // runpf(case9) in MATPOWER.
+        let expected = [
+            (1.0, 0.0),
+            (1.0, 9.669),
+            (1.0, 4.771),
+            (0.987, -2.407),
+            (0.975, -4.017),
+            (1.003, 1.926),
+            (0.986, 0.622),
+            (0.996, 3.799),
+            (0.958, -4.350),
+        ];
+        for (i, ((vm, va), (e_vm, e_va))) in solve(net).into_iter().zip(expected).enumerate() {
+            assert!((vm - e_vm).abs() < 6e-4, "bus {i}: vm {vm}");
+            assert!((va - e_va).abs() < 6e-4, "bus {i}: va {va}");
+        }
+    }
+
+    #[test]
+    fn test_case14_taps_and_shunt() {
+        let case = case("case14");
+        let net = Network::from(&case);
+        assert_eq!(net.trafo.as_ref().unwrap().len(), 3);
+        assert_eq!(net.shunt.as_ref().unwrap()[0].q_mvar, -19.0);
+        // MATPOWER bus numbers are kept.
+        assert_eq!(net.bus[13].index, 14);
+        assert_eq!(net.shunt.as_ref().unwrap()[0].bus, 9);
+        // runpf(case14) in MATPOWER; the Vm/Va stored in the case are the
+        // rounded IEEE CDF values.
+        let expected = [
+            (1.060, 0.0),
+            (1.045, -4.983),
+            (1.010, -12.725),
+            (1.018, -10.313),
+            (1.020, -8.774),
+            (1.070, -14.221),
+            (1.062, -13.360),
+            (1.090, -13.360),
+            (1.056, -14.939),
+            (1.051, -15.097),
+            (1.057, -14.791),
+            (1.055, -15.076),
+            (1.050, -15.156),
+            (1.036, -16.034),
+        ];
+        for (i, ((vm, va), (e_vm, e_va))) in solve(net).into_iter().zip(expected).enumerate() {
+            assert!((vm - e_vm).abs() < 6e-4, "bus {i}: vm {vm}");
+            assert!((va - e_va).abs() < 6e-4, "bus {i}: va {va}");
+        }
+    }
+
+    #[test]
+    fn test_network_export_round_trip() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let net = load_csv_zip(&format!("{}/cases/IEEE118/data.zip", dir)).unwrap();
+        let mpc = MatpowerCase::from(&net);
+        assert_eq!(mpc.bus.iter().filter(|b| b.bus_type == 3).count(), 1);
+
+        let mut text = Vec::new();
+        write_matpower(&mpc, &mut text).unwrap();
+        let back = Network::from(&parse_matpower(&String::from_utf8(text).unwrap()).unwrap());
+        assert_eq!(back.bus.len(), net.bus.len());
+        assert_eq!(
+            back.trafo.as_ref().map(Vec::len),
+            net.trafo.as_ref().map(Vec::len)
+        );
+
+        for (i, (a, b)) in solve(net).into_iter().zip(solve(back)).enumerate() {
+            assert!((a.0 - b.0).abs() < 1e-8, "bus {i}: {a:?} {b:?}");
+            assert!((a.1 - b.1).abs() < 1e-6, "bus {i}: {a:?} {b:?}");
+        }
+    }
+}
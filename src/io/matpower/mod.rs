@@ -0,0 +1,6 @@
+//! MATPOWER case file import and export.
+
+pub mod case;
+pub mod convert;
+pub use case::*;
+pub use convert::*;
@@ -1,3 +1,8 @@
+pub mod cim;
+pub mod ieee_cdf;
+pub mod matpower;
 pub mod pandapower;
+pub mod powermodels;
+pub mod psse;
 
 pub mod archive;
@@ -1,10 +1,17 @@
+use crate::basic::ecs::factory::StdTypeLibrary;
 use crate::basic::ecs::network::DataOps;
 use crate::basic::ecs::network::PowerGrid;
 use crate::basic::ecs::*;
 use crate::bevy_cmdbuffer::buffer::HarvardCommandBuffer;
 
+use crate::basic::ecs::post_processing::*;
+use crate::basic::ecs::post_processing::res_elements::bus_voltages;
+use crate::basic::ecs::powerflow::prelude::{PowerFlowMat, PowerFlowResult};
+use crate::prelude::pandapower;
 use crate::prelude::pandapower::*;
+use bevy_ecs::name::Name;
 use bevy_ecs::prelude::*;
+use bevy_ecs::query::{QueryFilter, QueryItem, ReadOnlyQueryData};
 use elements::*;
 
 trait IntoBundleVec<T, U> {
@@ -20,6 +27,16 @@ where
     }
 }
 
+/// [`ElementIndex`] of every row of an element table, from its pandapower
+/// index.
+fn row_indices<T: ElementRow>(rows: &Option<Vec<T>>) -> impl Iterator<Item = usize> {
+    let rows = rows.as_deref().unwrap_or_default();
+    element_indices(rows)
+        .into_iter()
+        .enumerate()
+        .map(|(pos, i)| usize::try_from(i).unwrap_or(pos))
+}
+
 pub trait LoadPandapowerNet {
     fn load_pandapower_net(&mut self, net: &Network);
 }
@@ -44,62 +61,67 @@ impl LoadPandapowerNet for World {
 
         // Transformers
         let ts: Vec<TransformerBundle> = net.trafo.clone().to_bundle_vec();
-        for t in ts {
-            let e = world.spawn_empty().id();
+        for (i, t) in row_indices(&net.trafo).zip(ts) {
+            let e = world.spawn(ElementIndex(i)).id();
             buffer.insert_bundle(world, e, t);
         }
 
         // Lines
         let lines: Vec<LineBundle> = net.line.clone().to_bundle_vec();
-        for l in lines {
-            let e = world.spawn_empty().id();
+        for (i, l) in row_indices(&net.line).zip(lines) {
+            let e = world.spawn(ElementIndex(i)).id();
             buffer.insert_bundle(world, e, l);
         }
 
         // Generators
         let gens: Vec<GeneratorBundle> = net.r#gen.clone().to_bundle_vec();
-        for g in gens {
-            let e = world.spawn_empty().id();
+        for (i, g) in row_indices(&net.r#gen).zip(gens) {
+            let e = world.spawn(ElementIndex(i)).id();
             buffer.insert_bundle(world, e, g);
         }
 
         // Loads
         let loads: Vec<LoadBundle> = net.load.clone().to_bundle_vec();
-        for l in loads {
-            let e = world.spawn_empty().id();
+        for (i, l) in row_indices(&net.load).zip(loads) {
+            let e = world.spawn(ElementIndex(i)).id();
             buffer.insert_bundle(world, e, l);
         }
 
         // Ext Grid
         let ext_grid: Vec<ExtGridBundle> = net.ext_grid.clone().to_bundle_vec();
-        for g in ext_grid {
-            let e = world.spawn_empty().id();
+        for (i, g) in row_indices(&net.ext_grid).zip(ext_grid) {
+            let e = world.spawn(ElementIndex(i)).id();
             buffer.insert_bundle(world, e, g);
         }
 
         // Shunts
         let shunts: Vec<ShuntBundle> = net.shunt.clone().to_bundle_vec();
-        for s in shunts {
-            let e = world.spawn_empty().id();
+        for (i, s) in row_indices(&net.shunt).zip(shunts) {
+            let e = world.spawn(ElementIndex(i)).id();
             buffer.insert_bundle(world, e, s);
         }
 
         // SGens
         let sgens: Vec<SGenBundle> = net.sgen.clone().to_bundle_vec();
-        for s in sgens {
-            let e = world.spawn_empty().id();
+        for (i, s) in row_indices(&net.sgen).zip(sgens) {
+            let e = world.spawn(ElementIndex(i)).id();
             buffer.insert_bundle(world, e, s);
         }
 
         // Switches
         let switches: Vec<SwitchBundle> = net.switch.clone().to_bundle_vec();
-        for s in switches {
-            let e = world.spawn_empty().id();
+        for (i, s) in row_indices(&net.switch).zip(switches) {
+            let e = world.spawn(ElementIndex(i)).id();
             buffer.insert_bundle(world, e, s);
         }
 
         buffer.apply(world);
 
+        if let Some(types) = &net.std_types {
+            world
+                .get_resource_or_insert_with(StdTypeLibrary::pandapower)
+                .extend_from(types);
+        }
         world.insert_resource(PFCommonData {
             wbase: net.f_hz * 2.0 * std::f64::consts::PI,
             f_hz: net.f_hz,
@@ -118,6 +140,421 @@ pub fn init_powergrid_from_net(net: &Network, world: &mut World) {
     world.load_pandapower_net(net);
 }
 
+/// Rebuilds pandapower tables from the element components of a world, the
+/// reverse of [`LoadPandapowerNet`].
+///
+/// Buses are indexed by [`BusID`]; every other table lists its elements in
+/// [`ElementIndex`] order with that index, which is the pandapower index of
+/// the network they were loaded from. Values are read from the current
+/// components, so edits made after loading are exported.
+///
+/// `std_types` is not exported: the [`StdTypeLibrary`] also holds
+/// pandapower's built-in types, and elements that name a standard type
+/// carry its resolved parameters, so the exported network is complete
+/// without it.
+pub trait ToPandapowerNet {
+    fn to_pandapower_net(&mut self) -> Network;
+
+    /// Result tables of the last post-processed power flow. Element rows
+    /// are keyed by their [`ElementIndex`].
+    fn pandapower_results(&mut self) -> NetworkResults;
+}
+
+/// Maps the components of `Q` of every entity matching `F`, ordered by
+/// [`ElementIndex`].
+fn ordered<Q, F, T>(world: &mut World, f: impl FnMut(QueryItem<'_, '_, Q>) -> T) -> Vec<T>
+where
+    Q: ReadOnlyQueryData,
+    F: QueryFilter,
+{
+    let mut q = world.query_filtered::<(Option<&ElementIndex>, Q), F>();
+    let mut rows = q.iter(world).collect::<Vec<_>>();
+    rows.sort_by_key(|(i, _)| i.copied());
+    rows.into_iter().map(|(_, item)| item).map(f).collect()
+}
+
+/// [`ordered`] for element tables: every row gets its entity's
+/// [`ElementIndex`] as pandapower index.
+fn ordered_elements<Q, F, T>(
+    world: &mut World,
+    mut f: impl FnMut(QueryItem<'_, '_, Q>) -> T,
+) -> Vec<T>
+where
+    Q: ReadOnlyQueryData,
+    F: QueryFilter,
+    T: ElementRow,
+{
+    ordered::<(Option<&ElementIndex>, Q), F, _>(world, |(i, item)| {
+        let mut row = f(item);
+        *row.index_mut() = i.map(|i| i.0 as i64);
+        row
+    })
+}
+
+/// Result component `R` of every entity matching `F`, paired with the
+/// entity's [`ElementIndex`].
+fn ordered_results<R, F>(world: &mut World) -> Vec<(i64, R)>
+where
+    R: Component + Clone,
+    F: QueryFilter,
+{
+    ordered::<(Option<&ElementIndex>, Option<&R>), F, _>(world, |(i, r)| {
+        Some((i?.0 as i64, r?.clone()))
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+fn name_of(name: Option<&Name>) -> Option<String> {
+    name.map(|n| n.to_string())
+}
+
+fn non_empty<T>(rows: Vec<T>) -> Option<Vec<T>> {
+    (!rows.is_empty()).then_some(rows)
+}
+
+impl ToPandapowerNet for World {
+    fn to_pandapower_net(&mut self) -> Network {
+        let world = self;
+        assign_element_indices(world);
+        let (f_hz, sn_mva) = world
+            .get_resource::<PFCommonData>()
+            .map_or((50.0, 1.0), |c| (c.f_hz, c.sbase));
+
+        let mut bus = ordered::<
+            (
+                &BusID,
+                Option<&Name>,
+                Option<&VmLimit<PerUnit>>,
+                &VNominal,
+                Option<&Zone>,
+                Option<&BusType>,
+                Has<OutOfService>,
+            ),
+            (),
+            _,
+        >(world, |(id, name, lim, vn, zone, ty, oos)| Bus {
+            index: id.0,
+            in_service: !oos,
+            max_vm_pu: lim.map(|l| l.max()),
+            min_vm_pu: lim.map(|l| l.min()),
+            name: name_of(name),
+            r#type: ty.map(|t| t.0.clone()),
+            vn_kv: vn.0.0,
+            zone: zone.map(|z| z.0),
+        });
+        bus.sort_by_key(|b| b.index);
+
+        let line = ordered_elements::<
+            (
+                &FromBus,
+                &ToBus,
+                &LineParams,
+                Option<&Name>,
+                Option<&StandardModelType>,
+                Option<&MaxLoadingPercent>,
+                Option<&LineType>,
+                Has<OutOfService>,
+            ),
+            With<elements::Line>,
+            _,
+        >(world, |(from, to, p, name, std, max_loading, ty, oos)| pandapower::Line {
+            index: None,
+            c_nf_per_km: p.c_nf_per_km,
+            df: p.df,
+            from_bus: from.0,
+            to_bus: to.0,
+            g_us_per_km: p.g_us_per_km,
+            in_service: !oos,
+            length_km: p.length_km,
+            max_i_ka: Some(p.max_i_ka),
+            max_loading_percent: max_loading.map(|m| m.0),
+            parallel: p.parallel,
+            r_ohm_per_km: p.r_ohm_per_km,
+            type_: ty.map(|t| t.0.clone()),
+            x_ohm_per_km: p.x_ohm_per_km,
+            name: name_of(name),
+            std_type: std.map(|s| s.0.clone()),
+        });
+
+        let trafo = ordered_elements::<
+            (
+                &TransformerDevice,
+                &FromBus,
+                &ToBus,
+                Option<&Name>,
+                Option<&StandardModelType>,
+                Has<OutOfService>,
+            ),
+            (),
+            _,
+        >(world, |(d, from, to, name, std, oos)| {
+            let tap = d.tap.clone();
+            pandapower::Transformer {
+                index: None,
+                df: d.df,
+                hv_bus: from.0 as i32,
+                i0_percent: d.i0_percent,
+                in_service: !oos,
+                lv_bus: to.0 as i32,
+                max_loading_percent: d.max_loading_percent,
+                parallel: d.parallel,
+                pfe_kw: d.pfe_kw,
+                shift_degree: d.shift_degree,
+                sn_mva: d.sn_mva,
+                tap_phase_shifter: tap.as_ref().is_some_and(|t| t.is_phase_shifter),
+                vn_hv_kv: d.vn_hv_kv,
+                vn_lv_kv: d.vn_lv_kv,
+                vk_percent: d.vk_percent,
+                vkr_percent: d.vkr_percent,
+                name: name_of(name),
+                std_type: std.map(|s| s.0.clone()),
+                tap_side: tap.as_ref().and_then(|t| t.side.clone()),
+                tap_neutral: tap.as_ref().and_then(|t| t.neutral),
+                tap_max: tap.as_ref().and_then(|t| t.max),
+                tap_pos: tap.as_ref().and_then(|t| t.pos),
+                tap_min: tap.as_ref().and_then(|t| t.min),
+                tap_step_degree: tap.as_ref().and_then(|t| t.step_degree),
+                tap_step_percent: tap.as_ref().and_then(|t| t.step_percent),
+            }
+        });
+
+        let r#gen = ordered_elements::<
+            (
+                &TargetBus,
+                &TargetPMW,
+                Option<&TargetVmPu>,
+                &PQLim,
+                &GeneratorCfg,
+                Has<Slack>,
+                Has<Uncontrollable>,
+                Option<&SnMva>,
+                Option<&Name>,
+                Has<OutOfService>,
+            ),
+            GenFilter,
+            _,
+        >(
+            world,
+            |(bus, p, vm, lim, cfg, slack, unc, sn, name, oos)| Gen {
+                index: None,
+                bus: bus.0,
+                controllable: Some(!unc),
+                in_service: !oos,
+                name: name_of(name),
+                p_mw: p.0,
+                scaling: cfg.scaling,
+                sn_mva: sn.map(|s| s.0),
+                type_: cfg.gen_type.clone(),
+                vm_pu: vm.map_or(1.0, |v| v.0),
+                slack,
+                max_p_mw: lim.p.max,
+                min_p_mw: lim.p.min,
+                max_q_mvar: lim.q.max,
+                min_q_mvar: lim.q.min,
+                slack_weight: cfg.slack_weight,
+            },
+        );
+
+        let load = ordered_elements::<
+            (
+                &TargetBus,
+                &TargetPMW,
+                Option<&TargetQMVar>,
+                &LoadCfg,
+                Option<&LoadModelType>,
+                Has<Uncontrollable>,
+                Option<&SnMva>,
+                Option<&Name>,
+                Has<OutOfService>,
+            ),
+            (),
+            _,
+        >(world, |(bus, p, q, cfg, model, unc, sn, name, oos)| Load {
+            index: None,
+            bus: bus.0,
+            const_i_percent: model.map_or(0.0, |m| m.const_i_percent),
+            const_z_percent: model.map_or(0.0, |m| m.const_z_percent),
+            controllable: Some(!unc),
+            in_service: !oos,
+            name: name_of(name),
+            p_mw: -p.0,
+            q_mvar: -q.map_or(0.0, |q| q.0),
+            scaling: cfg.scaling,
+            sn_mva: sn.map(|s| s.0),
+            type_: cfg.load_type.clone(),
+        });
+
+        let ext_grid = ordered_elements::<
+            (
+                &TargetBus,
+                Option<&TargetVmPu>,
+                &TargetVaDeg,
+                &GeneratorCfg,
+                Option<&PQLim>,
+                Option<&Name>,
+                Has<OutOfService>,
+            ),
+            ExtGridFilter,
+            _,
+        >(world, |(bus, vm, va, cfg, lim, name, oos)| ExtGrid {
+            index: None,
+            bus: bus.0,
+            in_service: !oos,
+            va_degree: va.0,
+            vm_pu: vm.map_or(1.0, |v| v.0),
+            max_p_mw: lim.map(|l| l.p.max),
+            min_p_mw: lim.map(|l| l.p.min),
+            max_q_mvar: lim.map(|l| l.q.max),
+            min_q_mvar: lim.map(|l| l.q.min),
+            slack_weight: cfg.slack_weight,
+            name: name_of(name),
+        });
+
+        let shunt = ordered_elements::<(&TargetBus, &ShuntDevice, Option<&Name>, Has<OutOfService>), (), _>(
+            world,
+            |(bus, d, name, oos)| Shunt {
+                index: None,
+                bus: bus.0,
+                q_mvar: d.q_mvar,
+                p_mw: d.p_mw,
+                vn_kv: d.vn_kv,
+                step: d.step,
+                max_step: d.max_step,
+                in_service: !oos,
+                name: name_of(name),
+            },
+        );
+
+        let sgen = ordered_elements::<
+            (
+                &TargetBus,
+                &SGenDevice,
+                Option<&TargetPMW>,
+                Option<&TargetQMVar>,
+                Has<Uncontrollable>,
+                Option<&Name>,
+                Has<OutOfService>,
+            ),
+            (),
+            _,
+        >(world, |(bus, d, p, q, unc, name, oos)| SGen {
+            index: None,
+            name: name_of(name),
+            bus: bus.0,
+            p_mw: p.map_or(d.p_mw, |p| p.0),
+            q_mvar: q.map_or(d.q_mvar, |q| q.0),
+            sn_mva: d.sn_mva,
+            scaling: d.scaling,
+            in_service: !oos,
+            type_: d.gen_type.clone(),
+            current_source: d.is_current_source,
+            controllable: Some(!unc),
+        });
+
+        let switch = ordered_elements::<
+            (&elements::Switch, &SwitchState, Option<&SwitchDeviceType>, Option<&Name>),
+            (),
+            _,
+        >(
+            world,
+            |(s, state, ty, name)| pandapower::Switch {
+                index: None,
+                bus: s.bus,
+                element: s.element,
+                et: s.et.clone(),
+                type_: ty.map(|t| t.0.clone()),
+                closed: state.0,
+                name: name_of(name),
+                z_ohm: s.z_ohm,
+            },
+        );
+
+        Network {
+            r#gen: non_empty(r#gen),
+            bus,
+            load: non_empty(load),
+            line: non_empty(line),
+            trafo: non_empty(trafo),
+            shunt: non_empty(shunt),
+            ext_grid: non_empty(ext_grid),
+            sgen: non_empty(sgen),
+            switch: non_empty(switch),
+            f_hz,
+            sn_mva,
+            std_types: None,
+        }
+    }
+
+    fn pandapower_results(&mut self) -> NetworkResults {
+        let world = self;
+        assign_element_indices(world);
+        // `SBusResult` holds a merged group's injection on its representative
+        // bus only; the voltage is the group's on every bus of it.
+        let s_bus = ordered::<(&BusID, &SBusResult), (), _>(world, |(id, s)| (id.0, s.0));
+        let mut bus = match (
+            world.get_resource::<PowerFlowMat>(),
+            world.get_resource::<PowerFlowResult>(),
+            world.get_resource::<NodeLookup>(),
+        ) {
+            (Some(mat), Some(res), Some(nodes)) => {
+                let v = bus_voltages(mat, res, world.get_resource());
+                s_bus
+                    .into_iter()
+                    .filter_map(|(id, s)| {
+                        let v = v[nodes.index(id)?];
+                        let res = BusResult {
+                            vm_pu: v.norm(),
+                            va_degree: v.arg().to_degrees(),
+                            p_mw: s.re,
+                            q_mvar: s.im,
+                        };
+                        Some((id, res))
+                    })
+                    .collect::<Vec<_>>()
+            }
+            _ => Vec::new(),
+        };
+        bus.sort_by_key(|(id, _)| *id);
+
+        NetworkResults {
+            converged: world
+                .get_resource::<PowerFlowResult>()
+                .is_some_and(|r| r.converged),
+            bus,
+            line: ordered_results::<LineResultData, With<elements::Line>>(world),
+            trafo: ordered_results::<TrafoResultData, With<TransformerDevice>>(world),
+            r#gen: ordered_results::<GenResultData, GenFilter>(world),
+            ext_grid: ordered_results::<ExtGridResultData, ExtGridFilter>(world),
+            load: ordered_results::<LoadResultData, With<LoadCfg>>(world),
+            sgen: ordered_results::<SGenResultData, With<SGenDevice>>(world),
+            shunt: ordered_results::<ShuntResultData, With<ShuntDevice>>(world),
+        }
+    }
+}
+
+impl ToPandapowerNet for PowerGrid {
+    fn to_pandapower_net(&mut self) -> Network {
+        self.world_mut().to_pandapower_net()
+    }
+
+    fn pandapower_results(&mut self) -> NetworkResults {
+        self.world_mut().pandapower_results()
+    }
+}
+
+impl ToPandapowerNet for bevy_app::App {
+    fn to_pandapower_net(&mut self) -> Network {
+        self.world_mut().to_pandapower_net()
+    }
+
+    fn pandapower_results(&mut self) -> NetworkResults {
+        self.world_mut().pandapower_results()
+    }
+}
+
+#[cfg(test)]
 #[allow(unused_imports)]
 mod tests {
     use bevy_ecs::system::RunSystemOnce;
@@ -141,4 +578,175 @@ mod tests {
         println!("{}", net.bus.len());
         world.insert_resource(PPNetwork(net));
     }
+
+    fn solved_grid(net: Network) -> PowerGrid {
+        use crate::basic::ecs::network::PowerFlow;
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.run_pf();
+        grid.post_process();
+        grid
+    }
+
+    #[test]
+    /// Exports a solved world to pandapower JSON, reloads it and checks the
+    /// reloaded case solves to the exported bus results.
+    fn test_export_round_trip() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let net = load_csv_zip(&format!("{}/cases/IEEE118/data.zip", dir)).unwrap();
+        let n_line = net.line.as_ref().unwrap().len();
+        let mut grid = solved_grid(net);
+
+        let exported = grid.to_pandapower_net();
+        let results = grid.pandapower_results();
+        assert!(results.converged);
+        assert_eq!(results.bus.len(), exported.bus.len());
+        assert_eq!(results.line.len(), n_line);
+        assert_eq!(exported.line.as_ref().unwrap().len(), n_line);
+
+        let json = to_pandapower_json(&exported, Some(&results)).unwrap();
+        let object = json["_object"].as_object().unwrap();
+        for key in ["res_bus", "res_line", "res_trafo", "res_gen", "res_ext_grid"] {
+            assert!(object.contains_key(key), "{key}");
+        }
+
+        let mut reloaded = solved_grid(load_pandapower_json_obj(json.as_object().unwrap()));
+        let again = reloaded.pandapower_results();
+        assert!(again.converged);
+        for ((i, a), (j, b)) in results.bus.iter().zip(&again.bus) {
+            assert_eq!(i, j);
+            assert!((a.vm_pu - b.vm_pu).abs() < 1e-8, "bus {i}");
+            assert!((a.va_degree - b.va_degree).abs() < 1e-6, "bus {i}");
+        }
+        for ((_, a), (_, b)) in results.line.iter().zip(&again.line) {
+            assert!((a.p_from_mw - b.p_from_mw).abs() < 1e-6, "{a:?} {b:?}");
+        }
+    }
+
+    #[test]
+    /// The frequency and base power come from the JSON file: the case is a
+    /// 50 Hz network on a 1 MVA base, where the Newton tolerance is tight
+    /// enough to pin its results.
+    fn test_json_frequency_and_base() {
+        use crate::basic::ecs::plugin::{SwitchPluginTypeA, default_app};
+
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/test/new_input_PFLV_modified.json", dir);
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(name).unwrap()).unwrap();
+        let net = load_pandapower_json_obj(json["pp_network"].as_object().unwrap());
+        assert_eq!((net.f_hz, net.sn_mva), (50.0, 1.0));
+        let mut app = default_app();
+        app.add_plugins(SwitchPluginTypeA);
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+        app.post_process();
+        let res = app.world_mut().pandapower_results();
+        assert!(res.converged);
+
+        let (_, b) = res.bus.iter().find(|(i, _)| *i == 5).unwrap();
+        assert!((b.vm_pu - 0.965840038).abs() < 1e-8, "{b:?}");
+        assert!((b.va_degree + 0.118579778).abs() < 1e-8, "{b:?}");
+        let (_, ext) = &res.ext_grid[0];
+        assert!((ext.p_mw - 0.035328398).abs() < 1e-8, "{ext:?}");
+        assert!((ext.q_mvar - 0.031601330).abs() < 1e-8, "{ext:?}");
+    }
+
+    #[test]
+    /// Exported tables follow the source rows even when the loaded entities
+    /// reuse freed indices out of order.
+    fn test_export_order_after_despawn() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let net = load_csv_zip(&format!("{}/cases/IEEE118/data.zip", dir)).unwrap();
+        let mut world = World::new();
+        let dummies = (0..1000).map(|_| world.spawn_empty().id()).collect::<Vec<_>>();
+        for e in dummies {
+            world.despawn(e);
+        }
+        world.load_pandapower_net(&net);
+
+        let exported = world.to_pandapower_net();
+        let ends = |lines: &[pandapower::Line]| {
+            lines.iter().map(|l| (l.from_bus, l.to_bus)).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ends(exported.line.as_ref().unwrap()),
+            ends(net.line.as_ref().unwrap())
+        );
+        let buses = |loads: &[pandapower::Load]| loads.iter().map(|l| l.bus).collect::<Vec<_>>();
+        assert_eq!(
+            buses(exported.load.as_ref().unwrap()),
+            buses(net.load.as_ref().unwrap())
+        );
+    }
+
+    fn pflv_net() -> Network {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/test/new_input_PFLV_modified.json", dir);
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(name).unwrap()).unwrap();
+        load_pandapower_json_obj(json["pp_network"].as_object().unwrap())
+    }
+
+    #[test]
+    /// Line ratings and types and switch types survive the export; the
+    /// standard type library is left out.
+    fn test_export_line_and_switch_info() {
+        let mut net = pflv_net();
+        let line = &mut net.line.as_mut().unwrap()[1];
+        line.max_loading_percent = Some(80.0);
+        line.type_ = Some("cs".into());
+        net.switch.as_mut().unwrap()[2].type_ = Some("CB".into());
+        let mut world = World::new();
+        world.load_pandapower_net(&net);
+
+        let exported = world.to_pandapower_net();
+        let lines = exported.line.unwrap();
+        assert_eq!(lines[1].max_loading_percent, Some(80.0));
+        assert_eq!(lines[1].type_.as_deref(), Some("cs"));
+        assert_eq!(lines[0].max_loading_percent, None);
+        let switches = exported.switch.unwrap();
+        assert_eq!(switches[2].type_.as_deref(), Some("CB"));
+        assert_eq!(switches[0].type_, None);
+        assert!(exported.std_types.is_none());
+    }
+
+    #[test]
+    /// Buses merged by closed switches report their group's voltage, and the
+    /// group's injection once.
+    fn test_results_merged_buses() {
+        use crate::basic::ecs::plugin::{SwitchPluginTypeA, default_app};
+
+        // A load on bus 12, which closed switches merge with buses 0, 28 and 30.
+        let mut net = pflv_net();
+        let loads = net.load.as_mut().unwrap();
+        let load = Load { bus: 12, p_mw: 0.004, ..loads[0].clone() };
+        loads.push(load);
+        let mut app = default_app();
+        app.add_plugins(SwitchPluginTypeA);
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+        app.post_process();
+        let results = app.world_mut().pandapower_results();
+        assert!(results.converged);
+        for (i, b) in &results.bus {
+            assert!(b.vm_pu > 0.5, "bus {i}: {b:?}");
+        }
+
+        // Net bus consumption is the negative of the branch losses.
+        let p_bus = results.bus.iter().map(|(_, b)| b.p_mw).sum::<f64>();
+        let losses = results.line.iter().map(|(_, l)| l.pl_mw).sum::<f64>()
+            + results.trafo.iter().map(|(_, t)| t.pl_mw).sum::<f64>();
+        assert!((p_bus + losses).abs() < 1e-6, "{p_bus} vs {losses}");
+        let group = results
+            .bus
+            .iter()
+            .filter(|(i, _)| [0, 12, 28, 30].contains(i))
+            .map(|(_, b)| b)
+            .collect::<Vec<_>>();
+        assert!(group.windows(2).all(|w| w[0].vm_pu == w[1].vm_pu));
+        let p_group = group.iter().map(|b| b.p_mw).sum::<f64>();
+        assert!((p_group - 0.004).abs() < 1e-6, "{group:?}");
+    }
 }
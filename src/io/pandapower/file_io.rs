@@ -1,6 +1,8 @@
 use csv::ReaderBuilder;
 use serde::Deserializer;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
 use std::{fs, fs::File};
 use std::{io::Read, option::Option};
 
@@ -39,6 +41,15 @@ where
     Ok(None)
 }
 
+/// Deserializes a float, reading a missing value (pandapower's NaN) as NaN.
+fn nan_if_missing<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let val: Option<f64> = Deserialize::deserialize(deserializer)?;
+    Ok(val.unwrap_or(f64::NAN))
+}
+
 /// Represents a bus in the network.
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "python", pyclass(get_all, set_all))]
@@ -69,6 +80,10 @@ impl Bus {
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 pub struct Gen {
+    /// pandapower index of the element; `None` when the table has no index,
+    /// in which case the row position is used.
+    #[serde(default)]
+    pub index: Option<i64>,
     pub bus: i64,
     pub controllable: Option<bool>,
     pub in_service: bool,
@@ -80,9 +95,13 @@ pub struct Gen {
     pub type_: Option<String>, // Added underscore to avoid conflict with Rust keyword
     pub vm_pu: f64,
     pub slack: bool,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub max_p_mw: f64,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub min_p_mw: f64,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub max_q_mvar: f64,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub min_q_mvar: f64,
     pub slack_weight: f64,
 }
@@ -93,7 +112,7 @@ impl Gen {
     #[new]
     #[pyo3(signature = (bus=0, controllable=None, in_service=true, name=None, p_mw=0.0, scaling=1.0, sn_mva=None, type_=None, vm_pu=1.0, slack=false, max_p_mw=0.0, min_p_mw=0.0, max_q_mvar=0.0, min_q_mvar=0.0, slack_weight=0.0))]
     pub fn new(bus: i64, controllable: Option<bool>, in_service: bool, name: Option<String>, p_mw: f64, scaling: f64, sn_mva: Option<f64>, type_: Option<String>, vm_pu: f64, slack: bool, max_p_mw: f64, min_p_mw: f64, max_q_mvar: f64, min_q_mvar: f64, slack_weight: f64) -> Self {
-        Self { bus, controllable, in_service, name, p_mw, scaling, sn_mva, type_: type_, vm_pu, slack, max_p_mw, min_p_mw, max_q_mvar, min_q_mvar, slack_weight }
+        Self { index: None, bus, controllable, in_service, name, p_mw, scaling, sn_mva, type_: type_, vm_pu, slack, max_p_mw, min_p_mw, max_q_mvar, min_q_mvar, slack_weight }
     }
 }
 
@@ -101,6 +120,10 @@ impl Gen {
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 pub struct Load {
+    /// pandapower index of the element; `None` when the table has no index,
+    /// in which case the row position is used.
+    #[serde(default)]
+    pub index: Option<i64>,
     pub bus: i64,
     pub const_i_percent: f64,
     pub const_z_percent: f64,
@@ -121,7 +144,7 @@ impl Load {
     #[new]
     #[pyo3(signature = (bus=0, const_i_percent=0.0, const_z_percent=0.0, controllable=None, in_service=true, name=None, p_mw=0.0, q_mvar=0.0, scaling=1.0, sn_mva=None, type_=None))]
     pub fn new(bus: i64, const_i_percent: f64, const_z_percent: f64, controllable: Option<bool>, in_service: bool, name: Option<String>, p_mw: f64, q_mvar: f64, scaling: f64, sn_mva: Option<f64>, type_: Option<String>) -> Self {
-        Self { bus, const_i_percent, const_z_percent, controllable, in_service, name, p_mw, q_mvar, scaling, sn_mva, type_: type_ }
+        Self { index: None, bus, const_i_percent, const_z_percent, controllable, in_service, name, p_mw, q_mvar, scaling, sn_mva, type_: type_ }
     }
 }
 
@@ -129,19 +152,27 @@ impl Load {
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 pub struct Line {
+    /// pandapower index of the element; `None` when the table has no index,
+    /// in which case the row position is used.
+    #[serde(default)]
+    pub index: Option<i64>,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub c_nf_per_km: f64,
     pub df: f64,
     pub from_bus: i64,
     pub to_bus: i64,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub g_us_per_km: f64,
     pub in_service: bool,
     pub length_km: f64,
     pub max_i_ka: Option<f64>,
     pub max_loading_percent: Option<f64>,
     pub parallel: i32,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub r_ohm_per_km: f64,
     #[serde(rename = "type")]
     pub type_: Option<String>,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub x_ohm_per_km: f64,
     pub name: Option<String>,
     pub std_type: Option<String>,
@@ -153,7 +184,7 @@ impl Line {
     #[new]
     #[pyo3(signature = (from_bus=0, to_bus=0, length_km=1.0, r_ohm_per_km=0.1, x_ohm_per_km=0.1, c_nf_per_km=0.0, g_us_per_km=0.0, in_service=true, parallel=1, max_i_ka=None, max_loading_percent=None, type_=None, name=None, std_type=None))]
     fn new(from_bus: i64, to_bus: i64, length_km: f64, r_ohm_per_km: f64, x_ohm_per_km: f64, c_nf_per_km: f64, g_us_per_km: f64, in_service: bool, parallel: i32, max_i_ka: Option<f64>, max_loading_percent: Option<f64>, type_: Option<String>, name: Option<String>, std_type: Option<String>) -> Self {
-        Self { from_bus, to_bus, length_km, r_ohm_per_km, x_ohm_per_km, c_nf_per_km, g_us_per_km, in_service, parallel, max_i_ka, max_loading_percent, type_: type_, name, std_type, df: 1.0 }
+        Self { index: None, from_bus, to_bus, length_km, r_ohm_per_km, x_ohm_per_km, c_nf_per_km, g_us_per_km, in_service, parallel, max_i_ka, max_loading_percent, type_: type_, name, std_type, df: 1.0 }
     }
 }
 
@@ -161,20 +192,32 @@ impl Line {
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 pub struct Transformer {
+    /// pandapower index of the element; `None` when the table has no index,
+    /// in which case the row position is used.
+    #[serde(default)]
+    pub index: Option<i64>,
     pub df: f64,
     pub hv_bus: i32,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub i0_percent: f64,
     pub in_service: bool,
     pub lv_bus: i32,
     pub max_loading_percent: Option<f64>,
     pub parallel: i32,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub pfe_kw: f64,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub shift_degree: f64,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub sn_mva: f64,
     pub tap_phase_shifter: bool,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub vn_hv_kv: f64,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub vn_lv_kv: f64,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub vk_percent: f64,
+    #[serde(deserialize_with = "nan_if_missing")]
     pub vkr_percent: f64,
     pub name: Option<String>,
     pub std_type: Option<String>,
@@ -193,7 +236,7 @@ impl Transformer {
     #[new]
     #[pyo3(signature = (hv_bus=0, lv_bus=0, sn_mva=1.0, vn_hv_kv=110.0, vn_lv_kv=10.0, vk_percent=10.0, vkr_percent=0.1, pfe_kw=0.0, i0_percent=0.0, shift_degree=0.0, in_service=true, parallel=1, tap_side=None, tap_pos=None, tap_neutral=None, tap_max=None, tap_min=None, tap_step_percent=None, tap_step_degree=None, tap_phase_shifter=false, name=None, std_type=None))]
     fn new(hv_bus: i32, lv_bus: i32, sn_mva: f64, vn_hv_kv: f64, vn_lv_kv: f64, vk_percent: f64, vkr_percent: f64, pfe_kw: f64, i0_percent: f64, shift_degree: f64, in_service: bool, parallel: i32, tap_side: Option<String>, tap_pos: Option<f64>, tap_neutral: Option<f64>, tap_max: Option<f64>, tap_min: Option<f64>, tap_step_percent: Option<f64>, tap_step_degree: Option<f64>, tap_phase_shifter: bool, name: Option<String>, std_type: Option<String>) -> Self {
-        Self { hv_bus, lv_bus, sn_mva, vn_hv_kv, vn_lv_kv, vk_percent, vkr_percent, pfe_kw, i0_percent, shift_degree, in_service, parallel, tap_side, tap_pos, tap_neutral, tap_max, tap_min, tap_step_percent, tap_step_degree, tap_phase_shifter, name, std_type, df: 1.0, max_loading_percent: None }
+        Self { index: None, hv_bus, lv_bus, sn_mva, vn_hv_kv, vn_lv_kv, vk_percent, vkr_percent, pfe_kw, i0_percent, shift_degree, in_service, parallel, tap_side, tap_pos, tap_neutral, tap_max, tap_min, tap_step_percent, tap_step_degree, tap_phase_shifter, name, std_type, df: 1.0, max_loading_percent: None }
     }
 }
 
@@ -201,6 +244,10 @@ impl Transformer {
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 pub struct ExtGrid {
+    /// pandapower index of the element; `None` when the table has no index,
+    /// in which case the row position is used.
+    #[serde(default)]
+    pub index: Option<i64>,
     pub bus: i64,
     pub in_service: bool,
     pub va_degree: f64,
@@ -219,7 +266,7 @@ impl ExtGrid {
     #[new]
     #[pyo3(signature = (bus=0, vm_pu=1.0, va_degree=0.0, in_service=true, slack_weight=1.0, name=None))]
     fn new(bus: i64, vm_pu: f64, va_degree: f64, in_service: bool, slack_weight: f64, name: Option<String>) -> Self {
-        Self { bus, vm_pu, va_degree, in_service, slack_weight, name, max_p_mw: None, min_p_mw: None, max_q_mvar: None, min_q_mvar: None }
+        Self { index: None, bus, vm_pu, va_degree, in_service, slack_weight, name, max_p_mw: None, min_p_mw: None, max_q_mvar: None, min_q_mvar: None }
     }
 }
 
@@ -227,6 +274,10 @@ impl ExtGrid {
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 pub struct SGen {
+    /// pandapower index of the element; `None` when the table has no index,
+    /// in which case the row position is used.
+    #[serde(default)]
+    pub index: Option<i64>,
     pub name: Option<String>,
     pub bus: i64,
     pub p_mw: f64,
@@ -246,7 +297,7 @@ impl SGen {
     #[new]
     #[pyo3(signature = (bus=0, p_mw=0.0, q_mvar=0.0, in_service=true, scaling=1.0, name=None, type_=None))]
     fn new(bus: i64, p_mw: f64, q_mvar: f64, in_service: bool, scaling: f64, name: Option<String>, type_: Option<String>) -> Self {
-        Self { bus, p_mw, q_mvar, in_service, scaling, name, type_: type_, sn_mva: None, current_source: false, controllable: None }
+        Self { index: None, bus, p_mw, q_mvar, in_service, scaling, name, type_: type_, sn_mva: None, current_source: false, controllable: None }
     }
 }
 
@@ -254,6 +305,10 @@ impl SGen {
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 pub struct Shunt {
+    /// pandapower index of the element; `None` when the table has no index,
+    /// in which case the row position is used.
+    #[serde(default)]
+    pub index: Option<i64>,
     pub bus: i64,
     pub q_mvar: f64,
     pub p_mw: f64,
@@ -270,7 +325,7 @@ impl Shunt {
     #[new]
     #[pyo3(signature = (bus=0, p_mw=0.0, q_mvar=0.0, vn_kv=110.0, in_service=true, name=None))]
     fn new(bus: i64, p_mw: f64, q_mvar: f64, vn_kv: f64, in_service: bool, name: Option<String>) -> Self {
-        Self { bus, p_mw, q_mvar, vn_kv, in_service, name, step: 1, max_step: 1 }
+        Self { index: None, bus, p_mw, q_mvar, vn_kv, in_service, name, step: 1, max_step: 1 }
     }
 }
 
@@ -293,6 +348,10 @@ pub enum SwitchType {
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "python", pyclass(get_all, set_all))]
 pub struct Switch {
+    /// pandapower index of the element; `None` when the table has no index,
+    /// in which case the row position is used.
+    #[serde(default)]
+    pub index: Option<i64>,
     pub bus: i64,
     pub element: i64,
     pub et: SwitchType,
@@ -309,7 +368,7 @@ impl Switch {
     #[new]
     #[pyo3(signature = (bus=0, element=0, et=SwitchType::SwitchTwoBuses, closed=true, name=None))]
     fn new(bus: i64, element: i64, et: SwitchType, closed: bool, name: Option<String>) -> Self {
-        Self { bus, element, et, closed, name, type_: None, z_ohm: 0.0 }
+        Self { index: None, bus, element, et, closed, name, type_: None, z_ohm: 0.0 }
     }
 }
 
@@ -325,6 +384,68 @@ impl From<&str> for SwitchType {
     }
 }
 
+/// Standard line type as stored in pandapower's `std_types["line"]`.
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+pub struct LineStdType {
+    pub r_ohm_per_km: f64,
+    pub x_ohm_per_km: f64,
+    pub c_nf_per_km: f64,
+    #[serde(default)]
+    pub g_us_per_km: f64,
+    pub max_i_ka: f64,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q_mm2: Option<f64>,
+}
+
+/// Standard transformer type as stored in pandapower's `std_types["trafo"]`.
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+pub struct TrafoStdType {
+    pub sn_mva: f64,
+    pub vn_hv_kv: f64,
+    pub vn_lv_kv: f64,
+    pub vk_percent: f64,
+    pub vkr_percent: f64,
+    pub pfe_kw: f64,
+    pub i0_percent: f64,
+    #[serde(default)]
+    pub shift_degree: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_group: Option<String>,
+    pub tap_side: Option<String>,
+    pub tap_neutral: Option<f64>,
+    pub tap_min: Option<f64>,
+    pub tap_max: Option<f64>,
+    pub tap_step_percent: Option<f64>,
+    pub tap_step_degree: Option<f64>,
+    #[serde(default)]
+    pub tap_phase_shifter: bool,
+}
+
+/// The `std_types` section of a pandapower network, keyed by type name.
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+pub struct StdTypes {
+    #[serde(default)]
+    pub line: HashMap<String, LineStdType>,
+    #[serde(default)]
+    pub trafo: HashMap<String, TrafoStdType>,
+}
+
+/// pandapower's built-in line and transformer standard types.
+static PANDAPOWER_STD_TYPES: LazyLock<StdTypes> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("std_types.json")).expect("invalid built-in std_types")
+});
+
+/// The line and transformer standard types pandapower ships with, e.g.
+/// `"NAYY 4x150 SE"` or `"0.4 MVA 20/0.4 kV"`.
+pub fn pandapower_std_types() -> &'static StdTypes {
+    &PANDAPOWER_STD_TYPES
+}
+
 /// Represents a network.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "python", pyclass(get_all, set_all))]
@@ -340,17 +461,50 @@ pub struct Network {
     pub switch: Option<Vec<Switch>>,
     pub f_hz: f64,
     pub sn_mva: f64,
+    /// Standard types defined by the network in addition to the built-in
+    /// ones.
+    #[serde(default)]
+    pub std_types: Option<StdTypes>,
 }
 
 
-pub trait ToCSV {
-    fn save_csv(&self) -> Result<(), &'static str>;
+/// Row of an element table that carries a pandapower index.
+pub(crate) trait ElementRow {
+    fn index(&self) -> Option<i64>;
+    fn index_mut(&mut self) -> &mut Option<i64>;
 }
 
-impl ToCSV for Network {
-    fn save_csv(&self) -> Result<(), &'static str> {
-        todo!()
-    }
+macro_rules! impl_element_row {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl ElementRow for $t {
+                fn index(&self) -> Option<i64> {
+                    self.index
+                }
+
+                fn index_mut(&mut self) -> &mut Option<i64> {
+                    &mut self.index
+                }
+            }
+        )*
+    };
+}
+
+impl_element_row!(Gen, Load, Line, Transformer, ExtGrid, SGen, Shunt, Switch);
+
+/// pandapower index of every row: the stored index, or the row position for
+/// rows without one.
+pub(crate) fn element_indices<T: ElementRow>(rows: &[T]) -> Vec<i64> {
+    rows.iter()
+        .enumerate()
+        .map(|(pos, r)| r.index().unwrap_or(pos as i64))
+        .collect()
+}
+
+/// Writes a network as a folder of pandapower CSV tables, the layout
+/// [`load_csv_folder`] reads.
+pub trait ToCSV {
+    fn save_csv(&self, folder: &str) -> Result<(), std::io::Error>;
 }
 
 impl Default for Network {
@@ -367,6 +521,7 @@ impl Default for Network {
             switch: None,
             f_hz: 60.0,
             sn_mva: 100.0,
+            std_types: None,
         }
     }
 }
@@ -541,8 +696,13 @@ fn load_pandapower_element_json<T: serde::de::DeserializeOwned>(
         .to_owned();
 
     let rows = map.get("data").and_then(|v| v.as_array()).unwrap();
+    let frame_index = map.get("index").and_then(|v| v.as_array());
 
-    for (index, row) in rows.iter().enumerate() {
+    for (pos, row) in rows.iter().enumerate() {
+        let index = frame_index
+            .and_then(|ix| ix.get(pos))
+            .and_then(Value::as_i64)
+            .unwrap_or(pos as i64);
         let obj: Map<String, Value> = Map::new();
         let mut obj: Map<String, Value> =
             headers
@@ -557,7 +717,7 @@ fn load_pandapower_element_json<T: serde::de::DeserializeOwned>(
 
         obj.insert(
             "index".to_string(),
-            Value::Number(serde_json::Number::from(index as i64)),
+            Value::Number(serde_json::Number::from(index)),
         );
 
         let elem: T = serde_json::from_value(obj.clone().into()).unwrap();
@@ -567,6 +727,33 @@ fn load_pandapower_element_json<T: serde::de::DeserializeOwned>(
     return Some(elements);
 }
 
+/// Entries of one `std_types` section; types with missing or invalid
+/// parameters are skipped.
+fn load_std_type_section<T: serde::de::DeserializeOwned>(
+    object: &Map<String, Value>,
+    key: &str,
+) -> HashMap<String, T> {
+    object
+        .get(key)
+        .and_then(Value::as_object)
+        .map(|section| {
+            section
+                .iter()
+                .filter_map(|(name, v)| Some((name.clone(), T::deserialize(v).ok()?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Reads the line and transformer sections of a pandapower `std_types`
+/// dictionary.
+pub fn load_std_types_json(object: &Map<String, Value>) -> StdTypes {
+    StdTypes {
+        line: load_std_type_section(object, "line"),
+        trafo: load_std_type_section(object, "trafo"),
+    }
+}
+
 pub fn load_pandapower_json(file_path: &str) -> Network {
     let map: Map<String, Value> = load_json(file_path).unwrap();
     load_pandapower_json_obj(&map)
@@ -587,6 +774,15 @@ pub fn load_pandapower_json_obj(json: &Map<String, Value>) -> Network {
         sgen:"sgen",
         switch:"switch"
     });
+    if let Some(f_hz) = object.get("f_hz").and_then(Value::as_f64) {
+        net.f_hz = f_hz;
+    }
+    if let Some(sn_mva) = object.get("sn_mva").and_then(Value::as_f64) {
+        net.sn_mva = sn_mva;
+    }
+    if let Some(std_types) = object.get("std_types").and_then(Value::as_object) {
+        net.std_types = Some(load_std_types_json(std_types));
+    }
 
     return net;
 }
@@ -602,6 +798,9 @@ mod tests {
         let folder = format!("{}/cases", dir);
         let filepath: String = folder.to_owned() + "/networks.json";
         let net = load_pandapower_json(&filepath);
+        let std_types = net.std_types.unwrap();
+        assert_eq!(std_types.line["NAYY 4x150 SE"].c_nf_per_km, 261.0);
+        assert_eq!(std_types.trafo.len(), pandapower_std_types().trafo.len());
         net.r#gen.unwrap();
     }
 
@@ -1,3 +1,5 @@
 pub mod ecs_net_conv;
 pub mod file_io;
+pub mod writer;
 pub use file_io::*;
+pub use writer::*;
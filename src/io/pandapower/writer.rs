@@ -0,0 +1,504 @@
+//! Writers for pandapower JSON and the CSV-zip layout read by
+//! [`load_csv_zip`](super::load_csv_zip).
+//!
+//! Element tables are written from a [`Network`]; result tables from
+//! [`NetworkResults`], whose rows carry the pandapower index of the element
+//! they belong to.
+
+use std::fs::{self, File};
+use std::io::{Seek, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use zip::write::SimpleFileOptions;
+
+use super::file_io::*;
+use crate::basic::ecs::post_processing::{
+    ExtGridResultData, GenResultData, LineResultData, LoadResultData, SGenResultData,
+    ShuntResultData, TrafoResultData,
+};
+
+/// pandapower versions stamped into written JSON files.
+const PP_VERSION: &str = "2.14.9";
+const PP_FORMAT_VERSION: &str = "2.14.0";
+
+/// Row of pandapower's `res_bus`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusResult {
+    pub vm_pu: f64,
+    pub va_degree: f64,
+    pub p_mw: f64,
+    pub q_mvar: f64,
+}
+
+/// Result tables of a solved network. Each row is paired with the
+/// pandapower index of its element.
+#[derive(Debug, Default, Clone)]
+pub struct NetworkResults {
+    pub converged: bool,
+    pub bus: Vec<(i64, BusResult)>,
+    pub line: Vec<(i64, LineResultData)>,
+    pub trafo: Vec<(i64, TrafoResultData)>,
+    pub r#gen: Vec<(i64, GenResultData)>,
+    pub ext_grid: Vec<(i64, ExtGridResultData)>,
+    pub load: Vec<(i64, LoadResultData)>,
+    pub sgen: Vec<(i64, SGenResultData)>,
+    pub shunt: Vec<(i64, ShuntResultData)>,
+}
+
+/// Receives the tables of a network one at a time.
+trait TableSink {
+    type Error;
+
+    /// `indexed` tables get an explicit index column in flat formats;
+    /// element tables carry their index as an `index` field instead.
+    fn table<T: Serialize + Default>(
+        &mut self,
+        key: &str,
+        rows: &[(i64, &T)],
+        indexed: bool,
+    ) -> Result<(), Self::Error>;
+}
+
+/// Rows of an element table with their pandapower index filled in.
+fn element_rows<T: ElementRow + Clone>(rows: &Option<Vec<T>>) -> Option<Vec<(i64, T)>> {
+    rows.as_ref().map(|rows| {
+        element_indices(rows)
+            .into_iter()
+            .zip(rows)
+            .map(|(i, r)| {
+                let mut r = r.clone();
+                *r.index_mut() = Some(i);
+                (i, r)
+            })
+            .collect()
+    })
+}
+
+fn indexed<T>(rows: &[(i64, T)]) -> Vec<(i64, &T)> {
+    rows.iter().map(|(i, r)| (*i, r)).collect()
+}
+
+/// Feeds every present table of `net` and `results` to `sink`.
+fn write_tables<S: TableSink>(
+    net: &Network,
+    results: Option<&NetworkResults>,
+    sink: &mut S,
+) -> Result<(), S::Error> {
+    let bus = net.bus.iter().map(|b| (b.index, b)).collect::<Vec<_>>();
+    sink.table("bus", &bus, false)?;
+    macro_rules! element {
+        ($($field:ident: $key:expr),* $(,)?) => {
+            $(
+                if let Some(rows) = element_rows(&net.$field) {
+                    sink.table($key, &indexed(&rows), false)?;
+                }
+            )*
+        };
+    }
+    element!(
+        r#gen: "gen",
+        load: "load",
+        line: "line",
+        trafo: "trafo",
+        shunt: "shunt",
+        ext_grid: "ext_grid",
+        sgen: "sgen",
+        switch: "switch",
+    );
+
+    if let Some(res) = results {
+        sink.table("res_bus", &indexed(&res.bus), true)?;
+        sink.table("res_line", &indexed(&res.line), true)?;
+        sink.table("res_trafo", &indexed(&res.trafo), true)?;
+        sink.table("res_gen", &indexed(&res.r#gen), true)?;
+        sink.table("res_ext_grid", &indexed(&res.ext_grid), true)?;
+        sink.table("res_load", &indexed(&res.load), true)?;
+        sink.table("res_sgen", &indexed(&res.sgen), true)?;
+        sink.table("res_shunt", &indexed(&res.shunt), true)?;
+    }
+    Ok(())
+}
+
+/// Collects tables as pandas `DataFrame`s in pandapower's JSON encoding.
+struct JsonSink(Map<String, Value>);
+
+impl TableSink for JsonSink {
+    type Error = serde_json::Error;
+
+    /// Writes the `split` orient (stored as a string, like pandapower).
+    /// Columns come from `T`'s fields; an `index` field becomes the frame
+    /// index instead.
+    fn table<T: Serialize + Default>(
+        &mut self,
+        key: &str,
+        rows: &[(i64, &T)],
+        _indexed: bool,
+    ) -> serde_json::Result<()> {
+        let columns = field_names(&T::default())?
+            .into_iter()
+            .filter(|c| c != "index")
+            .collect::<Vec<_>>();
+        let mut data = Vec::with_capacity(rows.len());
+        for (_, row) in rows {
+            let Value::Object(mut row) = serde_json::to_value(row)? else {
+                unreachable!("pandapower tables are structs")
+            };
+            data.push(
+                columns
+                    .iter()
+                    .map(|c| row.remove(c).unwrap_or(Value::Null))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        let dtype = columns
+            .iter()
+            .enumerate()
+            .map(|(c, name)| {
+                let values = data.iter().map(|r| &r[c]).filter(|v| !v.is_null());
+                (name.clone(), Value::from(infer_dtype(values)))
+            })
+            .collect::<Map<_, _>>();
+        let index = rows.iter().map(|(i, _)| *i).collect::<Vec<_>>();
+        let object = json!({ "columns": columns, "index": index, "data": data });
+        self.0.insert(
+            key.to_string(),
+            json!({
+                "_module": "pandas.core.frame",
+                "_class": "DataFrame",
+                "_object": object.to_string(),
+                "orient": "split",
+                "dtype": dtype,
+                "is_multiindex": false,
+                "is_multicolumn": false,
+            }),
+        );
+        Ok(())
+    }
+}
+
+/// Field names of a struct in declaration order, read from the header row
+/// the CSV serializer produces for it.
+fn field_names<T: Serialize>(value: &T) -> serde_json::Result<Vec<String>> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.serialize(value).map_err(serde::ser::Error::custom)?;
+    let bytes = wtr.into_inner().map_err(serde::ser::Error::custom)?;
+    let mut rdr = csv::Reader::from_reader(bytes.as_slice());
+    let headers = rdr.headers().map_err(serde::ser::Error::custom)?;
+    Ok(headers.iter().map(str::to_string).collect())
+}
+
+/// pandas dtype of a column from its non-null values.
+fn infer_dtype<'a>(mut values: impl Iterator<Item = &'a Value>) -> &'static str {
+    match values.next() {
+        Some(Value::Bool(_)) => "bool",
+        Some(Value::Number(n)) if n.is_f64() => "float64",
+        Some(Value::Number(_)) => {
+            if values.any(|v| v.as_number().is_some_and(|n| n.is_f64())) {
+                "float64"
+            } else {
+                "int64"
+            }
+        }
+        _ => "object",
+    }
+}
+
+/// Converts a network (and optionally its results) into a pandapower JSON
+/// document that `pandapower.from_json` can open and
+/// [`load_pandapower_json_obj`] reads back.
+pub fn to_pandapower_json(
+    net: &Network,
+    results: Option<&NetworkResults>,
+) -> serde_json::Result<Value> {
+    let mut sink = JsonSink(Map::new());
+    write_tables(net, results, &mut sink)?;
+    let mut object = sink.0;
+    if let Some(res) = results {
+        object.insert("converged".into(), res.converged.into());
+    }
+    object.insert("name".into(), "".into());
+    object.insert("f_hz".into(), net.f_hz.into());
+    object.insert("sn_mva".into(), net.sn_mva.into());
+    if let Some(std_types) = &net.std_types {
+        object.insert("std_types".into(), serde_json::to_value(std_types)?);
+    }
+    object.insert("version".into(), PP_VERSION.into());
+    object.insert("format_version".into(), PP_FORMAT_VERSION.into());
+
+    Ok(json!({
+        "_module": "pandapower.auxiliary",
+        "_class": "pandapowerNet",
+        "_object": object,
+    }))
+}
+
+/// Writes a network (and optionally its results) as a pandapower JSON file.
+pub fn save_pandapower_json(
+    net: &Network,
+    results: Option<&NetworkResults>,
+    path: impl AsRef<Path>,
+) -> Result<(), std::io::Error> {
+    let json = to_pandapower_json(net, results)?;
+    let file = File::create(path)?;
+    serde_json::to_writer(file, &json)?;
+    Ok(())
+}
+
+/// Renders one table as CSV text.
+fn csv_table<T: Serialize + Default>(rows: &[(i64, &T)], indexed: bool) -> csv::Result<String> {
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    let fields = field_names(&T::default()).map_err(std::io::Error::other)?;
+    if indexed {
+        wtr.write_field("index")?;
+    }
+    wtr.write_record(&fields)?;
+    for (i, row) in rows {
+        if indexed {
+            wtr.write_field(i.to_string())?;
+        }
+        wtr.serialize(row)?;
+    }
+    let bytes = wtr.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8(bytes).expect("csv output is UTF-8"))
+}
+
+/// Collects tables as `<key>.csv` files, skipping empty ones like the
+/// reader does.
+struct CsvSink(Vec<(String, String)>);
+
+impl TableSink for CsvSink {
+    type Error = csv::Error;
+
+    fn table<T: Serialize + Default>(
+        &mut self,
+        key: &str,
+        rows: &[(i64, &T)],
+        indexed: bool,
+    ) -> csv::Result<()> {
+        if !rows.is_empty() {
+            self.0
+                .push((format!("{key}.csv"), csv_table(rows, indexed)?));
+        }
+        Ok(())
+    }
+}
+
+/// Renders all tables of a network as `(file name, CSV text)` pairs.
+fn csv_files(
+    net: &Network,
+    results: Option<&NetworkResults>,
+) -> Result<Vec<(String, String)>, std::io::Error> {
+    let mut sink = CsvSink(Vec::new());
+    write_tables(net, results, &mut sink)?;
+    Ok(sink.0)
+}
+
+/// Writes a network (and optionally its results) as a ZIP of CSV files in
+/// the layout [`load_csv_zip`] reads.
+pub fn write_csv_zip<W: Write + Seek>(
+    net: &Network,
+    results: Option<&NetworkResults>,
+    writer: W,
+) -> Result<(), std::io::Error> {
+    let mut zip = zip::ZipWriter::new(writer);
+    for (name, text) in csv_files(net, results)? {
+        zip.start_file(name, SimpleFileOptions::default())?;
+        zip.write_all(text.as_bytes())?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Writes a network (and optionally its results) as a CSV-zip file.
+pub fn save_csv_zip(
+    net: &Network,
+    results: Option<&NetworkResults>,
+    path: impl AsRef<Path>,
+) -> Result<(), std::io::Error> {
+    write_csv_zip(net, results, File::create(path)?)
+}
+
+impl ToCSV for Network {
+    fn save_csv(&self, folder: &str) -> Result<(), std::io::Error> {
+        fs::create_dir_all(folder)?;
+        for (name, text) in csv_files(self, None)? {
+            fs::write(Path::new(folder).join(name), text)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// Equal up to the last bit of floats, which serde_json does not
+    /// round-trip exactly without its `float_roundtrip` feature.
+    fn close(a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::Number(x), Value::Number(y)) => {
+                let (x, y) = (x.as_f64().unwrap(), y.as_f64().unwrap());
+                (x - y).abs() <= 1e-12 * x.abs().max(1.0)
+            }
+            (Value::Object(x), Value::Object(y)) => {
+                x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| close(v, w)))
+            }
+            _ => a == b,
+        }
+    }
+
+    /// Compares two networks table by table and row by row.
+    fn same(a: &Network, b: &Network) {
+        let (Value::Object(a), Value::Object(b)) = (
+            serde_json::to_value(a).unwrap(),
+            serde_json::to_value(b).unwrap(),
+        ) else {
+            unreachable!()
+        };
+        assert_eq!(a.keys().collect::<Vec<_>>(), b.keys().collect::<Vec<_>>());
+        for (key, table) in &a {
+            match (table, &b[key]) {
+                (Value::Array(x), Value::Array(y)) => {
+                    assert_eq!(x.len(), y.len(), "{key}");
+                    for (i, (x, y)) in x.iter().zip(y).enumerate() {
+                        assert!(close(x, y), "{key}[{i}]: {x} != {y}");
+                    }
+                }
+                (x, y) => assert!(close(x, y), "{key}: {x} != {y}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let net = load_pandapower_json(&format!("{}/cases/networks.json", dir));
+        let json = to_pandapower_json(&net, None).unwrap();
+        let back = load_pandapower_json_obj(json.as_object().unwrap());
+        same(&net, &back);
+    }
+
+    /// Index, columns and rows of a frame in a written pandapower JSON.
+    fn json_frame(json: &Value, key: &str) -> (Vec<i64>, Vec<String>, Vec<Vec<Value>>) {
+        let frame: Value =
+            serde_json::from_str(json["_object"][key]["_object"].as_str().unwrap()).unwrap();
+        let index = serde_json::from_value(frame["index"].clone()).unwrap();
+        let columns = serde_json::from_value(frame["columns"].clone()).unwrap();
+        let data = serde_json::from_value(frame["data"].clone()).unwrap();
+        (index, columns, data)
+    }
+
+    /// Index and one column of a CSV table in a written CSV zip.
+    fn csv_column(path: &Path, key: &str, column: &str) -> Vec<(i64, f64)> {
+        let mut zip = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+        let mut rdr = csv::Reader::from_reader(zip.by_name(key).unwrap());
+        let headers = rdr.headers().unwrap().clone();
+        let col = headers.iter().position(|h| h == column).unwrap();
+        rdr.records()
+            .map(|r| {
+                let r = r.unwrap();
+                (r[0].parse().unwrap(), r[col].parse().unwrap())
+            })
+            .collect()
+    }
+
+    fn keys<T>(rows: &[(i64, T)]) -> Vec<i64> {
+        rows.iter().map(|(i, _)| *i).collect()
+    }
+
+    #[test]
+    /// Non-contiguous element indices and the result tables survive both
+    /// formats, with `res_*` rows keyed like their elements.
+    fn test_round_trip_with_results() {
+        use crate::basic::ecs::plugin::default_app;
+        use crate::basic::ecs::post_processing::PostProcessing;
+        use crate::io::pandapower::ecs_net_conv::ToPandapowerNet;
+        use crate::prelude::PPNetwork;
+
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let mut net = load_csv_zip(&format!("{}/cases/IEEE118/data.zip", dir)).unwrap();
+        // Gaps as left behind by dropping rows in pandapower.
+        for (i, l) in net.line.as_mut().unwrap().iter_mut().enumerate() {
+            l.index = Some(3 * i as i64 + 5);
+        }
+        for (i, l) in net.load.as_mut().unwrap().iter_mut().enumerate() {
+            l.index = Some(2 * i as i64 + 100);
+        }
+        let line_index = element_indices(net.line.as_ref().unwrap());
+        let load_index = element_indices(net.load.as_ref().unwrap());
+
+        let mut app = default_app();
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+        app.post_process();
+        let world = app.world_mut();
+        let exported = world.to_pandapower_net();
+        let results = world.pandapower_results();
+        assert!(results.converged);
+        assert_eq!(element_indices(exported.line.as_ref().unwrap()), line_index);
+        assert_eq!(element_indices(exported.load.as_ref().unwrap()), load_index);
+        assert_eq!(keys(&results.line), line_index);
+        assert_eq!(keys(&results.load), load_index);
+
+        let json = to_pandapower_json(&exported, Some(&results)).unwrap();
+        same(&exported, &load_pandapower_json_obj(json.as_object().unwrap()));
+        let (index, columns, data) = json_frame(&json, "res_line");
+        assert_eq!(index, line_index);
+        let col = columns.iter().position(|c| c == "loading_percent").unwrap();
+        for ((_, r), row) in results.line.iter().zip(&data) {
+            assert!((row[col].as_f64().unwrap() - r.loading_percent).abs() < 1e-12);
+        }
+        let (index, columns, data) = json_frame(&json, "res_bus");
+        assert_eq!(index, keys(&results.bus));
+        let col = columns.iter().position(|c| c == "vm_pu").unwrap();
+        for ((_, r), row) in results.bus.iter().zip(&data) {
+            assert!((row[col].as_f64().unwrap() - r.vm_pu).abs() < 1e-12);
+        }
+
+        let path = env::temp_dir().join(format!("rustpower_res_{}.zip", std::process::id()));
+        save_csv_zip(&exported, Some(&results), &path).unwrap();
+        let back = load_csv_zip(path.to_str().unwrap()).unwrap();
+        let res_line = csv_column(&path, "res_line.csv", "loading_percent");
+        let res_bus = csv_column(&path, "res_bus.csv", "vm_pu");
+        fs::remove_file(&path).unwrap();
+        same(&exported, &back);
+        assert_eq!(res_line.iter().map(|(i, _)| *i).collect::<Vec<_>>(), line_index);
+        for ((_, r), (_, v)) in results.line.iter().zip(&res_line) {
+            assert!((v - r.loading_percent).abs() < 1e-12);
+        }
+        for ((i, r), (j, v)) in results.bus.iter().zip(&res_bus) {
+            assert_eq!(i, j);
+            assert!((v - r.vm_pu).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_csv_zip_round_trip() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let mut net = load_csv_zip(&format!("{}/cases/IEEE118/data.zip", dir)).unwrap();
+        let path = env::temp_dir().join(format!("rustpower_{}.zip", std::process::id()));
+        save_csv_zip(&net, None, &path).unwrap();
+        let back = load_csv_zip(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        // The case has no index column; rows are written with their position.
+        fn positions<T: ElementRow>(rows: &mut Option<Vec<T>>) {
+            for (pos, r) in rows.iter_mut().flatten().enumerate() {
+                r.index_mut().get_or_insert(pos as i64);
+            }
+        }
+        positions(&mut net.r#gen);
+        positions(&mut net.load);
+        positions(&mut net.line);
+        positions(&mut net.trafo);
+        positions(&mut net.ext_grid);
+        positions(&mut net.sgen);
+        positions(&mut net.shunt);
+        positions(&mut net.switch);
+        same(&net, &back);
+    }
+}
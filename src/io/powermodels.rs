@@ -0,0 +1,283 @@
+//! PowerModels.jl network data (JSON) import.
+//!
+//! The JSON written by PowerModels (`PowerModels.export_json` or `JSON`
+//! of a parsed network dictionary) is read into a [`MatpowerCase`], which
+//! then converts into a [`Network`] the same way MATPOWER cases do. Both
+//! per-unit data (`"per_unit": true`, angles in radians) and mixed-unit
+//! data (MW, MVAr, degrees) are accepted.
+//!
+//! PowerModels keeps loads and shunts as separate components; they are
+//! summed into the demand and shunt columns of their bus. Branch shunts
+//! that differ between the two ends (`g_fr`/`b_fr` vs `g_to`/`b_to`) are
+//! split into the symmetric MATPOWER charging plus bus shunts at the
+//! branch ends, which gives the same admittance matrix. Costs, storage,
+//! switches and DC lines are not read.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::io::matpower::*;
+use crate::io::pandapower::Network;
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+/// Components of a table (`"bus"`, `"gen"`, ...) ordered by their index.
+fn components<'a>(data: &'a Map<String, Value>, table: &str) -> Vec<&'a Map<String, Value>> {
+    let mut rows = data
+        .get(table)
+        .and_then(Value::as_object)
+        .map(|t| t.values().filter_map(Value::as_object).collect::<Vec<_>>())
+        .unwrap_or_default();
+    rows.sort_by_key(|c| c.get("index").and_then(Value::as_i64).unwrap_or(0));
+    rows
+}
+
+fn num(c: &Map<String, Value>, key: &str, default: f64) -> f64 {
+    c.get(key).and_then(Value::as_f64).unwrap_or(default)
+}
+
+fn int(c: &Map<String, Value>, key: &str, default: i64) -> i64 {
+    c.get(key).and_then(Value::as_i64).unwrap_or(default)
+}
+
+fn bus_of(c: &Map<String, Value>, key: &str) -> Result<i64, Error> {
+    c.get(key)
+        .and_then(Value::as_i64)
+        .ok_or_else(|| invalid(format!("component without '{key}'")))
+}
+
+/// Converts a PowerModels network dictionary.
+pub fn powermodels_case(data: &Map<String, Value>) -> Result<MatpowerCase, Error> {
+    let base = num(data, "baseMVA", 100.0);
+    let per_unit = data
+        .get("per_unit")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    // Powers in MW/MVAr and angles in degrees.
+    let (power, angle) = if per_unit {
+        (base, 180.0 / std::f64::consts::PI)
+    } else {
+        (1.0, 1.0)
+    };
+    let mut case = MatpowerCase {
+        name: data
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or("case")
+            .to_string(),
+        base_mva: base,
+        ..Default::default()
+    };
+
+    let buses = components(data, "bus");
+    if buses.is_empty() {
+        return Err(invalid("network has no buses"));
+    }
+    let mut names = Vec::new();
+    for b in &buses {
+        case.bus.push(MpBus {
+            bus_i: bus_of(b, "bus_i").or_else(|_| bus_of(b, "index"))?,
+            bus_type: int(b, "bus_type", 1) as i32,
+            area: int(b, "area", 1),
+            vm: num(b, "vm", 1.0),
+            va: num(b, "va", 0.0) * angle,
+            base_kv: num(b, "base_kv", 0.0),
+            zone: int(b, "zone", 1),
+            vmax: num(b, "vmax", 1.1),
+            vmin: num(b, "vmin", 0.9),
+            ..Default::default()
+        });
+        names.push(b.get("name").and_then(Value::as_str).map(str::to_string));
+    }
+    if names.iter().all(Option::is_some) {
+        case.bus_name = Some(names.into_iter().flatten().collect());
+    }
+    let position = case
+        .bus
+        .iter()
+        .enumerate()
+        .map(|(i, b)| (b.bus_i, i))
+        .collect::<HashMap<_, _>>();
+    let pos = |id: i64| {
+        position
+            .get(&id)
+            .copied()
+            .ok_or_else(|| invalid(format!("unknown bus {id}")))
+    };
+
+    for l in components(data, "load") {
+        if int(l, "status", 1) != 0 {
+            let b = &mut case.bus[pos(bus_of(l, "load_bus")?)?];
+            b.pd += num(l, "pd", 0.0) * power;
+            b.qd += num(l, "qd", 0.0) * power;
+        }
+    }
+    for s in components(data, "shunt") {
+        if int(s, "status", 1) != 0 {
+            let b = &mut case.bus[pos(bus_of(s, "shunt_bus")?)?];
+            b.gs += num(s, "gs", 0.0) * power;
+            b.bs += num(s, "bs", 0.0) * power;
+        }
+    }
+
+    for br in components(data, "branch") {
+        let (fbus, tbus) = (bus_of(br, "f_bus")?, bus_of(br, "t_bus")?);
+        let status = int(br, "br_status", 1) as i32;
+        let tap = num(br, "tap", 1.0);
+        let shift = num(br, "shift", 0.0) * angle;
+        let transformer = br
+            .get("transformer")
+            .and_then(Value::as_bool)
+            .unwrap_or(tap != 1.0 || shift != 0.0);
+        let (g_fr, b_fr) = (num(br, "g_fr", 0.0), num(br, "b_fr", 0.0));
+        let (g_to, b_to) = (num(br, "g_to", 0.0), num(br, "b_to", 0.0));
+        // MATPOWER splits the charging evenly; the rest becomes bus shunts
+        // (behind the tap at the from end, like the branch shunt itself).
+        let b = 2.0 * b_fr.min(b_to);
+        if status != 0 {
+            let t2 = if transformer { tap * tap } else { 1.0 };
+            let from = &mut case.bus[pos(fbus)?];
+            from.gs += g_fr / t2 * base;
+            from.bs += (b_fr - b / 2.0) / t2 * base;
+            let to = &mut case.bus[pos(tbus)?];
+            to.gs += g_to * base;
+            to.bs += (b_to - b / 2.0) * base;
+        }
+        case.branch.push(MpBranch {
+            fbus,
+            tbus,
+            r: num(br, "br_r", 0.0),
+            x: num(br, "br_x", 0.0),
+            b,
+            rate_a: num(br, "rate_a", 0.0) * power,
+            rate_b: num(br, "rate_b", 0.0) * power,
+            rate_c: num(br, "rate_c", 0.0) * power,
+            ratio: if transformer { tap } else { 0.0 },
+            angle: shift,
+            status,
+            angmin: num(br, "angmin", -360.0 / angle) * angle,
+            angmax: num(br, "angmax", 360.0 / angle) * angle,
+            extra: Vec::new(),
+        });
+    }
+    for g in components(data, "gen") {
+        case.r#gen.push(MpGen {
+            bus: bus_of(g, "gen_bus")?,
+            pg: num(g, "pg", 0.0) * power,
+            qg: num(g, "qg", 0.0) * power,
+            qmax: num(g, "qmax", 0.0) * power,
+            qmin: num(g, "qmin", 0.0) * power,
+            vg: num(g, "vg", 1.0),
+            mbase: num(g, "mbase", base),
+            status: int(g, "gen_status", 1) as i32,
+            pmax: num(g, "pmax", 0.0) * power,
+            pmin: num(g, "pmin", 0.0) * power,
+            extra: Vec::new(),
+        });
+    }
+    Ok(case)
+}
+
+/// Parses the JSON text of a PowerModels network.
+pub fn parse_powermodels(text: &str) -> Result<MatpowerCase, Error> {
+    let value: Value = serde_json::from_str(text).map_err(|e| invalid(e.to_string()))?;
+    let data = value
+        .as_object()
+        .ok_or_else(|| invalid("PowerModels data is not a JSON object"))?;
+    powermodels_case(data)
+}
+
+/// Reads a PowerModels JSON file.
+pub fn load_powermodels(path: impl AsRef<Path>) -> Result<MatpowerCase, Error> {
+    parse_powermodels(&fs::read_to_string(path)?)
+}
+
+/// Reads a PowerModels JSON file as a [`Network`], ready to be inserted as
+/// [`PPNetwork`](crate::basic::ecs::elements::PPNetwork).
+pub fn load_powermodels_network(path: impl AsRef<Path>) -> Result<Network, Error> {
+    Ok(Network::from(&load_powermodels(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::elements::PPNetwork;
+    use crate::basic::ecs::network::{DataOps, PowerFlow, PowerGrid};
+    use crate::basic::ecs::post_processing::PostProcessing;
+    use crate::io::pandapower::ecs_net_conv::ToPandapowerNet;
+    use std::env;
+
+    /// Solved bus voltages `(vm_pu, va_degree)` in bus order.
+    fn solve(net: Network) -> Vec<(f64, f64)> {
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.run_pf();
+        grid.post_process();
+        let res = grid.pandapower_results();
+        assert!(res.converged);
+        res.bus
+            .iter()
+            .map(|(_, b)| (b.vm_pu, b.va_degree))
+            .collect()
+    }
+
+    fn json(name: &str) -> Value {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let path = format!("{dir}/cases/{name}");
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_case14_matches_matpower() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let mpc = load_matpower(format!("{dir}/cases/matpower/case14.m")).unwrap();
+        let case = powermodels_case(json("powermodels/case14.json").as_object().unwrap()).unwrap();
+        assert_eq!(case.bus.len(), 14);
+        assert_eq!(case.bus[8].bs, 19.0);
+        assert!((case.bus[2].pd - 94.2).abs() < 1e-12);
+        assert!((case.bus[3].va - -10.33).abs() < 1e-12);
+        assert_eq!(case.branch[7].ratio, 0.978);
+        assert_eq!(case.branch[0].ratio, 0.0);
+
+        let expected = solve(Network::from(&mpc));
+        for (i, (a, b)) in solve(Network::from(&case))
+            .into_iter()
+            .zip(expected)
+            .enumerate()
+        {
+            assert!((a.0 - b.0).abs() < 1e-9, "bus {i}: {a:?} {b:?}");
+            assert!((a.1 - b.1).abs() < 1e-7, "bus {i}: {a:?} {b:?}");
+        }
+    }
+
+    #[test]
+    fn test_asymmetric_branch_shunts() {
+        let mut data = json("powermodels/case14.json");
+        // Line 1-2 with an extra 0.01 p.u. charging at bus 1, transformer
+        // 4-7 (tap 0.978) with its magnetizing at the tapped bus 4.
+        data["branch"]["1"]["b_fr"] = 0.0364.into();
+        data["branch"]["1"]["g_fr"] = 0.002.into();
+        data["branch"]["8"]["b_fr"] = 0.02.into();
+        data["per_unit"] = false.into();
+        let case = powermodels_case(data.as_object().unwrap()).unwrap();
+
+        assert!((case.branch[0].b - 0.0528).abs() < 1e-12);
+        assert!((case.bus[0].bs - 1.0).abs() < 1e-9);
+        assert!((case.bus[0].gs - 0.2).abs() < 1e-12);
+        assert!((case.bus[1].bs).abs() < 1e-12);
+        assert_eq!(case.branch[7].b, 0.0);
+        assert!((case.bus[3].bs - 2.0 / (0.978 * 0.978)).abs() < 1e-9);
+        // Without per-unit scaling the stored values are taken as MW.
+        assert_eq!(case.bus[2].pd, 0.942);
+
+        assert!(parse_powermodels("[]").is_err());
+        assert!(parse_powermodels(r#"{"bus": {}}"#).is_err());
+    }
+}
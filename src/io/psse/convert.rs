@@ -0,0 +1,603 @@
+//! Conversion of PSS/E RAW cases into pandapower [`Network`]s.
+//!
+//! Buses keep their PSS/E number as index, their name (or number when
+//! unnamed) and zone. The first in-service generator at the swing bus
+//! becomes the external grid, generators at load buses become static
+//! generators. Branch line-end shunts, transformer magnetizing admittance
+//! and switched shunts (at their initial admittance) become shunts; system
+//! switching devices become bus-bus switches, which need one of the switch
+//! plugins to take effect.
+//!
+//! Transformers are mapped onto the tap-on-hv model of the transformer
+//! admittance setup: the winding 1 bus is `hv_bus` and the ratio between
+//! the two off-nominal turns ratios is applied as a single tap, with the
+//! series impedance referred to that side. Three-winding transformers get
+//! an extra star bus and one two-winding transformer per winding. Negative
+//! star-equivalent reactances cannot be represented and lose their sign.
+//! Areas have no counterpart in a [`Network`] and are only kept in the
+//! [`RawCase`].
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::io::Error;
+use std::path::Path;
+
+use super::raw::*;
+use crate::io::pandapower::*;
+
+fn non_empty<T>(rows: Vec<T>) -> Option<Vec<T>> {
+    (!rows.is_empty()).then_some(rows)
+}
+
+/// One winding-to-winding (or winding-to-star) branch of a transformer in
+/// system per unit, before it is turned into a [`Transformer`].
+struct TrafoBranch {
+    hv: i64,
+    lv: i64,
+    /// Off-nominal ratio at the hv side.
+    ratio: f64,
+    shift: f64,
+    r: f64,
+    x: f64,
+    sn_mva: f64,
+    rate_a: f64,
+    in_service: bool,
+    name: Option<String>,
+}
+
+struct Builder<'a> {
+    case: &'a RawCase,
+    net: Network,
+    /// Position of each bus number in `net.bus`.
+    pos: HashMap<i64, usize>,
+    load: Vec<Load>,
+    shunt: Vec<Shunt>,
+    trafo: Vec<Transformer>,
+}
+
+impl Builder<'_> {
+    fn bus(&self, number: i64) -> Option<i64> {
+        self.pos.contains_key(&number).then_some(number)
+    }
+
+    fn at(&self, bus: i64) -> &Bus {
+        &self.net.bus[self.pos[&bus]]
+    }
+
+    fn vn_kv(&self, bus: i64) -> f64 {
+        self.at(bus).vn_kv
+    }
+
+    /// Adds a shunt absorbing `g + jb` (system per unit) at `bus`.
+    fn add_shunt(&mut self, bus: i64, p_mw: f64, q_mvar: f64, in_service: bool, name: &str) {
+        if p_mw == 0.0 && q_mvar == 0.0 {
+            return;
+        }
+        self.shunt.push(Shunt {
+            index: None,
+            bus,
+            p_mw,
+            q_mvar,
+            vn_kv: self.vn_kv(bus),
+            step: 1,
+            max_step: 1,
+            in_service,
+            name: (!name.is_empty()).then(|| name.to_string()),
+        });
+    }
+
+    fn add_trafo(&mut self, t: TrafoBranch) {
+        let scale = t.sn_mva / self.case.sbase;
+        self.trafo.push(Transformer {
+            hv_bus: t.hv as i32,
+            lv_bus: t.lv as i32,
+            sn_mva: t.sn_mva,
+            vn_hv_kv: self.vn_kv(t.hv),
+            vn_lv_kv: self.vn_kv(t.lv),
+            vk_percent: t.r.hypot(t.x) * 100.0 * scale,
+            vkr_percent: t.r * 100.0 * scale,
+            pfe_kw: 0.0,
+            i0_percent: 0.0,
+            shift_degree: t.shift,
+            tap_side: Some("hv".into()),
+            tap_neutral: Some(0.0),
+            tap_pos: Some(1.0),
+            tap_step_percent: Some((t.ratio - 1.0) * 100.0),
+            tap_phase_shifter: false,
+            max_loading_percent: (t.rate_a > 0.0).then(|| t.rate_a / t.sn_mva * 100.0),
+            df: 1.0,
+            parallel: 1,
+            in_service: t.in_service,
+            name: t.name,
+            ..Default::default()
+        });
+    }
+
+    /// Off-nominal turns ratio of winding `w` at `bus`, in per unit of the
+    /// bus base voltage.
+    fn turns_ratio(&self, t: &RawTransformer, w: usize, bus: i64) -> f64 {
+        let winding = &t.windings[w];
+        let vn = self.vn_kv(bus);
+        match t.cw {
+            2 => winding.windv / vn,
+            3 if winding.nomv > 0.0 => winding.windv * winding.nomv / vn,
+            _ => winding.windv,
+        }
+    }
+
+    /// Impedance `k` (1-2, 2-3 or 3-1) of `t` in system per unit, with
+    /// `w` the winding whose voltage base it is given on.
+    fn impedance(&self, t: &RawTransformer, k: usize, w: usize, bus: i64) -> (f64, f64) {
+        let sb = if t.sbase[k] > 0.0 {
+            t.sbase[k]
+        } else {
+            self.case.sbase
+        };
+        let (r, x) = match t.cz {
+            2 => (t.r[k], t.x[k]),
+            3 => {
+                // R is the load loss in W, X the impedance magnitude.
+                let r = t.r[k] * 1e-6 / sb;
+                (r, (t.x[k] * t.x[k] - r * r).max(0.0).sqrt())
+            }
+            _ => return (t.r[k], t.x[k]),
+        };
+        let nomv = t.windings[w].nomv;
+        let v = if nomv > 0.0 {
+            nomv / self.vn_kv(bus)
+        } else {
+            1.0
+        };
+        let f = self.case.sbase / sb * v * v;
+        (r * f, x * f)
+    }
+
+    /// Magnetizing admittance `(g, b)` of `t` in system per unit.
+    fn magnetizing(&self, t: &RawTransformer) -> (f64, f64) {
+        if t.cm != 2 {
+            return (t.mag1, t.mag2);
+        }
+        // No-load loss in W and exciting current in pu on SBASE1-2.
+        let sb = if t.sbase[0] > 0.0 {
+            t.sbase[0]
+        } else {
+            self.case.sbase
+        };
+        let g = t.mag1 * 1e-6 / self.case.sbase;
+        let y = t.mag2 * sb / self.case.sbase;
+        (g, -(y * y - g * g).max(0.0).sqrt())
+    }
+
+    fn add_transformer(&mut self, t: &RawTransformer) {
+        let Some(buses) = t
+            .buses
+            .iter()
+            .take(if t.is_three_winding() { 3 } else { 2 })
+            .map(|&b| self.bus(b))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return;
+        };
+        let name = (!t.name.is_empty()).then(|| t.name.clone());
+        let base = self.case.sbase;
+        let (g, b) = self.magnetizing(t);
+        self.add_shunt(buses[0], g * base, -b * base, t.status != 0, &t.name);
+
+        if !t.is_three_winding() {
+            let t1 = self.turns_ratio(t, 0, buses[0]);
+            let t2 = self.turns_ratio(t, 1, buses[1]);
+            let (r, x) = self.impedance(t, 0, 0, buses[0]);
+            let sn_mva = if t.sbase[0] > 0.0 { t.sbase[0] } else { base };
+            // I -- t1:1 -- Z -- 1:t2 -- J is a single t1/t2 tap at I with
+            // the impedance referred through t2.
+            self.add_trafo(TrafoBranch {
+                hv: buses[0],
+                lv: buses[1],
+                ratio: t1 / t2,
+                shift: t.windings[0].ang,
+                r: r * t2 * t2,
+                x: x * t2 * t2,
+                sn_mva,
+                rate_a: t.windings[0].rate_a,
+                in_service: t.status != 0,
+                name,
+            });
+            return;
+        }
+
+        let star = self.net.bus.iter().map(|b| b.index).max().unwrap_or(0) + 1;
+        let zone = self.at(buses[0]).zone;
+        self.pos.insert(star, self.net.bus.len());
+        self.net.bus.push(Bus {
+            index: star,
+            in_service: t.status != 0,
+            name: Some(format!("{} star", name.as_deref().unwrap_or("3w"))),
+            r#type: Some("n".into()),
+            vn_kv: self.vn_kv(buses[0]),
+            zone,
+            ..Default::default()
+        });
+        let z12 = self.impedance(t, 0, 0, buses[0]);
+        let z23 = self.impedance(t, 1, 1, buses[1]);
+        let z31 = self.impedance(t, 2, 2, buses[2]);
+        let star_z = [
+            ((z12.0 + z31.0 - z23.0) / 2.0, (z12.1 + z31.1 - z23.1) / 2.0),
+            ((z12.0 + z23.0 - z31.0) / 2.0, (z12.1 + z23.1 - z31.1) / 2.0),
+            ((z23.0 + z31.0 - z12.0) / 2.0, (z23.1 + z31.1 - z12.1) / 2.0),
+        ];
+        // STAT 2, 3 and 4 take winding 2, 3 and 1 out of service.
+        let out = match t.status {
+            0 => [true; 3],
+            2 => [false, true, false],
+            3 => [false, false, true],
+            4 => [true, false, false],
+            _ => [false; 3],
+        };
+        for w in 0..3 {
+            let (r, x) = star_z[w];
+            let sn_mva = if t.sbase[w] > 0.0 { t.sbase[w] } else { base };
+            self.add_trafo(TrafoBranch {
+                hv: buses[w],
+                lv: star,
+                ratio: self.turns_ratio(t, w, buses[w]),
+                shift: t.windings[w].ang,
+                r,
+                x,
+                sn_mva,
+                rate_a: t.windings[w].rate_a,
+                in_service: !out[w],
+                name: name.clone(),
+            });
+        }
+    }
+}
+
+impl From<&RawCase> for Network {
+    fn from(case: &RawCase) -> Self {
+        let base = case.sbase;
+        let net = Network {
+            sn_mva: base,
+            f_hz: case.basfrq,
+            bus: case
+                .buses
+                .iter()
+                .map(|b| Bus {
+                    index: b.number,
+                    in_service: b.ide != 4,
+                    max_vm_pu: Some(b.nvhi),
+                    min_vm_pu: Some(b.nvlo),
+                    name: Some(if b.name.is_empty() {
+                        b.number.to_string()
+                    } else {
+                        b.name.clone()
+                    }),
+                    r#type: Some("b".into()),
+                    vn_kv: if b.base_kv > 0.0 { b.base_kv } else { 1.0 },
+                    zone: Some(b.zone),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let mut m = Builder {
+            case,
+            pos: case
+                .buses
+                .iter()
+                .enumerate()
+                .map(|(i, b)| (b.number, i))
+                .collect(),
+            net,
+            load: Vec::new(),
+            shunt: Vec::new(),
+            trafo: Vec::new(),
+        };
+
+        for l in &case.loads {
+            let Some(bus) = m.bus(l.bus) else { continue };
+            let name = (!l.id.is_empty()).then(|| l.id.clone());
+            // Constant power, current and admittance parts as separate
+            // loads; YQ is negative for inductive load.
+            let parts = [
+                (l.pl, l.ql, 0.0, 0.0),
+                (l.ip, l.iq, 100.0, 0.0),
+                (l.yp, -l.yq, 0.0, 100.0),
+            ];
+            for (p_mw, q_mvar, const_i_percent, const_z_percent) in parts {
+                if p_mw == 0.0 && q_mvar == 0.0 {
+                    continue;
+                }
+                m.load.push(Load {
+                    bus,
+                    p_mw,
+                    q_mvar,
+                    const_i_percent,
+                    const_z_percent,
+                    in_service: l.status != 0,
+                    scaling: 1.0,
+                    name: name.clone(),
+                    ..Default::default()
+                });
+            }
+        }
+        for s in &case.fixed_shunts {
+            if let Some(bus) = m.bus(s.bus) {
+                m.add_shunt(bus, s.gl, -s.bl, s.status != 0, &s.id);
+            }
+        }
+        for s in &case.switched_shunts {
+            if let Some(bus) = m.bus(s.bus) {
+                m.add_shunt(bus, 0.0, -s.binit, s.status != 0, &s.id);
+            }
+        }
+
+        let mut r#gen = Vec::new();
+        let mut ext_grid: Vec<ExtGrid> = Vec::new();
+        let mut sgen = Vec::new();
+        for g in &case.generators {
+            let Some(bus) = m.bus(g.bus) else { continue };
+            let b = &case.buses[m.pos[&bus]];
+            let in_service = g.status != 0;
+            let name = (!g.id.is_empty()).then(|| g.id.clone());
+            match b.ide {
+                3 if in_service && !ext_grid.iter().any(|e| e.bus == bus) => {
+                    ext_grid.push(ExtGrid {
+                        index: None,
+                        bus,
+                        in_service,
+                        va_degree: b.va,
+                        vm_pu: g.vs,
+                        max_p_mw: Some(g.pt),
+                        min_p_mw: Some(g.pb),
+                        max_q_mvar: Some(g.qt),
+                        min_q_mvar: Some(g.qb),
+                        slack_weight: 1.0,
+                        name,
+                    })
+                }
+                1 => sgen.push(SGen {
+                    bus,
+                    name,
+                    p_mw: g.pg,
+                    q_mvar: g.qg,
+                    sn_mva: Some(g.mbase),
+                    scaling: 1.0,
+                    in_service,
+                    controllable: Some(true),
+                    ..Default::default()
+                }),
+                _ => r#gen.push(Gen {
+                    index: None,
+                    bus,
+                    controllable: Some(true),
+                    in_service,
+                    name,
+                    p_mw: g.pg,
+                    scaling: 1.0,
+                    sn_mva: Some(g.mbase),
+                    type_: None,
+                    vm_pu: g.vs,
+                    slack: false,
+                    max_p_mw: g.pt,
+                    min_p_mw: g.pb,
+                    max_q_mvar: g.qt,
+                    min_q_mvar: g.qb,
+                    slack_weight: 0.0,
+                }),
+            }
+        }
+
+        let mut line = Vec::new();
+        for br in &case.branches {
+            let (Some(from), Some(to)) = (m.bus(br.from), m.bus(br.to)) else {
+                continue;
+            };
+            let vn = m.vn_kv(from);
+            let z_base = vn * vn / base;
+            let in_service = br.status != 0;
+            line.push(Line {
+                from_bus: from,
+                to_bus: to,
+                length_km: 1.0,
+                r_ohm_per_km: br.r * z_base,
+                x_ohm_per_km: br.x * z_base,
+                c_nf_per_km: br.b / z_base / (2.0 * PI * case.basfrq) * 1e9,
+                g_us_per_km: 0.0,
+                max_i_ka: Some(br.rate_a / (3f64.sqrt() * vn)),
+                df: 1.0,
+                parallel: 1,
+                in_service,
+                type_: Some("ol".into()),
+                name: (!br.name.is_empty()).then(|| br.name.clone()),
+                ..Default::default()
+            });
+            m.add_shunt(from, br.gi * base, -br.bi * base, in_service, &br.name);
+            m.add_shunt(to, br.gj * base, -br.bj * base, in_service, &br.name);
+        }
+
+        let mut switch = Vec::new();
+        for s in &case.switching_devices {
+            let (Some(from), Some(to)) = (m.bus(s.from), m.bus(s.to)) else {
+                continue;
+            };
+            switch.push(Switch {
+                index: None,
+                bus: from,
+                element: to,
+                et: SwitchType::SwitchTwoBuses,
+                type_: Some("CB".into()),
+                closed: s.status != 0,
+                name: (!s.name.is_empty()).then(|| s.name.clone()),
+                z_ohm: 0.0,
+            });
+        }
+
+        for t in &case.transformers {
+            m.add_transformer(t);
+        }
+
+        let mut net = m.net;
+        net.load = non_empty(m.load);
+        net.shunt = non_empty(m.shunt);
+        net.trafo = non_empty(m.trafo);
+        net.r#gen = non_empty(r#gen);
+        net.ext_grid = non_empty(ext_grid);
+        net.sgen = non_empty(sgen);
+        net.line = non_empty(line);
+        net.switch = non_empty(switch);
+        net
+    }
+}
+
+/// Reads a PSS/E RAW file as a [`Network`], ready to be inserted as
+/// [`PPNetwork`](crate::basic::ecs::elements::PPNetwork).
+pub fn load_raw_network(path: impl AsRef<Path>) -> Result<Network, Error> {
+    Ok(Network::from(&load_raw(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::elements::PPNetwork;
+    use crate::basic::ecs::plugin::{SwitchPluginTypeA, default_app};
+    use crate::basic::ecs::post_processing::PostProcessing;
+    use crate::io::pandapower::ecs_net_conv::ToPandapowerNet;
+    use std::env;
+
+    /// Solved bus voltages `(vm_pu, va_degree)` in bus order, with closed
+    /// bus-bus switches merging their buses.
+    fn solve(net: Network) -> Vec<(f64, f64)> {
+        let mut app = default_app();
+        if net.switch.is_some() {
+            app.add_plugins(SwitchPluginTypeA);
+        }
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+        app.post_process();
+        let res = app.pandapower_results();
+        assert!(res.converged);
+        res.bus
+            .iter()
+            .map(|(_, b)| (b.vm_pu, b.va_degree))
+            .collect()
+    }
+
+    fn network(name: &str) -> Network {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        load_raw_network(format!("{}/cases/psse/{}.raw", dir, name)).unwrap()
+    }
+
+    /// runpf(case14) in MATPOWER.
+    const CASE14: [(f64, f64); 14] = [
+        (1.060, 0.0),
+        (1.045, -4.983),
+        (1.010, -12.725),
+        (1.018, -10.313),
+        (1.020, -8.774),
+        (1.070, -14.221),
+        (1.062, -13.360),
+        (1.090, -13.360),
+        (1.056, -14.939),
+        (1.051, -15.097),
+        (1.057, -14.791),
+        (1.055, -15.076),
+        (1.050, -15.156),
+        (1.036, -16.034),
+    ];
+
+    fn assert_case14(res: &[(f64, f64)]) {
+        for (i, ((vm, va), (e_vm, e_va))) in res.iter().zip(CASE14).enumerate() {
+            assert!((vm - e_vm).abs() < 6e-4, "bus {i}: vm {vm}");
+            assert!((va - e_va).abs() < 6e-4, "bus {i}: va {va}");
+        }
+    }
+
+    #[test]
+    fn test_ieee14_v33_matches_matpower() {
+        let net = network("ieee14_v33");
+        assert_eq!(net.bus.len(), 14);
+        assert_eq!(net.line.as_ref().unwrap().len(), 17);
+        assert_eq!(net.trafo.as_ref().unwrap().len(), 3);
+        assert_eq!(net.ext_grid.as_ref().unwrap().len(), 1);
+        assert_eq!(net.r#gen.as_ref().unwrap().len(), 4);
+        assert_eq!(net.shunt.as_ref().unwrap()[0].q_mvar, -19.0);
+        assert_case14(&solve(net));
+    }
+
+    #[test]
+    fn test_ieee14_v35_transformer_units() {
+        // Same grid with transformer ratios in kV and on nominal winding
+        // voltages, impedances on their own MVA base, a switched instead of
+        // a fixed shunt and line 13-14 ending at a bus behind a closed
+        // breaker.
+        let net = network("ieee14_v35");
+        assert_eq!(net.switch.as_ref().unwrap().len(), 1);
+        let res = solve(net);
+        assert_eq!(res.len(), 15);
+        assert_case14(&res);
+    }
+
+    const THREE_BUSES: &str = "\
+0, 100.0, 33, 0, 0, 50.0
+three winding test
+
+     1,'HV',138.0,3,1,1,1,1.02,0.0,1.1,0.9
+     2,'MV', 69.0,1,1,1,1,1.0,0.0,1.1,0.9
+     3,'LV', 13.8,1,1,1,1,1.0,0.0,1.1,0.9
+";
+
+    const LOADS: &str = "\
+0 / END OF BUS DATA
+     2,'1',1,1,1,40.0,15.0
+     3,'1',1,1,1,20.0,8.0
+0 / END OF LOAD DATA
+0 / END OF FIXED SHUNT DATA
+     1,'1',0.0,0.0,999.0,-999.0,1.02,0,100.0,0,1,0,0,1,1,100,999.0,-999.0
+0 / END OF GENERATOR DATA
+0 / END OF BRANCH DATA
+";
+
+    #[test]
+    fn test_three_winding_star_equivalent() {
+        let three = format!(
+            "{THREE_BUSES}{LOADS}\
+     1, 2, 3,'1',1,1,1,0,0,2,'T3',1
+ 0.001,0.08,100, 0.002,0.06,100, 0.0015,0.10,100, 1.0,0.0
+ 1.02,0,0,60
+ 0.99,0,0,40
+ 1.00,0,0,25
+0 / END OF TRANSFORMER DATA
+"
+        );
+        // Star impedances (Z12 + Z31 - Z23) / 2 etc. on an explicit bus.
+        let star = format!(
+            "{THREE_BUSES}     4,'STAR',138.0,1,1,1,1,1.0,0.0,1.1,0.9\n{LOADS}\
+     1, 4, 0,'1',1,1,1,0,0,2,'T1',1
+ 0.00025,0.06,100
+ 1.02,0,0,60
+ 1.0,0
+     2, 4, 0,'1',1,1,1,0,0,2,'T2',1
+ 0.00075,0.02,100
+ 0.99,0,0,40
+ 1.0,0
+     3, 4, 0,'1',1,1,1,0,0,2,'T3',1
+ 0.00125,0.04,100
+ 1.0,0,0,25
+ 1.0,0
+0 / END OF TRANSFORMER DATA
+"
+        );
+        let three = Network::from(&parse_raw(&three).unwrap());
+        let star = Network::from(&parse_raw(&star).unwrap());
+        assert_eq!(three.bus.len(), 4);
+        assert_eq!(three.bus[3].index, 4);
+        assert_eq!(three.trafo.as_ref().unwrap().len(), 3);
+        assert_eq!(three.f_hz, 50.0);
+
+        let (a, b) = (solve(three), solve(star));
+        assert!(a[2].0 < 0.99);
+        for (i, (a, b)) in a.into_iter().zip(b).enumerate() {
+            assert!((a.0 - b.0).abs() < 1e-9, "bus {i}: {a:?} {b:?}");
+            assert!((a.1 - b.1).abs() < 1e-9, "bus {i}: {a:?} {b:?}");
+        }
+    }
+}
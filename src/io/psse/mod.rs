@@ -0,0 +1,7 @@
+//! PSS/E RAW file import.
+
+pub mod convert;
+pub mod raw;
+
+pub use convert::*;
+pub use raw::*;
@@ -0,0 +1,683 @@
+//! Parser for PSS/E RAW power flow files, revisions 33 to 35.
+//!
+//! Sections are positional and each ends with a record starting with `0`.
+//! Buses, loads, fixed shunts, generators, branches, system switching
+//! devices, two- and three-winding transformers, areas, zones and switched
+//! shunts are read; the other sections (DC lines, FACTS, ...) are skipped.
+//! Only the fields needed for a power flow model are kept.
+
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+/// Bus data record.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawBus {
+    pub number: i64,
+    pub name: String,
+    pub base_kv: f64,
+    /// 1 = load, 2 = generator, 3 = swing, 4 = isolated.
+    pub ide: i32,
+    pub area: i64,
+    pub zone: i64,
+    pub vm: f64,
+    pub va: f64,
+    pub nvhi: f64,
+    pub nvlo: f64,
+}
+
+/// Load data record. `ip`/`iq` are constant-current and `yp`/`yq`
+/// constant-admittance components, all in MW/MVAr at 1 p.u. voltage (`yq`
+/// is negative for inductive load).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawLoad {
+    pub bus: i64,
+    pub id: String,
+    pub status: i32,
+    pub area: i64,
+    pub zone: i64,
+    pub pl: f64,
+    pub ql: f64,
+    pub ip: f64,
+    pub iq: f64,
+    pub yp: f64,
+    pub yq: f64,
+}
+
+/// Fixed shunt data record, in MW/MVAr at 1 p.u. voltage (`bl` positive
+/// for capacitive).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawFixedShunt {
+    pub bus: i64,
+    pub id: String,
+    pub status: i32,
+    pub gl: f64,
+    pub bl: f64,
+}
+
+/// Generator data record.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawGenerator {
+    pub bus: i64,
+    pub id: String,
+    pub pg: f64,
+    pub qg: f64,
+    pub qt: f64,
+    pub qb: f64,
+    pub vs: f64,
+    pub ireg: i64,
+    pub mbase: f64,
+    pub status: i32,
+    pub pt: f64,
+    pub pb: f64,
+}
+
+/// Non-transformer branch data record; impedances in p.u. on the system
+/// base, line-end shunts `gi`..`bj` in p.u.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawBranch {
+    pub from: i64,
+    pub to: i64,
+    pub ckt: String,
+    pub r: f64,
+    pub x: f64,
+    pub b: f64,
+    pub name: String,
+    pub rate_a: f64,
+    pub gi: f64,
+    pub bi: f64,
+    pub gj: f64,
+    pub bj: f64,
+    pub status: i32,
+    pub length: f64,
+}
+
+/// System switching device record (revision 34 and later).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawSwitchingDevice {
+    pub from: i64,
+    pub to: i64,
+    pub ckt: String,
+    pub x: f64,
+    pub rate_a: f64,
+    pub status: i32,
+    pub name: String,
+}
+
+/// Per-winding data of a transformer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawWinding {
+    /// Off-nominal ratio, in the unit selected by `cw`.
+    pub windv: f64,
+    /// Nominal winding voltage in kV (0 means the bus base voltage).
+    pub nomv: f64,
+    /// Phase shift in degrees.
+    pub ang: f64,
+    pub rate_a: f64,
+}
+
+/// Two- or three-winding transformer record. `buses[2]` is 0 for a
+/// two-winding transformer; `r`, `x` and `sbase` hold the 1-2, 2-3 and 3-1
+/// impedances in the unit selected by `cz`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawTransformer {
+    pub buses: [i64; 3],
+    pub ckt: String,
+    pub cw: i32,
+    pub cz: i32,
+    pub cm: i32,
+    pub mag1: f64,
+    pub mag2: f64,
+    pub name: String,
+    /// 0 = out of service, 1 = in service; for three windings 2, 3 or 4
+    /// take winding 2, 3 or 1 out of service.
+    pub status: i32,
+    pub r: [f64; 3],
+    pub x: [f64; 3],
+    pub sbase: [f64; 3],
+    pub vmstar: f64,
+    pub anstar: f64,
+    pub windings: Vec<RawWinding>,
+}
+
+impl RawTransformer {
+    pub fn is_three_winding(&self) -> bool {
+        self.buses[2] != 0
+    }
+}
+
+/// Area or zone record.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawNamed {
+    pub number: i64,
+    pub name: String,
+}
+
+/// Switched shunt record. `binit` is the admittance switched in, in MVAr at
+/// 1 p.u. voltage; `blocks` are `(steps, MVAr per step)` pairs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawSwitchedShunt {
+    pub bus: i64,
+    pub id: String,
+    pub status: i32,
+    pub binit: f64,
+    pub blocks: Vec<(i32, f64)>,
+}
+
+/// Contents of a RAW file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawCase {
+    pub revision: i32,
+    /// System base in MVA.
+    pub sbase: f64,
+    /// Base frequency in Hz.
+    pub basfrq: f64,
+    pub title: [String; 2],
+    pub buses: Vec<RawBus>,
+    pub loads: Vec<RawLoad>,
+    pub fixed_shunts: Vec<RawFixedShunt>,
+    pub generators: Vec<RawGenerator>,
+    pub branches: Vec<RawBranch>,
+    pub switching_devices: Vec<RawSwitchingDevice>,
+    pub transformers: Vec<RawTransformer>,
+    pub areas: Vec<RawNamed>,
+    pub zones: Vec<RawNamed>,
+    pub switched_shunts: Vec<RawSwitchedShunt>,
+}
+
+impl Default for RawCase {
+    fn default() -> Self {
+        Self {
+            revision: 33,
+            sbase: 100.0,
+            basfrq: 60.0,
+            title: Default::default(),
+            buses: Vec::new(),
+            loads: Vec::new(),
+            fixed_shunts: Vec::new(),
+            generators: Vec::new(),
+            branches: Vec::new(),
+            switching_devices: Vec::new(),
+            transformers: Vec::new(),
+            areas: Vec::new(),
+            zones: Vec::new(),
+            switched_shunts: Vec::new(),
+        }
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+/// Splits a record into fields. Fields are separated by commas or blanks,
+/// strings are quoted with `'` or `"`, `/` starts a comment and an empty
+/// field between two commas stands for the default value.
+fn fields(line: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&c) = chars.peek() else { break };
+        match c {
+            '/' => break,
+            ',' => {
+                chars.next();
+                out.push(String::new());
+                continue;
+            }
+            '\'' | '"' => {
+                chars.next();
+                out.push(chars.by_ref().take_while(|&q| q != c).collect());
+            }
+            _ => {
+                let mut s = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ',' && *c != '/')
+                {
+                    s.push(c);
+                }
+                out.push(s);
+            }
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        chars.next_if_eq(&',');
+    }
+    out
+}
+
+/// Field accessors with PSS/E defaults for missing or blank fields.
+struct Record(Vec<String>);
+
+impl Record {
+    fn raw(&self, i: usize) -> Option<&str> {
+        self.0.get(i).map(|s| s.trim()).filter(|s| !s.is_empty())
+    }
+
+    fn f64(&self, i: usize, default: f64) -> Result<f64, Error> {
+        match self.raw(i) {
+            Some(s) => s
+                .parse()
+                .map_err(|_| invalid(format!("invalid number '{s}'"))),
+            None => Ok(default),
+        }
+    }
+
+    fn int(&self, i: usize, default: i64) -> Result<i64, Error> {
+        match self.raw(i) {
+            Some(s) => s
+                .parse::<i64>()
+                .or_else(|_| s.parse::<f64>().map(|v| v as i64))
+                .map_err(|_| invalid(format!("invalid integer '{s}'"))),
+            None => Ok(default),
+        }
+    }
+
+    fn str(&self, i: usize) -> String {
+        self.raw(i).unwrap_or_default().to_string()
+    }
+
+    fn is_end(&self) -> bool {
+        matches!(self.raw(0), None | Some("0") | Some("Q"))
+    }
+}
+
+/// Sections of a RAW file in file order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Bus,
+    Load,
+    FixedShunt,
+    Generator,
+    Branch,
+    SwitchingDevice,
+    Transformer,
+    Area,
+    Zone,
+    SwitchedShunt,
+    /// A section whose records are not read.
+    Skipped,
+}
+
+fn sections(revision: i32) -> Vec<Section> {
+    use Section::*;
+    let mut s = vec![Bus, Load, FixedShunt, Generator, Branch];
+    if revision >= 34 {
+        s.push(SwitchingDevice);
+    }
+    s.extend([
+        Transformer,
+        Area,
+        Skipped, // two-terminal DC
+        Skipped, // VSC DC
+        Skipped, // impedance correction
+        Skipped, // multi-terminal DC
+        Skipped, // multi-section line
+        Zone,
+        Skipped, // inter-area transfer
+        Skipped, // owner
+        Skipped, // FACTS
+        SwitchedShunt,
+    ]);
+    s
+}
+
+/// Data records of a file: comment (`@!`) and blank lines removed.
+struct Lines<'a> {
+    inner: std::iter::Peekable<std::str::Lines<'a>>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            inner: text.lines().peekable(),
+        }
+    }
+
+    fn next_line(&mut self) -> Option<&'a str> {
+        self.inner
+            .by_ref()
+            .find(|l| !l.trim().is_empty() && !l.trim_start().starts_with("@!"))
+    }
+
+    /// Title lines may be blank, so only comments are skipped.
+    fn next_title(&mut self) -> String {
+        self.inner
+            .by_ref()
+            .find(|l| !l.trim_start().starts_with("@!"))
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    }
+
+    fn next_record(&mut self) -> Option<Record> {
+        self.next_line().map(|l| Record(fields(l)))
+    }
+
+    fn record(&mut self, what: &str) -> Result<Record, Error> {
+        self.next_record()
+            .ok_or_else(|| invalid(format!("unexpected end of file in {what}")))
+    }
+}
+
+impl RawBus {
+    fn parse(r: &Record) -> Result<Self, Error> {
+        Ok(Self {
+            number: r.int(0, 0)?,
+            name: r.str(1),
+            base_kv: r.f64(2, 0.0)?,
+            ide: r.int(3, 1)? as i32,
+            area: r.int(4, 1)?,
+            zone: r.int(5, 1)?,
+            vm: r.f64(7, 1.0)?,
+            va: r.f64(8, 0.0)?,
+            nvhi: r.f64(9, 1.1)?,
+            nvlo: r.f64(10, 0.9)?,
+        })
+    }
+}
+
+impl RawLoad {
+    fn parse(r: &Record) -> Result<Self, Error> {
+        Ok(Self {
+            bus: r.int(0, 0)?,
+            id: r.str(1),
+            status: r.int(2, 1)? as i32,
+            area: r.int(3, 0)?,
+            zone: r.int(4, 0)?,
+            pl: r.f64(5, 0.0)?,
+            ql: r.f64(6, 0.0)?,
+            ip: r.f64(7, 0.0)?,
+            iq: r.f64(8, 0.0)?,
+            yp: r.f64(9, 0.0)?,
+            yq: r.f64(10, 0.0)?,
+        })
+    }
+}
+
+impl RawFixedShunt {
+    fn parse(r: &Record) -> Result<Self, Error> {
+        Ok(Self {
+            bus: r.int(0, 0)?,
+            id: r.str(1),
+            status: r.int(2, 1)? as i32,
+            gl: r.f64(3, 0.0)?,
+            bl: r.f64(4, 0.0)?,
+        })
+    }
+}
+
+impl RawGenerator {
+    fn parse(r: &Record, revision: i32) -> Result<Self, Error> {
+        // Revision 34 inserted NREG after IREG.
+        let o = (revision >= 34) as usize;
+        Ok(Self {
+            bus: r.int(0, 0)?,
+            id: r.str(1),
+            pg: r.f64(2, 0.0)?,
+            qg: r.f64(3, 0.0)?,
+            qt: r.f64(4, 9999.0)?,
+            qb: r.f64(5, -9999.0)?,
+            vs: r.f64(6, 1.0)?,
+            ireg: r.int(7, 0)?,
+            mbase: r.f64(8 + o, 100.0)?,
+            status: r.int(14 + o, 1)? as i32,
+            pt: r.f64(16 + o, 9999.0)?,
+            pb: r.f64(17 + o, -9999.0)?,
+        })
+    }
+}
+
+impl RawBranch {
+    fn parse(r: &Record, revision: i32) -> Result<Self, Error> {
+        // Revision 34 added NAME and twelve ratings.
+        let (name, rate, shunt) = if revision >= 34 {
+            (r.str(6), 7, 19)
+        } else {
+            (String::new(), 6, 9)
+        };
+        Ok(Self {
+            from: r.int(0, 0)?,
+            to: r.int(1, 0)?,
+            ckt: r.str(2),
+            r: r.f64(3, 0.0)?,
+            x: r.f64(4, 0.0)?,
+            b: r.f64(5, 0.0)?,
+            name,
+            rate_a: r.f64(rate, 0.0)?,
+            gi: r.f64(shunt, 0.0)?,
+            bi: r.f64(shunt + 1, 0.0)?,
+            gj: r.f64(shunt + 2, 0.0)?,
+            bj: r.f64(shunt + 3, 0.0)?,
+            status: r.int(shunt + 4, 1)? as i32,
+            length: r.f64(shunt + 6, 0.0)?,
+        })
+    }
+}
+
+impl RawSwitchingDevice {
+    fn parse(r: &Record) -> Result<Self, Error> {
+        Ok(Self {
+            from: r.int(0, 0)?,
+            to: r.int(1, 0)?,
+            ckt: r.str(2),
+            x: r.f64(3, 0.0001)?,
+            rate_a: r.f64(4, 0.0)?,
+            status: r.int(16, 1)? as i32,
+            name: r.str(20),
+        })
+    }
+}
+
+impl RawWinding {
+    fn parse(r: &Record) -> Result<Self, Error> {
+        Ok(Self {
+            windv: r.f64(0, 1.0)?,
+            nomv: r.f64(1, 0.0)?,
+            ang: r.f64(2, 0.0)?,
+            rate_a: r.f64(3, 0.0)?,
+        })
+    }
+}
+
+impl RawTransformer {
+    /// Reads the four (two windings) or five (three windings) lines of a
+    /// transformer starting with record 1 in `first`.
+    fn parse(first: &Record, lines: &mut Lines<'_>, sbase: f64) -> Result<Self, Error> {
+        let mut t = Self {
+            buses: [first.int(0, 0)?, first.int(1, 0)?, first.int(2, 0)?],
+            ckt: first.str(3),
+            cw: first.int(4, 1)? as i32,
+            cz: first.int(5, 1)? as i32,
+            cm: first.int(6, 1)? as i32,
+            mag1: first.f64(7, 0.0)?,
+            mag2: first.f64(8, 0.0)?,
+            name: first.str(10),
+            status: first.int(11, 1)? as i32,
+            ..Default::default()
+        };
+        let z = lines.record("transformer impedance data")?;
+        let (impedances, windings) = if t.is_three_winding() { (3, 3) } else { (1, 2) };
+        for k in 0..impedances {
+            t.r[k] = z.f64(3 * k, 0.0)?;
+            t.x[k] = z.f64(3 * k + 1, 0.0)?;
+            t.sbase[k] = z.f64(3 * k + 2, sbase)?;
+        }
+        t.vmstar = z.f64(9, 1.0)?;
+        t.anstar = z.f64(10, 0.0)?;
+        for _ in 0..windings {
+            t.windings.push(RawWinding::parse(
+                &lines.record("transformer winding data")?,
+            )?);
+        }
+        Ok(t)
+    }
+}
+
+impl RawNamed {
+    fn parse(r: &Record, name: usize) -> Result<Self, Error> {
+        Ok(Self {
+            number: r.int(0, 0)?,
+            name: r.str(name),
+        })
+    }
+}
+
+impl RawSwitchedShunt {
+    fn parse(r: &Record, revision: i32) -> Result<Self, Error> {
+        // Revision 34 added NREG, revision 35 the ID and per-block status.
+        let (id, status, binit, block) = match revision {
+            ..=33 => (String::new(), 3, 9, 2),
+            34 => (String::new(), 3, 10, 2),
+            _ => (r.str(1), 4, 11, 3),
+        };
+        let mut blocks = Vec::new();
+        let mut i = binit + 1;
+        while let Some(n) = r.raw(i + block - 2) {
+            let n = n
+                .parse::<f64>()
+                .map_err(|_| invalid(format!("invalid block size '{n}'")))?;
+            blocks.push((n as i32, r.f64(i + block - 1, 0.0)?));
+            i += block;
+        }
+        Ok(Self {
+            bus: r.int(0, 0)?,
+            id,
+            status: r.int(status, 1)? as i32,
+            binit: r.f64(binit, 0.0)?,
+            blocks,
+        })
+    }
+}
+
+/// Parses the text of a RAW file.
+pub fn parse_raw(text: &str) -> Result<RawCase, Error> {
+    let mut lines = Lines::new(text);
+    let head = lines.record("case identification")?;
+    let mut case = RawCase {
+        sbase: head.f64(1, 100.0)?,
+        revision: head.int(2, 33)? as i32,
+        basfrq: head.f64(5, 60.0)?,
+        ..Default::default()
+    };
+    for title in &mut case.title {
+        *title = lines.next_title();
+    }
+    if !(33..=35).contains(&case.revision) {
+        return Err(invalid(format!(
+            "unsupported PSS/E revision {}",
+            case.revision
+        )));
+    }
+
+    for section in sections(case.revision) {
+        let Some(mut r) = lines.next_record() else {
+            break;
+        };
+        // Revision 35 may start with system-wide data records such as
+        // `GENERAL, THRSHZ=...`; they end like any other section.
+        if section == Section::Bus && r.raw(0).is_some_and(|s| s.parse::<f64>().is_err()) {
+            while !r.is_end() {
+                r = lines.record("system-wide data")?;
+            }
+            r = lines.record("bus data")?;
+        }
+        while !r.is_end() {
+            match section {
+                Section::Bus => case.buses.push(RawBus::parse(&r)?),
+                Section::Load => case.loads.push(RawLoad::parse(&r)?),
+                Section::FixedShunt => case.fixed_shunts.push(RawFixedShunt::parse(&r)?),
+                Section::Generator => case
+                    .generators
+                    .push(RawGenerator::parse(&r, case.revision)?),
+                Section::Branch => case.branches.push(RawBranch::parse(&r, case.revision)?),
+                Section::SwitchingDevice => {
+                    case.switching_devices.push(RawSwitchingDevice::parse(&r)?)
+                }
+                Section::Transformer => {
+                    let t = RawTransformer::parse(&r, &mut lines, case.sbase)?;
+                    case.transformers.push(t);
+                }
+                Section::Area => case.areas.push(RawNamed::parse(&r, 4)?),
+                Section::Zone => case.zones.push(RawNamed::parse(&r, 1)?),
+                Section::SwitchedShunt => case
+                    .switched_shunts
+                    .push(RawSwitchedShunt::parse(&r, case.revision)?),
+                Section::Skipped => {}
+            }
+            let Some(next) = lines.next_record() else {
+                return Ok(case);
+            };
+            r = next;
+        }
+        if r.raw(0) == Some("Q") {
+            break;
+        }
+    }
+    if case.buses.is_empty() {
+        return Err(invalid("RAW file has no bus data"));
+    }
+    Ok(case)
+}
+
+/// Reads a RAW file.
+pub fn load_raw(path: impl AsRef<Path>) -> Result<RawCase, Error> {
+    parse_raw(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(
+            fields(" 1,,'BUS A  ', 2.5E-1 4 / comment, 5"),
+            ["1", "", "BUS A  ", "2.5E-1", "4"]
+        );
+        assert_eq!(fields("0 / END OF BUS DATA"), ["0"]);
+        assert_eq!(fields("RATING, 1, \"RATE1\""), ["RATING", "1", "RATE1"]);
+    }
+
+    #[test]
+    fn test_parse_v35() {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let case = load_raw(format!("{}/cases/psse/ieee14_v35.raw", dir)).unwrap();
+        assert_eq!(case.revision, 35);
+        assert_eq!(case.title[0], "IEEE 14 bus test case");
+        assert_eq!(case.buses.len(), 15);
+        assert_eq!(case.buses[0].name, "BUS 1");
+        assert_eq!(case.buses[5].base_kv, 69.0);
+        assert_eq!(case.loads.len(), 11);
+        assert!(case.fixed_shunts.is_empty());
+        assert_eq!(case.generators.len(), 5);
+        assert_eq!(case.generators[0].pt, 332.4);
+        assert_eq!(case.generators[1].qb, -40.0);
+        assert_eq!(case.branches.len(), 17);
+        assert_eq!(case.branches[0].b, 0.0528);
+
+        let sw = &case.switching_devices[0];
+        assert_eq!((sw.from, sw.to, sw.status), (14, 15, 1));
+        assert_eq!(sw.name, "SW 14-15");
+
+        assert_eq!(case.transformers.len(), 3);
+        let t = &case.transformers[1];
+        assert!(!t.is_three_winding());
+        assert_eq!((t.cw, t.cz, t.name.as_str()), (3, 2, "T4-9"));
+        assert_eq!(t.windings.len(), 2);
+        assert_eq!(t.windings[0].nomv, 140.0);
+        assert_eq!(t.windings[1].nomv, 69.0);
+
+        assert_eq!(case.areas[0].name, "IEEE14");
+        assert_eq!(case.zones[0].name, "ZONE 1");
+        let ss = &case.switched_shunts[0];
+        assert_eq!((ss.bus, ss.status, ss.binit), (9, 1, 19.0));
+        assert_eq!(ss.blocks, [(1, 19.0), (1, 5.0)]);
+    }
+
+    #[test]
+    fn test_rejects_unsupported_revision() {
+        let err = parse_raw("0, 100.0, 30, 0, 0, 60.0\n\n\n").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -1,6 +1,7 @@
 mod basic;
 pub mod bevy_cmdbuffer;
 pub mod io;
+pub mod probabilistic;
 pub mod testcases;
 pub mod timeseries;
 
@@ -12,6 +13,9 @@ pub mod prelude {
     pub use crate::io::pandapower;
     pub use crate::basic::ecs::network::{DataOps, PowerFlow, PowerGrid};
     pub use crate::basic::ecs::post_processing::PostProcessing;
+    pub use crate::basic::ecs::powerflow::batch::BatchPowerFlow;
+    pub use crate::basic::ecs::powerflow::sensitivity::SensitivityAnalysis;
+    pub use crate::probabilistic::ProbabilisticPowerFlow;
     pub use crate::basic::ecs::elements::PPNetwork;
     pub use crate::basic::ecs::powerflow::prelude::PowerFlowResult;
     pub use crate::basic::ecs::plugin::{default_app, IwamotoPlugin, CustomSolverActive};
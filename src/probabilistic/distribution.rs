@@ -0,0 +1,111 @@
+use bevy_ecs::prelude::*;
+use rand::Rng;
+use rand_distr::{Beta, Distribution as RandDistribution, Normal};
+use serde::{Deserialize, Serialize};
+
+/// Probability distribution of one uncertain quantity (MW or MVAr).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Distribution {
+    /// Gaussian with the given mean and standard deviation.
+    Normal { mean: f64, std: f64 },
+    /// Beta(`alpha`, `beta`) scaled to `[min, max]`, e.g. for PV output.
+    Beta {
+        alpha: f64,
+        beta: f64,
+        min: f64,
+        max: f64,
+    },
+    /// Resampled uniformly from recorded values (bootstrap).
+    Empirical(Vec<f64>),
+}
+
+impl Distribution {
+    /// Validates the parameters and builds a sampler.
+    pub fn sampler(&self) -> Result<Sampler, String> {
+        match self {
+            Distribution::Normal { mean, std } => {
+                if !std.is_finite() || *std < 0.0 {
+                    return Err(format!("Invalid normal standard deviation {std}"));
+                }
+                Normal::new(*mean, *std)
+                    .map(Sampler::Normal)
+                    .map_err(|e| format!("Invalid normal distribution: {e}"))
+            }
+            Distribution::Beta {
+                alpha,
+                beta,
+                min,
+                max,
+            } => {
+                if min.is_nan() || max.is_nan() || min > max {
+                    return Err(format!("Invalid beta range [{min}, {max}]"));
+                }
+                Beta::new(*alpha, *beta)
+                    .map(|b| Sampler::Beta(b, *min, *max - *min))
+                    .map_err(|e| format!("Invalid beta distribution: {e}"))
+            }
+            Distribution::Empirical(values) => {
+                if values.is_empty() {
+                    return Err("Empirical distribution has no samples".to_string());
+                }
+                Ok(Sampler::Empirical(values.clone()))
+            }
+        }
+    }
+}
+
+/// Validated, ready-to-draw form of a [`Distribution`].
+#[derive(Debug, Clone)]
+pub enum Sampler {
+    Normal(Normal<f64>),
+    Beta(Beta<f64>, f64, f64), // distribution, offset, span
+    Empirical(Vec<f64>),
+}
+
+impl Sampler {
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        match self {
+            Sampler::Normal(d) => d.sample(rng),
+            Sampler::Beta(d, min, span) => min + span * d.sample(rng),
+            Sampler::Empirical(values) => values[rng.random_range(0..values.len())],
+        }
+    }
+}
+
+/// Uncertain active/reactive power of a load or static generator.
+///
+/// Values follow the element's own convention, as in pandapower: consumed
+/// power for loads, generated power for sgens. A `None` entry keeps the
+/// nominal setpoint.
+#[derive(Component, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PowerUncertainty {
+    pub p_mw: Option<Distribution>,
+    pub q_mvar: Option<Distribution>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_sampler() {
+        assert!(Distribution::Normal { mean: 1.0, std: -1.0 }.sampler().is_err());
+        assert!(Distribution::Empirical(vec![]).sampler().is_err());
+
+        let beta = Distribution::Beta {
+            alpha: 2.0,
+            beta: 5.0,
+            min: 10.0,
+            max: 20.0,
+        }
+        .sampler()
+        .unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for _ in 0..1000 {
+            let x = beta.sample(&mut rng);
+            assert!((10.0..=20.0).contains(&x));
+        }
+    }
+}
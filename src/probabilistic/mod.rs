@@ -0,0 +1,16 @@
+//! Monte-Carlo probabilistic load flow.
+//!
+//! Attach a [`PowerUncertainty`] to loads and static generators, then call
+//! [`ProbabilisticPowerFlow::run_probabilistic_pf`] on an initialized
+//! network. Scenarios are drawn from a seeded RNG and solved in batches with
+//! the batched Newton-Raphson solver, keeping only running statistics so
+//! memory stays flat in the number of samples.
+
+pub mod distribution;
+pub mod montecarlo;
+
+pub use distribution::{Distribution, PowerUncertainty, Sampler};
+pub use montecarlo::{
+    BusVoltageStats, LineLoadingStats, MonteCarloConfig, ProbabilisticPowerFlow,
+    ProbabilisticReport,
+};
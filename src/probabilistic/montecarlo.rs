@@ -0,0 +1,455 @@
+use bevy_app::App;
+use bevy_ecs::name::Name;
+use bevy_ecs::prelude::*;
+use nalgebra::{DMatrix, DVector};
+use num_complex::Complex64;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::basic::ecs::elements::*;
+use crate::basic::ecs::network::{DataOps, PowerGrid};
+use crate::basic::ecs::post_processing::line_result;
+use crate::basic::ecs::powerflow::batch::BatchPowerFlow;
+use crate::basic::ecs::powerflow::systems::PowerFlowMat;
+
+use super::distribution::{PowerUncertainty, Sampler};
+
+/// Settings of a Monte-Carlo probabilistic load flow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloConfig {
+    /// Number of sampled scenarios.
+    pub samples: usize,
+    /// RNG seed; the same seed always yields the same report.
+    pub seed: u64,
+    /// Scenarios solved per batched call (bounds memory use).
+    pub batch_size: usize,
+    /// Worker threads per batch, `None` for the available parallelism.
+    pub threads: Option<usize>,
+    /// Line loading (%) above which a sample counts as an overload.
+    pub loading_limit_percent: f64,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self {
+            samples: 1000,
+            seed: 0,
+            batch_size: 256,
+            threads: None,
+            loading_limit_percent: 100.0,
+        }
+    }
+}
+
+/// Voltage magnitude statistics of one bus over the converged samples.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusVoltageStats {
+    pub bus: i64,
+    pub vm_mean: f64,
+    pub vm_std: f64,
+    pub vm_min: f64,
+    pub vm_max: f64,
+    pub p_undervoltage: f64, // P(vm < VmLimit::min)
+    pub p_overvoltage: f64,  // P(vm > VmLimit::max)
+}
+
+/// Loading statistics of one line over the converged samples.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineLoadingStats {
+    pub from_bus: i64,
+    pub to_bus: i64,
+    pub name: Option<String>,
+    pub loading_mean: f64,
+    pub loading_std: f64,
+    pub loading_max: f64,
+    pub p_overload: f64, // P(loading > loading_limit_percent)
+}
+
+/// Result of [`ProbabilisticPowerFlow::run_probabilistic_pf`].
+///
+/// Statistics are taken over the converged samples only; `converged` tells
+/// how many that were.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbabilisticReport {
+    pub samples: usize,
+    pub converged: usize,
+    pub seed: u64,
+    pub buses: Vec<BusVoltageStats>,
+    pub lines: Vec<LineLoadingStats>,
+}
+
+/// Welford running mean/variance with extrema and exceedance counts.
+#[derive(Debug, Clone)]
+struct RunningStats {
+    n: usize,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+    below: usize,
+    above: usize,
+}
+
+impl RunningStats {
+    fn new() -> Self {
+        Self {
+            n: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            below: 0,
+            above: 0,
+        }
+    }
+
+    fn push(&mut self, x: f64, lower: f64, upper: f64) {
+        self.n += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.n as f64;
+        self.m2 += delta * (x - self.mean);
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        self.below += (x < lower) as usize;
+        self.above += (x > upper) as usize;
+    }
+
+    /// `(mean, std, min, max, p_below, p_above)`; NaN when empty.
+    fn finish(&self) -> (f64, f64, f64, f64, f64, f64) {
+        if self.n == 0 {
+            return (f64::NAN, f64::NAN, f64::NAN, f64::NAN, f64::NAN, f64::NAN);
+        }
+        let n = self.n as f64;
+        let std = if self.n > 1 { (self.m2 / (n - 1.0)).sqrt() } else { 0.0 };
+        (
+            self.mean,
+            std,
+            self.min,
+            self.max,
+            self.below as f64 / n,
+            self.above as f64 / n,
+        )
+    }
+}
+
+/// A load or sgen with at least one uncertain setpoint.
+struct UncertainElement {
+    bus: usize, // dense bus index (column of the batch s_bus)
+    sign: f64, // -1 for loads (TargetPMW is an injection), +1 for sgens
+    p0: f64,
+    q0: f64,
+    p: Option<Sampler>,
+    q: Option<Sampler>,
+}
+
+impl UncertainElement {
+    /// Injection change (MW, MVAr) of one draw relative to the base case.
+    fn sample(&self, rng: &mut ChaCha8Rng) -> (f64, f64) {
+        let dp = self.p.as_ref().map_or(0.0, |s| self.sign * s.sample(rng) - self.p0);
+        let dq = self.q.as_ref().map_or(0.0, |s| self.sign * s.sample(rng) - self.q0);
+        (dp, dq)
+    }
+}
+
+/// Monte-Carlo probabilistic load flow over [`PowerUncertainty`] elements.
+///
+/// Each batch draws its scenarios from one seeded ChaCha8 stream, loads first
+/// and then the other elements in [`ElementIndex`] order, and solves them with
+/// [`BatchPowerFlow`]. The sampled scenarios only depend on the seed; the
+/// statistics do up to rounding, which with backends that refactor with
+/// earlier pivots (KLU) varies with `batch_size` and the thread count.
+/// The network must be initialized (`init_pf`) beforehand; its state is not
+/// modified.
+pub trait ProbabilisticPowerFlow {
+    fn run_probabilistic_pf(
+        &mut self,
+        cfg: &MonteCarloConfig,
+    ) -> Result<ProbabilisticReport, String>;
+}
+
+impl ProbabilisticPowerFlow for World {
+    fn run_probabilistic_pf(
+        &mut self,
+        cfg: &MonteCarloConfig,
+    ) -> Result<ProbabilisticReport, String> {
+        if self.contains_resource::<NodeAggRes>() {
+            return Err("Probabilistic load flow does not support merged buses".to_string());
+        }
+        assign_element_indices(self);
+        let mut elem_q = self.query_filtered::<(
+            &ElementIndex,
+            &PowerUncertainty,
+            &TargetBus,
+            &TargetPMW,
+            &TargetQMVar,
+            Has<LoadCfg>,
+        ), Without<OutOfService>>();
+        let mut bus_q = self.query::<(&BusID, Option<&VmLimit<PerUnit>>)>();
+        let mut line_q = self.query_filtered::<
+            (&FromBus, &ToBus, &LineParams, &Children, Option<&Name>),
+            (With<Line>, Without<OutOfService>),
+        >();
+        let mut admit_q = self.query_filtered::<(&Admittance, &VBase, &Port2), With<ChildOf>>();
+        let world: &World = self;
+
+        let mat = world
+            .get_resource::<PowerFlowMat>()
+            .ok_or("Power flow matrices are not initialized")?;
+        let sbase = world
+            .get_resource::<PFCommonData>()
+            .ok_or("Missing PFCommonData")?
+            .sbase;
+        let lookup = world
+            .get_resource::<NodeLookup>()
+            .ok_or("Missing NodeLookup")?;
+        let n = mat.v_bus_init.len();
+        let mut base = DVector::zeros(n);
+        for (new_idx, &old_idx) in mat.from_perm.iter().enumerate() {
+            base[old_idx] = mat.s_bus[new_idx];
+        }
+
+        let mut elems = elem_q.iter(world).collect::<Vec<_>>();
+        elems.sort_by_key(|&(i, .., is_load)| (!is_load, i.0));
+        let elems = elems
+            .into_iter()
+            .filter_map(|(_, unc, bus, p, q, is_load)| {
+                Some((unc, lookup.index(bus.0)?, p, q, is_load))
+            })
+            .map(|(unc, bus, p, q, is_load)| {
+                let sampler = |d: &Option<_>| d.as_ref().map(super::Distribution::sampler).transpose();
+                Ok(UncertainElement {
+                    bus,
+                    sign: if is_load { -1.0 } else { 1.0 },
+                    p0: p.0,
+                    q0: q.0,
+                    p: sampler(&unc.p_mw)?,
+                    q: sampler(&unc.q_mvar)?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut buses = bus_q
+            .iter(world)
+            .filter_map(|(id, lim)| {
+                let (min, max) = lim.map_or((f64::NEG_INFINITY, f64::INFINITY), |l| (l.min(), l.max()));
+                Some((id.0, lookup.index(id.0)?, min, max, RunningStats::new()))
+            })
+            .collect::<Vec<_>>();
+        buses.sort_by_key(|b| b.0);
+
+        let mut lines = line_q
+            .iter(world)
+            .filter_map(|(from, to, params, children, name)| {
+                let ends = (lookup.index(from.0)?, lookup.index(to.0)?);
+                let branches = children
+                    .iter()
+                    .filter_map(|c| admit_q.get(world, c).ok())
+                    .collect::<Vec<_>>();
+                Some(((from.0, to.0), ends, params.max_i_ka, branches, name, RunningStats::new()))
+            })
+            .collect::<Vec<_>>();
+
+        let mut rng = ChaCha8Rng::seed_from_u64(cfg.seed);
+        let batch = cfg.batch_size.max(1);
+        let limit = cfg.loading_limit_percent;
+        let mut converged = 0;
+        let mut done = 0;
+        while done < cfg.samples {
+            let m = batch.min(cfg.samples - done);
+            let mut s_bus = DMatrix::from_fn(m, n, |_, c| base[c]);
+            for k in 0..m {
+                for e in &elems {
+                    let (dp, dq) = e.sample(&mut rng);
+                    s_bus[(k, e.bus)] += Complex64::new(dp, dq) / sbase;
+                }
+            }
+
+            let res = world.run_pf_batch(&s_bus, cfg.threads)?;
+            for k in (0..m).filter(|&k| res.converged[k]) {
+                converged += 1;
+                let v = res.v.row(k).transpose();
+                for (_, idx, min, max, stats) in buses.iter_mut() {
+                    stats.push(v[*idx].norm(), *min, *max);
+                }
+                for ((from, to), (f, t), max_i_ka, branches, _, stats) in lines.iter_mut() {
+                    let branches = branches.iter().copied();
+                    let data = line_result((v[*f], v[*t]), *from, *to, *max_i_ka, branches);
+                    stats.push(data.loading_percent, f64::NEG_INFINITY, limit);
+                }
+            }
+            done += m;
+        }
+
+        let buses = buses
+            .into_iter()
+            .map(|(bus, _, _, _, stats)| {
+                let (vm_mean, vm_std, vm_min, vm_max, p_under, p_over) = stats.finish();
+                BusVoltageStats {
+                    bus,
+                    vm_mean,
+                    vm_std,
+                    vm_min,
+                    vm_max,
+                    p_undervoltage: p_under,
+                    p_overvoltage: p_over,
+                }
+            })
+            .collect();
+        let lines = lines
+            .into_iter()
+            .map(|((from_bus, to_bus), _, _, _, name, stats)| {
+                let (loading_mean, loading_std, _, loading_max, _, p_overload) = stats.finish();
+                LineLoadingStats {
+                    from_bus,
+                    to_bus,
+                    name: name.map(|n| n.to_string()),
+                    loading_mean,
+                    loading_std,
+                    loading_max,
+                    p_overload,
+                }
+            })
+            .collect();
+
+        Ok(ProbabilisticReport {
+            samples: cfg.samples,
+            converged,
+            seed: cfg.seed,
+            buses,
+            lines,
+        })
+    }
+}
+
+impl ProbabilisticPowerFlow for App {
+    fn run_probabilistic_pf(
+        &mut self,
+        cfg: &MonteCarloConfig,
+    ) -> Result<ProbabilisticReport, String> {
+        self.world_mut().run_probabilistic_pf(cfg)
+    }
+}
+
+impl ProbabilisticPowerFlow for PowerGrid {
+    fn run_probabilistic_pf(
+        &mut self,
+        cfg: &MonteCarloConfig,
+    ) -> Result<ProbabilisticReport, String> {
+        self.world_mut().run_probabilistic_pf(cfg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::plugin::default_app;
+    use crate::basic::ecs::powerflow::systems::PowerFlowResult;
+    use crate::io::pandapower::load_csv_zip;
+    use crate::prelude::PPNetwork;
+    use crate::probabilistic::Distribution;
+    use std::env;
+
+    fn app_118() -> App {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let name = format!("{}/cases/IEEE118/data.zip", dir);
+        let net = load_csv_zip(&name).unwrap();
+
+        let mut app = default_app();
+        app.world_mut().insert_resource(PPNetwork(net));
+        app.update();
+        app
+    }
+
+    /// Attaches a distribution built from each load's nominal MW.
+    fn attach(app: &mut App, f: impl Fn(f64) -> Distribution) {
+        let mut q = app
+            .world_mut()
+            .query_filtered::<(Entity, &TargetPMW), With<LoadCfg>>();
+        let loads = q
+            .iter(app.world())
+            .map(|(e, p)| (e, -p.0))
+            .collect::<Vec<_>>();
+        for (e, p_mw) in loads {
+            app.world_mut().entity_mut(e).insert(PowerUncertainty {
+                p_mw: Some(f(p_mw)),
+                q_mvar: None,
+            });
+        }
+    }
+
+    /// Equal reports up to the rounding a different batch split may cause.
+    fn same_stats(a: &ProbabilisticReport, b: &ProbabilisticReport) {
+        let close = |x: f64, y: f64| (x - y).abs() <= 1e-9 * x.abs().max(1.0);
+        assert_eq!((a.samples, a.converged, a.seed), (b.samples, b.converged, b.seed));
+        assert_eq!(a.buses.len(), b.buses.len());
+        for (x, y) in a.buses.iter().zip(&b.buses) {
+            assert_eq!(x.bus, y.bus);
+            assert!(close(x.vm_mean, y.vm_mean) && close(x.vm_std, y.vm_std), "{x:?} {y:?}");
+            assert!(close(x.vm_min, y.vm_min) && close(x.vm_max, y.vm_max), "{x:?} {y:?}");
+            assert_eq!((x.p_undervoltage, x.p_overvoltage), (y.p_undervoltage, y.p_overvoltage));
+        }
+        assert_eq!(a.lines.len(), b.lines.len());
+        for (x, y) in a.lines.iter().zip(&b.lines) {
+            assert_eq!((x.from_bus, x.to_bus), (y.from_bus, y.to_bus));
+            assert!(close(x.loading_mean, y.loading_mean), "{x:?} {y:?}");
+            assert!(close(x.loading_std, y.loading_std), "{x:?} {y:?}");
+            assert!(close(x.loading_max, y.loading_max), "{x:?} {y:?}");
+            assert_eq!(x.p_overload, y.p_overload);
+        }
+    }
+
+    #[test]
+    fn test_reproducible_118() {
+        let mut app = app_118();
+        attach(&mut app, |p| Distribution::Normal {
+            mean: p,
+            std: 0.1 * p.abs(),
+        });
+        let cfg = MonteCarloConfig {
+            samples: 64,
+            seed: 42,
+            batch_size: 24,
+            threads: Some(4),
+            ..Default::default()
+        };
+        let a = app.run_probabilistic_pf(&cfg).unwrap();
+        let b = app
+            .run_probabilistic_pf(&MonteCarloConfig {
+                threads: Some(1),
+                batch_size: 64,
+                ..cfg.clone()
+            })
+            .unwrap();
+        assert_eq!(a.converged, 64);
+        assert_eq!(a.buses.len(), 118);
+        assert!(a.buses.iter().any(|b| b.vm_std > 0.0));
+        same_stats(&a, &b);
+
+        let c = app
+            .run_probabilistic_pf(&MonteCarloConfig { seed: 43, ..cfg })
+            .unwrap();
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_degenerate_118() {
+        let mut app = app_118();
+        attach(&mut app, |p| Distribution::Empirical(vec![p]));
+        let report = app
+            .run_probabilistic_pf(&MonteCarloConfig {
+                samples: 8,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let mat = app.world().resource::<PowerFlowMat>();
+        let res = app.world().resource::<PowerFlowResult>();
+        assert_eq!(report.converged, 8);
+        for b in &report.buses {
+            let vm = res.v[mat.to_perm[b.bus as usize]].norm();
+            assert!(b.vm_std < 1e-12);
+            assert!((b.vm_mean - vm).abs() < 1e-8);
+        }
+    }
+}
@@ -14,6 +14,19 @@ use crate::basic::ecs::network::DataOps;
 
 use super::handles::*;
 
+/// Adds one f64 column per named field of the result rows to a dict.
+macro_rules! result_columns {
+    ($dict:ident, $rows:ident, $($field:ident),+) => {
+        $( $dict.set_item(stringify!($field), $rows.iter().map(|r| r.$field).collect::<Vec<f64>>().into_pyarray($dict.py()))?; )+
+    };
+}
+
+/// Voltages and per-scenario convergence flags returned by `solve_batch`.
+type BatchArrays<'py> = (
+    Bound<'py, numpy::PyArray2<num_complex::Complex64>>,
+    Bound<'py, numpy::PyArray1<bool>>,
+);
+
 /// Core power grid object.
 ///
 /// Supports three primary workflows:
@@ -44,11 +57,32 @@ pub struct SolveReport {
     /// Which rebuild level this solve triggered: "full" | "incremental"
     #[pyo3(get)]
     pub rebuild: String,
+    /// Per-iteration convergence trace; only set when tracing is enabled.
+    pub trace: Option<Vec<crate::basic::ecs::powerflow::trace::IterationTrace>>,
 }
 
 #[pymethods]
 impl SolveReport {
     fn __bool__(&self) -> bool { self.converged }
+
+    /// Per-iteration convergence trace as a list of dicts (None unless
+    /// `PowerGrid.enable_trace(True)` was called). Entry 0 is the start point.
+    #[getter]
+    fn trace<'py>(&self, py: Python<'py>) -> PyResult<Option<Vec<Bound<'py, pyo3::types::PyDict>>>> {
+        let Some(records) = &self.trace else { return Ok(None) };
+        let mut out = Vec::with_capacity(records.len());
+        for r in records {
+            let dict = pyo3::types::PyDict::new(py);
+            dict.set_item("iteration", r.iteration)?; dict.set_item("mismatch_norm", r.mismatch_norm)?;
+            dict.set_item("max_p_bus", r.max_p_bus)?; dict.set_item("max_p_mismatch", r.max_p_mismatch)?;
+            dict.set_item("max_q_bus", r.max_q_bus)?; dict.set_item("max_q_mismatch", r.max_q_mismatch)?;
+            dict.set_item("step_norm", r.step_norm)?;
+            dict.set_item("fill_ms", r.fill_ms)?; dict.set_item("factor_ms", r.factor_ms)?; dict.set_item("solve_ms", r.solve_ms)?;
+            out.push(dict);
+        }
+        Ok(Some(out))
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "SolveReport(converged={}, iterations={}, runtime_ms={:.3}, rebuild='{}')",
@@ -301,7 +335,7 @@ impl PowerGrid {
     fn new(case_path: Option<String>, qlim: bool, kwargs: Option<Bound<'_, pyo3::types::PyDict>>) -> PyResult<Self> {
         let mut inner = crate::prelude::PowerGrid::default();
         let buffer = crate::bevy_cmdbuffer::buffer::HarvardCommandBuffer::new();
-        inner.world_mut().insert_resource(crate::basic::ecs::factory::StdTypeLibrary::default());
+        inner.world_mut().insert_resource(crate::basic::ecs::factory::StdTypeLibrary::pandapower());
         // Default system base; overwritten by case ingestion or set_base().
         inner.world_mut().insert_resource(PFCommonData {
             sbase: 100.0,
@@ -468,17 +502,104 @@ impl PowerGrid {
         // Mark dirty for lazy post-processing: actual computation
         // deferred until res_bus / res_line is accessed.
         self.post_process_dirty = converged;
+        let trace = self
+            .inner
+            .world()
+            .get_resource::<crate::basic::ecs::powerflow::trace::ConvergenceTrace>()
+            .map(|t| t.records.clone());
         Ok(SolveReport {
             converged,
             iterations,
             runtime_ms: t0.elapsed().as_secs_f64() * 1e3,
             rebuild: if full_rebuild { "full" } else { "incremental" }.to_string(),
+            trace,
         })
     }
 
+    /// Solve many injection scenarios on the current topology, in parallel.
+    ///
+    /// s_bus: 2D complex array (scenarios x buses) of per-unit injections
+    /// (P + jQ) / sn_mva, injection sign convention, columns in bus-id order.
+    /// Every scenario starts from the current voltages; no grid state changes.
+    /// Grids with buses merged by closed switches raise a ValueError.
+    /// The GIL is released while the scenarios are solved.
+    /// Returns `(v, converged)`: the complex voltage matrix (same layout as
+    /// `s_bus`) and a per-scenario convergence flag array.
+    #[pyo3(signature = (s_bus, threads=None))]
+    fn solve_batch<'py>(
+        &mut self,
+        py: Python<'py>,
+        s_bus: numpy::PyReadonlyArray2<'py, num_complex::Complex64>,
+        threads: Option<usize>,
+    ) -> PyResult<BatchArrays<'py>> {
+        use crate::basic::ecs::powerflow::batch::BatchPowerFlow;
+        use crate::basic::ecs::powerflow::structure_update::FullRebuildEvent;
+        // Topology edits pending since the last solve must be applied first.
+        let rebuild_pending = !self.inner.world().contains_resource::<PowerFlowMat>()
+            || self
+                .inner
+                .world()
+                .get_resource::<bevy_ecs::message::Messages<FullRebuildEvent>>()
+                .map(|m| !m.is_empty())
+                .unwrap_or(false);
+        if rebuild_pending {
+            self.init_pf();
+        }
+
+        let s = s_bus.as_array();
+        let (rows, cols) = s.dim();
+        let s_mat = nalgebra::DMatrix::from_fn(rows, cols, |i, j| s[[i, j]]);
+        let world = self.inner.world();
+        let res = py
+            .allow_threads(|| world.run_pf_batch(&s_mat, threads))
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        let v = numpy::ndarray::Array2::from_shape_fn((rows, cols), |(i, j)| res.v[(i, j)]);
+        Ok((v.into_pyarray(py), res.converged.into_pyarray(py)))
+    }
+
+    /// Voltage and loss sensitivities of the last converged solve.
+    ///
+    /// Returns a dict with `dvm_dp`, `dvm_dq` (p.u./MW, p.u./MVAr) and
+    /// `dva_dp`, `dva_dq` (degree/MW, degree/MVAr) arrays of shape
+    /// (n_bus, len(buses)) in bus-id order, plus per-generator `gen_bus`,
+    /// `gen_name`, `loss_factor` and `penalty_factor` lists.
+    fn sensitivities<'py>(&mut self, py: Python<'py>, buses: Vec<i64>) -> PyResult<Bound<'py, pyo3::types::PyDict>> {
+        use crate::basic::ecs::powerflow::sensitivity::SensitivityAnalysis;
+        let rep = self
+            .inner
+            .sensitivities(&buses)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        let to_np = |m: &nalgebra::DMatrix<f64>| {
+            numpy::ndarray::Array2::from_shape_fn(m.shape(), |(i, j)| m[(i, j)]).into_pyarray(py)
+        };
+        let d = pyo3::types::PyDict::new(py);
+        d.set_item("buses", rep.buses.clone())?;
+        d.set_item("dvm_dp", to_np(&rep.dvm_dp))?;
+        d.set_item("dvm_dq", to_np(&rep.dvm_dq))?;
+        d.set_item("dva_dp", to_np(&rep.dva_dp))?;
+        d.set_item("dva_dq", to_np(&rep.dva_dq))?;
+        let gens = &rep.gen_loss_factors;
+        d.set_item("gen_bus", gens.iter().map(|g| g.bus).collect::<Vec<_>>())?;
+        d.set_item("gen_name", gens.iter().map(|g| g.name.clone()).collect::<Vec<_>>())?;
+        d.set_item("loss_factor", gens.iter().map(|g| g.loss_factor).collect::<Vec<_>>())?;
+        d.set_item("penalty_factor", gens.iter().map(|g| g.penalty_factor).collect::<Vec<_>>())?;
+        Ok(d)
+    }
+
     /// Reset the power flow solver state, clearing result vectors and resetting bus injections.
     fn reset_state(&mut self) { self.reset_state_impl(); }
 
+    /// Enable or disable per-iteration convergence tracing. When enabled,
+    /// `SolveReport.trace` lists mismatch, step and timing data per iteration.
+    fn enable_trace(&mut self, enable: bool) {
+        use crate::basic::ecs::powerflow::trace::ConvergenceTrace;
+        if enable {
+            self.inner.world_mut().init_resource::<ConvergenceTrace>();
+        } else {
+            self.inner.world_mut().remove_resource::<ConvergenceTrace>();
+        }
+    }
+
     /// Enable or disable the Iwamoto optimal multiplier solver dynamically at runtime.
     fn enable_iwamoto(&mut self, enable: bool) {
         if enable {
@@ -504,7 +625,15 @@ impl PowerGrid {
         self.inner.world().get_resource::<PowerFlowResult>().map(|r| r.iterations).unwrap_or(0)
     }
 
-    /// Complex bus voltages (p.u.) of the last solve, in original bus order.
+    /// Bus ids in the order of `v`: ascending, gaps allowed.
+    #[getter]
+    fn bus_ids<'py>(&self, py: Python<'py>) -> Bound<'py, numpy::PyArray1<i64>> {
+        let ids = self.inner.world().get_resource::<NodeLookup>().map(|l| l.ids().to_vec()).unwrap_or_default();
+        ids.into_pyarray(py)
+    }
+
+    /// Complex bus voltages (p.u.) of the last solve, one entry per bus in
+    /// ascending bus id order (see `bus_ids`).
     #[getter]
     fn v<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, numpy::PyArray1<num_complex::Complex64>>> {
         let world = self.inner.world();
@@ -519,10 +648,11 @@ impl PowerGrid {
         Ok(out.into_pyarray(py))
     }
 
-    /// Set the voltage start vector (p.u. complex), indexed by bus id.
+    /// Set the voltage start vector (p.u. complex), one entry per bus in
+    /// ascending bus id order (see `bus_ids`).
     /// Writes VBusPu on each bus, re-pins PV/slack setpoints (v_inj) so this
     /// is a pure warm start, and fires VoltageChangeEvent; the sync into the
-    /// solver vector applies the bus-id → solver-ordering permutation
+    /// solver vector applies the bus index → solver-ordering permutation
     /// internally (reorder_index in vbus_pu_update).
     #[setter]
     fn set_v(&mut self, _py: Python<'_>, v: Bound<'_, numpy::PyArray1<num_complex::Complex64>>) -> PyResult<()> {
@@ -535,27 +665,26 @@ impl PowerGrid {
         let n = world.resource::<PowerFlowMat>().v_bus_init.len();
         if v_arr.len() != n {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "v has length {}, expected {} (one entry per bus id)",
+                "v has length {}, expected {} (one entry per bus, in ascending bus id order)",
                 v_arr.len(),
                 n
             )));
         }
-        // Resolve bus id -> entity up front so the NodeLookup borrow ends
-        // before the component writes.
-        let entities: Vec<Option<Entity>> = {
-            let lookup = world.resource::<NodeLookup>();
-            (0..n).map(|i| lookup.get_entity(i as i64)).collect()
-        };
+        // Resolve dense index -> entity up front so the NodeLookup borrow
+        // ends before the component writes.
+        let entities: Vec<Entity> = world.resource::<NodeLookup>().iter().map(|(_, e)| e).collect();
         for (i, e) in entities.into_iter().enumerate() {
-            if let Some(e) = e {
-                if let Some(mut bus_v) = world.get_mut::<VBusPu>(e) {
-                    bus_v.0 = v_arr[i];
-                }
+            if let Some(mut bus_v) = world.get_mut::<VBusPu>(e) {
+                bus_v.0 = v_arr[i];
             }
         }
         // Re-pin PV/slack magnitude and slack angle targets: v overrides the
-        // start point, never the setpoints.
-        let _ = world.run_system_once(crate::basic::ecs::powerflow::init::v_inj);
+        // start point, never the setpoints. A setpoint on a missing bus is
+        // an error rather than a silently skipped re-pin.
+        let pinned: bevy_ecs::error::Result = world
+            .run_system_once(crate::basic::ecs::powerflow::init::v_inj)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("v_inj error: {}", e)))?;
+        pinned.map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("cannot re-pin voltage setpoints: {}", e)))?;
         let _ = world.write_message(crate::basic::ecs::powerflow::structure_update::VoltageChangeEvent);
         Ok(())
     }
@@ -728,6 +857,70 @@ impl PowerGrid {
     #[getter]
     fn res_line<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> { self.get_line_results(py) }
 
+    /// Transformer results of the last solve as a DataFrame (pandapower's res_trafo).
+    #[getter]
+    fn res_trafo<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let (dict, index, rows) = self.get_element_results_impl::<TrafoResultData>(py)?;
+        result_columns!(dict, rows, p_hv_mw, q_hv_mvar, p_lv_mw, q_lv_mvar, pl_mw, ql_mvar, i_hv_ka, i_lv_ka, vm_hv_pu, va_hv_degree, vm_lv_pu, va_lv_degree, loading_percent);
+        Self::element_frame(dict, index)
+    }
+
+    /// Generator results of the last solve as a DataFrame (pandapower's res_gen).
+    #[getter]
+    fn res_gen<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let (dict, index, rows) = self.get_element_results_impl::<GenResultData>(py)?;
+        result_columns!(dict, rows, p_mw, q_mvar, va_degree, vm_pu);
+        Self::element_frame(dict, index)
+    }
+
+    /// External grid results of the last solve as a DataFrame (pandapower's res_ext_grid).
+    #[getter]
+    fn res_ext_grid<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let (dict, index, rows) = self.get_element_results_impl::<ExtGridResultData>(py)?;
+        result_columns!(dict, rows, p_mw, q_mvar);
+        Self::element_frame(dict, index)
+    }
+
+    /// Load results of the last solve as a DataFrame (pandapower's res_load).
+    #[getter]
+    fn res_load<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let (dict, index, rows) = self.get_element_results_impl::<LoadResultData>(py)?;
+        result_columns!(dict, rows, p_mw, q_mvar);
+        Self::element_frame(dict, index)
+    }
+
+    /// Static generator results of the last solve as a DataFrame (pandapower's res_sgen).
+    #[getter]
+    fn res_sgen<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let (dict, index, rows) = self.get_element_results_impl::<SGenResultData>(py)?;
+        result_columns!(dict, rows, p_mw, q_mvar);
+        Self::element_frame(dict, index)
+    }
+
+    /// Shunt results of the last solve as a DataFrame (pandapower's res_shunt).
+    #[getter]
+    fn res_shunt<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let (dict, index, rows) = self.get_element_results_impl::<ShuntResultData>(py)?;
+        result_columns!(dict, rows, p_mw, q_mvar, vm_pu);
+        Self::element_frame(dict, index)
+    }
+
+    /// Limit violations of the last solve as a DataFrame (entity, name, kind,
+    /// value, limit, severity), most severe first. Voltages in p.u., branch
+    /// loadings and severity in percent. Empty unless the solve converged.
+    fn violations<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.ensure_post_processed();
+        let report = PostProcessing::violations(&mut self.inner);
+        let dict = pyo3::types::PyDict::new(py);
+        dict.set_item("entity", report.iter().map(|v| v.entity.to_bits()).collect::<Vec<_>>())?;
+        dict.set_item("name", report.iter().map(|v| v.name.clone()).collect::<Vec<_>>())?;
+        dict.set_item("kind", report.iter().map(|v| v.kind.to_string()).collect::<Vec<_>>())?;
+        dict.set_item("value", report.iter().map(|v| v.value).collect::<Vec<_>>())?;
+        dict.set_item("limit", report.iter().map(|v| v.limit).collect::<Vec<_>>())?;
+        dict.set_item("severity", report.iter().map(|v| v.severity).collect::<Vec<_>>())?;
+        py.import("pandas")?.call_method1("DataFrame", (dict,))
+    }
+
     /// Return a pandas DataFrame showing all bus parameters in the case.
     fn display_case_buses<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> { self.get_bus_params(py) }
 
@@ -911,6 +1104,38 @@ impl PowerGrid {
         Ok(dict)
     }
 
+    /// Id columns of every element carrying `T`, in [`ElementIndex`] order:
+    /// its bus (`hv_bus` / `lv_bus` for transformers) and name. Returns the
+    /// element indices and the rows so the caller can add the result columns
+    /// and index the frame.
+    fn get_element_results_impl<'py, T: Component + Clone>(&mut self, py: Python<'py>) -> PyResult<(Bound<'py, pyo3::types::PyDict>, Vec<i64>, Vec<T>)> {
+        self.ensure_post_processed();
+        let world = self.inner.world_mut();
+        assign_element_indices(world);
+        let mut query = world.query::<(Option<&ElementIndex>, Option<&TargetBus>, Option<&FromBus>, Option<&ToBus>, Option<&bevy_ecs::name::Name>, &T)>();
+        let mut rows = query.iter(world).collect::<Vec<_>>();
+        rows.sort_by_key(|r| r.0.copied());
+        let dict = pyo3::types::PyDict::new(py);
+        if rows.iter().all(|r| r.1.is_some()) {
+            dict.set_item("bus", rows.iter().map(|r| r.1.map_or(-1, |b| b.0)).collect::<Vec<_>>().into_pyarray(py))?;
+        } else {
+            dict.set_item("hv_bus", rows.iter().map(|r| r.2.map_or(-1, |b| b.0)).collect::<Vec<_>>().into_pyarray(py))?;
+            dict.set_item("lv_bus", rows.iter().map(|r| r.3.map_or(-1, |b| b.0)).collect::<Vec<_>>().into_pyarray(py))?;
+        }
+        dict.set_item("name", rows.iter().map(|r| r.4.map(|n| n.to_string())).collect::<Vec<_>>())?;
+        let index = rows.iter().map(|r| r.0.map_or(-1, |i| i.0 as i64)).collect();
+        Ok((dict, index, rows.into_iter().map(|r| r.5.clone()).collect()))
+    }
+
+    /// DataFrame of element result columns indexed by element index, like
+    /// pandapower's `res_*` tables.
+    fn element_frame<'py>(dict: Bound<'py, pyo3::types::PyDict>, index: Vec<i64>) -> PyResult<Bound<'py, PyAny>> {
+        let py = dict.py();
+        let kwargs = pyo3::types::PyDict::new(py);
+        kwargs.set_item("index", index.into_pyarray(py))?;
+        py.import("pandas")?.call_method("DataFrame", (dict,), Some(&kwargs))
+    }
+
     fn get_line_results_impl<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, pyo3::types::PyDict>> {
         let world = self.inner.world_mut();
         let mut from_bus = Vec::new(); let mut to_bus = Vec::new();
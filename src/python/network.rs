@@ -92,6 +92,7 @@ impl Network {
     }
 
     fn extract_lines(&self, py: Python<'_>, df: Bound<'_, PyAny>) -> PyResult<Vec<Line>> {
+        let index = Self::get_int_vec(&df, "index")?;
         let from_bus = Self::get_int_vec(&df, "from_bus")?;
         let to_bus = Self::get_int_vec(&df, "to_bus")?;
         let length_km = Self::get_float_vec(&df, "length_km")?;
@@ -104,11 +105,12 @@ impl Network {
         let names = if df.hasattr("name")? { Self::get_opt_str_vec(py, &df, "name")? } else { vec![None; from_bus.len()] };
 
         Ok((0..from_bus.len()).map(|i| Line {
-            from_bus: from_bus[i], to_bus: to_bus[i], length_km: length_km[i], r_ohm_per_km: r_ohm[i], x_ohm_per_km: x_ohm[i], c_nf_per_km: c_nf[i], g_us_per_km: g_us[i], in_service: in_service[i], parallel: parallel[i], name: names[i].clone(), df: 1.0, max_i_ka: Some(0.0), max_loading_percent: None, type_: None, std_type: None,
+            index: Some(index[i]), from_bus: from_bus[i], to_bus: to_bus[i], length_km: length_km[i], r_ohm_per_km: r_ohm[i], x_ohm_per_km: x_ohm[i], c_nf_per_km: c_nf[i], g_us_per_km: g_us[i], in_service: in_service[i], parallel: parallel[i], name: names[i].clone(), df: 1.0, max_i_ka: Some(0.0), max_loading_percent: None, type_: None, std_type: None,
         }).collect())
     }
 
     fn extract_trafos(&self, py: Python<'_>, df: Bound<'_, PyAny>) -> PyResult<Vec<Transformer>> {
+        let index = Self::get_int_vec(&df, "index")?;
         let hv_bus = Self::get_int32_vec(&df, "hv_bus")?;
         let lv_bus = Self::get_int32_vec(&df, "lv_bus")?;
         let sn_mva = Self::get_float_vec(&df, "sn_mva")?;
@@ -127,11 +129,12 @@ impl Network {
         let tap_step_percent = if df.hasattr("tap_step_percent")? { Self::get_opt_float_vec(py, &df, "tap_step_percent")? } else { vec![None; hv_bus.len()] };
 
         Ok((0..hv_bus.len()).map(|i| Transformer {
-            hv_bus: hv_bus[i], lv_bus: lv_bus[i], sn_mva: sn_mva[i], vn_hv_kv: vn_hv[i], vn_lv_kv: vn_lv[i], vk_percent: vk[i], vkr_percent: vkr[i], pfe_kw: pfe[i], i0_percent: i0[i], shift_degree: shift[i], in_service: in_service[i], tap_pos: tap_pos[i], tap_side: tap_side[i].clone(), tap_neutral: tap_neutral[i], tap_step_percent: tap_step_percent[i], parallel: 1, df: 1.0, tap_phase_shifter: false, name: None, std_type: None, max_loading_percent: None, tap_max: None, tap_min: None, tap_step_degree: None,
+            index: Some(index[i]), hv_bus: hv_bus[i], lv_bus: lv_bus[i], sn_mva: sn_mva[i], vn_hv_kv: vn_hv[i], vn_lv_kv: vn_lv[i], vk_percent: vk[i], vkr_percent: vkr[i], pfe_kw: pfe[i], i0_percent: i0[i], shift_degree: shift[i], in_service: in_service[i], tap_pos: tap_pos[i], tap_side: tap_side[i].clone(), tap_neutral: tap_neutral[i], tap_step_percent: tap_step_percent[i], parallel: 1, df: 1.0, tap_phase_shifter: false, name: None, std_type: None, max_loading_percent: None, tap_max: None, tap_min: None, tap_step_degree: None,
         }).collect())
     }
 
     fn extract_loads(&self, py: Python<'_>, df: Bound<'_, PyAny>) -> PyResult<Vec<Load>> {
+        let index = Self::get_int_vec(&df, "index")?;
         let bus = Self::get_int_vec(&df, "bus")?;
         let p_mw = Self::get_float_vec(&df, "p_mw")?;
         let q_mvar = Self::get_float_vec(&df, "q_mvar")?;
@@ -139,11 +142,12 @@ impl Network {
         let names = if df.hasattr("name")? { Self::get_opt_str_vec(py, &df, "name")? } else { vec![None; bus.len()] };
 
         Ok((0..bus.len()).map(|i| Load {
-            bus: bus[i], p_mw: p_mw[i], q_mvar: q_mvar[i], in_service: in_service[i], name: names[i].clone(), scaling: 1.0, const_i_percent: 0.0, const_z_percent: 0.0, controllable: None, sn_mva: None, type_: None,
+            index: Some(index[i]), bus: bus[i], p_mw: p_mw[i], q_mvar: q_mvar[i], in_service: in_service[i], name: names[i].clone(), scaling: 1.0, const_i_percent: 0.0, const_z_percent: 0.0, controllable: None, sn_mva: None, type_: None,
         }).collect())
     }
 
     fn extract_gens(&self, py: Python<'_>, df: Bound<'_, PyAny>) -> PyResult<Vec<Gen>> {
+        let index = Self::get_int_vec(&df, "index")?;
         let bus = Self::get_int_vec(&df, "bus")?;
         let p_mw = Self::get_float_vec(&df, "p_mw")?;
         let vm_pu = Self::get_float_vec(&df, "vm_pu")?;
@@ -166,22 +170,24 @@ impl Network {
         let min_p = opt_limit("min_p_mw", -1e9)?;
 
         Ok((0..bus.len()).map(|i| Gen {
-            bus: bus[i], p_mw: p_mw[i], vm_pu: vm_pu[i], in_service: in_service[i], slack: slack[i], scaling: 1.0, max_p_mw: max_p[i], min_p_mw: min_p[i], max_q_mvar: max_q[i], min_q_mvar: min_q[i], slack_weight: 0.0, controllable: None, name: names[i].clone(), sn_mva: None, type_: None,
+            index: Some(index[i]), bus: bus[i], p_mw: p_mw[i], vm_pu: vm_pu[i], in_service: in_service[i], slack: slack[i], scaling: 1.0, max_p_mw: max_p[i], min_p_mw: min_p[i], max_q_mvar: max_q[i], min_q_mvar: min_q[i], slack_weight: 0.0, controllable: None, name: names[i].clone(), sn_mva: None, type_: None,
         }).collect())
     }
 
     fn extract_ext_grids(&self, _py: Python<'_>, df: Bound<'_, PyAny>) -> PyResult<Vec<ExtGrid>> {
+        let index = Self::get_int_vec(&df, "index")?;
         let bus = Self::get_int_vec(&df, "bus")?;
         let vm_pu = Self::get_float_vec(&df, "vm_pu")?;
         let va_degree = Self::get_float_vec(&df, "va_degree")?;
         let in_service = Self::get_bool_vec(&df, "in_service")?;
 
         Ok((0..bus.len()).map(|i| ExtGrid {
-            bus: bus[i], vm_pu: vm_pu[i], va_degree: va_degree[i], in_service: in_service[i], slack_weight: 1.0, name: None, max_p_mw: None, min_p_mw: None, max_q_mvar: None, min_q_mvar: None,
+            index: Some(index[i]), bus: bus[i], vm_pu: vm_pu[i], va_degree: va_degree[i], in_service: in_service[i], slack_weight: 1.0, name: None, max_p_mw: None, min_p_mw: None, max_q_mvar: None, min_q_mvar: None,
         }).collect())
     }
 
     fn extract_shunts(&self, _py: Python<'_>, df: Bound<'_, PyAny>) -> PyResult<Vec<Shunt>> {
+        let index = Self::get_int_vec(&df, "index")?;
         let bus = Self::get_int_vec(&df, "bus")?;
         let p_mw = Self::get_float_vec(&df, "p_mw")?;
         let q_mvar = Self::get_float_vec(&df, "q_mvar")?;
@@ -189,29 +195,31 @@ impl Network {
         let vn_kv = if df.hasattr("vn_kv")? { Self::get_float_vec(&df, "vn_kv")? } else { vec![110.0; bus.len()] };
 
         Ok((0..bus.len()).map(|i| Shunt {
-            bus: bus[i], p_mw: p_mw[i], q_mvar: q_mvar[i], in_service: in_service[i], vn_kv: vn_kv[i], step: 1, max_step: 1, name: None,
+            index: Some(index[i]), bus: bus[i], p_mw: p_mw[i], q_mvar: q_mvar[i], in_service: in_service[i], vn_kv: vn_kv[i], step: 1, max_step: 1, name: None,
         }).collect())
     }
 
     fn extract_sgens(&self, _py: Python<'_>, df: Bound<'_, PyAny>) -> PyResult<Vec<SGen>> {
+        let index = Self::get_int_vec(&df, "index")?;
         let bus = Self::get_int_vec(&df, "bus")?;
         let p_mw = Self::get_float_vec(&df, "p_mw")?;
         let q_mvar = Self::get_float_vec(&df, "q_mvar")?;
         let in_service = Self::get_bool_vec(&df, "in_service")?;
 
         Ok((0..bus.len()).map(|i| SGen {
-            bus: bus[i], p_mw: p_mw[i], q_mvar: q_mvar[i], in_service: in_service[i], scaling: 1.0, name: None, type_: None, sn_mva: None, current_source: false, controllable: None,
+            index: Some(index[i]), bus: bus[i], p_mw: p_mw[i], q_mvar: q_mvar[i], in_service: in_service[i], scaling: 1.0, name: None, type_: None, sn_mva: None, current_source: false, controllable: None,
         }).collect())
     }
 
     fn extract_switches(&self, py: Python<'_>, df: Bound<'_, PyAny>) -> PyResult<Vec<Switch>> {
+        let index = Self::get_int_vec(&df, "index")?;
         let bus = Self::get_int_vec(&df, "bus")?;
         let element = Self::get_int_vec(&df, "element")?;
         let closed = Self::get_bool_vec(&df, "closed")?;
         let et = if df.hasattr("et")? { Self::get_opt_str_vec(py, &df, "et")? } else { vec![None; bus.len()] };
 
         Ok((0..bus.len()).map(|i| Switch {
-            bus: bus[i], element: element[i], closed: closed[i], et: SwitchType::from(et[i].as_deref().unwrap_or("b")), name: None, type_: None, z_ohm: 0.0,
+            index: Some(index[i]), bus: bus[i], element: element[i], closed: closed[i], et: SwitchType::from(et[i].as_deref().unwrap_or("b")), name: None, type_: None, z_ohm: 0.0,
         }).collect())
     }
 }
@@ -44,6 +44,7 @@ impl NewtonSolver {
         app.insert_resource(PowerFlowConfig {
             max_it: Some(10),
             tol: Some(1e-8),
+            ..Default::default()
         });
         
         Self {
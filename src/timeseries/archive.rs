@@ -6,7 +6,7 @@ use crate::{
     io::archive::aurora_format::{ArchivePlugin, ArchiveSnapshotRes},
     timeseries::{
         scheduled::{ScheduledLog, ScheduledStaticActions},
-        sim_time::{DeltaTime, Time},
+        sim_time::{DeltaTime, SimClock, Time},
         state::TimeSeriesData,
     },
 };
@@ -36,7 +36,7 @@ impl<T> ArcMutExt<T> for Arc<T> {
 /// Registers all relevant time-dependent resources such as:
 /// - [`TimeSeriesData`] for voltage history
 /// - [`ScheduledLog`] for executed events
-/// - [`Time`], [`DeltaTime`] for time progression
+/// - [`Time`], [`DeltaTime`] and [`SimClock`] for time progression
 /// - [`ScheduledStaticActions`] for queued actions
 ///
 /// # Dependencies
@@ -69,7 +69,7 @@ impl Plugin for TimeSeriesArchivePlugin {
         // Register input/output resources (for full snapshot/restore)
         let mut reg = a.0.case_file_reg.clone();
         let d = unsafe { reg.get_mut_unchecked() };
-        register_res_all!(d, [ScheduledLog, Time, DeltaTime]);
+        register_res_all!(d, [ScheduledLog, Time, DeltaTime, SimClock]);
         register_all!(d, [ScheduledStaticActions]);
     }
 }
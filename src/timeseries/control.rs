@@ -0,0 +1,685 @@
+//! Controllers for quasi-static time-series simulation, in the style of
+//! pandapower's control module.
+//!
+//! A [`Controller`] adjusts element set points until its control rule is
+//! satisfied. Controllers live in the [`Controllers`] resource with a
+//! `level` and an `order`:
+//!
+//! - Once per frame, before the solve, [`Controller::time_step`] is called
+//!   for every controller, e.g. to apply profile values.
+//! - After every converged power flow, [`run_controllers`] asks the
+//!   controllers of the lowest level that is not settled yet whether they
+//!   are converged and calls [`Controller::control_step`] on the others, in
+//!   `order`. The power flow is then repeated through the nonlinear outer
+//!   loop ([`run_outer_iteration`]). Higher levels only start once all lower
+//!   levels are converged; as in pandapower, settled levels are not
+//!   revisited.
+//!
+//! Set point changes go through the [`ParamDiff`] bus, so a control step only
+//! updates bus injections. The outcome of a frame is reported in
+//! [`ControlStatus`].
+//!
+//! Built-in controllers: [`ConstControl`] (profile values), [`QofPControl`]
+//! (reactive power from a characteristic of the active power) and the
+//! voltage droops [`QofUControl`] and [`PofUControl`].
+//!
+//! [`run_outer_iteration`]: crate::basic::ecs::powerflow::nonlinear_schedule::run_outer_iteration
+
+use std::io::Error;
+
+use bevy_app::{App, First, Plugin, Update};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::basic::ecs::elements::*;
+use crate::basic::ecs::network::SolverStage;
+use crate::basic::ecs::powerflow::mutation::ParamDiff;
+use crate::basic::ecs::powerflow::nonlinear_schedule::{
+    ConvergedResult, NonLinearSchedulePlugin, NonlinearConvType,
+};
+use crate::basic::ecs::powerflow::prelude::*;
+use crate::basic::ecs::powerflow::qlim::modify_qlim_system;
+use crate::basic::ecs::powerflow::structure_update::StructureUpdatePlugin;
+use crate::basic::ecs::powerflow::voltage_init::solver_index;
+use crate::timeseries::profile::{ProfileQuantity, ProfileTable};
+use crate::timeseries::sim_time::{Time, advance, advance_clock};
+
+/// A control rule evaluated in the nonlinear outer loop.
+pub trait Controller: Send + Sync + 'static {
+    /// Called once per frame before the solve with the simulation time in
+    /// seconds.
+    fn time_step(&mut self, _world: &mut World, _time: f64) {}
+
+    /// Whether the rule is satisfied at the current power flow result.
+    fn is_converged(&self, world: &World) -> bool;
+
+    /// Adjusts set points towards the rule. Only called while
+    /// [`Controller::is_converged`] is false.
+    fn control_step(&mut self, world: &mut World);
+}
+
+struct ControllerEntry {
+    level: i32,
+    order: i32,
+    controller: Box<dyn Controller>,
+}
+
+/// Registered controllers.
+#[derive(Resource)]
+pub struct Controllers {
+    entries: Vec<ControllerEntry>,
+    /// Maximum number of control iterations per frame.
+    pub max_iter: usize,
+}
+
+impl Default for Controllers {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            max_iter: 30,
+        }
+    }
+}
+
+impl Controllers {
+    /// Adds a controller. Lower levels settle first; within a level
+    /// controllers step in ascending `order`, then in insertion order.
+    pub fn add(&mut self, controller: impl Controller, level: i32, order: i32) {
+        let idx = self
+            .entries
+            .partition_point(|e| (e.level, e.order) <= (level, order));
+        self.entries.insert(
+            idx,
+            ControllerEntry {
+                level,
+                order,
+                controller: Box::new(controller),
+            },
+        );
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Outcome of the control loop of the current frame.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct ControlStatus {
+    /// All controllers are settled.
+    pub converged: bool,
+    /// Control iterations (re-solves requested by controllers) this frame.
+    pub iterations: usize,
+    /// Index of the level currently being settled.
+    level: usize,
+}
+
+/// Calls [`Controller::time_step`] on all controllers and resets
+/// [`ControlStatus`] for the new frame.
+pub fn controllers_time_step(world: &mut World) {
+    world.insert_resource(ControlStatus::default());
+    let t = world
+        .get_resource::<Time>()
+        .map_or(0.0, Time::elapsed_seconds);
+    world.resource_scope(|world, mut ctrl: Mut<Controllers>| {
+        for entry in &mut ctrl.entries {
+            entry.controller.time_step(world, t);
+        }
+    });
+}
+
+/// Runs one control iteration after a converged power flow and requests a
+/// further outer iteration if a controller changed its set points.
+///
+/// Does nothing while another outer-loop rule (e.g. Q limits) has already
+/// requested a re-solve. Stops with `ControlStatus::converged == false`
+/// after [`Controllers::max_iter`] iterations.
+pub fn run_controllers(world: &mut World) {
+    if world.resource::<ConvergedResult>().converged != NonlinearConvType::Converged {
+        return;
+    }
+    let mut status = world.resource::<ControlStatus>().clone();
+    if status.converged {
+        return;
+    }
+    let stepped = world.resource_scope(|world, mut ctrl: Mut<Controllers>| {
+        let max_iter = ctrl.max_iter;
+        let mut levels = ctrl.entries.iter().map(|e| e.level).collect::<Vec<_>>();
+        levels.dedup();
+        while let Some(&level) = levels.get(status.level) {
+            let pending = ctrl
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.level == level && !e.controller.is_converged(world))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            if pending.is_empty() {
+                status.level += 1;
+                continue;
+            }
+            if status.iterations >= max_iter {
+                return None;
+            }
+            for i in pending {
+                ctrl.entries[i].controller.control_step(world);
+            }
+            status.iterations += 1;
+            return Some(true);
+        }
+        Some(false)
+    });
+    match stepped {
+        Some(true) => {
+            let v = world.resource::<PowerFlowResult>().v.clone();
+            world.resource_mut::<PowerFlowMat>().v_bus_init = v;
+            world.resource_mut::<ConvergedResult>().converged = NonlinearConvType::Continue;
+        }
+        Some(false) => status.converged = true,
+        None => {}
+    }
+    world.insert_resource(status);
+}
+
+/// Piecewise-linear curve through `(x, y)` points, constant beyond the
+/// first and last point.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Characteristic {
+    x: Vec<f64>,
+    y: Vec<f64>,
+}
+
+impl Characteristic {
+    /// Curve through the given points; `x` must be strictly increasing.
+    pub fn new(points: impl IntoIterator<Item = (f64, f64)>) -> Result<Self, String> {
+        let (x, y): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+        if x.is_empty() {
+            return Err("characteristic needs at least one point".to_string());
+        }
+        if x.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("characteristic x values must be strictly increasing".to_string());
+        }
+        Ok(Self { x, y })
+    }
+
+    pub fn eval(&self, x: f64) -> f64 {
+        let i = self.x.partition_point(|&xi| xi <= x);
+        if i == 0 {
+            return self.y[0];
+        }
+        if i == self.x.len() {
+            return self.y[i - 1];
+        }
+        let (x0, x1, y0, y1) = (self.x[i - 1], self.x[i], self.y[i - 1], self.y[i]);
+        y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+    }
+}
+
+/// Voltage magnitude (p.u.) of the last power flow at the bus of `entity`.
+fn element_vm(world: &World, entity: Entity) -> Option<f64> {
+    let bus = world.get::<TargetBus>(entity)?.0;
+    let idx = world.get_resource::<NodeLookup>()?.index(bus)?;
+    let mat = world.get_resource::<PowerFlowMat>()?;
+    let res = world.get_resource::<PowerFlowResult>()?;
+    let i = solver_index(mat, world.get_resource::<NodeAggRes>(), idx)?;
+    res.v.get(i).map(|v| v.norm())
+}
+
+/// Set point of an element in its own convention: consumption for loads,
+/// production for generators and static generators.
+fn target(world: &World, entity: Entity, quantity: ProfileQuantity) -> Option<f64> {
+    let value = match quantity {
+        ProfileQuantity::P => world.get::<TargetPMW>(entity)?.0,
+        ProfileQuantity::Q => world.get::<TargetQMVar>(entity)?.0,
+    };
+    let is_load = world.entity(entity).contains::<LoadCfg>();
+    Some(if is_load { -value } else { value })
+}
+
+/// Writes a set point in the element's convention and posts the change as
+/// a [`ParamDiff::Injection`]; out-of-service elements get no diff.
+fn set_target(world: &mut World, entity: Entity, quantity: ProfileQuantity, value: f64) {
+    let Some(old) = target(world, entity, quantity) else {
+        return;
+    };
+    if old == value {
+        return;
+    }
+    let mut e = world.entity_mut(entity);
+    let is_load = e.contains::<LoadCfg>();
+    let oos = e.contains::<OutOfService>();
+    let bus = e.get::<TargetBus>().unwrap().0;
+    let delta = if is_load { old - value } else { value - old };
+    match quantity {
+        ProfileQuantity::P => e.get_mut::<TargetPMW>().unwrap().0 += delta,
+        ProfileQuantity::Q => e.get_mut::<TargetQMVar>().unwrap().0 += delta,
+    }
+    if oos {
+        return;
+    }
+    let (dp_mw, dq_mvar) = match quantity {
+        ProfileQuantity::P => (delta, 0.0),
+        ProfileQuantity::Q => (0.0, delta),
+    };
+    world.write_message(ParamDiff::Injection {
+        bus,
+        dp_mw,
+        dq_mvar,
+    });
+}
+
+fn in_service(world: &World, entity: Entity) -> bool {
+    world
+        .get_entity(entity)
+        .is_ok_and(|e| !e.contains::<OutOfService>())
+}
+
+/// Writes a profile column to an element set point every time step, the
+/// counterpart of pandapower's `ConstControl`. Always converged.
+pub struct ConstControl {
+    entity: Entity,
+    quantity: ProfileQuantity,
+    profile: ProfileTable,
+    scale: f64,
+}
+
+impl ConstControl {
+    /// Drives `quantity` of `entity` with `column` of `table`, multiplied by
+    /// `scale`. Values use the element's convention; NaN samples leave the
+    /// set point unchanged.
+    pub fn new(
+        entity: Entity,
+        quantity: ProfileQuantity,
+        table: &ProfileTable,
+        column: &str,
+        scale: f64,
+    ) -> Result<Self, Error> {
+        let idx = table.column_index(column).ok_or_else(|| {
+            Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no profile column `{column}`"),
+            )
+        })?;
+        let profile = ProfileTable::new(
+            table.time.clone(),
+            vec![(column.to_string(), table.columns[idx].clone())],
+        )?
+        .with_interpolation(table.interpolation);
+        Ok(Self {
+            entity,
+            quantity,
+            profile,
+            scale,
+        })
+    }
+}
+
+impl Controller for ConstControl {
+    fn time_step(&mut self, world: &mut World, time: f64) {
+        let value = self.profile.value_at(0, time) * self.scale;
+        if !value.is_nan() {
+            set_target(world, self.entity, self.quantity, value);
+        }
+    }
+
+    fn is_converged(&self, _world: &World) -> bool {
+        true
+    }
+
+    fn control_step(&mut self, _world: &mut World) {}
+}
+
+/// Sets the reactive power of a static generator from a characteristic of
+/// its active power (MW -> MVar), e.g. a cos φ(P) rule.
+pub struct QofPControl {
+    entity: Entity,
+    curve: Characteristic,
+    /// Tolerance in MVar.
+    pub tol: f64,
+}
+
+impl QofPControl {
+    pub fn new(entity: Entity, curve: Characteristic) -> Self {
+        Self {
+            entity,
+            curve,
+            tol: 1e-3,
+        }
+    }
+
+    fn q_set(&self, world: &World) -> Option<f64> {
+        target(world, self.entity, ProfileQuantity::P).map(|p| self.curve.eval(p))
+    }
+}
+
+impl Controller for QofPControl {
+    fn is_converged(&self, world: &World) -> bool {
+        if !in_service(world, self.entity) {
+            return true;
+        }
+        let q = target(world, self.entity, ProfileQuantity::Q);
+        match (q, self.q_set(world)) {
+            (Some(q), Some(q_set)) => (q - q_set).abs() <= self.tol,
+            _ => true,
+        }
+    }
+
+    fn control_step(&mut self, world: &mut World) {
+        if let Some(q_set) = self.q_set(world) {
+            set_target(world, self.entity, ProfileQuantity::Q, q_set);
+        }
+    }
+}
+
+/// Q(U) droop of a static generator: reactive power (MVar) from a
+/// characteristic of its bus voltage (p.u.).
+///
+/// Each step moves the reactive power by `damping` times the distance to
+/// the characteristic, which keeps steep droops from oscillating.
+pub struct QofUControl {
+    entity: Entity,
+    curve: Characteristic,
+    /// Fraction of the remaining deviation corrected per step, in (0, 1].
+    pub damping: f64,
+    /// Tolerance in MVar.
+    pub tol: f64,
+}
+
+impl QofUControl {
+    pub fn new(entity: Entity, curve: Characteristic) -> Self {
+        Self {
+            entity,
+            curve,
+            damping: 0.5,
+            tol: 1e-3,
+        }
+    }
+
+    pub fn with_damping(mut self, damping: f64) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Current and characteristic reactive power.
+    fn q(&self, world: &World) -> Option<(f64, f64)> {
+        let q = target(world, self.entity, ProfileQuantity::Q)?;
+        let vm = element_vm(world, self.entity)?;
+        Some((q, self.curve.eval(vm)))
+    }
+}
+
+impl Controller for QofUControl {
+    fn is_converged(&self, world: &World) -> bool {
+        if !in_service(world, self.entity) {
+            return true;
+        }
+        self.q(world)
+            .is_none_or(|(q, q_set)| (q - q_set).abs() <= self.tol)
+    }
+
+    fn control_step(&mut self, world: &mut World) {
+        if let Some((q, q_set)) = self.q(world) {
+            let q = q + self.damping * (q_set - q);
+            set_target(world, self.entity, ProfileQuantity::Q, q);
+        }
+    }
+}
+
+/// P(U) droop of a static generator: the active power is limited to a
+/// fraction of the available power given by a characteristic of the bus
+/// voltage (p.u. -> fraction).
+///
+/// The available power is the set point found at the start of each frame,
+/// so profiles or other controllers of a lower `order` may change it; a set
+/// point still equal to the last curtailed value is taken as unchanged.
+pub struct PofUControl {
+    entity: Entity,
+    curve: Characteristic,
+    /// Fraction of the remaining deviation corrected per step, in (0, 1].
+    pub damping: f64,
+    /// Tolerance in MW.
+    pub tol: f64,
+    p_available: Option<f64>,
+    p_set: Option<f64>,
+}
+
+impl PofUControl {
+    pub fn new(entity: Entity, curve: Characteristic) -> Self {
+        Self {
+            entity,
+            curve,
+            damping: 0.5,
+            tol: 1e-3,
+            p_available: None,
+            p_set: None,
+        }
+    }
+
+    pub fn with_damping(mut self, damping: f64) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Current and characteristic active power.
+    fn p(&self, world: &World) -> Option<(f64, f64)> {
+        let p = target(world, self.entity, ProfileQuantity::P)?;
+        let vm = element_vm(world, self.entity)?;
+        Some((p, self.p_available? * self.curve.eval(vm)))
+    }
+}
+
+impl Controller for PofUControl {
+    fn time_step(&mut self, world: &mut World, _time: f64) {
+        let p = target(world, self.entity, ProfileQuantity::P);
+        if p != self.p_set {
+            self.p_available = p;
+        }
+        if let Some(p) = self.p_available {
+            set_target(world, self.entity, ProfileQuantity::P, p);
+            self.p_set = Some(p);
+        }
+    }
+
+    fn is_converged(&self, world: &World) -> bool {
+        if !in_service(world, self.entity) {
+            return true;
+        }
+        self.p(world)
+            .is_none_or(|(p, p_set)| (p - p_set).abs() <= self.tol)
+    }
+
+    fn control_step(&mut self, world: &mut World) {
+        if let Some((p, p_set)) = self.p(world) {
+            let p = p + self.damping * (p_set - p);
+            set_target(world, self.entity, ProfileQuantity::P, p);
+            self.p_set = Some(p);
+        }
+    }
+}
+
+/// Runs the [`Controllers`] in the nonlinear outer loop.
+///
+/// # System Scheduling
+/// - [`controllers_time_step`] runs in `First`, after the simulation time
+///   advanced.
+/// - [`run_controllers`] runs in `Update` within
+///   [`SolverStage::AfterSolve`], after the Q-limit check.
+///
+/// # Plugin Dependencies
+/// Adds [`StructureUpdatePlugin`] and [`NonLinearSchedulePlugin`] if they
+/// are not present.
+#[derive(Default)]
+pub struct ControllerPlugin;
+
+impl Plugin for ControllerPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<StructureUpdatePlugin>() {
+            app.add_plugins(StructureUpdatePlugin);
+        }
+        if !app.is_plugin_added::<NonLinearSchedulePlugin>() {
+            app.add_plugins(NonLinearSchedulePlugin);
+        }
+        app.init_resource::<Controllers>();
+        app.init_resource::<ControlStatus>();
+        app.add_systems(
+            First,
+            controllers_time_step.after(advance).after(advance_clock),
+        );
+        app.add_systems(
+            Update,
+            run_controllers
+                .in_set(SolverStage::AfterSolve)
+                .after(modify_qlim_system)
+                .run_if(resource_exists::<PowerFlowResult>),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::network::{DataOps, PowerFlow, PowerGrid};
+    use crate::io::pandapower::{SGen, load_csv_zip};
+    use crate::prelude::PPNetwork;
+    use crate::timeseries::profile::parse_profiles_csv;
+    use crate::timeseries::scheduled::ElementKind;
+    use crate::timeseries::sim_time::{DeltaTime, TimePlugin};
+    use std::env;
+    use std::sync::{Arc, Mutex};
+
+    /// IEEE 118 with one static generator at a load bus.
+    fn grid_118(p_mw: f64) -> (PowerGrid, Entity) {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let mut net = load_csv_zip(&format!("{dir}/cases/IEEE118/data.zip")).unwrap();
+        net.sgen = Some(vec![SGen {
+            bus: 1,
+            p_mw,
+            scaling: 1.0,
+            in_service: true,
+            ..Default::default()
+        }]);
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.app_mut().add_plugins((TimePlugin, ControllerPlugin));
+        grid.world_mut().insert_resource(DeltaTime(900.0));
+        let sgen = ElementKind::SGen.entity(grid.world_mut(), 0).unwrap();
+        (grid, sgen)
+    }
+
+    #[test]
+    fn test_characteristic() {
+        let c = Characteristic::new([(0.95, 1.0), (1.05, -1.0)]).unwrap();
+        assert_eq!(c.eval(0.9), 1.0);
+        assert!((c.eval(1.0)).abs() < 1e-12);
+        assert_eq!(c.eval(1.1), -1.0);
+        assert!(Characteristic::new([(1.0, 0.0), (1.0, 1.0)]).is_err());
+        assert!(Characteristic::new([]).is_err());
+    }
+
+    #[test]
+    fn test_q_of_u_droop() {
+        let (mut grid, sgen) = grid_118(50.0);
+        let curve = Characteristic::new([(0.95, 20.0), (1.05, -20.0)]).unwrap();
+        let ctrl = QofUControl::new(sgen, curve.clone());
+        grid.world_mut()
+            .resource_mut::<Controllers>()
+            .add(ctrl, 0, 0);
+        grid.run_pf();
+
+        let world = grid.world();
+        let status = world.resource::<ControlStatus>();
+        assert!(status.converged && status.iterations > 1);
+        assert!(world.resource::<PowerFlowResult>().converged);
+        let q = world.get::<TargetQMVar>(sgen).unwrap().0;
+        let vm = element_vm(world, sgen).unwrap();
+        assert!(q != 0.0);
+        assert!((q - curve.eval(vm)).abs() <= 1e-3);
+    }
+
+    #[test]
+    fn test_profile_and_droop() {
+        let (mut grid, sgen) = grid_118(0.0);
+        let table = parse_profiles_csv("time,pv\n0,0\n900,80\n1800,120\n").unwrap();
+        let p_of_u = Characteristic::new([(0.9, 1.0), (1.2, 0.0)]).unwrap();
+        let q_of_p = Characteristic::new([(0.0, 0.0), (100.0, -30.0)]).unwrap();
+        {
+            let mut ctrl = grid.world_mut().resource_mut::<Controllers>();
+            let profile = ConstControl::new(sgen, ProfileQuantity::P, &table, "pv", 1.0).unwrap();
+            ctrl.add(QofPControl::new(sgen, q_of_p.clone()), 0, 2);
+            ctrl.add(PofUControl::new(sgen, p_of_u.clone()), 0, 1);
+            ctrl.add(profile, 0, 0);
+            assert_eq!(ctrl.len(), 3);
+        }
+
+        // Frames at 900 s and 1800 s; the droop never accumulates across
+        // frames because the profile resets the available power.
+        for p_available in [80.0, 120.0] {
+            grid.run_pf();
+            let world = grid.world();
+            assert!(world.resource::<ControlStatus>().converged);
+            let p = world.get::<TargetPMW>(sgen).unwrap().0;
+            let q = world.get::<TargetQMVar>(sgen).unwrap().0;
+            let vm = element_vm(world, sgen).unwrap();
+            assert!(p < p_available);
+            assert!((p - p_available * p_of_u.eval(vm)).abs() <= 2e-3);
+            assert!((q - q_of_p.eval(p)).abs() <= 1e-3);
+        }
+    }
+
+    /// Converges after `steps` control steps per frame, logging its name.
+    struct Counter {
+        name: &'static str,
+        steps: usize,
+        taken: usize,
+        log: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl Controller for Counter {
+        fn time_step(&mut self, _world: &mut World, _time: f64) {
+            self.taken = 0;
+        }
+
+        fn is_converged(&self, _world: &World) -> bool {
+            self.taken >= self.steps
+        }
+
+        fn control_step(&mut self, _world: &mut World) {
+            self.taken += 1;
+            self.log.lock().unwrap().push(self.name);
+        }
+    }
+
+    #[test]
+    fn test_levels_and_max_iter() {
+        let (mut grid, _) = grid_118(0.0);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let counter = |name, steps| Counter {
+            name,
+            steps,
+            taken: 0,
+            log: log.clone(),
+        };
+        {
+            let mut ctrl = grid.world_mut().resource_mut::<Controllers>();
+            ctrl.add(counter("b", 1), 1, 0);
+            ctrl.add(counter("a2", 1), 0, 1);
+            ctrl.add(counter("a1", 2), 0, 0);
+        }
+        grid.run_pf();
+        assert_eq!(*log.lock().unwrap(), ["a1", "a2", "a1", "b"]);
+        let status = grid.world().resource::<ControlStatus>();
+        assert!(status.converged);
+        assert_eq!(status.iterations, 3);
+
+        grid.world_mut().resource_mut::<Controllers>().max_iter = 2;
+        grid.run_pf();
+        let status = grid.world().resource::<ControlStatus>();
+        assert!(!status.converged);
+        assert_eq!(status.iterations, 2);
+    }
+}
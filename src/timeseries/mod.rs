@@ -3,6 +3,15 @@
 #[cfg(feature = "archive")]
 pub mod archive;
 
+/// Controller framework (pandapower-style control loop) run in the nonlinear outer loop.
+pub mod control;
+
+/// Load and generation profiles (CSV/Parquet) applied to elements every step.
+pub mod profile;
+
+/// Per-step recording of selected results, in memory or streamed to Parquet.
+pub mod recorder;
+
 /// Plugin that enables time-driven scheduled event injection (e.g., switching operations).
 pub mod scheduled;
 
@@ -15,7 +24,8 @@ use bevy_app::plugin_group;
 use sim_time::TimePlugin;
 
 use crate::timeseries::{
-    archive::TimeSeriesArchivePlugin, scheduled::ScheduledEventPlugin, state::StateTransferPlugin,
+    archive::TimeSeriesArchivePlugin, profile::ProfilePlugin, recorder::ResultRecorderPlugin,
+    scheduled::ScheduledEventPlugin, state::StateTransferPlugin,
 };
 
 plugin_group! {
@@ -25,6 +35,8 @@ plugin_group! {
 /// This group includes systems for:
 /// - Advancing and tracking simulation time (`TimePlugin`)
 /// - Executing scheduled control events at specified timestamps (`ScheduledEventPlugin`)
+/// - Applying load and generation profiles each step (`ProfilePlugin`)
+/// - Recording selected results each step (`ResultRecorderPlugin`)
 /// - Propagating or transferring simulation state (`StateTransferPlugin`)
 /// - Optionally archiving time-series results (`TimeSeriesArchivePlugin`, feature-gated)
 /// # Feature Flags
@@ -34,6 +46,8 @@ plugin_group! {
         :TimePlugin,
         :StateTransferPlugin,
         :ScheduledEventPlugin,
+        :ProfilePlugin,
+        :ResultRecorderPlugin,
 
         #[cfg(feature = "archive")]
         crate::timeseries:::TimeSeriesArchivePlugin,
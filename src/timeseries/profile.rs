@@ -0,0 +1,462 @@
+//! Load and generation profiles for quasi-static time-series simulation.
+//!
+//! A [`ProfileTable`] is a columnar time series: one time axis in seconds
+//! and any number of named value columns, read from CSV or (with the
+//! `arrow` feature) Parquet. The first column of a file is the time axis,
+//! every other column one profile. Missing values are NaN and leave the
+//! element unchanged for that step.
+//!
+//! [`Profiles`] binds columns to element entities with a scaling factor.
+//! Every frame [`apply_profiles`] samples the bound columns at the current
+//! simulation [`Time`] and writes `TargetPMW` / `TargetQMVar` of the
+//! elements, posting the changes as [`ParamDiff`] injections so only the
+//! bus injections are updated instead of rebuilding the case. Values use
+//! the element's own convention: consumption for loads, production for
+//! generators and static generators.
+
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::basic::ecs::elements::*;
+use crate::basic::ecs::network::SolverStage;
+use crate::basic::ecs::powerflow::mutation::ParamDiff;
+use crate::basic::ecs::powerflow::structure_update::StructureUpdatePlugin;
+use crate::timeseries::sim_time::Time;
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+/// How a profile is evaluated between its samples. Before the first and
+/// after the last sample the nearest sample holds.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Interpolation {
+    /// The last sample at or before the current time holds until the next.
+    #[default]
+    Previous,
+    /// Linear between neighbouring samples.
+    Linear,
+}
+
+/// Element quantity a profile column drives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProfileQuantity {
+    /// Active power in MW.
+    P,
+    /// Reactive power in MVar.
+    Q,
+}
+
+/// Columnar time series sharing one time axis.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ProfileTable {
+    /// Sample times in seconds, non-decreasing.
+    pub time: Vec<f64>,
+    /// Column names, in file order.
+    pub names: Vec<String>,
+    /// Column values, one entry per sample time.
+    pub columns: Vec<Vec<f64>>,
+    /// Evaluation between samples.
+    pub interpolation: Interpolation,
+}
+
+impl ProfileTable {
+    /// Builds a table from a time axis and named columns.
+    pub fn new(time: Vec<f64>, columns: Vec<(String, Vec<f64>)>) -> Result<Self, Error> {
+        if !time.is_sorted() {
+            return Err(invalid("profile time axis is not sorted"));
+        }
+        let (names, columns): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+        if let Some(i) = columns.iter().position(|c| c.len() != time.len()) {
+            return Err(invalid(format!(
+                "profile column '{}' has {} values for {} times",
+                names[i],
+                columns[i].len(),
+                time.len()
+            )));
+        }
+        Ok(Self {
+            time,
+            names,
+            columns,
+            interpolation: Interpolation::default(),
+        })
+    }
+
+    /// Sets the evaluation between samples.
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Number of samples.
+    pub fn len(&self) -> usize {
+        self.time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.time.is_empty()
+    }
+
+    /// Position of the column called `name`.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// Value of column `col` at time `t`; NaN for an empty table.
+    pub fn value_at(&self, col: usize, t: f64) -> f64 {
+        let values = &self.columns[col];
+        // First sample after t.
+        let next = self.time.partition_point(|&ti| ti <= t);
+        match next {
+            0 => values.first().copied().unwrap_or(f64::NAN),
+            n if n == self.time.len() => values[n - 1],
+            n => match self.interpolation {
+                Interpolation::Previous => values[n - 1],
+                Interpolation::Linear => {
+                    let (t0, t1) = (self.time[n - 1], self.time[n]);
+                    let w = (t - t0) / (t1 - t0);
+                    values[n - 1] + w * (values[n] - values[n - 1])
+                }
+            },
+        }
+    }
+}
+
+/// Parses profiles from CSV text: a header row, the time axis in the first
+/// column and one profile per further column. Empty fields are NaN.
+pub fn parse_profiles_csv(text: &str) -> Result<ProfileTable, Error> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let headers = rdr.headers().map_err(|e| invalid(e.to_string()))?.clone();
+    if headers.len() < 2 {
+        return Err(invalid(
+            "profile CSV needs a time column and a value column",
+        ));
+    }
+    let mut time = Vec::new();
+    let mut columns = vec![Vec::new(); headers.len() - 1];
+    for record in rdr.records() {
+        let record = record.map_err(|e| invalid(e.to_string()))?;
+        let mut fields = record.iter().map(|s| {
+            if s.is_empty() {
+                Ok(f64::NAN)
+            } else {
+                s.parse::<f64>()
+                    .map_err(|_| invalid(format!("invalid number '{s}' in profile CSV")))
+            }
+        });
+        time.push(fields.next().unwrap_or(Ok(f64::NAN))?);
+        for (col, v) in columns.iter_mut().zip(fields) {
+            col.push(v?);
+        }
+    }
+    let names = headers.iter().skip(1).map(str::to_string);
+    ProfileTable::new(time, names.zip(columns).collect())
+}
+
+/// Reads profiles from a CSV file, see [`parse_profiles_csv`].
+pub fn load_profiles_csv(path: impl AsRef<Path>) -> Result<ProfileTable, Error> {
+    parse_profiles_csv(&fs::read_to_string(path)?)
+}
+
+/// Reads profiles from a Parquet file: the time axis in the first column
+/// and one profile per further column. All columns must be numeric; nulls
+/// are NaN.
+#[cfg(feature = "arrow")]
+pub fn load_profiles_parquet(path: impl AsRef<Path>) -> Result<ProfileTable, Error> {
+    use arrow::array::{Array, Float64Array, RecordBatchReader};
+    use arrow::compute::cast;
+    use arrow::datatypes::DataType;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let file = fs::File::open(path)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .and_then(|b| b.build())
+        .map_err(|e| invalid(e.to_string()))?;
+    let schema = reader.schema();
+    if schema.fields().len() < 2 {
+        return Err(invalid(
+            "profile table needs a time column and a value column",
+        ));
+    }
+    let mut values = vec![Vec::new(); schema.fields().len()];
+    for batch in reader {
+        let batch = batch.map_err(|e| invalid(e.to_string()))?;
+        for (out, column) in values.iter_mut().zip(batch.columns()) {
+            let column = cast(column, &DataType::Float64).map_err(|e| invalid(e.to_string()))?;
+            let column = column
+                .as_any()
+                .downcast_ref::<Float64Array>()
+                .expect("cast to Float64");
+            out.extend((0..column.len()).map(|i| {
+                if column.is_null(i) {
+                    f64::NAN
+                } else {
+                    column.value(i)
+                }
+            }));
+        }
+    }
+    let mut values = values.into_iter();
+    let time = values.next().unwrap_or_default();
+    let names = schema.fields().iter().skip(1).map(|f| f.name().clone());
+    ProfileTable::new(time, names.zip(values).collect())
+}
+
+/// One profile column driving one element quantity.
+#[derive(Debug, Clone)]
+pub struct ProfileBinding {
+    pub entity: Entity,
+    /// Index into [`Profiles::tables`].
+    pub table: usize,
+    /// Column index in the table.
+    pub column: usize,
+    pub quantity: ProfileQuantity,
+    /// Factor applied to the profile values.
+    pub scale: f64,
+}
+
+/// Profile tables and their bindings to elements.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct Profiles {
+    pub tables: Vec<ProfileTable>,
+    pub bindings: Vec<ProfileBinding>,
+}
+
+impl Profiles {
+    /// Adds a table and returns its index.
+    pub fn add_table(&mut self, table: ProfileTable) -> usize {
+        self.tables.push(table);
+        self.tables.len() - 1
+    }
+
+    /// Binds `column` of `table` to `quantity` of `entity`. Returns false if
+    /// the table or column does not exist.
+    pub fn bind(
+        &mut self,
+        entity: Entity,
+        table: usize,
+        column: &str,
+        quantity: ProfileQuantity,
+        scale: f64,
+    ) -> bool {
+        let Some(column) = self.tables.get(table).and_then(|t| t.column_index(column)) else {
+            return false;
+        };
+        self.bindings.push(ProfileBinding {
+            entity,
+            table,
+            column,
+            quantity,
+            scale,
+        });
+        true
+    }
+
+    /// Binds every column of `table` named like one of `elements` to that
+    /// element. Returns the number of bindings made.
+    pub fn bind_by_name<'a>(
+        &mut self,
+        table: usize,
+        elements: impl IntoIterator<Item = (Entity, &'a str)>,
+        quantity: ProfileQuantity,
+        scale: f64,
+    ) -> usize {
+        elements
+            .into_iter()
+            .filter(|&(entity, name)| self.bind(entity, table, name, quantity, scale))
+            .count()
+    }
+}
+
+/// Writes the profile values at the current time into the bound elements.
+///
+/// `TargetPMW` / `TargetQMVar` are updated and the differences posted as
+/// [`ParamDiff::Injection`]; out-of-service elements are updated without a
+/// diff. Unchanged and NaN values are skipped.
+#[allow(clippy::type_complexity)]
+pub fn apply_profiles(
+    time: Res<Time>,
+    profiles: Res<Profiles>,
+    mut elements: Query<(
+        &TargetBus,
+        Option<&mut TargetPMW>,
+        Option<&mut TargetQMVar>,
+        Has<LoadCfg>,
+        Has<OutOfService>,
+    )>,
+    mut diffs: MessageWriter<ParamDiff>,
+) {
+    let t = time.elapsed_seconds();
+    for b in &profiles.bindings {
+        let value = profiles.tables[b.table].value_at(b.column, t) * b.scale;
+        if value.is_nan() {
+            continue;
+        }
+        let Ok((bus, p, q, is_load, oos)) = elements.get_mut(b.entity) else {
+            continue;
+        };
+        // Loads store consumption as a negative injection.
+        let target = if is_load { -value } else { value };
+        let old = match b.quantity {
+            ProfileQuantity::P => p.map(|mut p| std::mem::replace(&mut p.0, target)),
+            ProfileQuantity::Q => q.map(|mut q| std::mem::replace(&mut q.0, target)),
+        };
+        let Some(old) = old else { continue };
+        if old == target || oos {
+            continue;
+        }
+        let (dp_mw, dq_mvar) = match b.quantity {
+            ProfileQuantity::P => (target - old, 0.0),
+            ProfileQuantity::Q => (0.0, target - old),
+        };
+        diffs.write(ParamDiff::Injection {
+            bus: bus.0,
+            dp_mw,
+            dq_mvar,
+        });
+    }
+}
+
+/// Drives element set points from [`Profiles`] every frame.
+///
+/// [`apply_profiles`] runs in `Update` within [`SolverStage::BeforeSolve`]
+/// whenever a [`Profiles`] resource exists, so the solve of a frame sees the
+/// profile values at that frame's time.
+#[derive(Default)]
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<StructureUpdatePlugin>() {
+            app.add_plugins(StructureUpdatePlugin);
+        }
+        app.add_systems(
+            Update,
+            apply_profiles
+                .run_if(resource_exists::<Profiles>)
+                .run_if(resource_exists::<Time>)
+                .in_set(SolverStage::BeforeSolve),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::network::{DataOps, PowerFlow, PowerGrid};
+    use crate::basic::ecs::powerflow::prelude::PowerFlowResult;
+    use crate::io::pandapower::{Network, load_csv_zip};
+    use crate::timeseries::sim_time::{DeltaTime, TimePlugin};
+    use std::env;
+
+    const CSV: &str = "time,load_a,sgen_b\n0,1.0,\n900,2.0,0.5\n1800,4.0,1.5\n";
+
+    #[test]
+    fn test_csv_and_interpolation() {
+        let table = parse_profiles_csv(CSV).unwrap();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.names, ["load_a", "sgen_b"]);
+        assert!(table.columns[1][0].is_nan());
+        let a = table.column_index("load_a").unwrap();
+        assert_eq!(table.value_at(a, -10.0), 1.0);
+        assert_eq!(table.value_at(a, 450.0), 1.0);
+        assert_eq!(table.value_at(a, 900.0), 2.0);
+        assert_eq!(table.value_at(a, 9000.0), 4.0);
+        let table = table.with_interpolation(Interpolation::Linear);
+        assert_eq!(table.value_at(a, 450.0), 1.5);
+        assert_eq!(table.value_at(a, 1350.0), 3.0);
+
+        assert!(parse_profiles_csv("time,a\n900,1\n0,2\n").is_err());
+        assert!(parse_profiles_csv("time,a\n0,x\n").is_err());
+        assert!(parse_profiles_csv("time\n0\n").is_err());
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_parquet() {
+        use arrow::array::{ArrayRef, Float32Array, Int64Array, RecordBatch};
+        use parquet::arrow::ArrowWriter;
+        use std::sync::Arc;
+
+        let batch = RecordBatch::try_from_iter([
+            (
+                "t",
+                Arc::new(Int64Array::from(vec![0, 900, 1800])) as ArrayRef,
+            ),
+            (
+                "pv",
+                Arc::new(Float32Array::from(vec![Some(0.0), None, Some(2.5)])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let path =
+            env::temp_dir().join(format!("rustpower_profiles_{}.parquet", std::process::id()));
+        let mut writer =
+            ArrowWriter::try_new(fs::File::create(&path).unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let table = load_profiles_parquet(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(table.time, [0.0, 900.0, 1800.0]);
+        assert_eq!(table.names, ["pv"]);
+        assert_eq!(table.columns[0][2], 2.5);
+        assert!(table.columns[0][1].is_nan());
+    }
+
+    fn ieee118() -> Network {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        load_csv_zip(&format!("{dir}/cases/IEEE118/data.zip")).unwrap()
+    }
+
+    fn solve(net: Network) -> PowerFlowResult {
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.run_pf();
+        grid.world().resource::<PowerFlowResult>().clone()
+    }
+
+    #[test]
+    fn test_profiles_drive_power_flow() {
+        let net = ieee118();
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net.clone()));
+        grid.init_pf_net();
+        grid.app_mut().add_plugins((TimePlugin, ProfilePlugin));
+        grid.world_mut().insert_resource(DeltaTime(900.0));
+
+        let world = grid.world_mut();
+        let loads = ElementKind::Load.entities(world);
+        let mut profiles = Profiles::default();
+        let table = profiles.add_table(parse_profiles_csv(CSV).unwrap());
+        assert!(profiles.bind(loads[0], table, "load_a", ProfileQuantity::P, 10.0));
+        assert!(profiles.bind(loads[0], table, "sgen_b", ProfileQuantity::Q, 2.0));
+        assert!(!profiles.bind(loads[1], table, "missing", ProfileQuantity::P, 1.0));
+        world.insert_resource(profiles);
+
+        // Frames at 900 s and 1800 s.
+        for (p_mw, q_mvar) in [(20.0, 1.0), (40.0, 3.0)] {
+            grid.run_pf();
+            let world = grid.world();
+            assert_eq!(world.get::<TargetPMW>(loads[0]).unwrap().0, -p_mw);
+            assert_eq!(world.get::<TargetQMVar>(loads[0]).unwrap().0, -q_mvar);
+
+            let mut expected = net.clone();
+            let load = &mut expected.load.as_mut().unwrap()[0];
+            (load.p_mw, load.q_mvar) = (p_mw, q_mvar);
+            let expected = solve(expected);
+            let res = world.resource::<PowerFlowResult>();
+            assert!(res.converged);
+            assert!((&res.v - &expected.v).camax() < 1e-8);
+        }
+    }
+}
@@ -0,0 +1,586 @@
+//! Per-step recording of power flow results for time-series simulation.
+//!
+//! A [`ResultRecorder`] captures a configurable set of [`RecordedQuantity`]s
+//! after every solve into columnar buffers, one [`RecordedSeries`] per
+//! quantity with one column per element. Columns are keyed by original ids:
+//! the `BusID` for bus quantities and the pandapower index
+//! ([`ElementIndex`]) for element quantities.
+//!
+//! By default everything stays in memory. With the `arrow` feature the
+//! recorder can instead stream to one Parquet file per quantity
+//! ([`ResultRecorder::with_parquet`]): every `chunk_rows` steps the buffers
+//! are written as one row group and cleared, so long runs on large cases
+//! only hold one chunk in RAM. Each file has a `time` column followed by
+//! one column per id, like the pandapower `OutputWriter` tables. When a
+//! [`SimClock`] is present the files also get a `timestamp` column, and
+//! buffered steps can be looked up by timestamp.
+
+use std::io::Error;
+#[cfg(feature = "arrow")]
+use std::path::{Path, PathBuf};
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_ecs::prelude::*;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::basic::ecs::elements::*;
+use crate::basic::ecs::post_processing::res_elements::{
+    bus_voltages, extract_res_devices, extract_res_trafo,
+};
+use crate::basic::ecs::post_processing::{
+    GenResultData, LineResultData, TrafoResultData, extract_res_line,
+};
+use crate::basic::ecs::powerflow::prelude::*;
+use crate::timeseries::scheduled::{ElementKind, scheduled_action_system};
+use crate::timeseries::sim_time::{SimClock, Time};
+
+/// Result quantity captured by the [`ResultRecorder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RecordedQuantity {
+    /// Bus voltage magnitude (p.u.), one column per bus id.
+    BusVm,
+    /// Bus voltage angle (degrees), one column per bus id.
+    BusVa,
+    /// Line loading (%), one column per line.
+    LineLoading,
+    /// Transformer loading (%), one column per transformer.
+    TrafoLoading,
+    /// Reactive power of voltage-controlling generators (MVar), one column
+    /// per generator.
+    GenQ,
+    /// Total active power losses of lines and transformers (MW), a single
+    /// column with id 0.
+    Losses,
+}
+
+impl RecordedQuantity {
+    /// All recordable quantities.
+    pub const ALL: [RecordedQuantity; 6] = [
+        RecordedQuantity::BusVm,
+        RecordedQuantity::BusVa,
+        RecordedQuantity::LineLoading,
+        RecordedQuantity::TrafoLoading,
+        RecordedQuantity::GenQ,
+        RecordedQuantity::Losses,
+    ];
+
+    /// Name of the quantity as `<result table>.<column>`, also the file stem
+    /// of its Parquet output.
+    pub fn name(self) -> &'static str {
+        match self {
+            RecordedQuantity::BusVm => "res_bus.vm_pu",
+            RecordedQuantity::BusVa => "res_bus.va_degree",
+            RecordedQuantity::LineLoading => "res_line.loading_percent",
+            RecordedQuantity::TrafoLoading => "res_trafo.loading_percent",
+            RecordedQuantity::GenQ => "res_gen.q_mvar",
+            RecordedQuantity::Losses => "res_losses.pl_mw",
+        }
+    }
+}
+
+/// Buffered values of one quantity.
+#[derive(Debug, Clone)]
+pub struct RecordedSeries {
+    pub quantity: RecordedQuantity,
+    /// Column ids, fixed by the first recorded step.
+    pub ids: Vec<i64>,
+    /// One buffer per id, aligned with [`ResultRecorder::time`].
+    pub columns: Vec<Vec<f64>>,
+}
+
+impl RecordedSeries {
+    fn new(quantity: RecordedQuantity) -> Self {
+        Self {
+            quantity,
+            ids: Vec::new(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Buffered values of the column with the given id.
+    pub fn column(&self, id: i64) -> Option<&[f64]> {
+        let idx = self.ids.iter().position(|&i| i == id)?;
+        Some(&self.columns[idx])
+    }
+
+    /// Appends one step. The first step fixes the ids; later steps are
+    /// matched by id, ids that are gone record NaN and new ones are dropped.
+    fn push(&mut self, ids: Vec<i64>, values: Vec<f64>) {
+        if self.columns.is_empty() {
+            self.columns = values.into_iter().map(|v| vec![v]).collect();
+            self.ids = ids;
+        } else if ids == self.ids {
+            for (column, v) in self.columns.iter_mut().zip(values) {
+                column.push(v);
+            }
+        } else {
+            let values = ids
+                .into_iter()
+                .zip(values)
+                .collect::<std::collections::HashMap<_, _>>();
+            for (column, id) in self.columns.iter_mut().zip(&self.ids) {
+                column.push(values.get(id).copied().unwrap_or(f64::NAN));
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.columns.iter_mut().for_each(Vec::clear);
+    }
+}
+
+/// Parquet output of a streaming recorder.
+#[cfg(feature = "arrow")]
+struct ParquetSink {
+    dir: PathBuf,
+    chunk_rows: usize,
+    /// One writer per series, opened with the first chunk. Behind a mutex
+    /// only to make the resource `Sync`.
+    writers: Vec<Option<std::sync::Mutex<ParquetWriter>>>,
+}
+
+#[cfg(feature = "arrow")]
+type ParquetWriter = parquet::arrow::ArrowWriter<std::fs::File>;
+
+/// Records the configured quantities after every solve.
+///
+/// Insert it as a resource next to [`ResultRecorderPlugin`]. Steps that did
+/// not converge are recorded as NaN; out-of-service lines record zero.
+#[derive(Resource)]
+pub struct ResultRecorder {
+    series: Vec<RecordedSeries>,
+    time: Vec<f64>,
+    rows_flushed: usize,
+    start: Option<NaiveDateTime>,
+    error: Option<Error>,
+    #[cfg(feature = "arrow")]
+    sink: Option<ParquetSink>,
+}
+
+impl ResultRecorder {
+    /// Recorder that keeps all steps in memory.
+    pub fn new(quantities: impl IntoIterator<Item = RecordedQuantity>) -> Self {
+        let mut series: Vec<RecordedSeries> = Vec::new();
+        for q in quantities {
+            if series.iter().all(|s| s.quantity != q) {
+                series.push(RecordedSeries::new(q));
+            }
+        }
+        Self {
+            series,
+            time: Vec::new(),
+            rows_flushed: 0,
+            start: None,
+            error: None,
+            #[cfg(feature = "arrow")]
+            sink: None,
+        }
+    }
+
+    /// Streams the recorded steps to `<dir>/<quantity name>.parquet`,
+    /// writing and clearing the buffers every `chunk_rows` steps. The files
+    /// are complete after [`ResultRecorder::finish`].
+    #[cfg(feature = "arrow")]
+    pub fn with_parquet(mut self, dir: impl AsRef<Path>, chunk_rows: usize) -> Self {
+        self.sink = Some(ParquetSink {
+            dir: dir.as_ref().to_path_buf(),
+            chunk_rows: chunk_rows.max(1),
+            writers: self.series.iter().map(|_| None).collect(),
+        });
+        self
+    }
+
+    /// Times of the buffered steps (seconds).
+    pub fn time(&self) -> &[f64] {
+        &self.time
+    }
+
+    /// Buffered values of a quantity, if it is recorded.
+    pub fn series(&self, quantity: RecordedQuantity) -> Option<&RecordedSeries> {
+        self.series.iter().find(|s| s.quantity == quantity)
+    }
+
+    /// Calendar timestamps of the buffered steps, if a [`SimClock`] was
+    /// present while recording.
+    pub fn timestamps(&self) -> Option<Vec<NaiveDateTime>> {
+        let start = self.start?;
+        let clock = SimClock::new(start);
+        Some(self.time.iter().map(|&t| clock.timestamp(t)).collect())
+    }
+
+    /// Buffer row of the step at simulation time `t` (seconds).
+    pub fn row(&self, t: f64) -> Option<usize> {
+        self.time.iter().position(|&ti| ti == t)
+    }
+
+    /// Buffer row of the step at a calendar timestamp of `clock`.
+    pub fn row_at_timestamp(&self, clock: &SimClock, timestamp: NaiveDateTime) -> Option<usize> {
+        self.row(clock.seconds(timestamp))
+    }
+
+    /// Number of steps recorded so far, written or buffered.
+    pub fn rows_recorded(&self) -> usize {
+        self.rows_flushed + self.time.len()
+    }
+
+    fn push_step(&mut self, t: f64, values: Vec<(Vec<i64>, Vec<f64>)>) {
+        self.time.push(t);
+        for (series, (ids, values)) in self.series.iter_mut().zip(values) {
+            series.push(ids, values);
+        }
+        #[cfg(feature = "arrow")]
+        if self.error.is_none()
+            && let Some(sink) = &self.sink
+            && self.time.len() >= sink.chunk_rows
+            && let Err(e) = self.flush()
+        {
+            // Keep the rows buffered; the error is returned by `finish`.
+            self.error = Some(e);
+        }
+    }
+
+    /// Writes the buffered steps to the Parquet files and clears the
+    /// buffers. Does nothing for an in-memory recorder.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        #[cfg(feature = "arrow")]
+        if let Some(sink) = &mut self.sink {
+            if self.time.is_empty() {
+                return Ok(());
+            }
+            std::fs::create_dir_all(&sink.dir)?;
+            for (series, writer) in self.series.iter().zip(&mut sink.writers) {
+                write_chunk(&sink.dir, series, &self.time, self.start, writer)?;
+            }
+            self.rows_flushed += self.time.len();
+            self.time.clear();
+            self.series.iter_mut().for_each(RecordedSeries::clear);
+        }
+        Ok(())
+    }
+
+    /// Flushes the remaining steps and closes the Parquet files. Returns the
+    /// first write error of the run, if any.
+    pub fn finish(&mut self) -> Result<(), Error> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.flush()?;
+        #[cfg(feature = "arrow")]
+        if let Some(sink) = &mut self.sink {
+            for writer in sink.writers.iter_mut().filter_map(Option::take) {
+                let writer = writer.into_inner().unwrap();
+                writer.close().map_err(|e| Error::other(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ResultRecorder {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+/// Writes the buffered rows of one series as a row group, opening the file
+/// on the first chunk.
+#[cfg(feature = "arrow")]
+fn write_chunk(
+    dir: &Path,
+    series: &RecordedSeries,
+    time: &[f64],
+    start: Option<NaiveDateTime>,
+    writer: &mut Option<std::sync::Mutex<ParquetWriter>>,
+) -> Result<(), Error> {
+    use arrow::array::{ArrayRef, Float64Array, RecordBatch, TimestampMicrosecondArray};
+    use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    let to_err = |e: &dyn std::fmt::Display| Error::other(e.to_string());
+    let mut fields = vec![Field::new("time", DataType::Float64, false)];
+    let mut arrays = vec![Arc::new(Float64Array::from(time.to_vec())) as ArrayRef];
+    if let Some(start) = start {
+        let clock = SimClock::new(start);
+        let micros = time
+            .iter()
+            .map(|&t| clock.timestamp(t).and_utc().timestamp_micros());
+        let unit = DataType::Timestamp(TimeUnit::Microsecond, None);
+        fields.push(Field::new("timestamp", unit, false));
+        arrays.push(Arc::new(TimestampMicrosecondArray::from_iter_values(
+            micros,
+        )));
+    }
+    for (id, column) in series.ids.iter().zip(&series.columns) {
+        fields.push(Field::new(id.to_string(), DataType::Float64, false));
+        arrays.push(Arc::new(Float64Array::from(column.clone())));
+    }
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(|e| to_err(&e))?;
+
+    if writer.is_none() {
+        let path = dir.join(format!("{}.parquet", series.quantity.name()));
+        let file = std::fs::File::create(path)?;
+        let w = ArrowWriter::try_new(file, schema, None).map_err(|e| to_err(&e))?;
+        *writer = Some(std::sync::Mutex::new(w));
+    }
+    let writer = writer.as_mut().unwrap().get_mut().unwrap();
+    writer.write(&batch).map_err(|e| to_err(&e))?;
+    // End the row group so the chunk leaves memory.
+    writer.flush().map_err(|e| to_err(&e))
+}
+
+/// Ids and values of one quantity for the current solve.
+fn element_values<T: Component>(
+    world: &mut World,
+    kind: ElementKind,
+    value: impl Fn(&T) -> f64,
+) -> (Vec<i64>, Vec<f64>) {
+    kind.entities(world)
+        .into_iter()
+        .map(|e| {
+            let e = world.entity(e);
+            // `entities` gives every element an index.
+            let id = e.get::<ElementIndex>().unwrap().0 as i64;
+            if e.contains::<OutOfService>() {
+                (id, 0.0)
+            } else {
+                (id, e.get::<T>().map_or(f64::NAN, &value))
+            }
+        })
+        .unzip()
+}
+
+/// Captures the recorded quantities of the current solve into the
+/// [`ResultRecorder`], running the result extraction the quantities need.
+pub fn record_results(world: &mut World) {
+    let Some(quantities) = world
+        .get_resource::<ResultRecorder>()
+        .map(|r| r.series.iter().map(|s| s.quantity).collect::<Vec<_>>())
+    else {
+        return;
+    };
+    let (Some(res), Some(mat), Some(nodes)) = (
+        world.get_resource::<PowerFlowResult>(),
+        world.get_resource::<PowerFlowMat>(),
+        world.get_resource::<NodeLookup>(),
+    ) else {
+        return;
+    };
+    let converged = res.converged;
+    let bus_ids = nodes.ids().to_vec();
+    let v = bus_voltages(mat, res, world.get_resource::<NodeAggRes>());
+
+    let has = |q: RecordedQuantity| quantities.contains(&q);
+    if converged {
+        use RecordedQuantity::*;
+        if has(LineLoading) || has(Losses) {
+            world.run_system_cached(extract_res_line).unwrap();
+        }
+        if has(TrafoLoading) || has(Losses) {
+            world.run_system_cached(extract_res_trafo).unwrap();
+        }
+        if has(GenQ) {
+            world.run_system_cached(extract_res_devices).unwrap();
+        }
+    }
+
+    let mut values = Vec::with_capacity(quantities.len());
+    for q in &quantities {
+        let (ids, mut vals) = match q {
+            RecordedQuantity::BusVm => (bus_ids.clone(), v.iter().map(|v| v.norm()).collect()),
+            RecordedQuantity::BusVa => (
+                bus_ids.clone(),
+                v.iter().map(|v| v.arg().to_degrees()).collect(),
+            ),
+            RecordedQuantity::LineLoading => {
+                element_values(world, ElementKind::Line, |r: &LineResultData| {
+                    r.loading_percent
+                })
+            }
+            RecordedQuantity::TrafoLoading => {
+                element_values(world, ElementKind::Trafo, |r: &TrafoResultData| {
+                    r.loading_percent
+                })
+            }
+            RecordedQuantity::GenQ => {
+                element_values(world, ElementKind::Gen, |r: &GenResultData| r.q_mvar)
+            }
+            RecordedQuantity::Losses => {
+                let (_, lines) =
+                    element_values(world, ElementKind::Line, |r: &LineResultData| r.pl_mw);
+                let (_, trafos) =
+                    element_values(world, ElementKind::Trafo, |r: &TrafoResultData| r.pl_mw);
+                (vec![0], vec![lines.iter().chain(&trafos).sum()])
+            }
+        };
+        if !converged {
+            vals.fill(f64::NAN);
+        }
+        values.push((ids, vals));
+    }
+
+    let t = world.get_resource::<Time>().map(|t| t.elapsed_seconds());
+    let start = world.get_resource::<SimClock>().map(|c| c.start);
+    let mut recorder = world.resource_mut::<ResultRecorder>();
+    recorder.start = recorder.start.or(start);
+    let t = t.unwrap_or(recorder.rows_recorded() as f64);
+    recorder.push_step(t, values);
+}
+
+/// Plugin that runs [`record_results`] in `PostUpdate` whenever a
+/// [`ResultRecorder`] resource is present.
+///
+/// Recording runs before [`scheduled_action_system`], so a step records the
+/// solved state and not the actions due at its end.
+#[derive(Default)]
+pub struct ResultRecorderPlugin;
+
+impl Plugin for ResultRecorderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            record_results
+                .run_if(resource_exists::<ResultRecorder>)
+                .run_if(resource_exists::<PowerFlowResult>)
+                .before(scheduled_action_system),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::network::{DataOps, PowerFlow, PowerGrid};
+    use crate::basic::ecs::post_processing::{PostProcessing, VBusResult};
+    use crate::io::pandapower::load_csv_zip;
+    use crate::prelude::PPNetwork;
+    use crate::timeseries::sim_time::{DeltaTime, TimePlugin};
+    use std::env;
+
+    fn grid_118(recorder: ResultRecorder) -> PowerGrid {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        let net = load_csv_zip(&format!("{dir}/cases/IEEE118/data.zip")).unwrap();
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.app_mut()
+            .add_plugins((TimePlugin, ResultRecorderPlugin));
+        grid.world_mut().insert_resource(DeltaTime(900.0));
+        grid.world_mut().insert_resource(recorder);
+        grid
+    }
+
+    #[test]
+    fn test_record_in_memory() {
+        let mut grid = grid_118(ResultRecorder::new(RecordedQuantity::ALL));
+        for _ in 0..3 {
+            grid.run_pf();
+        }
+        grid.post_process();
+
+        let world = grid.world_mut();
+        let lines = ElementKind::Line.entities(world);
+        let gens = ElementKind::Gen.entities(world);
+        let rec = world.resource::<ResultRecorder>();
+        assert_eq!(rec.time(), [900.0, 1800.0, 2700.0]);
+
+        let vm = rec.series(RecordedQuantity::BusVm).unwrap();
+        assert_eq!(vm.ids.len(), 118);
+        let v = world
+            .resource::<NodeLookup>()
+            .get_entity(vm.ids[10])
+            .unwrap();
+        let v = world.get::<VBusResult>(v).unwrap().0;
+        assert!((vm.columns[10][2] - v.norm()).abs() < 1e-12);
+
+        let loading = rec.series(RecordedQuantity::LineLoading).unwrap();
+        let line = world.get::<LineResultData>(lines[7]).unwrap();
+        assert_eq!(loading.column(7).unwrap()[2], line.loading_percent);
+
+        let q = rec.series(RecordedQuantity::GenQ).unwrap();
+        assert_eq!(q.ids.len(), gens.len());
+        let gen_q = world.get::<GenResultData>(gens[2]).unwrap().q_mvar;
+        assert_eq!(q.columns[2][2], gen_q);
+
+        let losses = rec
+            .series(RecordedQuantity::Losses)
+            .unwrap()
+            .column(0)
+            .unwrap();
+        assert!(losses.iter().all(|&p| p > 0.0));
+
+        // Columns stay keyed by row index when an element is removed.
+        world.despawn(lines[3]);
+        let (ids, values) =
+            element_values(world, ElementKind::Line, |r: &LineResultData| r.loading_percent);
+        assert_eq!(ids.len(), lines.len() - 1);
+        assert!(!ids.contains(&3));
+        assert_eq!(ids[3], 4);
+        let line = world.get::<LineResultData>(lines[4]).unwrap();
+        assert_eq!(values[3], line.loading_percent);
+    }
+
+    #[test]
+    fn test_record_before_scheduled_actions() {
+        use crate::timeseries::scheduled::*;
+        let mut grid = grid_118(ResultRecorder::new([RecordedQuantity::LineLoading]));
+        grid.app_mut().add_plugins(ScheduledEventPlugin);
+        let outage = ScheduledActionKind::SetInService {
+            element: ElementKind::Line,
+            index: 5,
+            in_service: false,
+        };
+        grid.world_mut().spawn(ScheduledStaticActions {
+            queue: [ScheduledStaticAction::new(900.0, outage)].into(),
+        });
+        grid.run_pf();
+        grid.run_pf();
+
+        // The outage is due at the first step and takes effect in the second.
+        let rec = grid.world().resource::<ResultRecorder>();
+        let loading = rec.series(RecordedQuantity::LineLoading).unwrap();
+        assert!(loading.column(5).unwrap()[0] > 0.0);
+        assert_eq!(loading.column(5).unwrap()[1], 0.0);
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_stream_parquet() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let dir = env::temp_dir().join(format!("rustpower_recorder_{}", std::process::id()));
+        let recorder = ResultRecorder::new([RecordedQuantity::BusVm, RecordedQuantity::Losses])
+            .with_parquet(&dir, 2);
+        let mut grid = grid_118(recorder);
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        grid.world_mut().insert_resource(SimClock::new(start));
+        for _ in 0..5 {
+            grid.run_pf();
+        }
+        let mut rec = grid.world_mut().resource_mut::<ResultRecorder>();
+        // Two chunks written, the fifth step still buffered.
+        assert_eq!(rec.time(), [4500.0]);
+        assert_eq!(rec.rows_recorded(), 5);
+        let last = start + chrono::TimeDelta::minutes(75);
+        assert_eq!(rec.timestamps().unwrap(), [last]);
+        assert_eq!(rec.row_at_timestamp(&SimClock::new(start), last), Some(0));
+        rec.finish().unwrap();
+
+        let file = std::fs::File::open(dir.join("res_bus.vm_pu.parquet")).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let meta = reader.metadata();
+        assert_eq!(meta.num_row_groups(), 3);
+        assert_eq!(meta.file_metadata().num_rows(), 5);
+        let schema = meta.file_metadata().schema_descr();
+        assert_eq!(schema.num_columns(), 120);
+        assert_eq!(schema.column(0).name(), "time");
+        assert_eq!(schema.column(1).name(), "timestamp");
+        assert!(dir.join("res_losses.pl_mw.parquet").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,14 +2,18 @@ use bevy_app::App;
 use bevy_app::Plugin;
 use bevy_app::PostUpdate;
 use bevy_ecs::prelude::*;
+use chrono::NaiveDateTime;
 use nalgebra::Complex;
 use nalgebra::SimdComplexField;
 use serde::Deserialize;
 use serde::Serialize;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
+pub use crate::basic::ecs::elements::ElementKind;
 use crate::basic::ecs::elements::*;
-use crate::timeseries::sim_time::Time;
+use crate::basic::ecs::powerflow::mutation::ParamDiff;
+use crate::basic::ecs::powerflow::structure_update::{FullRebuildEvent, StructureUpdatePlugin};
+use crate::timeseries::sim_time::{SimClock, Time};
 
 /// Represents a dynamic ECS-side action scheduled for execution at a specific simulation time.
 ///
@@ -29,8 +33,10 @@ pub struct ScheduledDynActions {
 
 /// Enum representing a static, serializable scheduled event.
 ///
-/// Each variant corresponds to a well-defined ECS mutation,
-/// such as changing a target power or voltage at a specified bus.
+/// Each variant corresponds to a well-defined ECS mutation, such as
+/// changing a target power or voltage at a specified bus, switching, or
+/// taking equipment out of service. Elements are addressed by
+/// [`ElementKind`] index.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum ScheduledActionKind {
     /// Set real power target (P) in MW for a bus.
@@ -41,6 +47,35 @@ pub enum ScheduledActionKind {
     SetTargetVM { bus: i64, value: f64 },
     /// Set voltage angle in degrees.
     SetTargetVa { bus: i64, value: f64 },
+    /// Open (`closed: false`) or close the switch at `index`.
+    SetSwitch { index: usize, closed: bool },
+    /// Take an element in or out of service.
+    SetInService {
+        element: ElementKind,
+        index: usize,
+        in_service: bool,
+    },
+    /// Move the tap changer of the transformer at `index` to `pos`, clamped
+    /// to its tap range.
+    SetTapPosition { index: usize, pos: f64 },
+    /// Set the number of active steps of the shunt at `index`, clamped to
+    /// `0..=max_step`.
+    SetShuntStep { index: usize, step: i32 },
+    /// Multiply the active and reactive power of the load at `index`.
+    ScaleLoad { index: usize, factor: f64 },
+}
+
+impl ScheduledActionKind {
+    /// Bus id addressed by a bus set point action.
+    pub fn bus(&self) -> Option<i64> {
+        match *self {
+            Self::SetTargetPMW { bus, .. }
+            | Self::SetTargetQMvar { bus, .. }
+            | Self::SetTargetVM { bus, .. }
+            | Self::SetTargetVa { bus, .. } => Some(bus),
+            _ => None,
+        }
+    }
 }
 
 /// Represents one static action with an execution timestamp.
@@ -48,20 +83,52 @@ pub enum ScheduledActionKind {
 /// These actions are deterministic and serializable.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ScheduledStaticAction {
+    /// Simulation time in seconds.
     pub execute_at: f64,
     pub action: ScheduledActionKind,
 }
 
+impl ScheduledStaticAction {
+    pub fn new(execute_at: f64, action: ScheduledActionKind) -> Self {
+        Self { execute_at, action }
+    }
+
+    /// Action executing at a calendar timestamp of `clock`.
+    pub fn at_timestamp(
+        clock: &SimClock,
+        timestamp: NaiveDateTime,
+        action: ScheduledActionKind,
+    ) -> Self {
+        Self::new(clock.seconds(timestamp), action)
+    }
+
+    /// Calendar timestamp at which the action executes.
+    pub fn timestamp(&self, clock: &SimClock) -> NaiveDateTime {
+        clock.timestamp(self.execute_at)
+    }
+}
+
 /// ECS component storing a queue of static scheduled actions.
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct ScheduledStaticActions {
     pub queue: VecDeque<ScheduledStaticAction>,
 }
 
+/// A due action that could not be applied, e.g. because it addresses a bus
+/// that does not exist.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FailedAction {
+    pub action: ScheduledStaticAction,
+    pub reason: String,
+}
+
 /// Resource used to track and log all executed scheduled actions.
 #[derive(Resource, Default, Serialize, Deserialize, Clone, Debug)]
 pub struct ScheduledLog {
     pub executed: Vec<ScheduledStaticAction>,
+    /// Due actions that were skipped.
+    #[serde(default)]
+    pub failed: Vec<FailedAction>,
 }
 
 /// Safely mutates a component of type `T` on the given entity by queueing the change in a deferred system.
@@ -86,13 +153,109 @@ where
     });
 }
 
+/// Applies a topology or equipment action to the world.
+///
+/// Switching, service changes, tap positions and shunt steps change the
+/// admittance matrix or the node types and post a [`FullRebuildEvent`];
+/// load scaling only moves injections and goes through the [`ParamDiff`]
+/// bus.
+///
+/// Elements are looked up in `index_maps`, filled per element kind on first use.
+///
+/// Returns an error without changing anything if the addressed element does
+/// not exist, or if a tap position is set on a transformer without a tap
+/// changer.
+fn apply_element_action(
+    world: &mut World,
+    index_maps: &mut HashMap<ElementKind, HashMap<usize, Entity>>,
+    action: ScheduledActionKind,
+) -> Result<(), String> {
+    use ScheduledActionKind::*;
+    let (kind, index) = match action {
+        SetSwitch { index, .. } => (ElementKind::Switch, index),
+        SetInService { element, index, .. } => (element, index),
+        SetTapPosition { index, .. } => (ElementKind::Trafo, index),
+        SetShuntStep { index, .. } => (ElementKind::Shunt, index),
+        ScaleLoad { index, .. } => (ElementKind::Load, index),
+        _ => unreachable!("not an element action: {action:?}"),
+    };
+    let entity = index_maps
+        .entry(kind)
+        .or_insert_with(|| kind.index_map(world))
+        .get(&index)
+        .copied()
+        .ok_or_else(|| format!("no {kind:?} element at index {index}"))?;
+    let mut e = world.entity_mut(entity);
+    match action {
+        SetSwitch { closed, .. } => {
+            e.insert(SwitchState(closed));
+        }
+        SetInService { in_service, .. } => {
+            if in_service {
+                e.remove::<OutOfService>();
+            } else {
+                e.insert(OutOfService);
+            }
+        }
+        SetTapPosition { pos, .. } => {
+            let mut dev = e.get_mut::<TransformerDevice>().unwrap();
+            let Some(tap) = dev.tap.as_mut() else {
+                return Err(format!("{kind:?} element at index {index} has no tap changer"));
+            };
+            let pos = tap.max.map_or(pos, |max| pos.min(max));
+            tap.pos = Some(tap.min.map_or(pos, |min| pos.max(min)));
+        }
+        SetShuntStep { step, .. } => {
+            let mut dev = e.get_mut::<ShuntDevice>().unwrap();
+            dev.step = step.clamp(0, dev.max_step.max(0));
+        }
+        ScaleLoad { factor, .. } => {
+            let bus = e.get::<TargetBus>().unwrap().0;
+            let oos = e.contains::<OutOfService>();
+            let mut p = e.get_mut::<TargetPMW>().unwrap();
+            let dp_mw = p.0 * (factor - 1.0);
+            p.0 += dp_mw;
+            let mut q = e.get_mut::<TargetQMVar>().unwrap();
+            let dq_mvar = q.0 * (factor - 1.0);
+            q.0 += dq_mvar;
+            if !oos {
+                world.write_message(ParamDiff::Injection {
+                    bus,
+                    dp_mw,
+                    dq_mvar,
+                });
+            }
+            return Ok(());
+        }
+        _ => unreachable!(),
+    }
+    world.write_message(FullRebuildEvent);
+    Ok(())
+}
+
+/// Applies the due element actions of one frame in order and logs them.
+fn apply_element_actions(world: &mut World, actions: Vec<ScheduledStaticAction>) {
+    let mut index_maps = HashMap::new();
+    for act in actions {
+        let result = apply_element_action(world, &mut index_maps, act.action.clone());
+        let mut log = world.resource_mut::<ScheduledLog>();
+        match result {
+            Ok(()) => log.executed.push(act),
+            Err(reason) => log.failed.push(FailedAction { action: act, reason }),
+        }
+    }
+}
+
 /// Executes scheduled static actions that are due at the current simulation time.
 ///
 /// For each [`ScheduledStaticActions`] component:
 /// - If the current time >= `execute_at`, performs the associated [`ScheduledActionKind`].
 /// - Applies changes via deferred `commands.queue(...)`.
-/// - Logs all executed actions in [`ScheduledLog`] for traceability.
-fn scheduled_action_system(
+/// - Logs all executed actions in [`ScheduledLog`] for traceability; actions
+///   addressing a bus or element that does not exist are skipped and logged
+///   as failed. Element actions are applied together by one command, which
+///   logs them.
+pub fn scheduled_action_system(
     time: Res<Time>,
     common: Res<PFCommonData>,
     lut: Res<NodeLookup>,
@@ -102,38 +265,54 @@ fn scheduled_action_system(
 ) {
     let now = time.elapsed_seconds();
     let sbase_frac = 1.0 / common.sbase;
+    let mut element_actions = Vec::new();
     for mut sched in &mut query {
         while let Some(action) = sched.queue.front() {
             if action.execute_at <= now {
                 let act = sched.queue.pop_front().unwrap();
                 let action = act.action.clone();
+                let entity = match action.bus().map(|bus| (bus, lut.get_entity(bus))) {
+                    Some((bus, None)) => {
+                        log.failed.push(FailedAction {
+                            action: act,
+                            reason: format!("bus {bus} does not exist"),
+                        });
+                        continue;
+                    }
+                    Some((_, entity)) => entity,
+                    None => None,
+                };
                 match action {
-                    ScheduledActionKind::SetTargetPMW { bus, value } => {
-                        let entity = lut.get_entity(bus).unwrap();
+                    ScheduledActionKind::SetTargetPMW { value, .. } => {
+                        let entity = entity.unwrap();
                         write_component::<SBusInjPu, _>(&mut commands, entity, move |a| {
                             a.0.re = value * sbase_frac;
                         });
                     }
-                    ScheduledActionKind::SetTargetQMvar { bus, value } => {
-                        let entity = lut.get_entity(bus).unwrap();
+                    ScheduledActionKind::SetTargetQMvar { value, .. } => {
+                        let entity = entity.unwrap();
                         write_component::<SBusInjPu, _>(&mut commands, entity, move |a| {
                             a.0.im = value * sbase_frac;
                         });
                     }
-                    ScheduledActionKind::SetTargetVM { bus, value } => {
-                        let entity = lut.get_entity(bus).unwrap();
+                    ScheduledActionKind::SetTargetVM { value, .. } => {
+                        let entity = entity.unwrap();
                         write_component::<VBusPu, _>(&mut commands, entity, move |a| {
                             let angle = a.0.simd_argument();
                             a.0 = Complex::from_polar(value, angle);
                         });
                     }
-                    ScheduledActionKind::SetTargetVa { bus, value } => {
-                        let entity = lut.get_entity(bus).unwrap();
+                    ScheduledActionKind::SetTargetVa { value, .. } => {
+                        let entity = entity.unwrap();
                         write_component::<VBusPu, _>(&mut commands, entity, move |a| {
                             let mag = a.0.norm();
                             a.0 = Complex::from_polar(mag, value.to_radians());
                         });
                     }
+                    _ => {
+                        element_actions.push(act);
+                        continue;
+                    }
                 }
                 log.executed.push(act);
             } else {
@@ -141,6 +320,9 @@ fn scheduled_action_system(
             }
         }
     }
+    if !element_actions.is_empty() {
+        commands.queue(move |world: &mut World| apply_element_actions(world, element_actions));
+    }
 }
 // fn scheduled_dyn_action_system(
 //     time: Res<Time>,
@@ -166,7 +348,8 @@ fn scheduled_action_system(
 /// Plugin for handling time-scheduled system actions in ECS-based simulations.
 ///
 /// Supports:
-/// - Static event scheduling (serializable actions applied at exact simulation times)
+/// - Static event scheduling (serializable actions applied at exact simulation times),
+///   including switching, outages, tap and shunt step changes and load scaling
 /// - Logging of all executed actions for auditing
 /// - (Optional) Dynamic scheduling via runtime closures [see `ScheduledDynActions`]
 ///
@@ -184,7 +367,320 @@ pub struct ScheduledEventPlugin;
 
 impl Plugin for ScheduledEventPlugin {
     fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<StructureUpdatePlugin>() {
+            app.add_plugins(StructureUpdatePlugin);
+        }
         app.init_resource::<ScheduledLog>();
         app.add_systems(PostUpdate, scheduled_action_system);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic::ecs::network::{DataOps, PowerFlow, PowerGrid};
+    use crate::basic::ecs::powerflow::prelude::PowerFlowResult;
+    use crate::basic::ecs::powerflow::structure_update::LastStructureAction;
+    use crate::io::pandapower::{Network, load_csv_zip};
+    use crate::prelude::PPNetwork;
+    use crate::timeseries::sim_time::{DeltaTime, TimePlugin};
+    use std::env;
+
+    fn ieee118() -> Network {
+        let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        load_csv_zip(&format!("{dir}/cases/IEEE118/data.zip")).unwrap()
+    }
+
+    fn grid(net: Network, actions: Vec<ScheduledStaticAction>) -> PowerGrid {
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.app_mut()
+            .add_plugins((TimePlugin, ScheduledEventPlugin));
+        grid.world_mut().insert_resource(DeltaTime(900.0));
+        grid.world_mut().spawn(ScheduledStaticActions {
+            queue: actions.into(),
+        });
+        grid
+    }
+
+    fn solve(net: Network) -> PowerFlowResult {
+        let mut grid = PowerGrid::default();
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.run_pf();
+        grid.world().resource::<PowerFlowResult>().clone()
+    }
+
+    fn at(execute_at: f64, action: ScheduledActionKind) -> ScheduledStaticAction {
+        ScheduledStaticAction::new(execute_at, action)
+    }
+
+    #[test]
+    fn test_outage_schedule_118() {
+        use ScheduledActionKind::*;
+        let net = ieee118();
+        let actions = vec![
+            at(
+                900.0,
+                SetInService {
+                    element: ElementKind::Line,
+                    index: 5,
+                    in_service: false,
+                },
+            ),
+            at(
+                900.0,
+                SetInService {
+                    element: ElementKind::Gen,
+                    index: 3,
+                    in_service: false,
+                },
+            ),
+            at(
+                900.0,
+                ScaleLoad {
+                    index: 0,
+                    factor: 1.5,
+                },
+            ),
+        ];
+        // Round-trip through serde, as an archived schedule would.
+        let json = serde_json::to_string(&actions).unwrap();
+        let actions: Vec<ScheduledStaticAction> = serde_json::from_str(&json).unwrap();
+        let mut grid = grid(net.clone(), actions);
+
+        // The actions execute after the solve at t = 900 s and take effect
+        // in the next frame.
+        grid.run_pf();
+        grid.run_pf();
+        let world = grid.world();
+        assert_eq!(world.resource::<ScheduledLog>().executed.len(), 3);
+        assert!(world.resource::<LastStructureAction>().full_rebuild);
+
+        let mut expected = net.clone();
+        expected.line.as_mut().unwrap()[5].in_service = false;
+        expected.r#gen.as_mut().unwrap()[3].in_service = false;
+        let load = &mut expected.load.as_mut().unwrap()[0];
+        (load.p_mw, load.q_mvar) = (load.p_mw * 1.5, load.q_mvar * 1.5);
+        let expected = solve(expected);
+        let res = world.resource::<PowerFlowResult>();
+        assert!(res.converged);
+        assert!((&res.v - &expected.v).camax() < 1e-8);
+
+        // Restoring the line and generator brings back the base case.
+        let world = grid.world_mut();
+        let mut q = world.query::<&mut ScheduledStaticActions>();
+        q.single_mut(world).unwrap().queue.extend([
+            at(
+                2700.0,
+                SetInService {
+                    element: ElementKind::Line,
+                    index: 5,
+                    in_service: true,
+                },
+            ),
+            at(
+                2700.0,
+                SetInService {
+                    element: ElementKind::Gen,
+                    index: 3,
+                    in_service: true,
+                },
+            ),
+            at(
+                2700.0,
+                ScaleLoad {
+                    index: 0,
+                    factor: 1.0 / 1.5,
+                },
+            ),
+        ]);
+        grid.run_pf();
+        grid.run_pf();
+        let res = grid.world().resource::<PowerFlowResult>();
+        assert!((&res.v - &solve(net).v).camax() < 1e-8);
+    }
+
+    #[test]
+    fn test_tap_and_shunt_steps() {
+        use ScheduledActionKind::*;
+        let net = ieee118();
+        let mut grid = grid(
+            net,
+            vec![
+                at(
+                    900.0,
+                    SetTapPosition {
+                        index: 0,
+                        pos: 100.0,
+                    },
+                ),
+                at(900.0, SetShuntStep { index: 0, step: 0 }),
+            ],
+        );
+        grid.run_pf();
+        let v0 = grid.world().resource::<PowerFlowResult>().v.clone();
+        grid.run_pf();
+
+        let world = grid.world_mut();
+        let trafo = ElementKind::Trafo.entity(world, 0).unwrap();
+        let shunt = ElementKind::Shunt.entity(world, 0).unwrap();
+        let tap = world
+            .get::<TransformerDevice>(trafo)
+            .unwrap()
+            .tap
+            .clone()
+            .unwrap();
+        assert_eq!(tap.pos, tap.max.or(Some(100.0)));
+        assert_eq!(world.get::<ShuntDevice>(shunt).unwrap().step, 0);
+        let res = world.resource::<PowerFlowResult>();
+        assert!(res.converged);
+        assert!((&res.v - &v0).camax() > 1e-6);
+    }
+
+    #[test]
+    fn test_unknown_targets_are_logged() {
+        use ScheduledActionKind::*;
+        let mut grid = grid(
+            ieee118(),
+            vec![
+                at(
+                    900.0,
+                    SetTargetPMW {
+                        bus: 9999,
+                        value: 1.0,
+                    },
+                ),
+                at(900.0, SetTargetQMvar { bus: 0, value: 0.0 }),
+                at(
+                    900.0,
+                    ScaleLoad {
+                        index: 10_000,
+                        factor: 2.0,
+                    },
+                ),
+                at(900.0, SetTapPosition { index: 1, pos: 2.0 }),
+            ],
+        );
+        let world = grid.world_mut();
+        let trafo = ElementKind::Trafo.entity(world, 1).unwrap();
+        world.get_mut::<TransformerDevice>(trafo).unwrap().tap = None;
+        grid.run_pf();
+        let world = grid.world();
+        let log = world.resource::<ScheduledLog>();
+        assert_eq!(log.executed.len(), 1);
+        assert_eq!(log.failed.len(), 3);
+        assert_eq!(log.failed[0].action.action.bus(), Some(9999));
+        assert_eq!(log.failed[0].reason, "bus 9999 does not exist");
+        assert_eq!(log.failed[1].reason, "no Load element at index 10000");
+        assert_eq!(log.failed[2].reason, "Trafo element at index 1 has no tap changer");
+        assert!(world.get::<TransformerDevice>(trafo).unwrap().tap.is_none());
+    }
+
+    #[test]
+    fn test_elements_addressed_by_row_after_despawn() {
+        let net = ieee118();
+        let mut grid = PowerGrid::default();
+        // Freed entity indices are reused last-in first-out, so the loaded
+        // elements get entity indices in reverse row order.
+        let world = grid.world_mut();
+        let dummies = (0..1000).map(|_| world.spawn_empty().id()).collect::<Vec<_>>();
+        for e in dummies {
+            world.despawn(e);
+        }
+        world.insert_resource(PPNetwork(net.clone()));
+        grid.init_pf_net();
+
+        let world = grid.world_mut();
+        let loads = net.load.as_ref().unwrap();
+        let bus_of = |world: &World, e: Entity| world.get::<TargetBus>(e).unwrap().0;
+        let entities = ElementKind::Load.entities(world);
+        assert_eq!(entities.len(), loads.len());
+        for (e, load) in entities.iter().zip(loads) {
+            assert_eq!(bus_of(world, *e), load.bus);
+        }
+
+        // Despawning an element leaves the other rows where they are.
+        world.despawn(entities[1]);
+        let e = ElementKind::Load.entity(world, 2).unwrap();
+        assert_eq!(bus_of(world, e), loads[2].bus);
+        assert!(ElementKind::Load.entity(world, 1).is_none());
+    }
+
+    /// Solves `net` with the switch plugin that merges buses, optionally
+    /// running `actions` through the schedule first.
+    fn solve_switched(net: Network, actions: Vec<ScheduledStaticAction>) -> PowerGrid {
+        use crate::basic::ecs::plugin::SwitchPluginTypeA;
+        let mut grid = PowerGrid::default();
+        grid.app_mut().add_plugins(SwitchPluginTypeA);
+        grid.world_mut().insert_resource(PPNetwork(net));
+        grid.init_pf_net();
+        grid.app_mut()
+            .add_plugins((TimePlugin, ScheduledEventPlugin));
+        grid.world_mut().insert_resource(DeltaTime(900.0));
+        grid.world_mut().spawn(ScheduledStaticActions {
+            queue: actions.into(),
+        });
+        grid.run_pf();
+        grid
+    }
+
+    #[test]
+    fn test_switching_schedule_118() {
+        use crate::io::pandapower::{Switch, SwitchType};
+        use ScheduledActionKind::*;
+        let mut net = ieee118();
+        let (line5, line0) = {
+            let lines = net.line.as_ref().unwrap();
+            (lines[5].clone(), lines[0].clone())
+        };
+        let switch = |bus, element, et| Switch {
+            index: None,
+            bus,
+            element,
+            et,
+            type_: None,
+            closed: true,
+            name: None,
+            z_ohm: 0.0,
+        };
+        // A bus coupler merging the ends of line 0, and a breaker at the
+        // sending end of line 5.
+        net.switch = Some(vec![
+            switch(line0.from_bus, line0.to_bus, SwitchType::SwitchTwoBuses),
+            switch(line5.from_bus, 5, SwitchType::SwitchBusLine),
+        ]);
+        let actions = vec![
+            at(900.0, SetSwitch { index: 0, closed: false }),
+            at(
+                900.0,
+                SetInService {
+                    element: ElementKind::Switch,
+                    index: 1,
+                    in_service: false,
+                },
+            ),
+        ];
+        let mut grid = solve_switched(net.clone(), actions);
+        let merged = grid.world().resource::<PowerFlowResult>().v.clone();
+        let n = grid.world().resource::<NodeLookup>().len();
+        assert_eq!(merged.len(), n - 1);
+        grid.run_pf();
+        assert_eq!(grid.world().resource::<ScheduledLog>().executed.len(), 2);
+
+        let mut expected = net.clone();
+        expected.switch.as_mut().unwrap()[0].closed = false;
+        let expected_grid = solve_switched(expected.clone(), vec![]);
+        let unmerged = expected_grid.world().resource::<PowerFlowResult>();
+        expected.switch.as_mut().unwrap()[1].closed = false;
+        let expected = solve_switched(expected, vec![]);
+        let expected = expected.world().resource::<PowerFlowResult>();
+        let res = grid.world().resource::<PowerFlowResult>();
+        assert!(res.converged);
+        assert_eq!(res.v.len(), n);
+        assert!((&res.v - &expected.v).camax() < 1e-8);
+        // Opening line 5 at one end moves the solution.
+        assert!((&res.v - &unmerged.v).camax() > 1e-6);
+    }
+}
@@ -1,7 +1,10 @@
 use bevy_app::{App, First, Plugin};
 use bevy_ecs::prelude::*;
-use derive_more::derive::*;
+use chrono::{Months, NaiveDateTime, TimeDelta};
+use derive_more::derive::{Add, Div, From, Into, Mul};
 use serde::{Deserialize, Serialize};
+
+use crate::timeseries::scheduled::ScheduledStaticActions;
 /// Represents a fixed simulation time step in seconds.
 ///
 /// This resource defines how much simulation time advances per frame.
@@ -31,11 +34,160 @@ impl Time {
 pub fn advance(mut t: ResMut<Time>, dt: Res<DeltaTime>) {
     t.0 += dt.0;
 }
-/// Provides global simulation time tracking for deterministic, fixed-step simulations.
+
+/// How a [`SimClock`] advances [`Time`] each frame.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Stepping {
+    /// Steps of the [`DeltaTime`] resource.
+    #[default]
+    Fixed,
+    /// Calendar steps of `months` months plus `seconds`, counted from the
+    /// start so month ends do not drift (Jan 31, Feb 29, Mar 31, ...).
+    /// Time zone naive: every day has 86400 s.
+    Calendar { months: u32, seconds: i64 },
+    /// The given times (seconds since the start, ascending) in turn, e.g.
+    /// the time axis of a profile table. Time holds after the last one.
+    Index(Vec<f64>),
+    /// Straight to the next pending scheduled action. Time holds when none
+    /// is left.
+    NextEvent,
+}
+
+/// Calendar for the simulation time.
+///
+/// [`Time`] stays the number of seconds since `start`; the clock maps it to
+/// naive timestamps and decides the size of each step. While this resource
+/// is present [`advance_clock`] replaces [`advance`] and writes the step it
+/// took into [`DeltaTime`].
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimClock {
+    /// Timestamp of `Time(0.0)`.
+    pub start: NaiveDateTime,
+    pub stepping: Stepping,
+    /// Shorten steps that would pass a pending scheduled action so the
+    /// action runs at its own time.
+    pub stop_at_events: bool,
+}
+
+impl SimClock {
+    /// Clock starting at `start` with [`Stepping::Fixed`] steps.
+    pub fn new(start: NaiveDateTime) -> Self {
+        Self {
+            start,
+            stepping: Stepping::Fixed,
+            stop_at_events: false,
+        }
+    }
+
+    pub fn with_stepping(mut self, stepping: Stepping) -> Self {
+        self.stepping = stepping;
+        self
+    }
+
+    pub fn with_stop_at_events(mut self, stop_at_events: bool) -> Self {
+        self.stop_at_events = stop_at_events;
+        self
+    }
+
+    /// [`Stepping::Index`] over the given timestamps.
+    pub fn with_timestamp_index(self, index: impl IntoIterator<Item = NaiveDateTime>) -> Self {
+        let index = index.into_iter().map(|ts| self.seconds(ts)).collect();
+        self.with_stepping(Stepping::Index(index))
+    }
+
+    /// Timestamp of a simulation time in seconds (microsecond resolution).
+    pub fn timestamp(&self, seconds: f64) -> NaiveDateTime {
+        self.start + TimeDelta::microseconds((seconds * 1e6).round() as i64)
+    }
+
+    /// Simulation time in seconds of a timestamp.
+    pub fn seconds(&self, timestamp: NaiveDateTime) -> f64 {
+        (timestamp - self.start).as_seconds_f64()
+    }
+
+    /// Timestamp of the current simulation time.
+    pub fn now(&self, time: &Time) -> NaiveDateTime {
+        self.timestamp(time.0)
+    }
+
+    /// Start of the `k`-th calendar step.
+    fn calendar_step(&self, months: u32, seconds: i64, k: i64) -> Option<f64> {
+        let ts = if k >= 0 {
+            let m = u32::try_from(k).ok()?.checked_mul(months)?;
+            self.start.checked_add_months(Months::new(m))?
+        } else {
+            let m = u32::try_from(-k).ok()?.checked_mul(months)?;
+            self.start.checked_sub_months(Months::new(m))?
+        };
+        let ts = ts.checked_add_signed(TimeDelta::try_seconds(seconds.checked_mul(k)?)?)?;
+        Some(self.seconds(ts))
+    }
+
+    /// The time after `now`, or `None` if the clock holds. `dt` is the
+    /// [`DeltaTime`] for fixed steps, `next_event` the earliest pending
+    /// scheduled action after `now`.
+    pub fn next_time(&self, now: f64, dt: Option<f64>, next_event: Option<f64>) -> Option<f64> {
+        let next = match &self.stepping {
+            Stepping::Fixed => dt.map(|dt| now + dt),
+            Stepping::Calendar { months, seconds } => {
+                let (months, seconds) = (*months, *seconds);
+                let approx = months as f64 * 2_629_746.0 + seconds as f64;
+                if approx <= 0.0 {
+                    return None;
+                }
+                // Last step at or before `now`, from an estimate.
+                let mut k = (now / approx).floor() as i64;
+                while self.calendar_step(months, seconds, k)? > now {
+                    k -= 1;
+                }
+                while self.calendar_step(months, seconds, k + 1)? <= now {
+                    k += 1;
+                }
+                self.calendar_step(months, seconds, k + 1)
+            }
+            Stepping::Index(index) => index.iter().copied().find(|&t| t > now),
+            Stepping::NextEvent => return next_event,
+        };
+        match (next, next_event) {
+            (Some(next), Some(event)) if self.stop_at_events => Some(next.min(event)),
+            _ => next,
+        }
+    }
+}
+
+/// Advances [`Time`] by the step of the [`SimClock`] and records the step
+/// in [`DeltaTime`] (zero while the clock holds).
+///
+/// # Scheduling
+/// Runs in the [`First`] schedule instead of [`advance`] while a
+/// [`SimClock`] exists.
+pub fn advance_clock(
+    mut t: ResMut<Time>,
+    clock: Res<SimClock>,
+    dt: Option<ResMut<DeltaTime>>,
+    actions: Query<&ScheduledStaticActions>,
+) {
+    let now = t.0;
+    let next_event = actions
+        .iter()
+        .flat_map(|a| a.queue.iter().map(|a| a.execute_at))
+        .filter(|&at| at > now)
+        .min_by(f64::total_cmp);
+    let next = clock.next_time(now, dt.as_ref().map(|dt| dt.0), next_event);
+    let step = next.map_or(0.0, |next| next - now);
+    t.0 += step;
+    if let Some(mut dt) = dt
+        && !matches!(clock.stepping, Stepping::Fixed)
+    {
+        dt.0 = step;
+    }
+}
+/// Provides global simulation time tracking for deterministic simulations.
 ///
 /// # Features:
 /// - Adds the [`Time`] resources.
-/// - Automatically advances [`Time`] by [`DeltaTime`] every frame.
+/// - Automatically advances [`Time`] by [`DeltaTime`] every frame, or by the
+///   steps of a [`SimClock`] when one is inserted.
 /// - Runs the time advancement system early in the frame (`First` schedule).
 ///
 /// # Example Usage:
@@ -51,13 +203,112 @@ pub fn advance(mut t: ResMut<Time>, dt: Res<DeltaTime>) {
 /// ```
 ///
 /// # Scheduling Behavior:
-/// - [`advance`] is run in the [`First`] schedule to ensure systems in `Update` see the updated time.
+/// - [`advance`] (or [`advance_clock`]) is run in the [`First`] schedule to ensure systems in `Update` see the updated time.
 #[derive(Default)]
 pub struct TimePlugin;
 
 impl Plugin for TimePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Time>();
-        app.add_systems(First, advance);
+        app.add_systems(
+            First,
+            (
+                advance.run_if(not(resource_exists::<SimClock>)),
+                advance_clock.run_if(resource_exists::<SimClock>),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeseries::scheduled::{ScheduledActionKind, ScheduledStaticAction};
+    use chrono::NaiveDate;
+
+    fn ts(month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, month, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn clock_app(clock: SimClock, dt: f64) -> App {
+        let mut app = App::new();
+        app.add_plugins(TimePlugin);
+        app.insert_resource(DeltaTime(dt));
+        app.insert_resource(clock);
+        app
+    }
+
+    fn steps(app: &mut App, n: usize) -> Vec<NaiveDateTime> {
+        (0..n)
+            .map(|_| {
+                app.update();
+                let world = app.world();
+                world.resource::<SimClock>().now(world.resource::<Time>())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_calendar_stepping() {
+        let clock = SimClock::new(ts(1, 31, 0)).with_stepping(Stepping::Calendar {
+            months: 1,
+            seconds: 3600,
+        });
+        let mut app = clock_app(clock, 0.0);
+        assert_eq!(
+            steps(&mut app, 3),
+            [ts(2, 29, 1), ts(3, 31, 2), ts(4, 30, 3)]
+        );
+
+        let clock = SimClock::new(ts(1, 1, 0)).with_stepping(Stepping::Fixed);
+        let mut app = clock_app(clock, 900.0);
+        assert_eq!(steps(&mut app, 1)[0], ts(1, 1, 0) + TimeDelta::minutes(15));
+    }
+
+    #[test]
+    fn test_index_stepping() {
+        let clock = SimClock::new(ts(1, 1, 0)).with_timestamp_index([
+            ts(1, 1, 1),
+            ts(1, 1, 2),
+            ts(1, 1, 5),
+        ]);
+        let mut app = clock_app(clock, 0.0);
+        let mut dts = Vec::new();
+        for _ in 0..4 {
+            app.update();
+            dts.push(app.world().resource::<DeltaTime>().0);
+        }
+        assert_eq!(dts, [3600.0, 3600.0, 10800.0, 0.0]);
+        assert_eq!(app.world().resource::<Time>().0, 5.0 * 3600.0);
+    }
+
+    #[test]
+    fn test_event_stepping() {
+        let clock = SimClock::new(ts(1, 1, 0));
+        let action = ScheduledActionKind::SetTargetPMW { bus: 0, value: 0.0 };
+        let actions = ScheduledStaticActions {
+            queue: [
+                ScheduledStaticAction::new(1000.0, action.clone()),
+                ScheduledStaticAction::at_timestamp(&clock, ts(1, 1, 3), action),
+            ]
+            .into(),
+        };
+
+        let mut next = clock_app(clock.clone().with_stepping(Stepping::NextEvent), 0.0);
+        next.world_mut().spawn(actions.clone());
+        let times = steps(&mut next, 3);
+        assert_eq!(times[0], clock.timestamp(1000.0));
+        assert_eq!(times[1..], [ts(1, 1, 3), ts(1, 1, 3)]);
+
+        let mut stop = clock_app(clock.clone().with_stop_at_events(true), 900.0);
+        stop.world_mut().spawn(actions);
+        let times = steps(&mut stop, 3)
+            .into_iter()
+            .map(|t| clock.seconds(t))
+            .collect::<Vec<_>>();
+        assert_eq!(times, [900.0, 1000.0, 1900.0]);
     }
 }
@@ -1,5 +1,6 @@
 use bevy_app::{App, First, Plugin, PostUpdate};
 use bevy_ecs::prelude::*;
+use chrono::NaiveDateTime;
 use nalgebra::{Complex, DVector};
 use serde::{Deserialize, Serialize};
 
@@ -14,7 +15,7 @@ use crate::{
     prelude::PowerFlowResult,
 };
 
-use super::sim_time::Time;
+use super::sim_time::{SimClock, Time};
 
 /// Stores a sequence of time-tagged voltage states over the course of the simulation.
 ///
@@ -28,6 +29,23 @@ pub struct TimeSeriesData {
     pub data: Vec<DVector<Complex<f64>>>,
 }
 
+impl TimeSeriesData {
+    /// Voltage snapshot taken at simulation time `t` (seconds).
+    pub fn at(&self, t: f64) -> Option<&DVector<Complex<f64>>> {
+        let idx = self.t.iter().position(|&ti| ti == t)?;
+        self.data.get(idx)
+    }
+
+    /// Voltage snapshot taken at a calendar timestamp of `clock`.
+    pub fn at_timestamp(
+        &self,
+        clock: &SimClock,
+        timestamp: NaiveDateTime,
+    ) -> Option<&DVector<Complex<f64>>> {
+        self.at(clock.seconds(timestamp))
+    }
+}
+
 /// Updates the solver’s initial voltage vector using the latest simulation result.
 ///
 /// This enables iterative solvers to reuse the previous converged solution as a warm start.